@@ -0,0 +1,306 @@
+// ------- ARENA TREE -------
+// Rooted tree of any arity stored in a single vector: nodes are identified by
+// their index, the root being 0, and keep their parent and their children in
+// insertion order. Every node stores the size of its subtree, updated along
+// the ancestors when a node is added, so reading it is O(1) and adding is
+// O(depth). Trees that never read sizes and grow into long chains can skip
+// that upkeep, making additions O(1). Traversals are iterators with explicit
+// stacks, so deep trees do not overflow the call stack.
+
+use std::ops::{Index, IndexMut};
+
+/// Reason why a mutation of a tree was rejected. New variants may be added,
+/// so matches need a wildcard arm.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TreeError {
+    /// `id` is not a node of a tree with `len` nodes
+    NodeNotFound { id: usize, len: usize },
+    /// The binary node `parent` already has the child on that side
+    ChildAlreadySet { parent: usize, is_left: bool },
+}
+
+impl std::fmt::Display for TreeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TreeError::NodeNotFound { id, len } => {
+                write!(f, "node {} does not exist in a tree of {} nodes", id, len)
+            }
+            TreeError::ChildAlreadySet { parent, is_left } => {
+                let side = if *is_left { "left" } else { "right" };
+                write!(f, "node {} has its {} child already set", parent, side)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TreeError {}
+
+struct ArenaNode<T> {
+    value: T,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    size: usize, // Nodes in the subtree, the node included, if sizes are kept
+}
+
+pub struct ArenaTree<T> {
+    nodes: Vec<ArenaNode<T>>,
+    keep_sizes: bool, // Whether add_child updates the sizes of the ancestors
+}
+
+impl<T> ArenaTree<T> {
+    /// Id of the root
+    pub const ROOT: usize = 0;
+
+    /// A tree made of a root holding `value`
+    pub fn with_root(value: T) -> Self {
+        Self {
+            nodes: vec![ArenaNode {
+                value,
+                parent: None,
+                children: Vec::new(),
+                size: 1,
+            }],
+            keep_sizes: true,
+        }
+    }
+
+    /// A tree made of a root holding `value` that does not keep subtree
+    /// sizes: add_child is O(1) and subtree_size walks the subtree
+    pub fn with_root_unsized(value: T) -> Self {
+        Self {
+            keep_sizes: false,
+            ..Self::with_root(value)
+        }
+    }
+
+    /// Number of nodes, at least one
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Always false, as a tree has a root
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Adds a node holding `value` as the last child of `parent` and returns
+    /// its id
+    pub fn add_child(&mut self, parent: usize, value: T) -> Result<usize, TreeError> {
+        self.check(parent)?;
+        let id = self.nodes.len();
+        self.nodes.push(ArenaNode {
+            value,
+            parent: Some(parent),
+            children: Vec::new(),
+            size: 1,
+        });
+        self.nodes[parent].children.push(id);
+        if self.keep_sizes {
+            let mut v = Some(parent);
+            while let Some(u) = v {
+                self.nodes[u].size += 1;
+                v = self.nodes[u].parent;
+            }
+        }
+        Ok(id)
+    }
+
+    /// Value of the node `id`, None if there is no such node
+    pub fn get(&self, id: usize) -> Option<&T> {
+        self.nodes.get(id).map(|node| &node.value)
+    }
+
+    pub fn get_mut(&mut self, id: usize) -> Option<&mut T> {
+        self.nodes.get_mut(id).map(|node| &mut node.value)
+    }
+
+    /// Parent of `id`, None for the root
+    pub fn parent(&self, id: usize) -> Option<usize> {
+        self.node(id).parent
+    }
+
+    /// Children of `id` in insertion order
+    pub fn children(&self, id: usize) -> &[usize] {
+        &self.node(id).children
+    }
+
+    /// Number of nodes in the subtree of `id`, `id` included, in O(1), or
+    /// O(size) on a tree built with `with_root_unsized`
+    pub fn subtree_size(&self, id: usize) -> usize {
+        if self.keep_sizes {
+            self.node(id).size
+        } else {
+            self.preorder(id).count()
+        }
+    }
+
+    /// Number of edges between `id` and the root
+    pub fn depth(&self, id: usize) -> usize {
+        self.ancestors(id).count()
+    }
+
+    /// Proper ancestors of `id`, from its parent up to the root
+    pub fn ancestors(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+        std::iter::successors(self.parent(id), move |&v| self.nodes[v].parent)
+    }
+
+    /// Nodes of the subtree of `id` in preorder: every node before its
+    /// children, children in insertion order
+    pub fn preorder(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+        self.node(id);
+        let mut stack = vec![id];
+        std::iter::from_fn(move || {
+            let v = stack.pop()?;
+            stack.extend(self.nodes[v].children.iter().rev());
+            Some(v)
+        })
+    }
+
+    /// Nodes of the subtree of `id` in postorder: every node after its
+    /// children, children in insertion order
+    pub fn postorder(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+        self.node(id);
+        // (node, number of its children already visited)
+        let mut stack = vec![(id, 0)];
+        std::iter::from_fn(move || loop {
+            let (v, next) = stack.last_mut()?;
+            let v = *v;
+            match self.nodes[v].children.get(*next) {
+                Some(&child) => {
+                    *next += 1;
+                    stack.push((child, 0));
+                }
+                None => {
+                    stack.pop();
+                    return Some(v);
+                }
+            }
+        })
+    }
+
+    /// Ok if `id` is a node of the tree
+    fn check(&self, id: usize) -> Result<(), TreeError> {
+        if id < self.nodes.len() {
+            Ok(())
+        } else {
+            Err(TreeError::NodeNotFound {
+                id,
+                len: self.nodes.len(),
+            })
+        }
+    }
+
+    /// The node `id`, panicking with the TreeError message if it does not exist
+    fn node(&self, id: usize) -> &ArenaNode<T> {
+        if let Err(error) = self.check(id) {
+            panic!("{}", error);
+        }
+        &self.nodes[id]
+    }
+}
+
+impl<T> Index<usize> for ArenaTree<T> {
+    type Output = T;
+
+    fn index(&self, id: usize) -> &T {
+        &self.node(id).value
+    }
+}
+
+impl<T> IndexMut<usize> for ArenaTree<T> {
+    fn index_mut(&mut self, id: usize) -> &mut T {
+        self.node(id);
+        &mut self.nodes[id].value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A tree of `n` nodes where node i hangs from a pseudo-random earlier node
+    fn build(n: usize) -> ArenaTree<usize> {
+        let mut tree = ArenaTree::with_root(0);
+        for i in 1..n {
+            let parent = (i * 7919 + 13) % i;
+            assert_eq!(tree.add_child(parent, i), Ok(i));
+        }
+        tree
+    }
+
+    #[test]
+    fn test_structure() {
+        let tree = build(200);
+        assert_eq!(tree.len(), 200);
+        assert_eq!(tree.parent(ArenaTree::<usize>::ROOT), None);
+        for v in 0..tree.len() {
+            assert_eq!(tree[v], v);
+            // sizes match the traversals, which visit every node once
+            let pre: Vec<usize> = tree.preorder(v).collect();
+            let mut post: Vec<usize> = tree.postorder(v).collect();
+            assert_eq!(pre.len(), tree.subtree_size(v));
+            assert_eq!(pre[0], v);
+            assert_eq!(post.last(), Some(&v));
+            // in postorder every node comes after its children
+            for (i, &u) in post.iter().enumerate() {
+                assert!(tree.children(u).iter().all(|c| post[..i].contains(c)));
+            }
+            post.sort();
+            let mut sorted = pre.clone();
+            sorted.sort();
+            assert_eq!(post, sorted);
+            for &c in tree.children(v) {
+                assert_eq!(tree.parent(c), Some(v));
+            }
+            let ancestors: Vec<usize> = tree.ancestors(v).collect();
+            assert_eq!(ancestors.len(), tree.depth(v));
+            assert_eq!(ancestors.last().copied(), (v > 0).then_some(0));
+        }
+        assert_eq!(tree.subtree_size(0), 200);
+    }
+
+    #[test]
+    fn test_sizes_follow_insertions() {
+        let mut tree = ArenaTree::with_root(0);
+        let mut chain = ArenaTree::with_root_unsized(0);
+        let mut bottom = 0;
+        for i in 1..100 {
+            bottom = tree.add_child(bottom, i).unwrap();
+            chain.add_child(i - 1, i).unwrap();
+            if i % 3 == 0 {
+                tree.add_child(i / 2, i).unwrap();
+            }
+            for v in 0..tree.len() {
+                assert_eq!(tree.subtree_size(v), tree.preorder(v).count());
+            }
+            assert_eq!(chain.subtree_size(0), i + 1);
+        }
+        assert_eq!(chain.subtree_size(50), 50);
+    }
+
+    #[test]
+    fn test_errors_and_values() {
+        let mut tree = ArenaTree::with_root("root");
+        let a = tree.add_child(0, "a").unwrap();
+        let b = tree.add_child(a, "b").unwrap();
+        assert_eq!(
+            tree.add_child(7, "c"),
+            Err(TreeError::NodeNotFound { id: 7, len: 3 })
+        );
+        assert_eq!(tree.len(), 3);
+        tree[b] = "bb";
+        *tree.get_mut(a).unwrap() = "aa";
+        assert_eq!(tree.get(b), Some(&"bb"));
+        assert_eq!(tree.get(a), Some(&"aa"));
+        assert_eq!(tree.get(3), None);
+        assert_eq!(tree.subtree_size(a), 2);
+        assert_eq!(tree.depth(b), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "node 5 does not exist in a tree of 1 nodes")]
+    fn test_missing_node_panics() {
+        ArenaTree::with_root(1).children(5);
+    }
+}
@@ -0,0 +1,121 @@
+// ------- FLATTENED TREE -------
+// Subtree queries on a Tree with the Euler tour and the segment tree of
+// handson2. The tour numbers the nodes in preorder, turning the subtree of
+// every node into a contiguous range [tin, tout], so the keys laid out in that
+// order go into a MaxSegmentTree, and subtree maximum and subtree chmin become
+// its range query and range update, in O(log n).
+
+use crate::{Tree, TreeError};
+use handson2::euler_tour::EulerTourFlatten;
+use handson2::MaxSegmentTree;
+
+/// The keys of a Tree in preorder, behind a MaxSegmentTree. It is a snapshot:
+/// nodes added to the tree afterwards are not part of it
+pub struct FlatTree {
+    tour: EulerTourFlatten,
+    segment_tree: MaxSegmentTree,
+}
+
+impl FlatTree {
+    /// Number of nodes
+    pub fn len(&self) -> usize {
+        self.tour.len()
+    }
+
+    /// Always false, as a tree has a root
+    pub fn is_empty(&self) -> bool {
+        self.tour.is_empty()
+    }
+
+    /// Positions [start, end] of the subtree of `id` in the preorder
+    fn range(&self, id: usize) -> Result<(usize, usize), TreeError> {
+        if id >= self.len() {
+            return Err(TreeError::NodeNotFound {
+                id,
+                len: self.len(),
+            });
+        }
+        Ok(self.tour.subtree_range(id))
+    }
+
+    /// Current key of `id`, None if there is no such node
+    pub fn key(&mut self, id: usize) -> Option<u32> {
+        let (start, _) = self.range(id).ok()?;
+        Some(self.segment_tree.query(start, start))
+    }
+
+    /// Largest key in the subtree of `id`, None if there is no such node
+    pub fn subtree_max(&mut self, id: usize) -> Option<u32> {
+        let (start, end) = self.range(id).ok()?;
+        Some(self.segment_tree.query(start, end))
+    }
+
+    /// Replace every key k in the subtree of `id` with min(k, value)
+    pub fn update_subtree_chmin(&mut self, id: usize, value: u32) -> Result<(), TreeError> {
+        let (start, end) = self.range(id)?;
+        self.segment_tree.update(start, end, value);
+        Ok(())
+    }
+}
+
+impl Tree {
+    /// Flatten the tree by its preorder into a `FlatTree` answering subtree
+    /// maximum and subtree chmin queries, in O(n)
+    pub fn flatten(&self) -> FlatTree {
+        let children: Vec<Vec<usize>> = (0..self.len())
+            .map(|id| self.nodes.children(id).to_vec())
+            .collect();
+        let tour = EulerTourFlatten::new(&children, 0);
+        let keys: Vec<u32> = (0..self.len()).map(|id| self.nodes[id].key).collect();
+        FlatTree {
+            segment_tree: MaxSegmentTree::new(&tour.flatten(&keys)),
+            tour,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use handson2::rng::Rng;
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = Rng::new(17);
+        for _ in 0..50 {
+            let mut tree = Tree::with_root(rng.gen_range(0, 999) as u32);
+            for _ in 0..rng.gen_range(0, 99) {
+                let parent = rng.gen_range(0, tree.len() as u64 - 1) as usize;
+                let key = rng.gen_range(0, 999) as u32;
+                let _ = tree.try_add_node(parent, key, rng.gen_bool(0.5));
+            }
+            let n = tree.len();
+            let mut flat = tree.flatten();
+            let mut keys: Vec<u32> = (0..n).map(|id| tree.key(id).unwrap()).collect();
+            // nodes of the subtree of `id`, by climbing from every node
+            let in_subtree = |v: usize, id: usize| {
+                std::iter::successors(Some(v), |&x| tree.parent(x)).any(|x| x == id)
+            };
+            for _ in 0..100 {
+                let id = rng.gen_range(0, n as u64 - 1) as usize;
+                let subtree: Vec<usize> = (0..n).filter(|&v| in_subtree(v, id)).collect();
+                if rng.gen_bool(0.5) {
+                    let value = rng.gen_range(0, 999) as u32;
+                    assert_eq!(flat.update_subtree_chmin(id, value), Ok(()));
+                    for &v in &subtree {
+                        keys[v] = keys[v].min(value);
+                    }
+                } else {
+                    let expected = subtree.iter().map(|&v| keys[v]).max();
+                    assert_eq!(flat.subtree_max(id), expected);
+                }
+                assert_eq!(flat.key(id), Some(keys[id]));
+            }
+            assert_eq!(flat.subtree_max(n), None);
+            assert_eq!(
+                flat.update_subtree_chmin(n, 0),
+                Err(TreeError::NodeNotFound { id: n, len: n })
+            );
+        }
+    }
+}
@@ -1,138 +1,564 @@
+//! Hands-on 1: binary trees stored in an arena, with a check for the Binary
+//! Search Tree property under several duplicate policies, and maximum path
+//! sums between leaves, between any two nodes or from the root to a leaf.
+//! BSTs can be rebuilt into balanced ones with `Tree::rebalance`, and nodes
+//! related by `Tree::lca` and `Tree::distance`. `Tree::flatten` lays the
+//! keys out in preorder behind the `MaxSegmentTree` of handson2, for subtree
+//! maximum and subtree chmin queries.
+//!
+//! Build a `Tree` from its root with `Tree::with_root`, then attach children
+//! with `Tree::add_node` (or `Tree::try_add_node`, which reports errors instead
+//! of panicking). Nodes are identified by the ids these methods return, the
+//! root being 0. The generic n-ary `ArenaTree` behind it is exported as well.
+
+pub mod arena;
+pub mod flat;
+
+pub use arena::{ArenaTree, TreeError};
+pub use flat::FlatTree;
 use std::cmp::{max, min};
+use std::sync::OnceLock;
 
+/// A binary tree node: its key, and whether it is the left or the right child
+/// of its parent (meaningless for the root)
 struct Node {
     key: u32,
-    id_left: Option<usize>,
-    id_right: Option<usize>,
+    is_left: bool,
 }
 
 impl Node {
-    fn new(key: u32) -> Self {
-        Self {
-            key,
-            id_left: None,
-            id_right: None,
+    fn new(key: u32, is_left: bool) -> Self {
+        Self { key, is_left }
+    }
+}
+
+/// Where keys equal to the key of a node may appear in a BST
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BstPolicy {
+    /// Left subtree keys are <= the node key, right subtree keys are >
+    #[default]
+    DuplicatesLeft,
+    /// Left subtree keys are < the node key, right subtree keys are >=
+    DuplicatesRight,
+    /// Left subtree keys are < the node key, right subtree keys are >
+    NoDuplicates,
+}
+
+impl BstPolicy {
+    /// Whether `key` fits between the largest key `max_left` of its left
+    /// subtree and the smallest key `min_right` of its right subtree
+    fn allows(self, max_left: Option<u32>, key: u32, min_right: Option<u32>) -> bool {
+        let (left_ok, right_ok) = match self {
+            BstPolicy::DuplicatesLeft => (
+                max_left.is_none_or(|m| m <= key),
+                min_right.is_none_or(|m| key < m),
+            ),
+            BstPolicy::DuplicatesRight => (
+                max_left.is_none_or(|m| m < key),
+                min_right.is_none_or(|m| key <= m),
+            ),
+            BstPolicy::NoDuplicates => (
+                max_left.is_none_or(|m| m < key),
+                min_right.is_none_or(|m| key < m),
+            ),
+        };
+        left_ok && right_ok
+    }
+}
+
+/// Endpoints allowed for the paths of `Tree::max_path`, a leaf being a node
+/// without children
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PathKind {
+    /// Two distinct leaves, as in `Tree::max_path_sum`
+    LeafToLeaf,
+    /// Any two nodes, possibly the same one
+    AnyToAny,
+    /// The root and a leaf, the root alone if it is a leaf
+    RootToLeaf,
+}
+
+/// Aggregates of the keys of a subtree, see `Tree::subtree_stats`. New
+/// aggregates may be added, so it cannot be built or destructured outside
+/// this crate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SubtreeStats {
+    pub size: usize,   // Number of nodes
+    pub sum: u64,      // Sum of the keys
+    pub min: u32,      // Smallest key
+    pub max: u32,      // Largest key
+    pub height: usize, // Edges on the longest path down to a leaf
+}
+
+impl SubtreeStats {
+    fn leaf(key: u32) -> Self {
+        SubtreeStats {
+            size: 1,
+            sum: key as u64,
+            min: key,
+            max: key,
+            height: 0,
         }
     }
 }
 
-struct Tree {
-    nodes: Vec<Node>,
+/// A binary tree of u32 keys on top of the generic `ArenaTree`, whose nodes
+/// have at most one left and one right child. The fields are private: the
+/// tree is inspected through accessors, so its representation can change.
+pub struct Tree {
+    nodes: ArenaTree<Node>,
+    lifting: OnceLock<Lifting>, // Built by the first ancestor query, reset by add_node
+    stats: OnceLock<Vec<SubtreeStats>>, // Built by the first subtree query
+}
+
+/// Binary lifting tables: `up[k][v]` is the ancestor of `v` 2^k levels up,
+/// the root for the ones that would be above it
+struct Lifting {
+    depth: Vec<usize>,
+    up: Vec<Vec<usize>>,
+}
+
+impl Lifting {
+    fn new(tree: &Tree) -> Self {
+        let n = tree.len();
+        let mut depth = vec![0; n];
+        let mut parent = vec![0; n];
+        // the preorder visits every parent before its children
+        for id in tree.nodes.preorder(0).skip(1) {
+            parent[id] = tree.nodes.parent(id).unwrap();
+            depth[id] = depth[parent[id]] + 1;
+        }
+        let mut up = vec![parent];
+        while 1 << up.len() < n {
+            let last = up.last().unwrap();
+            let next = last.iter().map(|&v| last[v]).collect();
+            up.push(next);
+        }
+        Lifting { depth, up }
+    }
+
+    fn lca(&self, a: usize, b: usize) -> usize {
+        let (mut a, mut b) = if self.depth[a] >= self.depth[b] {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        let diff = self.depth[a] - self.depth[b];
+        for (k, up) in self.up.iter().enumerate() {
+            if diff >> k & 1 == 1 {
+                a = up[a];
+            }
+        }
+        if a == b {
+            return a;
+        }
+        for up in self.up.iter().rev() {
+            if up[a] != up[b] {
+                a = up[a];
+                b = up[b];
+            }
+        }
+        self.up[0][a]
+    }
 }
 
 impl Tree {
+    /// A tree made of a root, with id 0, holding `key`
     pub fn with_root(key: u32) -> Self {
         Self {
-            nodes: vec![Node::new(key)],
+            // sizes come with the subtree stats, so deep chains grow in O(1)
+            nodes: ArenaTree::with_root_unsized(Node::new(key, true)),
+            lifting: OnceLock::new(),
+            stats: OnceLock::new(),
         }
     }
 
+    /// Number of nodes, at least one
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Always false, as a tree has a root
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Key of the node `id`, None if there is no such node
+    pub fn key(&self, id: usize) -> Option<u32> {
+        self.nodes.get(id).map(|node| node.key)
+    }
+
+    /// Left child of `id`, None if it has none or if `id` does not exist
+    pub fn left(&self, id: usize) -> Option<usize> {
+        (id < self.len()).then(|| self.child(id, true)).flatten()
+    }
+
+    /// Right child of `id`, None if it has none or if `id` does not exist
+    pub fn right(&self, id: usize) -> Option<usize> {
+        (id < self.len()).then(|| self.child(id, false)).flatten()
+    }
+
+    /// Parent of `id`, None for the root or if `id` does not exist
+    pub fn parent(&self, id: usize) -> Option<usize> {
+        (id < self.len()).then(|| self.nodes.parent(id)).flatten()
+    }
+
+    /// Number of edges on the longest path from the root down to a leaf
+    pub fn height(&self) -> usize {
+        // the preorder visits every parent before its children
+        let mut depth = vec![0; self.len()];
+        for id in self.nodes.preorder(0).skip(1) {
+            depth[id] = depth[self.nodes.parent(id).unwrap()] + 1;
+        }
+        depth.into_iter().max().unwrap_or(0)
+    }
+
+    /// The left child of `id` if `is_left` is `true`, the right child otherwise
+    fn child(&self, id: usize, is_left: bool) -> Option<usize> {
+        self.nodes
+            .children(id)
+            .iter()
+            .copied()
+            .find(|&c| self.nodes[c].is_left == is_left)
+    }
+
     /// Adds a child to the node with `parent_id` and returns the id of the new node.
     /// The new node has the specified `key`. The new node is the left  child of the
     /// node `parent_id` iff `is_left` is `true`, the right child otherwise.
     ///
     /// # Panics
-    /// Panics if the `parent_id` does not exist, or if the node `parent_id ` has
-    /// the child already set.
+    /// Panics with the message of the TreeError if the `parent_id` does not
+    /// exist, or if the node `parent_id` has the child already set.
     pub fn add_node(&mut self, parent_id: usize, key: u32, is_left: bool) -> usize {
-        assert!(
-            parent_id < self.nodes.len(),
-            "Parent node id does not exist"
-        );
-        if is_left {
-            assert_eq!(
-                self.nodes[parent_id].id_left, None,
-                "Parent node has the left child already set"
-            );
-        } else {
-            assert_eq!(
-                self.nodes[parent_id].id_right, None,
-                "Parent node has the right child already set"
-            );
-        }
-
-        let child_id = self.nodes.len();
-        self.nodes.push(Node::new(key));
-
-        let child = if is_left {
-            &mut self.nodes[parent_id].id_left
-        } else {
-            &mut self.nodes[parent_id].id_right
-        };
-
-        *child = Some(child_id);
+        self.try_add_node(parent_id, key, is_left)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
 
-        child_id
+    /// Checked add_node: the id of the new node, or the reason why it cannot
+    /// be added
+    pub fn try_add_node(
+        &mut self,
+        parent_id: usize,
+        key: u32,
+        is_left: bool,
+    ) -> Result<usize, TreeError> {
+        if parent_id < self.len() && self.child(parent_id, is_left).is_some() {
+            return Err(TreeError::ChildAlreadySet {
+                parent: parent_id,
+                is_left,
+            });
+        }
+        let id = self.nodes.add_child(parent_id, Node::new(key, is_left))?;
+        self.lifting = OnceLock::new();
+        // once queried, the aggregates of the ancestors follow every insertion
+        if let Some(stats) = self.stats.get_mut() {
+            stats.push(SubtreeStats::leaf(key));
+            for (distance, v) in self.nodes.ancestors(id).enumerate() {
+                let s = &mut stats[v];
+                s.size += 1;
+                s.sum += key as u64;
+                s.min = min(s.min, key);
+                s.max = max(s.max, key);
+                s.height = max(s.height, distance + 1);
+            }
+        }
+        Ok(id)
     }
 
     /* ---------- Exercise  #1 ---------- */
     /* Write a method to check if the binary tree is a Binary Search Tree. */
 
-    ///return True if the tree is a BST
+    /// Return true if the tree is a BST: every key is at least the keys of its
+    /// left subtree and smaller than the keys of its right subtree
     pub fn is_bst(&self) -> bool {
-        self.rec_is_bst(Some(0)).0
-    }
-
-    /// A private recursive function that check if a
-    /// subtree rooted at `node_id` is a BST
-    fn rec_is_bst(&self, node_id: Option<usize>) -> (bool, u32, u32) {
-        if let Some(id) = node_id {
-            assert!(id < self.nodes.len(), "Node id is out of range");
-            let node: &Node = &self.nodes[id];
-            let (ans_l, max_l, min_l) = self.rec_is_bst(node.id_left);
-            let (ans_r, max_r, min_r) = self.rec_is_bst(node.id_right);
-            let ans_node: bool = ans_l && ans_r && node.key >= max_l && node.key < min_r;
-            let max_node = max(node.key, max(max_l, max_r));
-            let min_node = min(node.key, min(min_l, min_r));
-
-            return (ans_node, max_node, min_node);
-        }
+        self.is_bst_with(BstPolicy::DuplicatesLeft)
+    }
 
-        (true, 0, u32::MAX)
+    /// Return true if the tree is a BST where duplicate keys follow `policy`
+    pub fn is_bst_with(&self, policy: BstPolicy) -> bool {
+        // (is a BST, largest key, smallest key) of every subtree, filled in
+        // postorder with an explicit stack so that deep trees do not overflow
+        let mut subtree = vec![(true, 0, 0); self.len()];
+        for id in self.nodes.postorder(0) {
+            let key = self.nodes[id].key;
+            let left = self.child(id, true).map(|c| subtree[c]);
+            let right = self.child(id, false).map(|c| subtree[c]);
+            let ans_node = left.is_none_or(|l| l.0)
+                && right.is_none_or(|r| r.0)
+                && policy.allows(left.map(|l| l.1), key, right.map(|r| r.2));
+            let children = [left, right].into_iter().flatten();
+            let max_node = children.clone().map(|c| c.1).fold(key, max);
+            let min_node = children.map(|c| c.2).fold(key, min);
+            subtree[id] = (ans_node, max_node, min_node);
+        }
+        subtree[0].0
     }
 
     /* ---------- Exercise  #2 ---------- */
     /* Write a method to solve the Maximum Path Sum problem. The method must return
     the sum of the maximum simple path connecting two leaves. */
 
-    /// return the maximum path sum
+    /// Return the maximum sum of the keys on a simple path connecting two
+    /// leaves, None if the tree has fewer than two leaves
     pub fn max_path_sum(&self) -> Option<u32> {
-        self.rec_max_path_sum(Some(0)).0
-    }
-
-    /// A private recursive function that return the maximum path sum and
-    /// the maximum leaf-node path cost for a subtree rooted at `node_id`.
-    /// If there not exist a path between two leaves, return None
-    fn rec_max_path_sum(&self, node_id: Option<usize>) -> (Option<u32>, Option<u32>) {
-        if let Some(id) = node_id {
-            assert!(id < self.nodes.len(), "Node id is out of range");
-            let node = &self.nodes[id];
-            let (best_l, max_l) = self.rec_max_path_sum(node.id_left);
-            let (best_r, max_r) = self.rec_max_path_sum(node.id_right);
-
-            let ml = max_l.unwrap_or(0);
-            let mr = max_r.unwrap_or(0);
-            let bl = best_l.unwrap_or(0);
-            let br = best_r.unwrap_or(0);
-
-            let path = node.key + ml + mr;
-            let best = Some(max(path, max(bl, br)));
-            let max_val = Some(max(ml, mr) + node.key);
-
-            match (best_l, max_l, best_r, max_r) {
-                // if everything is defined or i do not receive a best so far from children
-                (_, Some(_), _, Some(_)) => (best, max_val),
-                // if I don't have both best so far and max from right child
-                (Some(_), Some(_), None, None) => (Some(bl), Some(path)),
-                // if I don't have both best so far and max from left child
-                (None, None, Some(_), Some(_)) => (Some(br), Some(path)),
-                // if I only have max from left or from right or if I'm a leaf
-                (None, _, None, _) => (None, Some(path)),
-                _ => unreachable!("This code should never be reached"),
+        // the pair of max_path_sum_step for every subtree, filled in
+        // postorder with an explicit stack so that deep trees do not overflow
+        let mut subtree = vec![(None, None); self.len()];
+        for id in self.nodes.postorder(0) {
+            let left = self.child(id, true).map_or((None, None), |c| subtree[c]);
+            let right = self.child(id, false).map_or((None, None), |c| subtree[c]);
+            subtree[id] = self.max_path_sum_step(id, left, right);
+        }
+        subtree[0].0
+    }
+
+    /// Return the maximum path sum and the maximum leaf-node path cost for
+    /// the subtree rooted at `id` from the ones of its `left` and `right`
+    /// subtrees, (None, None) for a missing child. If there not exist a path
+    /// between two leaves, the maximum path sum is None
+    fn max_path_sum_step(
+        &self,
+        id: usize,
+        (best_l, max_l): (Option<u32>, Option<u32>),
+        (best_r, max_r): (Option<u32>, Option<u32>),
+    ) -> (Option<u32>, Option<u32>) {
+        let node = &self.nodes[id];
+
+        let ml = max_l.unwrap_or(0);
+        let mr = max_r.unwrap_or(0);
+        let bl = best_l.unwrap_or(0);
+        let br = best_r.unwrap_or(0);
+
+        let path = node.key + ml + mr;
+        let best = Some(max(path, max(bl, br)));
+        let max_val = Some(max(ml, mr) + node.key);
+
+        match (best_l, max_l, best_r, max_r) {
+            // if everything is defined or i do not receive a best so far from children
+            (_, Some(_), _, Some(_)) => (best, max_val),
+            // if I don't have both best so far and max from right child
+            (Some(_), Some(_), None, None) => (Some(bl), Some(path)),
+            // if I don't have both best so far and max from left child
+            (None, None, Some(_), Some(_)) => (Some(br), Some(path)),
+            // if I only have max from left or from right or if I'm a leaf
+            (None, _, None, _) => (None, Some(path)),
+            _ => unreachable!("This code should never be reached"),
+        }
+    }
+
+    /// Return the maximum sum of the keys on a path of the given kind, None
+    /// if there is no such path (only possible for `PathKind::LeafToLeaf`).
+    /// Sums are u64, so long paths do not overflow
+    pub fn max_path_sum_with(&self, kind: PathKind) -> Option<u64> {
+        self.max_path(kind).map(|(sum, _)| sum)
+    }
+
+    /// Return the maximum sum of the keys on a path of the given kind along
+    /// with the ids of the nodes of the path, from one endpoint to the other
+    /// (from the root for `PathKind::RootToLeaf`)
+    pub fn max_path(&self, kind: PathKind) -> Option<(u64, Vec<usize>)> {
+        // down[v] = maximum sum of a path from v down to a leaf
+        let mut down = vec![0; self.len()];
+        // best sum of a path of the required kind and its topmost node
+        let mut best: Option<(u64, usize)> = None;
+        for id in self.nodes.postorder(0) {
+            let key = self.nodes[id].key as u64;
+            let down_l = self.child(id, true).map(|c| down[c]);
+            let down_r = self.child(id, false).map(|c| down[c]);
+            down[id] = key + max(down_l, down_r).unwrap_or(0);
+            let through = match kind {
+                PathKind::LeafToLeaf => down_l.zip(down_r).map(|(l, r)| key + l + r),
+                PathKind::AnyToAny => Some(key + down_l.unwrap_or(0) + down_r.unwrap_or(0)),
+                PathKind::RootToLeaf => (id == 0).then_some(down[id]),
+            };
+            if let Some(sum) = through {
+                if best.is_none_or(|(b, _)| sum > b) {
+                    best = Some((sum, id));
+                }
             }
-        } else {
-            (None, None)
         }
+
+        let (sum, top) = best?;
+        if kind == PathKind::RootToLeaf {
+            return Some((sum, self.descend(&down, top)));
+        }
+        let mut path = match self.child(top, true) {
+            Some(l) => self.descend(&down, l),
+            None => Vec::new(),
+        };
+        path.reverse();
+        path.push(top);
+        if let Some(r) = self.child(top, false) {
+            path.extend(self.descend(&down, r));
+        }
+        Some((sum, path))
+    }
+
+    /* ---------- Ancestors ---------- */
+
+    /// Lowest common ancestor of `a` and `b`, the deepest node having both in
+    /// its subtree, None if either does not exist. The first query after a
+    /// change of the tree builds binary lifting tables in O(n log n), the
+    /// next ones take O(log n)
+    pub fn lca(&self, a: usize, b: usize) -> Option<usize> {
+        if a >= self.len() || b >= self.len() {
+            return None;
+        }
+        Some(self.lifting().lca(a, b))
+    }
+
+    /// Number of edges on the path between `a` and `b`, None if either does
+    /// not exist
+    pub fn distance(&self, a: usize, b: usize) -> Option<usize> {
+        let c = self.lca(a, b)?;
+        let depth = &self.lifting().depth;
+        Some(depth[a] + depth[b] - 2 * depth[c])
+    }
+
+    fn lifting(&self) -> &Lifting {
+        self.lifting.get_or_init(|| Lifting::new(self))
+    }
+
+    /* ---------- Subtree aggregates ---------- */
+
+    /// Size, key sum, smallest and largest key and height of the subtree of
+    /// `id`, None if there is no such node. The first query computes them for
+    /// every node in O(n), then add_node keeps them up to date in O(depth),
+    /// so building a tree before querying it stays linear
+    pub fn subtree_stats(&self, id: usize) -> Option<SubtreeStats> {
+        (id < self.len()).then(|| self.stats()[id])
+    }
+
+    fn stats(&self) -> &[SubtreeStats] {
+        self.stats.get_or_init(|| {
+            let mut stats = vec![SubtreeStats::leaf(0); self.len()];
+            for id in self.nodes.postorder(0) {
+                let mut s = SubtreeStats::leaf(self.nodes[id].key);
+                for &c in self.nodes.children(id) {
+                    let c = stats[c];
+                    s.size += c.size;
+                    s.sum += c.sum;
+                    s.min = min(s.min, c.min);
+                    s.max = max(s.max, c.max);
+                    s.height = max(s.height, c.height + 1);
+                }
+                stats[id] = s;
+            }
+            stats
+        })
+    }
+
+    /* ---------- Balancing ---------- */
+
+    /// If the tree is a BST (in the sense of `is_bst`), rebuild it with the
+    /// same keys into a BST of minimum height and return the new id of every
+    /// old node, `mapping[old_id]`, the new root being 0 as always. Runs of
+    /// equal keys must stay on the left of each other, so they may keep the
+    /// tree higher than the minimum. Return None and leave the tree untouched
+    /// if it is not a BST
+    pub fn rebalance(&mut self) -> Option<Vec<usize>> {
+        if !self.is_bst() {
+            return None;
+        }
+        let order: Vec<usize> = self.inorder().collect();
+        let key = |i: usize| self.nodes[order[i]].key;
+        // middle of the sorted range [lo, hi), moved to the last of its
+        // duplicates so that they all go to its left
+        let pick = |lo: usize, hi: usize| {
+            let mut mid = lo + (hi - lo) / 2;
+            while mid + 1 < hi && key(mid + 1) == key(mid) {
+                mid += 1;
+            }
+            mid
+        };
+
+        let mut mapping = vec![0; self.len()];
+        let root = pick(0, order.len());
+        let mut balanced = Tree::with_root(key(root));
+        // (range, root of the range, its new id)
+        let mut stack = vec![(0, order.len(), root, 0)];
+        while let Some((lo, hi, mid, id)) = stack.pop() {
+            mapping[order[mid]] = id;
+            for (lo, hi, is_left) in [(lo, mid, true), (mid + 1, hi, false)] {
+                if lo < hi {
+                    let child = pick(lo, hi);
+                    let child_id = balanced.add_node(id, key(child), is_left);
+                    stack.push((lo, hi, child, child_id));
+                }
+            }
+        }
+        *self = balanced;
+        Some(mapping)
+    }
+
+    /// Iterator over the node ids in symmetric order (left subtree, node,
+    /// right subtree) using O(1) extra space and O(n) time overall. It
+    /// replaces a Morris traversal with a walk on the parent pointers, with
+    /// the same space bound and without modifying the tree
+    pub fn inorder(&self) -> Inorder<'_> {
+        Inorder {
+            tree: self,
+            next: Some(self.leftmost(0)),
+        }
+    }
+
+    /// The node reached from `id` by going left as long as possible
+    fn leftmost(&self, id: usize) -> usize {
+        let mut id = id;
+        while let Some(left) = self.child(id, true) {
+            id = left;
+        }
+        id
+    }
+
+    /// The path from `id` down to a leaf following the children with the
+    /// largest `down` sums
+    fn descend(&self, down: &[u64], id: usize) -> Vec<usize> {
+        let mut path = vec![id];
+        let mut id = id;
+        while let Some(next) = [self.child(id, true), self.child(id, false)]
+            .into_iter()
+            .flatten()
+            .max_by_key(|&c| down[c])
+        {
+            path.push(next);
+            id = next;
+        }
+        path
+    }
+}
+
+/// Inorder iterator of a `Tree`, used instead of a Morris traversal. Morris
+/// gets O(1) extra space by threading the tree temporarily, so it needs the
+/// tree mutably. Here nodes know their parent: the iterator only stores the
+/// next id, O(1) extra space as well, and finds each successor as the leftmost
+/// node of the right subtree or by climbing, so the tree is only borrowed.
+pub struct Inorder<'a> {
+    tree: &'a Tree,
+    next: Option<usize>,
+}
+
+impl Iterator for Inorder<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let id = self.next?;
+        self.next = match self.tree.child(id, false) {
+            Some(right) => Some(self.tree.leftmost(right)),
+            // climb to the first ancestor whose left subtree holds `id`
+            None => {
+                let mut v = id;
+                loop {
+                    match self.tree.nodes.parent(v) {
+                        None => break None,
+                        Some(p) if self.tree.nodes[v].is_left => break Some(p),
+                        Some(p) => v = p,
+                    }
+                }
+            }
+        };
+        Some(id)
     }
 }
 
@@ -141,6 +567,7 @@ impl Tree {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use handson2::rng::Rng;
 
     /// test for exercise 1
     #[test]
@@ -303,4 +730,372 @@ mod tests {
             "This tree has max path sum of 272"
         );
     }
+
+    /// A random binary tree of `n` nodes with keys below `max_key`, each node
+    /// attached to a free side of a random earlier node
+    fn random_tree(rng: &mut Rng, n: usize, max_key: u64) -> Tree {
+        let mut tree = Tree::with_root(rng.gen_range(0, max_key - 1) as u32);
+        while tree.len() < n {
+            let parent = rng.gen_range(0, tree.len() as u64 - 1) as usize;
+            let is_left = rng.gen_bool(0.5);
+            let _ = tree.try_add_node(parent, rng.gen_range(0, max_key - 1) as u32, is_left);
+        }
+        tree
+    }
+
+    /// Which endpoints a path kind accepts
+    type Ends<'a> = &'a dyn Fn(usize, usize) -> bool;
+
+    // test for the ancestor queries against climbing the parents
+    #[test]
+    fn test_lca_and_distance() {
+        let mut rng = Rng::new(5);
+        for _ in 0..100 {
+            let n = rng.gen_range(1, 80) as usize;
+            let mut tree = random_tree(&mut rng, n, 10);
+            for round in 0..2 {
+                let ancestors = |v: usize| -> Vec<usize> {
+                    std::iter::successors(Some(v), |&x| tree.parent(x)).collect()
+                };
+                for _ in 0..50 {
+                    let a = rng.gen_range(0, tree.len() as u64 - 1) as usize;
+                    let b = rng.gen_range(0, tree.len() as u64 - 1) as usize;
+                    let (up_a, up_b) = (ancestors(a), ancestors(b));
+                    let (i, &c) = up_a
+                        .iter()
+                        .enumerate()
+                        .find(|(_, v)| up_b.contains(v))
+                        .unwrap();
+                    let j = up_b.iter().position(|&v| v == c).unwrap();
+                    assert_eq!(tree.lca(a, b), Some(c));
+                    assert_eq!(tree.distance(a, b), Some(i + j));
+                }
+                assert_eq!(tree.lca(0, tree.len()), None);
+                assert_eq!(tree.distance(tree.len() + 3, 0), None);
+                // the tables are rebuilt after the tree grows
+                if round == 0 {
+                    for _ in 0..20 {
+                        let parent = rng.gen_range(0, tree.len() as u64 - 1) as usize;
+                        let _ = tree.try_add_node(parent, 0, rng.gen_bool(0.5));
+                    }
+                }
+            }
+        }
+    }
+
+    // test for the subtree aggregates, queried while the tree grows
+    #[test]
+    fn test_subtree_stats() {
+        let mut rng = Rng::new(9);
+        for _ in 0..50 {
+            let mut tree = Tree::with_root(rng.gen_range(0, 999) as u32);
+            for _ in 0..60 {
+                let parent = rng.gen_range(0, tree.len() as u64 - 1) as usize;
+                let _ = tree.try_add_node(parent, rng.gen_range(0, 999) as u32, rng.gen_bool(0.5));
+                let id = rng.gen_range(0, tree.len() as u64 - 1) as usize;
+                let nodes: Vec<usize> = tree.nodes.preorder(id).collect();
+                let keys: Vec<u32> = nodes.iter().map(|&v| tree.key(v).unwrap()).collect();
+                let depth = |v: usize| tree.nodes.depth(v);
+                let expected = SubtreeStats {
+                    size: nodes.len(),
+                    sum: keys.iter().map(|&k| k as u64).sum(),
+                    min: *keys.iter().min().unwrap(),
+                    max: *keys.iter().max().unwrap(),
+                    height: nodes.iter().map(|&v| depth(v)).max().unwrap() - depth(id),
+                };
+                assert_eq!(tree.subtree_stats(id), Some(expected));
+                assert_eq!(tree.subtree_stats(0).unwrap().height, tree.height());
+            }
+            assert_eq!(tree.subtree_stats(tree.len()), None);
+        }
+    }
+
+    // test for the inorder iterator against a recursive traversal
+    #[test]
+    fn test_inorder() {
+        fn visit(tree: &Tree, id: Option<usize>, order: &mut Vec<usize>) {
+            if let Some(id) = id {
+                visit(tree, tree.left(id), order);
+                order.push(id);
+                visit(tree, tree.right(id), order);
+            }
+        }
+        let mut rng = Rng::new(3);
+        for _ in 0..300 {
+            let n = rng.gen_range(1, 60) as usize;
+            let tree = random_tree(&mut rng, n, 10);
+            let mut expected = Vec::new();
+            visit(&tree, Some(0), &mut expected);
+            assert_eq!(tree.inorder().collect::<Vec<_>>(), expected);
+        }
+    }
+
+    // test for the path kinds of exercise 2
+    #[test]
+    fn test_max_path_kinds_against_naive() {
+        let mut rng = Rng::new(7);
+        for _ in 0..500 {
+            let n = rng.gen_range(1, 12) as usize;
+            let tree = random_tree(&mut rng, n, 100);
+            let is_leaf = |v: usize| tree.left(v).is_none() && tree.right(v).is_none();
+            // path between u and v through their lowest common ancestor
+            let path = |u: usize, v: usize| -> Vec<usize> {
+                let up = |w: usize| -> Vec<usize> {
+                    std::iter::successors(Some(w), |&x| tree.parent(x)).collect()
+                };
+                let (mut a, mut b) = (up(u), up(v));
+                while a.len() > 1 && b.len() > 1 && a[a.len() - 2] == b[b.len() - 2] {
+                    a.pop();
+                    b.pop();
+                }
+                b.pop();
+                a.extend(b.into_iter().rev());
+                a
+            };
+            let sum = |p: &[usize]| p.iter().map(|&v| tree.key(v).unwrap() as u64).sum::<u64>();
+            let best = |ends: Ends| {
+                (0..n)
+                    .flat_map(|u| (0..n).map(move |v| (u, v)))
+                    .filter(|&(u, v)| ends(u, v))
+                    .map(|(u, v)| sum(&path(u, v)))
+                    .max()
+            };
+            let kinds: [(PathKind, Ends); 3] = [
+                (PathKind::LeafToLeaf, &|u, v| {
+                    u != v && is_leaf(u) && is_leaf(v)
+                }),
+                (PathKind::AnyToAny, &|_, _| true),
+                (PathKind::RootToLeaf, &|u, v| u == 0 && is_leaf(v)),
+            ];
+            for (kind, ends) in kinds {
+                let expected = best(ends);
+                assert_eq!(tree.max_path_sum_with(kind), expected);
+                let Some((total, nodes)) = tree.max_path(kind) else {
+                    assert_eq!(expected, None);
+                    continue;
+                };
+                // the reconstruction is the path between its endpoints
+                assert_eq!(Some(total), expected);
+                assert_eq!(sum(&nodes), total);
+                let (first, last) = (nodes[0], *nodes.last().unwrap());
+                assert!(ends(first, last));
+                assert_eq!(nodes, path(first, last));
+            }
+            assert_eq!(
+                tree.max_path_sum().map(u64::from),
+                tree.max_path_sum_with(PathKind::LeafToLeaf)
+            );
+        }
+    }
+
+    // both exercises on a degenerate tree far deeper than the call stack allows
+    #[test]
+    fn test_deep_left_spine() {
+        let n = 1_000_000;
+        let mut tree = Tree::with_root(1);
+        let mut bottom = 0;
+        for _ in 1..n {
+            bottom = tree.add_node(bottom, 1, true);
+        }
+        tree.add_node(0, 2, false);
+        assert!(tree.is_bst());
+        assert!(!tree.is_bst_with(BstPolicy::NoDuplicates));
+        assert!(!tree.is_bst_with(BstPolicy::DuplicatesRight));
+        assert_eq!(tree.max_path_sum(), Some(n + 2));
+        let (sum, path) = tree.max_path(PathKind::RootToLeaf).unwrap();
+        assert_eq!((sum, path.len()), (n as u64, n as usize));
+        assert_eq!(path.last(), Some(&bottom));
+        let mut order = tree.inorder();
+        assert_eq!(order.next(), Some(bottom));
+        assert_eq!(order.last(), Some(n as usize));
+    }
+
+    // test for the rebalancing of BSTs
+    #[test]
+    fn test_rebalance() {
+        let mut rng = Rng::new(11);
+        for round in 0..300 {
+            let n = rng.gen_range(1, 200) as usize;
+            // BST insertions of sorted, random or heavily duplicated keys
+            let max_key = if round % 3 == 2 { 5 } else { 1000 };
+            let mut keys: Vec<u32> = (0..n)
+                .map(|_| rng.gen_range(0, max_key - 1) as u32)
+                .collect();
+            if round % 3 == 0 {
+                keys.sort();
+            }
+            let mut tree = Tree::with_root(keys[0]);
+            for &key in &keys[1..] {
+                let mut id = 0;
+                loop {
+                    let is_left = key <= tree.key(id).unwrap();
+                    match tree.child(id, is_left) {
+                        Some(c) => id = c,
+                        None => {
+                            tree.add_node(id, key, is_left);
+                            break;
+                        }
+                    }
+                }
+            }
+            let old_keys: Vec<u32> = (0..n).map(|id| tree.key(id).unwrap()).collect();
+            let sorted: Vec<u32> = tree.inorder().map(|id| old_keys[id]).collect();
+
+            let mapping = tree.rebalance().unwrap();
+            assert_eq!(tree.len(), n);
+            assert!(tree.is_bst());
+            for (old, &new) in mapping.iter().enumerate() {
+                assert_eq!(tree.key(new), Some(old_keys[old]));
+            }
+            let mut targets = mapping.clone();
+            targets.sort();
+            assert_eq!(targets, (0..n).collect::<Vec<_>>());
+            let after: Vec<u32> = tree.inorder().map(|id| tree.key(id).unwrap()).collect();
+            assert_eq!(after, sorted);
+            let mut distinct = sorted.clone();
+            distinct.dedup();
+            if distinct.len() == n {
+                // a perfectly balanced tree: 2^height <= n < 2^(height + 1)
+                assert_eq!(tree.height(), n.ilog2() as usize);
+            }
+        }
+
+        // not a BST: nothing changes
+        let mut tree = Tree::with_root(5);
+        tree.add_node(0, 7, true);
+        tree.add_node(1, 1, true);
+        assert_eq!(tree.height(), 2);
+        assert_eq!(tree.rebalance(), None);
+        assert_eq!(
+            (tree.key(0), tree.left(0), tree.height()),
+            (Some(5), Some(1), 2)
+        );
+    }
+
+    /// Reference BST check straight from the definition: every key of the left
+    /// and right subtrees of every node is compared with the node key
+    fn naive_is_bst(tree: &Tree, policy: BstPolicy) -> bool {
+        let keys = |root: Option<usize>| -> Vec<u32> {
+            root.map_or(Vec::new(), |r| {
+                tree.nodes.preorder(r).map(|v| tree.nodes[v].key).collect()
+            })
+        };
+        (0..tree.len()).all(|v| {
+            let key = tree.nodes[v].key;
+            let (left, right) = (keys(tree.left(v)), keys(tree.right(v)));
+            match policy {
+                BstPolicy::DuplicatesLeft => {
+                    left.iter().all(|&k| k <= key) && right.iter().all(|&k| k > key)
+                }
+                BstPolicy::DuplicatesRight => {
+                    left.iter().all(|&k| k < key) && right.iter().all(|&k| k >= key)
+                }
+                BstPolicy::NoDuplicates => {
+                    left.iter().all(|&k| k < key) && right.iter().all(|&k| k > key)
+                }
+            }
+        })
+    }
+
+    // test for the duplicate policies of exercise 1
+    #[test]
+    fn test_bst_policies_against_naive() {
+        let policies = [
+            BstPolicy::DuplicatesLeft,
+            BstPolicy::DuplicatesRight,
+            BstPolicy::NoDuplicates,
+        ];
+        let mut rng = Rng::new(2024);
+        let mut valid = [0; 3];
+        for round in 0..3000 {
+            // BST insertions of few distinct keys, with duplicates sent to a
+            // random side; in broken rounds some steps go the wrong way
+            let n = rng.gen_range(1, 8) as usize;
+            let keys: Vec<u32> = (0..n)
+                .map(|_| [0, 1, 2, 3, u32::MAX][rng.gen_range(0, 4) as usize])
+                .collect();
+            let broken = round % 3 == 0;
+            let mut tree = Tree::with_root(keys[0]);
+            for &key in &keys[1..] {
+                let mut v = 0;
+                loop {
+                    let node_key = tree.nodes[v].key;
+                    let go_left = key < node_key || (key == node_key && rng.gen_bool(0.5));
+                    let go_left = if broken && rng.gen_bool(0.25) {
+                        !go_left
+                    } else {
+                        go_left
+                    };
+                    match tree.child(v, go_left) {
+                        Some(c) => v = c,
+                        None => {
+                            tree.add_node(v, key, go_left);
+                            break;
+                        }
+                    }
+                }
+            }
+            for (i, &policy) in policies.iter().enumerate() {
+                let expected = naive_is_bst(&tree, policy);
+                assert_eq!(tree.is_bst_with(policy), expected);
+                valid[i] += expected as usize;
+            }
+            assert_eq!(tree.is_bst(), tree.is_bst_with(BstPolicy::default()));
+        }
+        // every policy sees both outcomes
+        assert!(valid.iter().all(|&v| v > 100 && v < 2900));
+
+        let mut leaf_max = Tree::with_root(u32::MAX);
+        leaf_max.add_node(0, 0, true);
+        assert!(leaf_max.is_bst_with(BstPolicy::NoDuplicates));
+    }
+
+    // test for the public accessors and the checked builder
+    #[test]
+    fn test_public_api() {
+        let mut chain = Tree::with_root(4);
+        assert_eq!(chain.max_path(PathKind::LeafToLeaf), None);
+        assert_eq!(chain.max_path(PathKind::RootToLeaf), Some((4, vec![0])));
+        chain.add_node(0, u32::MAX, false);
+        assert_eq!(
+            chain.max_path(PathKind::AnyToAny),
+            Some((u32::MAX as u64 + 4, vec![0, 1]))
+        );
+
+        let mut tree = Tree::with_root(10);
+        let left = tree.add_node(0, 5, true);
+        let right = tree.try_add_node(0, 15, false).unwrap();
+        assert_eq!(tree.len(), 3);
+        assert_eq!((tree.left(0), tree.right(0)), (Some(left), Some(right)));
+        assert_eq!(
+            (tree.key(left), tree.key(right), tree.key(3)),
+            (Some(5), Some(15), None)
+        );
+        assert_eq!(
+            (tree.parent(0), tree.parent(right), tree.parent(9)),
+            (None, Some(0), None)
+        );
+        assert_eq!((tree.left(left), tree.right(9)), (None, None));
+
+        assert_eq!(
+            tree.try_add_node(0, 1, true),
+            Err(TreeError::ChildAlreadySet {
+                parent: 0,
+                is_left: true
+            })
+        );
+        assert_eq!(
+            tree.try_add_node(4, 1, true),
+            Err(TreeError::NodeNotFound { id: 4, len: 3 })
+        );
+        assert_eq!(tree.len(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "node 0 has its right child already set")]
+    fn test_add_node_twice_panics() {
+        let mut tree = Tree::with_root(1);
+        tree.add_node(0, 2, false);
+        tree.add_node(0, 3, false);
+    }
 }
@@ -0,0 +1,155 @@
+// ------- ARRAY UTILITIES -------
+// Prefix/suffix extrema, nearest smaller/greater elements with a monotonic
+// stack, and the largest rectangle in a histogram.
+
+/// `result[i]` is the maximum of `values[..=i]`
+pub fn prefix_max<T: Ord + Copy>(values: &[T]) -> Vec<T> {
+    scan(values.iter().copied(), |a, b| a.max(b))
+}
+
+/// `result[i]` is the minimum of `values[..=i]`
+pub fn prefix_min<T: Ord + Copy>(values: &[T]) -> Vec<T> {
+    scan(values.iter().copied(), |a, b| a.min(b))
+}
+
+/// `result[i]` is the maximum of `values[i..]`
+pub fn suffix_max<T: Ord + Copy>(values: &[T]) -> Vec<T> {
+    let mut result = scan(values.iter().rev().copied(), |a, b| a.max(b));
+    result.reverse();
+    result
+}
+
+/// `result[i]` is the minimum of `values[i..]`
+pub fn suffix_min<T: Ord + Copy>(values: &[T]) -> Vec<T> {
+    let mut result = scan(values.iter().rev().copied(), |a, b| a.min(b));
+    result.reverse();
+    result
+}
+
+// running fold of an iterator, keeping every intermediate value
+fn scan<T: Copy>(values: impl Iterator<Item = T>, f: impl Fn(T, T) -> T) -> Vec<T> {
+    let mut result: Vec<T> = Vec::new();
+    for v in values {
+        let next = match result.last() {
+            Some(&last) => f(last, v),
+            None => v,
+        };
+        result.push(next);
+    }
+    result
+}
+
+/// Index of the closest element on the left strictly smaller than `values[i]`
+pub fn previous_smaller<T: Ord>(values: &[T]) -> Vec<Option<usize>> {
+    nearest(values, (0..values.len()).collect(), |top, current| {
+        top < current
+    })
+}
+
+/// Index of the closest element on the right strictly smaller than `values[i]`
+pub fn next_smaller<T: Ord>(values: &[T]) -> Vec<Option<usize>> {
+    nearest(values, (0..values.len()).rev().collect(), |top, current| {
+        top < current
+    })
+}
+
+/// Index of the closest element on the left strictly greater than `values[i]`
+pub fn previous_greater<T: Ord>(values: &[T]) -> Vec<Option<usize>> {
+    nearest(values, (0..values.len()).collect(), |top, current| {
+        top > current
+    })
+}
+
+/// Index of the closest element on the right strictly greater than `values[i]`
+pub fn next_greater<T: Ord>(values: &[T]) -> Vec<Option<usize>> {
+    nearest(values, (0..values.len()).rev().collect(), |top, current| {
+        top > current
+    })
+}
+
+// Monotonic stack visiting the indices in `order`: for each index, pops the
+// candidates that cannot be the answer (`keep` is false) and reports the top
+fn nearest<T>(
+    values: &[T],
+    order: Vec<usize>,
+    keep: impl Fn(&T, &T) -> bool,
+) -> Vec<Option<usize>> {
+    let mut result = vec![None; values.len()];
+    let mut stack: Vec<usize> = Vec::new();
+    for i in order {
+        while let Some(&top) = stack.last() {
+            if keep(&values[top], &values[i]) {
+                break;
+            }
+            stack.pop();
+        }
+        result[i] = stack.last().copied();
+        stack.push(i);
+    }
+    result
+}
+
+/// Area of the largest rectangle fitting under the histogram `heights`, where
+/// every bar has width 1
+pub fn largest_rectangle(heights: &[u64]) -> u64 {
+    let left = previous_smaller(heights);
+    let right = next_smaller(heights);
+    heights
+        .iter()
+        .enumerate()
+        .map(|(i, &h)| {
+            // the bar i extends until the first strictly lower bar on each side
+            let start = left[i].map_or(0, |l| l + 1);
+            let end = right[i].unwrap_or(heights.len());
+            h * (end - start) as u64
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_prefix_suffix() {
+        let values = [3, 1, 4, 1, 5, 9, 2];
+        assert_eq!(prefix_max(&values), vec![3, 3, 4, 4, 5, 9, 9]);
+        assert_eq!(prefix_min(&values), vec![3, 1, 1, 1, 1, 1, 1]);
+        assert_eq!(suffix_max(&values), vec![9, 9, 9, 9, 9, 9, 2]);
+        assert_eq!(suffix_min(&values), vec![1, 1, 1, 1, 2, 2, 2]);
+        assert!(prefix_max::<u32>(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_nearest_elements_against_brute_force() {
+        let mut rng = Rng::new(4);
+        let values: Vec<u32> = (0..200).map(|_| rng.gen_range(0, 10) as u32).collect();
+        let brute = |i: usize, left: bool, pred: &dyn Fn(u32) -> bool| -> Option<usize> {
+            if left {
+                (0..i).rev().find(|&j| pred(values[j]))
+            } else {
+                (i + 1..values.len()).find(|&j| pred(values[j]))
+            }
+        };
+
+        let (ps, ns) = (previous_smaller(&values), next_smaller(&values));
+        let (pg, ng) = (previous_greater(&values), next_greater(&values));
+        for i in 0..values.len() {
+            let v = values[i];
+            assert_eq!(ps[i], brute(i, true, &|x| x < v));
+            assert_eq!(ns[i], brute(i, false, &|x| x < v));
+            assert_eq!(pg[i], brute(i, true, &|x| x > v));
+            assert_eq!(ng[i], brute(i, false, &|x| x > v));
+        }
+    }
+
+    #[test]
+    fn test_largest_rectangle() {
+        assert_eq!(largest_rectangle(&[2, 1, 5, 6, 2, 3]), 10);
+        assert_eq!(largest_rectangle(&[2, 2, 2, 2]), 8);
+        assert_eq!(largest_rectangle(&[]), 0);
+        assert_eq!(largest_rectangle(&[0, 7, 0]), 7);
+    }
+}
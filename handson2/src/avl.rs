@@ -0,0 +1,353 @@
+// ------- AVL MULTISET -------
+// Order-statistic multiset on an AVL tree: every node keeps the height and
+// the size of its subtree, the heights of two siblings differ by at most one
+// and rotations restore that after each insertion or removal. Height is at
+// most 1.44 log2(n), so insert, erase, k-th smallest and rank are O(log n)
+// in the worst case, for any values and without knowing them in advance.
+// The operations are shared with IndexedMultiset by the OrderedSetOps trait.
+
+use std::cmp::Ordering;
+
+/// Operations of an order-statistic multiset
+pub trait OrderedSetOps<T> {
+    /// Number of stored elements, counting duplicates
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds one occurrence of `x`
+    fn insert(&mut self, x: T);
+
+    /// Removes one occurrence of `x`, returns false if `x` was not present
+    fn erase_one(&mut self, x: &T) -> bool;
+
+    /// Number of occurrences of `x`
+    fn count(&self, x: &T) -> usize;
+
+    fn contains(&self, x: &T) -> bool {
+        self.count(x) > 0
+    }
+
+    /// Number of elements strictly smaller than `x`
+    fn count_less(&self, x: &T) -> usize;
+
+    /// The k-th smallest element, k starting from 0
+    fn kth(&self, k: usize) -> Option<&T>;
+
+    fn min(&self) -> Option<&T> {
+        self.kth(0)
+    }
+
+    fn max(&self) -> Option<&T> {
+        self.len().checked_sub(1).and_then(|k| self.kth(k))
+    }
+}
+
+type Link<T> = Option<Box<AvlNode<T>>>;
+
+struct AvlNode<T> {
+    value: T,
+    left: Link<T>,
+    right: Link<T>,
+    height: u32, // Nodes on the longest path down to a leaf, the node included
+    size: usize, // Nodes in the subtree, the node included
+}
+
+fn height<T>(link: &Link<T>) -> u32 {
+    link.as_ref().map_or(0, |node| node.height)
+}
+
+fn size<T>(link: &Link<T>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+impl<T> AvlNode<T> {
+    fn update(&mut self) {
+        self.height = 1 + height(&self.left).max(height(&self.right));
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+}
+
+fn rotate_right<T>(mut node: Box<AvlNode<T>>) -> Box<AvlNode<T>> {
+    let mut left = node.left.take().unwrap();
+    node.left = left.right.take();
+    node.update();
+    left.right = Some(node);
+    left.update();
+    left
+}
+
+fn rotate_left<T>(mut node: Box<AvlNode<T>>) -> Box<AvlNode<T>> {
+    let mut right = node.right.take().unwrap();
+    node.right = right.left.take();
+    node.update();
+    right.left = Some(node);
+    right.update();
+    right
+}
+
+// Restores the AVL property at a node whose children differ in height by at
+// most two, with one or two rotations
+fn rebalance<T>(mut node: Box<AvlNode<T>>) -> Box<AvlNode<T>> {
+    node.update();
+    let (hl, hr) = (height(&node.left), height(&node.right));
+    if hl > hr + 1 {
+        let left = node.left.take().unwrap();
+        node.left = Some(if height(&left.right) > height(&left.left) {
+            rotate_left(left)
+        } else {
+            left
+        });
+        rotate_right(node)
+    } else if hr > hl + 1 {
+        let right = node.right.take().unwrap();
+        node.right = Some(if height(&right.left) > height(&right.right) {
+            rotate_right(right)
+        } else {
+            right
+        });
+        rotate_left(node)
+    } else {
+        node
+    }
+}
+
+fn insert<T: Ord>(link: Link<T>, value: T) -> Box<AvlNode<T>> {
+    let Some(mut node) = link else {
+        return Box::new(AvlNode {
+            value,
+            left: None,
+            right: None,
+            height: 1,
+            size: 1,
+        });
+    };
+    if value < node.value {
+        node.left = Some(insert(node.left.take(), value));
+    } else {
+        node.right = Some(insert(node.right.take(), value));
+    }
+    rebalance(node)
+}
+
+// Detaches the smallest node of a subtree, returns the rest and that node
+fn remove_min<T>(mut node: Box<AvlNode<T>>) -> (Link<T>, Box<AvlNode<T>>) {
+    match node.left.take() {
+        None => (node.right.take(), node),
+        Some(left) => {
+            let (rest, min) = remove_min(left);
+            node.left = rest;
+            (Some(rebalance(node)), min)
+        }
+    }
+}
+
+// Removes one node holding `value`, if any, setting `removed`
+fn remove<T: Ord>(link: Link<T>, value: &T, removed: &mut bool) -> Link<T> {
+    let mut node = link?;
+    match value.cmp(&node.value) {
+        Ordering::Less => node.left = remove(node.left.take(), value, removed),
+        Ordering::Greater => node.right = remove(node.right.take(), value, removed),
+        Ordering::Equal => {
+            *removed = true;
+            let left = node.left.take();
+            let Some(right) = node.right.take() else {
+                return left;
+            };
+            // the successor takes the place of the node
+            let (rest, mut successor) = remove_min(right);
+            successor.left = left;
+            successor.right = rest;
+            return Some(rebalance(successor));
+        }
+    }
+    Some(rebalance(node))
+}
+
+/// Order-statistic multiset on an AVL tree, with worst-case O(log n)
+/// operations
+pub struct AvlMultiset<T> {
+    root: Link<T>,
+}
+
+impl<T> Default for AvlMultiset<T> {
+    fn default() -> Self {
+        AvlMultiset { root: None }
+    }
+}
+
+impl<T: Ord> AvlMultiset<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of stored elements, counting duplicates
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Number of nodes on the longest root-to-leaf path, 0 when empty
+    pub fn height(&self) -> u32 {
+        height(&self.root)
+    }
+
+    /// Adds one occurrence of `x`
+    pub fn insert(&mut self, x: T) {
+        self.root = Some(insert(self.root.take(), x));
+    }
+
+    /// Removes one occurrence of `x`, returns false if `x` was not present
+    pub fn erase_one(&mut self, x: &T) -> bool {
+        let mut removed = false;
+        self.root = remove(self.root.take(), x, &mut removed);
+        removed
+    }
+
+    // Number of elements e with less(e), for a predicate true on a prefix of
+    // the sorted elements
+    fn count_while(&self, less: impl Fn(&T) -> bool) -> usize {
+        let mut count = 0;
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if less(&node.value) {
+                count += size(&node.left) + 1;
+                link = &node.right;
+            } else {
+                link = &node.left;
+            }
+        }
+        count
+    }
+
+    /// Number of occurrences of `x`
+    pub fn count(&self, x: &T) -> usize {
+        self.count_while(|e| e <= x) - self.count_less(x)
+    }
+
+    pub fn contains(&self, x: &T) -> bool {
+        self.count(x) > 0
+    }
+
+    /// Number of elements strictly smaller than `x`
+    pub fn count_less(&self, x: &T) -> usize {
+        self.count_while(|e| e < x)
+    }
+
+    /// The k-th smallest element, k starting from 0
+    pub fn kth(&self, k: usize) -> Option<&T> {
+        let mut k = k;
+        let mut link = &self.root;
+        while let Some(node) = link {
+            let left = size(&node.left);
+            match k.cmp(&left) {
+                Ordering::Less => link = &node.left,
+                Ordering::Equal => return Some(&node.value),
+                Ordering::Greater => {
+                    k -= left + 1;
+                    link = &node.right;
+                }
+            }
+        }
+        None
+    }
+
+    pub fn min(&self) -> Option<&T> {
+        self.kth(0)
+    }
+
+    pub fn max(&self) -> Option<&T> {
+        self.len().checked_sub(1).and_then(|k| self.kth(k))
+    }
+}
+
+impl<T: Ord> OrderedSetOps<T> for AvlMultiset<T> {
+    fn len(&self) -> usize {
+        AvlMultiset::len(self)
+    }
+
+    fn insert(&mut self, x: T) {
+        AvlMultiset::insert(self, x)
+    }
+
+    fn erase_one(&mut self, x: &T) -> bool {
+        AvlMultiset::erase_one(self, x)
+    }
+
+    fn count(&self, x: &T) -> usize {
+        AvlMultiset::count(self, x)
+    }
+
+    fn count_less(&self, x: &T) -> usize {
+        AvlMultiset::count_less(self, x)
+    }
+
+    fn kth(&self, k: usize) -> Option<&T> {
+        AvlMultiset::kth(self, k)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexed_multiset::IndexedMultiset;
+    use crate::rng::Rng;
+
+    // Random operations on `set` checked against a sorted vector, through the
+    // shared trait only
+    fn check_against_sorted_vec(mut set: impl OrderedSetOps<i32>, seed: u64) {
+        let mut rng = Rng::new(seed);
+        let mut sorted: Vec<i32> = Vec::new();
+        for _ in 0..3000 {
+            let x = rng.gen_range_i64(-20, 19) as i32;
+            if rng.gen_range(0, 2) == 0 {
+                let pos = sorted.iter().position(|&v| v == x);
+                assert_eq!(set.erase_one(&x), pos.is_some());
+                if let Some(pos) = pos {
+                    sorted.remove(pos);
+                }
+            } else {
+                set.insert(x);
+                let pos = sorted.partition_point(|&v| v < x);
+                sorted.insert(pos, x);
+            }
+            assert_eq!(set.len(), sorted.len());
+            assert_eq!(set.count_less(&x), sorted.partition_point(|&v| v < x));
+            assert_eq!(set.count(&x), sorted.iter().filter(|&&v| v == x).count());
+            let k = rng.gen_range(0, sorted.len() as u64) as usize;
+            assert_eq!(set.kth(k), sorted.get(k));
+            assert_eq!(set.min(), sorted.first());
+            assert_eq!(set.max(), sorted.last());
+        }
+    }
+
+    #[test]
+    fn test_shared_operations() {
+        check_against_sorted_vec(AvlMultiset::new(), 90);
+        let universe: Vec<i32> = (-20..20).collect();
+        check_against_sorted_vec(IndexedMultiset::new(&universe), 90);
+    }
+
+    #[test]
+    fn test_height_stays_logarithmic() {
+        // sorted insertions degenerate a plain BST into a chain
+        let mut set = AvlMultiset::new();
+        for x in 0..100_000 {
+            set.insert(x);
+        }
+        assert_eq!(set.height(), 17);
+        for x in (0..100_000).step_by(2) {
+            assert!(set.erase_one(&x));
+        }
+        assert!(!set.erase_one(&0));
+        assert_eq!(set.len(), 50_000);
+        assert!(set.height() <= 16 * 144 / 100 + 1);
+        assert_eq!(set.kth(123), Some(&247));
+        assert_eq!(set.count_less(&1000), 500);
+    }
+}
@@ -0,0 +1,404 @@
+// ------- BIG UNSIGNED INTEGERS -------
+// Arbitrary precision naturals stored as base 10^9 limbs, least significant
+// first, so decimal parsing and printing are linear. Multiplication switches
+// from schoolbook to Karatsuba to NTT convolution as the operands grow. The
+// NTT convolves the limbs modulo three primes and rebuilds every coefficient,
+// up to min(len) * 10^18, with the Chinese remainder theorem. Operands too long
+// for one transform are cut into blocks whose products are added up.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Sub};
+use std::str::FromStr;
+
+use crate::convolution::convolution;
+use crate::modint::{Mod998244353, ModInt};
+
+const BASE: u64 = 1_000_000_000;
+const BASE_DIGITS: usize = 9;
+// Limb counts where the faster multiplication algorithms start to pay off
+const KARATSUBA_THRESHOLD: usize = 32;
+const NTT_THRESHOLD: usize = 1024;
+// Longest product the NTT handles: the largest transform modulo 998244353
+const NTT_MAX_LEN: usize = 1 << 23;
+
+type Mod167772161 = ModInt<167_772_161>;
+type Mod469762049 = ModInt<469_762_049>;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BigUint {
+    limbs: Vec<u32>, // No trailing zero limbs, so zero is the empty vector
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseBigUintError;
+
+impl fmt::Display for ParseBigUintError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid decimal number")
+    }
+}
+
+impl BigUint {
+    pub fn zero() -> Self {
+        BigUint { limbs: Vec::new() }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// Number of decimal digits, 1 for zero
+    pub fn digits(&self) -> usize {
+        match self.limbs.last() {
+            None => 1,
+            Some(&top) => (self.limbs.len() - 1) * BASE_DIGITS + top.to_string().len(),
+        }
+    }
+
+    /// `self - other`, None if the result would be negative
+    pub fn checked_sub(&self, other: &BigUint) -> Option<BigUint> {
+        if *self < *other {
+            return None;
+        }
+        let mut limbs = self.limbs.clone();
+        let mut borrow = 0;
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let sub = other.limbs.get(i).copied().unwrap_or(0) as i64 + borrow;
+            let mut value = *limb as i64 - sub;
+            borrow = 0;
+            if value < 0 {
+                value += BASE as i64;
+                borrow = 1;
+            }
+            *limb = value as u32;
+        }
+        Some(BigUint::normalized(limbs))
+    }
+
+    fn normalized(mut limbs: Vec<u32>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        BigUint { limbs }
+    }
+
+    // Propagates the carries of oversized limb values
+    fn from_wide(wide: &[u128]) -> Self {
+        let mut limbs = Vec::with_capacity(wide.len() + 2);
+        let mut carry = 0u128;
+        for &w in wide {
+            let value = w + carry;
+            limbs.push((value % BASE as u128) as u32);
+            carry = value / BASE as u128;
+        }
+        while carry > 0 {
+            limbs.push((carry % BASE as u128) as u32);
+            carry /= BASE as u128;
+        }
+        BigUint::normalized(limbs)
+    }
+
+    fn mul_ntt(&self, other: &BigUint) -> BigUint {
+        self.mul_ntt_blocks(other, NTT_MAX_LEN / 2)
+    }
+
+    // Product of the pieces of `block` limbs of each operand, so that every
+    // pair fits a single transform
+    fn mul_ntt_blocks(&self, other: &BigUint, block: usize) -> BigUint {
+        let mut wide = vec![0u128; self.limbs.len() + other.limbs.len()];
+        for (i, a) in self.limbs.chunks(block).enumerate() {
+            for (j, b) in other.limbs.chunks(block).enumerate() {
+                let offset = (i + j) * block;
+                for (w, c) in wide[offset..].iter_mut().zip(mul_ntt_wide(a, b)) {
+                    *w += c;
+                }
+            }
+        }
+        BigUint::from_wide(&wide)
+    }
+}
+
+// Polynomial product of limb vectors with the NTT, carries resolved by the
+// caller. The three moduli multiply to ~7.9 * 10^25, above every coefficient
+// min(len) * (10^9 - 1)^2 for products of up to 2^23 limbs (~3.7 * 10^7
+// digits per side)
+fn mul_ntt_wide(a: &[u32], b: &[u32]) -> Vec<u128> {
+    assert!(
+        a.len() + b.len() - 1 <= NTT_MAX_LEN,
+        "Operands too large for the NTT"
+    );
+    fn convolve<const M: u32>(a: &[u32], b: &[u32]) -> Vec<ModInt<M>> {
+        let lift = |x: &[u32]| -> Vec<ModInt<M>> {
+            x.iter().map(|&limb| ModInt::new(limb as u64)).collect()
+        };
+        convolution(&lift(a), &lift(b))
+    }
+    let r1 = convolve::<998_244_353>(a, b);
+    let r2 = convolve::<167_772_161>(a, b);
+    let r3 = convolve::<469_762_049>(a, b);
+
+    // Garner: x = x1 + x2 m1 + x3 m1 m2, with every xi below its modulus
+    let m1 = Mod998244353::modulus() as u64;
+    let m2 = Mod167772161::modulus() as u64;
+    let m1_inv_2 = Mod167772161::new(m1).inv();
+    let m1_3 = Mod469762049::new(m1);
+    let m12_inv_3 = (m1_3 * Mod469762049::new(m2)).inv();
+    (0..r1.len())
+        .map(|k| {
+            let x1 = r1[k].value() as u64;
+            let x2 = ((r2[k] - Mod167772161::new(x1)) * m1_inv_2).value() as u64;
+            let x3 = (r3[k] - Mod469762049::new(x1) - Mod469762049::new(x2) * m1_3) * m12_inv_3;
+            x1 as u128 + x2 as u128 * m1 as u128 + x3.value() as u128 * (m1 * m2) as u128
+        })
+        .collect()
+}
+
+// Polynomial product of limb vectors, carries resolved by the caller
+fn mul_schoolbook(a: &[u64], b: &[u64]) -> Vec<u128> {
+    let mut result = vec![0u128; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        for (j, &y) in b.iter().enumerate() {
+            result[i + j] += x as u128 * y as u128;
+        }
+    }
+    result
+}
+
+// (a0 + a1 x)(b0 + b1 x) = z0 + ((a0 + a1)(b0 + b1) - z0 - z2) x + z2 x^2,
+// with x = BASE^half: three half-size products instead of four
+fn mul_karatsuba(a: &[u64], b: &[u64]) -> Vec<u128> {
+    let n = a.len().max(b.len());
+    if a.len().min(b.len()) <= KARATSUBA_THRESHOLD {
+        return mul_schoolbook(a, b);
+    }
+    let half = n / 2;
+    let split = |x: &[u64]| -> (Vec<u64>, Vec<u64>) {
+        let lo = x[..half.min(x.len())].to_vec();
+        let hi = x.get(half..).map_or(Vec::new(), <[u64]>::to_vec);
+        (lo, hi)
+    };
+    let (a0, a1) = split(a);
+    let (b0, b1) = split(b);
+    let sum = |x: &[u64], y: &[u64]| -> Vec<u64> {
+        (0..x.len().max(y.len()))
+            .map(|i| x.get(i).copied().unwrap_or(0) + y.get(i).copied().unwrap_or(0))
+            .collect()
+    };
+    let z0 = mul_karatsuba(&a0, &b0);
+    let z2 = mul_karatsuba(&a1, &b1);
+    let mut z1 = mul_karatsuba(&sum(&a0, &a1), &sum(&b0, &b1));
+    for (i, &v) in z0.iter().enumerate() {
+        z1[i] -= v;
+    }
+    for (i, &v) in z2.iter().enumerate() {
+        z1[i] -= v;
+    }
+
+    let mut result = vec![0u128; a.len() + b.len()];
+    for (i, &v) in z0.iter().enumerate() {
+        result[i] += v;
+    }
+    for (i, &v) in z1.iter().enumerate() {
+        if v != 0 {
+            result[i + half] += v;
+        }
+    }
+    for (i, &v) in z2.iter().enumerate() {
+        if v != 0 {
+            result[i + 2 * half] += v;
+        }
+    }
+    result
+}
+
+impl From<u64> for BigUint {
+    fn from(mut value: u64) -> Self {
+        let mut limbs = Vec::new();
+        while value > 0 {
+            limbs.push((value % BASE) as u32);
+            value /= BASE;
+        }
+        BigUint { limbs }
+    }
+}
+
+impl FromStr for BigUint {
+    type Err = ParseBigUintError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || !s.bytes().all(|c| c.is_ascii_digit()) {
+            return Err(ParseBigUintError);
+        }
+        let bytes = s.as_bytes();
+        let limbs = bytes
+            .rchunks(BASE_DIGITS)
+            .map(|chunk| chunk.iter().fold(0, |acc, &c| acc * 10 + (c - b'0') as u32))
+            .collect();
+        Ok(BigUint::normalized(limbs))
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.limbs.split_last() {
+            None => f.pad("0"),
+            Some((top, rest)) => {
+                let mut s = top.to_string();
+                for limb in rest.iter().rev() {
+                    s.push_str(&format!("{:09}", limb));
+                }
+                f.pad(&s)
+            }
+        }
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: &BigUint) -> BigUint {
+        let len = self.limbs.len().max(other.limbs.len());
+        let mut limbs = Vec::with_capacity(len + 1);
+        let mut carry = 0;
+        for i in 0..len {
+            let a = self.limbs.get(i).copied().unwrap_or(0) as u64;
+            let b = other.limbs.get(i).copied().unwrap_or(0) as u64;
+            let value = a + b + carry;
+            limbs.push((value % BASE) as u32);
+            carry = value / BASE;
+        }
+        if carry > 0 {
+            limbs.push(carry as u32);
+        }
+        BigUint { limbs }
+    }
+}
+
+impl Sub for &BigUint {
+    type Output = BigUint;
+
+    /// # Panics
+    /// Panics if `other > self`.
+    fn sub(self, other: &BigUint) -> BigUint {
+        self.checked_sub(other).expect("Subtraction underflow")
+    }
+}
+
+impl Mul for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: &BigUint) -> BigUint {
+        if self.is_zero() || other.is_zero() {
+            return BigUint::zero();
+        }
+        if self.limbs.len().min(other.limbs.len()) >= NTT_THRESHOLD {
+            return self.mul_ntt(other);
+        }
+        let a: Vec<u64> = self.limbs.iter().map(|&x| x as u64).collect();
+        let b: Vec<u64> = other.limbs.iter().map(|&x| x as u64).collect();
+        BigUint::from_wide(&mul_karatsuba(&a, &b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn random_big(rng: &mut Rng, digits: usize) -> BigUint {
+        let s: String = (0..digits)
+            .map(|_| (b'0' + rng.gen_range(0, 9) as u8) as char)
+            .collect();
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_print() {
+        for s in ["0", "7", "1000000000", "123456789012345678901234567890"] {
+            assert_eq!(s.parse::<BigUint>().unwrap().to_string(), s);
+        }
+        assert_eq!("000123".parse::<BigUint>().unwrap().to_string(), "123");
+        assert_eq!("".parse::<BigUint>(), Err(ParseBigUintError));
+        assert_eq!("12a".parse::<BigUint>(), Err(ParseBigUintError));
+        assert_eq!(BigUint::from(0).digits(), 1);
+        assert_eq!(BigUint::from(1_000_000_000).digits(), 10);
+    }
+
+    #[test]
+    fn test_against_u128() {
+        let mut rng = Rng::new(34);
+        for _ in 0..1000 {
+            let (x, y) = (rng.next_u64(), rng.next_u64());
+            let (a, b) = (BigUint::from(x), BigUint::from(y));
+            assert_eq!((&a + &b).to_string(), (x as u128 + y as u128).to_string());
+            assert_eq!((&a * &b).to_string(), (x as u128 * y as u128).to_string());
+            assert_eq!(a.cmp(&b), x.cmp(&y));
+            match x.checked_sub(y) {
+                Some(d) => assert_eq!(&a - &b, BigUint::from(d)),
+                None => assert_eq!(a.checked_sub(&b), None),
+            }
+        }
+    }
+
+    #[test]
+    fn test_multiplication_algorithms_agree() {
+        let mut rng = Rng::new(35);
+        for (n, m) in [(300, 300), (1000, 50), (2000, 2000), (12_000, 10_000)] {
+            let a = random_big(&mut rng, n);
+            let b = random_big(&mut rng, m);
+            let (x, y): (Vec<u64>, Vec<u64>) = (
+                a.limbs.iter().map(|&v| v as u64).collect(),
+                b.limbs.iter().map(|&v| v as u64).collect(),
+            );
+            let expected = BigUint::from_wide(&mul_schoolbook(&x, &y));
+            assert_eq!(BigUint::from_wide(&mul_karatsuba(&x, &y)), expected);
+            assert_eq!(&a * &b, expected);
+            if a.limbs.len().min(b.limbs.len()) > 1 {
+                assert_eq!(a.mul_ntt(&b), expected);
+            }
+            // (a + b) - b round trip
+            assert_eq!(&(&a + &b) - &b, a);
+        }
+        // the largest coefficients, (10^9 - 1)^2 times the overlap
+        let nines = BigUint::normalized(vec![999_999_999; 3000]);
+        let x: Vec<u64> = vec![999_999_999; 3000];
+        let expected = BigUint::from_wide(&mul_karatsuba(&x, &x));
+        assert_eq!(nines.mul_ntt(&nines), expected);
+    }
+
+    #[test]
+    // operands above the transform limit are multiplied block by block
+    fn test_ntt_blocks() {
+        let mut rng = Rng::new(36);
+        let a = random_big(&mut rng, 30_000);
+        let b = random_big(&mut rng, 45_000);
+        let expected = &a * &b;
+        for block in [1024, 1500, 4000] {
+            assert_eq!(a.mul_ntt_blocks(&b, block), expected);
+            assert_eq!(b.mul_ntt_blocks(&a, block), expected);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Operands too large for the NTT")]
+    fn test_ntt_length_limit() {
+        let a = vec![1; NTT_MAX_LEN / 2 + 1];
+        mul_ntt_wide(&a, &a);
+    }
+}
@@ -0,0 +1,70 @@
+// Test data generator for the hands-on exercises.
+//
+// Usage: cargo run --bin gen -- [exercise] [count] [n] [m] [seed] [out_dir]
+//
+// Without arguments it regenerates the fixture files used by the unit tests
+// (data/exercise1 and data/exercise2). With arguments it writes `count` cases
+// of size `n` with `m` queries for the chosen exercise, e.g.
+// `cargo run --release --bin gen -- 1 3 100000 100000 17 stress` for large inputs.
+
+use handson2::rng::Rng;
+use handson2::testgen::{gen_exercise1, gen_exercise2, write_exercise1, write_exercise2};
+use std::env;
+use std::path::Path;
+
+// sizes of the fixture files shipped in data/
+const EXERCISE1_SIZES: [usize; 11] = [1, 2, 3, 5, 8, 10, 50, 100, 500, 1000, 5000];
+const EXERCISE2_SIZES: [usize; 8] = [1, 2, 5, 10, 50, 100, 1000, 5000];
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if args.is_empty() {
+        regenerate_fixtures();
+        return;
+    }
+
+    let arg = |i: usize, default: usize| -> usize {
+        args.get(i)
+            .map(|a| a.parse().expect("Arguments must be non-negative integers"))
+            .unwrap_or(default)
+    };
+    let exercise = arg(0, 1);
+    let count = arg(1, 1);
+    let n = arg(2, 100_000);
+    let m = arg(3, 100_000);
+    let seed = arg(4, 0) as u64;
+    let out_dir = args
+        .get(5)
+        .cloned()
+        .unwrap_or_else(|| format!("data/exercise{}", exercise));
+
+    let mut rng = Rng::new(seed);
+    let dir = Path::new(&out_dir);
+    for index in 0..count {
+        match exercise {
+            1 => write_exercise1(&gen_exercise1(&mut rng, n, m, 1_000_000_000), dir, index),
+            2 => write_exercise2(&gen_exercise2(&mut rng, n, m), dir, index),
+            _ => panic!("Exercise must be 1 or 2"),
+        }
+        .expect("Failed to write test case");
+    }
+    println!("Wrote {} case(s) into {}", count, out_dir);
+}
+
+// Rebuild the deterministic fixture set used by `cargo test`
+fn regenerate_fixtures() {
+    let mut rng = Rng::new(2024);
+    let dir = Path::new("data").join("exercise1");
+    for (index, &n) in EXERCISE1_SIZES.iter().enumerate() {
+        let case = gen_exercise1(&mut rng, n, n, 1000);
+        write_exercise1(&case, &dir, index).expect("Failed to write test case");
+    }
+
+    let dir = Path::new("data").join("exercise2");
+    for (index, &n) in EXERCISE2_SIZES.iter().enumerate() {
+        let case = gen_exercise2(&mut rng, n, n);
+        write_exercise2(&case, &dir, index).expect("Failed to write test case");
+    }
+    println!("Regenerated fixtures in data/");
+}
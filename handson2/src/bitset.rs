@@ -0,0 +1,215 @@
+// ------- BITSET -------
+// Fixed-length set of bits stored in 64-bit words. Shifts move bits towards
+// higher (`<<`) or lower (`>>`) indices and drop the ones falling outside.
+
+use std::ops::{BitAndAssign, BitOrAssign, BitXorAssign, Shl, Shr};
+
+const WORD: usize = 64;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitSet {
+    len: usize,
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    /// A bitset of `len` zero bits
+    pub fn new(len: usize) -> Self {
+        BitSet {
+            len,
+            words: vec![0; len.div_ceil(WORD)],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, i: usize) -> bool {
+        assert!(i < self.len, "Index out of range");
+        self.words[i / WORD] >> (i % WORD) & 1 == 1
+    }
+
+    pub fn set(&mut self, i: usize, value: bool) {
+        assert!(i < self.len, "Index out of range");
+        if value {
+            self.words[i / WORD] |= 1 << (i % WORD);
+        } else {
+            self.words[i / WORD] &= !(1 << (i % WORD));
+        }
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// True if no bit is set
+    pub fn none(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    /// Iterates over the indices of the set bits in increasing order
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(i * WORD + bit)
+            })
+        })
+    }
+
+    /// `self |= self << k`, the step of the subset-sum DP over reachable sums
+    pub fn or_shift(&mut self, k: usize) {
+        let shifted = &*self << k;
+        *self |= &shifted;
+    }
+
+    // clear the bits past `len` in the last word
+    fn trim(&mut self) {
+        if !self.len.is_multiple_of(WORD) {
+            let last = self.words.len() - 1;
+            self.words[last] &= (1 << (self.len % WORD)) - 1;
+        }
+    }
+}
+
+impl BitAndAssign<&BitSet> for BitSet {
+    fn bitand_assign(&mut self, other: &BitSet) {
+        assert_eq!(self.len, other.len, "Bitsets must have the same length");
+        self.words
+            .iter_mut()
+            .zip(&other.words)
+            .for_each(|(a, b)| *a &= b);
+    }
+}
+
+impl BitOrAssign<&BitSet> for BitSet {
+    fn bitor_assign(&mut self, other: &BitSet) {
+        assert_eq!(self.len, other.len, "Bitsets must have the same length");
+        self.words
+            .iter_mut()
+            .zip(&other.words)
+            .for_each(|(a, b)| *a |= b);
+    }
+}
+
+impl BitXorAssign<&BitSet> for BitSet {
+    fn bitxor_assign(&mut self, other: &BitSet) {
+        assert_eq!(self.len, other.len, "Bitsets must have the same length");
+        self.words
+            .iter_mut()
+            .zip(&other.words)
+            .for_each(|(a, b)| *a ^= b);
+    }
+}
+
+impl Shl<usize> for &BitSet {
+    type Output = BitSet;
+
+    fn shl(self, k: usize) -> BitSet {
+        let mut result = BitSet::new(self.len);
+        let (word_shift, bit_shift) = (k / WORD, k % WORD);
+        for i in (word_shift..self.words.len()).rev() {
+            let src = i - word_shift;
+            let mut word = self.words[src] << bit_shift;
+            if bit_shift > 0 && src > 0 {
+                word |= self.words[src - 1] >> (WORD - bit_shift);
+            }
+            result.words[i] = word;
+        }
+        result.trim();
+        result
+    }
+}
+
+impl Shr<usize> for &BitSet {
+    type Output = BitSet;
+
+    fn shr(self, k: usize) -> BitSet {
+        let mut result = BitSet::new(self.len);
+        let (word_shift, bit_shift) = (k / WORD, k % WORD);
+        for i in 0..self.words.len().saturating_sub(word_shift) {
+            let src = i + word_shift;
+            let mut word = self.words[src] >> bit_shift;
+            if bit_shift > 0 && src + 1 < self.words.len() {
+                word |= self.words[src + 1] << (WORD - bit_shift);
+            }
+            result.words[i] = word;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn random_bits(rng: &mut Rng, len: usize) -> (BitSet, Vec<bool>) {
+        let bits: Vec<bool> = (0..len).map(|_| rng.next_u64() & 1 == 1).collect();
+        let mut set = BitSet::new(len);
+        for (i, &b) in bits.iter().enumerate() {
+            set.set(i, b);
+        }
+        (set, bits)
+    }
+
+    fn to_vec(set: &BitSet) -> Vec<bool> {
+        (0..set.len()).map(|i| set.get(i)).collect()
+    }
+
+    #[test]
+    fn test_shifts_against_vec() {
+        let mut rng = Rng::new(14);
+        for len in [1, 63, 64, 65, 200] {
+            let (set, bits) = random_bits(&mut rng, len);
+            for k in [0, 1, 5, 63, 64, 65, 130, 300] {
+                let left: Vec<bool> = (0..len).map(|i| i >= k && bits[i - k]).collect();
+                let right: Vec<bool> = (0..len).map(|i| i + k < len && bits[i + k]).collect();
+                assert_eq!(to_vec(&(&set << k)), left, "len {} shl {}", len, k);
+                assert_eq!(to_vec(&(&set >> k)), right, "len {} shr {}", len, k);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bitwise_ops() {
+        let mut rng = Rng::new(15);
+        let (a, bits_a) = random_bits(&mut rng, 130);
+        let (b, bits_b) = random_bits(&mut rng, 130);
+        let mut and = a.clone();
+        and &= &b;
+        let mut or = a.clone();
+        or |= &b;
+        let mut xor = a.clone();
+        xor ^= &b;
+        for i in 0..130 {
+            assert_eq!(and.get(i), bits_a[i] && bits_b[i]);
+            assert_eq!(or.get(i), bits_a[i] || bits_b[i]);
+            assert_eq!(xor.get(i), bits_a[i] != bits_b[i]);
+        }
+        assert_eq!(or.count_ones(), or.ones().count());
+        assert!(or.ones().all(|i| or.get(i)));
+    }
+
+    #[test]
+    fn test_subset_sum() {
+        // reachable sums of {3, 5, 7} up to 20
+        let mut reachable = BitSet::new(21);
+        reachable.set(0, true);
+        for w in [3, 5, 7] {
+            reachable.or_shift(w);
+        }
+        let sums: Vec<usize> = reachable.ones().collect();
+        assert_eq!(sums, vec![0, 3, 5, 7, 8, 10, 12, 15]);
+    }
+}
@@ -0,0 +1,201 @@
+// ------- BURROWS-WHEELER TRANSFORM -------
+// The BWT of s is the last column of the sorted rotations of s$, where $ is a
+// sentinel smaller than every character: it is read off the suffix array and
+// inverted with the LF mapping, the k-th occurrence of a character in the last
+// column being its k-th occurrence in the first one. The FM-index counts the
+// occurrences of a pattern by backward search over the BWT, keeping only the
+// BWT and sampled occurrence counts instead of the whole suffix array.
+
+use crate::suffix_array::SuffixArray;
+use std::cmp::Ordering;
+
+/// The BWT of `s`: the last column without the sentinel, and the row at which
+/// the sentinel is
+pub fn bwt<T: Ord + Clone>(s: &[T]) -> (Vec<T>, usize) {
+    let sa = SuffixArray::new(s);
+    // row 0 is the rotation starting with the sentinel, then the suffixes
+    let mut last = Vec::with_capacity(s.len());
+    let mut primary = 0;
+    if let Some(c) = s.last() {
+        last.push(c.clone());
+    }
+    for (row, &p) in sa.suffixes().iter().enumerate() {
+        match p {
+            0 => primary = row + 1,
+            p => last.push(s[p - 1].clone()),
+        }
+    }
+    (last, primary)
+}
+
+/// The string whose BWT is `last` with the sentinel at row `primary`
+pub fn inverse_bwt<T: Ord + Clone>(last: &[T], primary: usize) -> Vec<T> {
+    let n = last.len();
+    assert!(primary <= n, "Index out of range");
+    // character at each row of the full last column, None for the sentinel
+    let column = |row: usize| match row.cmp(&primary) {
+        Ordering::Less => Some(&last[row]),
+        Ordering::Equal => None,
+        Ordering::Greater => Some(&last[row - 1]),
+    };
+    // a stable sort of the rows by character lists the first column from
+    // row 1 on, the sentinel row 0 excluded: LF maps each row there
+    let mut rows: Vec<usize> = (0..=n).filter(|&row| row != primary).collect();
+    rows.sort_by(|&a, &b| column(a).cmp(&column(b)));
+    let mut lf = vec![0; n + 1];
+    for (k, &row) in rows.iter().enumerate() {
+        lf[row] = k + 1;
+    }
+    // row 0 starts with the sentinel, so it ends with the last character
+    let mut s = Vec::with_capacity(n);
+    let mut row = 0;
+    for _ in 0..n {
+        s.push(column(row).unwrap().clone());
+        row = lf[row];
+    }
+    s.reverse();
+    s
+}
+
+// Occurrence counts are stored every SAMPLE rows
+const SAMPLE: usize = 64;
+
+/// Counts pattern occurrences in a byte string in O(|pattern| * SAMPLE)
+pub struct FmIndex {
+    n: usize,
+    code: [u16; 256],  // Code of each byte, NONE if absent from the text
+    last: Vec<u16>,    // BWT of s$ as codes, the sentinel being NONE
+    first: Vec<usize>, // first[c] = first row starting with code c
+    samples: Vec<u32>, // samples[b * sigma + c] = count of c in last[..b * SAMPLE]
+}
+
+const NONE: u16 = u16::MAX;
+
+impl FmIndex {
+    pub fn new(s: &[u8]) -> Self {
+        let n = s.len();
+        let mut present = [false; 256];
+        for &c in s {
+            present[c as usize] = true;
+        }
+        let mut code = [NONE; 256];
+        let mut sigma = 0;
+        for c in 0..256 {
+            if present[c] {
+                code[c] = sigma as u16;
+                sigma += 1;
+            }
+        }
+
+        let (bwt, primary) = bwt(s);
+        let mut last: Vec<u16> = bwt.iter().map(|&c| code[c as usize]).collect();
+        last.insert(primary, NONE);
+
+        let mut first = vec![0; sigma];
+        let mut counts = vec![0u32; sigma];
+        for &c in s {
+            counts[code[c as usize] as usize] += 1;
+        }
+        let mut row = 1; // the sentinel row comes first
+        for c in 0..sigma {
+            first[c] = row;
+            row += counts[c] as usize;
+        }
+
+        let mut samples = Vec::with_capacity(((n + 1) / SAMPLE + 1) * sigma);
+        let mut running = vec![0u32; sigma];
+        for (i, &c) in last.iter().enumerate() {
+            if i % SAMPLE == 0 {
+                samples.extend_from_slice(&running);
+            }
+            if c != NONE {
+                running[c as usize] += 1;
+            }
+        }
+        if (n + 1).is_multiple_of(SAMPLE) {
+            samples.extend_from_slice(&running);
+        }
+        FmIndex {
+            n,
+            code,
+            last,
+            first,
+            samples,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    // Occurrences of code c in the first i rows of the last column
+    fn rank(&self, c: u16, i: usize) -> usize {
+        let block = i / SAMPLE;
+        let sampled = self.samples[block * self.first.len() + c as usize] as usize;
+        sampled
+            + self.last[block * SAMPLE..i]
+                .iter()
+                .filter(|&&x| x == c)
+                .count()
+    }
+
+    /// Number of (possibly overlapping) occurrences of `pattern`
+    pub fn count(&self, pattern: &[u8]) -> usize {
+        // rows [lo, hi) start with the suffix of the pattern matched so far
+        let (mut lo, mut hi) = (0, self.n + 1);
+        for &c in pattern.iter().rev() {
+            let c = self.code[c as usize];
+            if c == NONE {
+                return 0;
+            }
+            lo = self.first[c as usize] + self.rank(c, lo);
+            hi = self.first[c as usize] + self.rank(c, hi);
+            if lo >= hi {
+                return 0;
+            }
+        }
+        hi - lo
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_banana() {
+        // rotations of banana$: $banana a$banan ana$ban anana$b banana$ nana$ba na$bana
+        let (last, primary) = bwt(b"banana");
+        assert_eq!((last.as_slice(), primary), (&b"annbaa"[..], 4));
+        assert_eq!(inverse_bwt(&last, primary), b"banana");
+        let index = FmIndex::new(b"banana");
+        assert_eq!(index.count(b"ana"), 2);
+        assert_eq!(index.count(b"a"), 3);
+        assert_eq!(index.count(b"nab"), 0);
+        assert_eq!(index.count(b""), 7);
+        assert_eq!(bwt::<u8>(&[]), (vec![], 0));
+    }
+
+    #[test]
+    fn test_random_against_naive() {
+        let mut rng = Rng::new(61);
+        for _ in 0..30 {
+            let n = rng.gen_range(0, 300) as usize;
+            let s: Vec<u8> = (0..n).map(|_| b'a' + rng.gen_range(0, 2) as u8).collect();
+            let (last, primary) = bwt(&s);
+            assert_eq!(inverse_bwt(&last, primary), s);
+            let index = FmIndex::new(&s);
+            for _ in 0..50 {
+                let len = rng.gen_range(1, 5) as usize;
+                let pattern: Vec<u8> = (0..len).map(|_| b'a' + rng.gen_range(0, 3) as u8).collect();
+                let naive = s.windows(len).filter(|w| *w == &pattern[..]).count();
+                assert_eq!(index.count(&pattern), naive);
+            }
+        }
+    }
+}
@@ -0,0 +1,150 @@
+// ------- CARTESIAN TREE -------
+// Binary tree over the positions of an array: heap-ordered by value (the
+// minimum is the root) and in-order equal to the array. The minimum of
+// values[l..=r] sits at the lowest common ancestor of l and r, which turns
+// range minimum queries into LCA queries. Built in O(n) with a monotonic stack.
+
+use crate::graph::Graph;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CartesianTree {
+    root: Option<usize>,
+    parent: Vec<Option<usize>>,
+    left: Vec<Option<usize>>,
+    right: Vec<Option<usize>>,
+}
+
+/// The min-rooted Cartesian tree of `values`; among equal values the leftmost
+/// one is the ancestor of the others
+pub fn cartesian_tree<T: Ord>(values: &[T]) -> CartesianTree {
+    let n = values.len();
+    let mut parent = vec![None; n];
+    let mut left = vec![None; n];
+    let mut right = vec![None; n];
+    // right spine of the tree built so far, values non-decreasing upwards
+    let mut stack: Vec<usize> = Vec::with_capacity(n);
+    for i in 0..n {
+        let mut last = None;
+        while let Some(&top) = stack.last() {
+            if values[top] <= values[i] {
+                break;
+            }
+            last = stack.pop();
+        }
+        // the popped chain becomes the left subtree of i
+        if let Some(child) = last {
+            left[i] = Some(child);
+            parent[child] = Some(i);
+        }
+        if let Some(&top) = stack.last() {
+            right[top] = Some(i);
+            parent[i] = Some(top);
+        }
+        stack.push(i);
+    }
+    CartesianTree {
+        root: stack.first().copied(),
+        parent,
+        left,
+        right,
+    }
+}
+
+impl CartesianTree {
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// Position of the minimum, None for an empty array
+    pub fn root(&self) -> Option<usize> {
+        self.root
+    }
+
+    /// Parent of every position, None for the root
+    pub fn parents(&self) -> &[Option<usize>] {
+        &self.parent
+    }
+
+    pub fn parent(&self, i: usize) -> Option<usize> {
+        self.parent[i]
+    }
+
+    pub fn left(&self, i: usize) -> Option<usize> {
+        self.left[i]
+    }
+
+    pub fn right(&self, i: usize) -> Option<usize> {
+        self.right[i]
+    }
+
+    /// The tree as an undirected graph with unit weights
+    pub fn to_graph(&self) -> Graph {
+        let mut graph = Graph::new(self.len());
+        for (v, p) in self.parent.iter().enumerate() {
+            if let Some(p) = *p {
+                graph.add_undirected_edge(p, v, 1);
+            }
+        }
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_example() {
+        let tree = cartesian_tree(&[3, 1, 4, 1, 5, 9, 2, 6]);
+        assert_eq!(tree.root(), Some(1));
+        assert_eq!(
+            tree.parents(),
+            &[
+                Some(1),
+                None,
+                Some(3),
+                Some(1),
+                Some(6),
+                Some(4),
+                Some(3),
+                Some(6)
+            ]
+        );
+        assert_eq!((tree.left(6), tree.right(6)), (Some(4), Some(7)));
+        assert_eq!(tree.to_graph().edge_count(), 14);
+        assert_eq!(cartesian_tree::<u32>(&[]).root(), None);
+    }
+
+    #[test]
+    fn test_lca_is_range_minimum() {
+        let mut rng = Rng::new(46);
+        for _ in 0..50 {
+            let n = rng.gen_range(1, 60) as usize;
+            let values: Vec<u64> = (0..n).map(|_| rng.gen_range(0, 10)).collect();
+            let tree = cartesian_tree(&values);
+            let ancestors = |mut v: usize| {
+                let mut path = vec![v];
+                while let Some(p) = tree.parent(v) {
+                    path.push(p);
+                    v = p;
+                }
+                path
+            };
+            for l in 0..n {
+                let up = ancestors(l);
+                for r in l..n {
+                    let lca = ancestors(r).into_iter().find(|v| up.contains(v)).unwrap();
+                    // the leftmost minimum of the range
+                    let min = *values[l..=r].iter().min().unwrap();
+                    let expected = l + values[l..=r].iter().position(|&v| v == min).unwrap();
+                    assert_eq!(lca, expected);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,228 @@
+// ------- CLIQUES AND COLORINGS -------
+// Exact exponential algorithms for small graphs, with vertex sets as u64
+// masks. A maximum independent set is found by branch and bound: vertices of
+// degree <= 1 are always taken, otherwise a vertex of maximum degree is either
+// taken, dropping its neighbours, or discarded, in about O(1.38^n) for n up to
+// 40-ish; a maximum clique is one in the complement. The chromatic number uses
+// inclusion-exclusion: with I(S) the number of independent subsets of S (a
+// zeta transform), the graph is k-colorable iff sum over S of
+// (-1)^(n-|S|) I(S)^k > 0, checked modulo a prime in O(n 2^n).
+
+use crate::graph::Graph;
+use crate::modint::Mod998244353;
+use crate::sos::subset_zeta;
+
+type Mint = Mod998244353;
+
+// Neighbourhoods as masks, ignoring loops
+fn adjacency(graph: &Graph) -> Vec<u64> {
+    assert!(graph.len() <= 64, "At most 64 vertices are supported");
+    (0..graph.len())
+        .map(|v| {
+            graph
+                .neighbors(v)
+                .iter()
+                .filter(|&&(w, _)| w != v)
+                .fold(0, |mask, &(w, _)| mask | 1 << w)
+        })
+        .collect()
+}
+
+fn vertices(mask: u64) -> Vec<usize> {
+    (0..64).filter(|&v| mask >> v & 1 == 1).collect()
+}
+
+// Maximum independent set within `mask`
+fn independent_within(adj: &[u64], mask: u64) -> u64 {
+    if mask == 0 {
+        return 0;
+    }
+    let (mut pivot, mut max_degree) = (0, 0);
+    let mut rest = mask;
+    while rest != 0 {
+        let v = rest.trailing_zeros() as usize;
+        rest &= rest - 1;
+        let degree = (adj[v] & mask).count_ones();
+        if degree <= 1 {
+            return 1 << v | independent_within(adj, mask & !(1 << v) & !adj[v]);
+        }
+        if degree > max_degree {
+            (pivot, max_degree) = (v, degree);
+        }
+    }
+    let with = 1 << pivot | independent_within(adj, mask & !(1 << pivot) & !adj[pivot]);
+    let without = independent_within(adj, mask & !(1 << pivot));
+    if with.count_ones() >= without.count_ones() {
+        with
+    } else {
+        without
+    }
+}
+
+/// Vertices of a maximum independent set of the undirected `graph`
+pub fn max_independent_set(graph: &Graph) -> Vec<usize> {
+    let n = graph.len();
+    let all = if n == 64 { u64::MAX } else { (1 << n) - 1 };
+    vertices(independent_within(&adjacency(graph), all))
+}
+
+/// Vertices of a maximum clique of the undirected `graph`
+pub fn max_clique(graph: &Graph) -> Vec<usize> {
+    let n = graph.len();
+    let adj = adjacency(graph);
+    let all = if n == 64 { u64::MAX } else { (1 << n) - 1 };
+    let complement: Vec<u64> = adj
+        .iter()
+        .enumerate()
+        .map(|(v, &mask)| all & !mask & !(1 << v))
+        .collect();
+    vertices(independent_within(&complement, all))
+}
+
+/// Minimum number of colors of a proper coloring of the undirected `graph`,
+/// for up to about 25 vertices
+pub fn chromatic_number(graph: &Graph) -> usize {
+    let n = graph.len();
+    assert!(n <= 30, "Too many vertices");
+    if n == 0 {
+        return 0;
+    }
+    let adj = adjacency(graph);
+    // independent[S] = 1 if S is independent, then the count of its subsets
+    let mut independent = vec![Mint::new(0); 1 << n];
+    independent[0] = Mint::new(1);
+    for s in 1usize..1 << n {
+        let v = s.trailing_zeros() as usize;
+        let rest = s & (s - 1);
+        if independent[rest].value() == 1 && adj[v] & rest as u64 == 0 {
+            independent[s] = Mint::new(1);
+        }
+    }
+    subset_zeta(&mut independent);
+    let mut powers = independent.clone();
+    for k in 1..n {
+        let colorings: Mint = powers
+            .iter()
+            .enumerate()
+            .map(|(s, &p)| {
+                if (n - s.count_ones() as usize) % 2 == 1 {
+                    -p
+                } else {
+                    p
+                }
+            })
+            .sum();
+        if colorings.value() != 0 {
+            return k;
+        }
+        for (p, &i) in powers.iter_mut().zip(&independent) {
+            *p *= i;
+        }
+    }
+    n
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn random_graph(rng: &mut Rng, n: usize, density: f64) -> Graph {
+        let mut graph = Graph::new(n);
+        for u in 0..n {
+            for v in 0..u {
+                if rng.gen_bool(density) {
+                    graph.add_undirected_edge(u, v, 1);
+                }
+            }
+        }
+        graph
+    }
+
+    fn is_independent(graph: &Graph, set: &[usize]) -> bool {
+        set.iter()
+            .all(|&v| graph.neighbors(v).iter().all(|&(w, _)| !set.contains(&w)))
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = Rng::new(82);
+        for _ in 0..60 {
+            let n = rng.gen_range(0, 10) as usize;
+            let density = rng.gen_f64();
+            let graph = random_graph(&mut rng, n, density);
+            let adj = adjacency(&graph);
+            let independent = |s: u64| vertices(s).iter().all(|&v| adj[v] & s == 0);
+            let clique = |s: u64| vertices(s).iter().all(|&v| s & !(adj[v] | 1 << v) == 0);
+            let best = |pred: &dyn Fn(u64) -> bool| {
+                (0..1u64 << n)
+                    .filter(|&s| pred(s))
+                    .map(u64::count_ones)
+                    .max()
+                    .unwrap() as usize
+            };
+            let set = max_independent_set(&graph);
+            assert!(is_independent(&graph, &set));
+            assert_eq!(set.len(), best(&independent));
+            let set = max_clique(&graph);
+            let mask = set.iter().fold(0, |m, &v| m | 1u64 << v);
+            assert!(clique(mask));
+            assert_eq!(set.len(), best(&clique));
+
+            // smallest k such that some assignment of k colors is proper
+            let chromatic = (1..=n)
+                .find(|&k| {
+                    let mut colors = vec![0; n];
+                    loop {
+                        let proper = (0..n).all(|v| {
+                            graph
+                                .neighbors(v)
+                                .iter()
+                                .all(|&(w, _)| colors[w] != colors[v])
+                        });
+                        if proper {
+                            return true;
+                        }
+                        // next assignment in base k
+                        let Some(i) = (0..n).find(|&i| colors[i] + 1 < k) else {
+                            return false;
+                        };
+                        colors[i] += 1;
+                        colors[..i].fill(0);
+                    }
+                })
+                .unwrap_or(0);
+            assert_eq!(chromatic_number(&graph), chromatic);
+        }
+    }
+
+    #[test]
+    fn test_larger_graphs() {
+        // the Petersen graph has independence number 4 and is 3-chromatic
+        let mut petersen = Graph::new(10);
+        for i in 0..5 {
+            petersen.add_undirected_edge(i, (i + 1) % 5, 1);
+            petersen.add_undirected_edge(i, i + 5, 1);
+            petersen.add_undirected_edge(i + 5, (i + 2) % 5 + 5, 1);
+        }
+        assert_eq!(max_independent_set(&petersen).len(), 4);
+        assert_eq!(max_clique(&petersen).len(), 2);
+        assert_eq!(chromatic_number(&petersen), 3);
+
+        // 40 vertices covered by 8 disjoint 5-cliques, each holding at most one
+        // vertex of an independent set
+        let mut rng = Rng::new(83);
+        let mut graph = Graph::new(40);
+        for u in 0..40 {
+            for v in 0..u {
+                if u / 5 == v / 5 || rng.gen_bool(0.1) {
+                    graph.add_undirected_edge(u, v, 1);
+                }
+            }
+        }
+        let set = max_independent_set(&graph);
+        assert!(is_independent(&graph, &set));
+        assert!(set.len() <= 8);
+        assert!(max_clique(&graph).len() >= 5);
+    }
+}
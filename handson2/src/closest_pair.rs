@@ -0,0 +1,137 @@
+// ------- DYNAMIC CLOSEST PAIR -------
+// Closest pair of points under insertions. Points are bucketed in a grid of
+// cells as wide as the current minimum distance d, so a closer partner of a
+// new point lies in one of the 3 x 3 cells around it, each holding O(1)
+// points. When d shrinks the grid is rebuilt; for points inserted in random
+// order that happens O(log n) times in expectation, so insertions take O(1)
+// expected amortized time (O(n) in the worst case).
+
+use crate::geometry::Point;
+use crate::hash::FastHashMap;
+
+#[derive(Clone, Debug, Default)]
+pub struct ClosestPair {
+    points: Vec<Point>,
+    best: Option<(f64, usize, usize)>, // Minimum distance and its pair
+    cell: f64,                         // Width of the grid cells
+    grid: FastHashMap<(i64, i64), Vec<usize>>,
+}
+
+impl ClosestPair {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Minimum distance between two inserted points, None with fewer than two
+    pub fn min_dist(&self) -> Option<f64> {
+        self.best.map(|(d, _, _)| d)
+    }
+
+    /// Indices, in insertion order, of a closest pair
+    pub fn closest(&self) -> Option<(usize, usize)> {
+        self.best.map(|(_, i, j)| (i, j))
+    }
+
+    /// Inserts `p` and returns its index
+    pub fn insert(&mut self, p: Point) -> usize {
+        let index = self.points.len();
+        self.points.push(p);
+        match self.best {
+            // a duplicate point was seen, nothing can get closer
+            Some((0.0, _, _)) => {}
+            Some(_) => {
+                let (cx, cy) = self.key(p);
+                let mut closer = None;
+                for x in cx - 1..=cx + 1 {
+                    for y in cy - 1..=cy + 1 {
+                        for &j in self.grid.get(&(x, y)).into_iter().flatten() {
+                            let d = self.points[j].dist(p);
+                            if d < closer.map_or(self.best.unwrap().0, |(d, _, _)| d) {
+                                closer = Some((d, j, index));
+                            }
+                        }
+                    }
+                }
+                match closer {
+                    Some(best) => {
+                        self.best = Some(best);
+                        self.rebuild();
+                    }
+                    None => self.grid.entry((cx, cy)).or_default().push(index),
+                }
+            }
+            None if index == 1 => {
+                self.best = Some((self.points[0].dist(p), 0, 1));
+                self.rebuild();
+            }
+            None => {}
+        }
+        index
+    }
+
+    // Grid cell of p
+    fn key(&self, p: Point) -> (i64, i64) {
+        (
+            (p.x / self.cell).floor() as i64,
+            (p.y / self.cell).floor() as i64,
+        )
+    }
+
+    // Buckets every point again with cells of the current minimum distance
+    fn rebuild(&mut self) {
+        self.grid.clear();
+        let d = self.best.unwrap().0;
+        if d == 0.0 {
+            return;
+        }
+        self.cell = d;
+        for i in 0..self.points.len() {
+            let key = self.key(self.points[i]);
+            self.grid.entry(key).or_default().push(i);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = Rng::new(70);
+        for round in 0..20 {
+            // small coordinate ranges force duplicates and rebuilds
+            let range = if round % 2 == 0 { 1000 } else { 20 };
+            let mut pairs = ClosestPair::new();
+            assert_eq!(pairs.min_dist(), None);
+            let mut points = Vec::new();
+            for _ in 0..150 {
+                let p = Point::new(
+                    rng.gen_range_i64(-range, range) as f64 / 3.0,
+                    rng.gen_range_i64(-range, range) as f64 / 3.0,
+                );
+                points.push(p);
+                assert_eq!(pairs.insert(p), points.len() - 1);
+                let brute = (0..points.len())
+                    .flat_map(|i| (0..i).map(move |j| (i, j)))
+                    .map(|(i, j)| points[i].dist(points[j]))
+                    .fold(None, |best: Option<f64>, d| {
+                        Some(best.map_or(d, |b| b.min(d)))
+                    });
+                assert_eq!(pairs.min_dist(), brute);
+                if let Some((i, j)) = pairs.closest() {
+                    assert_eq!(Some(points[i].dist(points[j])), brute);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,208 @@
+// ------- COMBINATORIAL ITERATION -------
+// Exhaustive enumeration for brute force references and small cases:
+// permutations in lexicographic order (each distinct arrangement of a
+// multiset once), k-subsets of indices in lexicographic order, and all the
+// subsets of n elements in Gray code order, where consecutive subsets differ
+// by one element so that their costs can be updated incrementally.
+
+/// Rearranges `values` into the next lexicographically greater permutation.
+/// Returns false, leaving `values` sorted, if it was the last one.
+pub fn next_permutation<T: Ord>(values: &mut [T]) -> bool {
+    // the longest non-increasing suffix cannot grow on its own
+    let Some(pivot) = (1..values.len()).rev().find(|&i| values[i - 1] < values[i]) else {
+        values.reverse();
+        return false;
+    };
+    let pivot = pivot - 1;
+    let successor = (pivot + 1..values.len())
+        .rev()
+        .find(|&i| values[pivot] < values[i])
+        .unwrap();
+    values.swap(pivot, successor);
+    values[pivot + 1..].reverse();
+    true
+}
+
+/// Rearranges `values` into the previous lexicographically smaller
+/// permutation. Returns false, leaving `values` sorted in decreasing order,
+/// if it was the first one.
+pub fn prev_permutation<T: Ord>(values: &mut [T]) -> bool {
+    let Some(pivot) = (1..values.len()).rev().find(|&i| values[i - 1] > values[i]) else {
+        values.reverse();
+        return false;
+    };
+    let pivot = pivot - 1;
+    let predecessor = (pivot + 1..values.len())
+        .rev()
+        .find(|&i| values[pivot] > values[i])
+        .unwrap();
+    values.swap(pivot, predecessor);
+    values[pivot + 1..].reverse();
+    true
+}
+
+/// Iterator over the distinct permutations of a multiset
+pub struct Permutations<T> {
+    current: Option<Vec<T>>, // None once exhausted
+}
+
+/// The distinct permutations of `values` in lexicographic order, starting
+/// from the sorted one
+pub fn permutations<T: Ord + Clone>(values: &[T]) -> Permutations<T> {
+    let mut current = values.to_vec();
+    current.sort();
+    Permutations {
+        current: Some(current),
+    }
+}
+
+impl<T: Ord + Clone> Iterator for Permutations<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Vec<T>> {
+        let current = self.current.as_mut()?;
+        let item = current.clone();
+        if !next_permutation(current) {
+            self.current = None;
+        }
+        Some(item)
+    }
+}
+
+/// Iterator over the k-subsets of `0..n`
+pub struct Combinations {
+    n: usize,
+    current: Option<Vec<usize>>, // Increasing indices, None once exhausted
+}
+
+/// The increasing `k`-tuples of indices in `0..n`, in lexicographic order
+pub fn combinations(n: usize, k: usize) -> Combinations {
+    Combinations {
+        n,
+        current: (k <= n).then(|| (0..k).collect()),
+    }
+}
+
+impl Iterator for Combinations {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        let current = self.current.as_mut()?;
+        let item = current.clone();
+        let k = current.len();
+        // the rightmost index that can still move right
+        match (0..k).rev().find(|&i| current[i] < self.n - k + i) {
+            Some(i) => {
+                current[i] += 1;
+                for j in i + 1..k {
+                    current[j] = current[j - 1] + 1;
+                }
+            }
+            None => self.current = None,
+        }
+        Some(item)
+    }
+}
+
+/// The `i`-th Gray code, which differs from the previous one in one bit
+pub fn gray_code(i: u64) -> u64 {
+    i ^ (i >> 1)
+}
+
+/// Inverse of `gray_code`
+pub fn gray_rank(mut code: u64) -> u64 {
+    let mut shift = 1;
+    while shift < 64 {
+        code ^= code >> shift;
+        shift *= 2;
+    }
+    code
+}
+
+/// All the subsets of `n < 64` elements as bit masks in Gray code order,
+/// starting from the empty one, each with the element toggled to reach it
+/// from the previous subset
+pub fn gray_subsets(n: u32) -> impl Iterator<Item = (u64, Option<usize>)> {
+    assert!(n < 64, "Too many elements");
+    (0..1u64 << n).map(|i| {
+        let changed = (i > 0).then(|| i.trailing_zeros() as usize);
+        (gray_code(i), changed)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permutations() {
+        let all: Vec<Vec<u32>> = permutations(&[3, 1, 2]).collect();
+        let expected = [
+            [1, 2, 3],
+            [1, 3, 2],
+            [2, 1, 3],
+            [2, 3, 1],
+            [3, 1, 2],
+            [3, 2, 1],
+        ];
+        assert_eq!(all, expected);
+
+        // a multiset: 7! / (3! 2! 2!) distinct arrangements, strictly increasing
+        let multiset: Vec<Vec<char>> =
+            permutations(&"aabbccc".chars().collect::<Vec<_>>()).collect();
+        assert_eq!(multiset.len(), 210);
+        assert!(multiset.windows(2).all(|w| w[0] < w[1]));
+
+        // walking back from the last permutation visits them in reverse
+        let mut values = vec![1, 2, 2, 3];
+        let mut forward = vec![values.clone()];
+        while next_permutation(&mut values) {
+            forward.push(values.clone());
+        }
+        assert_eq!(values, vec![1, 2, 2, 3]);
+        values.reverse();
+        let mut backward = vec![values.clone()];
+        while prev_permutation(&mut values) {
+            backward.push(values.clone());
+        }
+        backward.reverse();
+        assert_eq!(forward, backward);
+        assert_eq!(permutations::<u8>(&[]).count(), 1);
+    }
+
+    #[test]
+    fn test_combinations() {
+        let all: Vec<Vec<usize>> = combinations(4, 2).collect();
+        let expected = [[0, 1], [0, 2], [0, 3], [1, 2], [1, 3], [2, 3]];
+        assert_eq!(all, expected);
+        for n in 0..10 {
+            let mut total = 0;
+            for k in 0..=n {
+                let subsets: Vec<Vec<usize>> = combinations(n, k).collect();
+                assert!(subsets.windows(2).all(|w| w[0] < w[1]));
+                total += subsets.len();
+            }
+            assert_eq!(total, 1 << n);
+        }
+        assert_eq!(combinations(2, 3).count(), 0);
+    }
+
+    #[test]
+    fn test_gray_subsets() {
+        let n = 6;
+        let mut seen = vec![false; 1 << n];
+        let mut previous = 0;
+        for (i, (mask, changed)) in gray_subsets(n).enumerate() {
+            assert!(!seen[mask as usize]);
+            seen[mask as usize] = true;
+            assert_eq!(gray_rank(mask), i as u64);
+            match changed {
+                Some(bit) => assert_eq!(mask ^ previous, 1 << bit),
+                None => assert_eq!(mask, 0),
+            }
+            previous = mask;
+        }
+        assert!(seen.iter().all(|&s| s));
+        assert_eq!(gray_rank(gray_code(u64::MAX)), u64::MAX);
+    }
+}
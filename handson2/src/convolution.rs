@@ -0,0 +1,169 @@
+// ------- CONVOLUTION -------
+// Polynomial multiplication modulo a prime with the number theoretic
+// transform in O(n log n). Moduli that do not support a transform of the
+// needed length (e.g. 10^9 + 7) and small inputs use the schoolbook product.
+
+use crate::modint::ModInt;
+
+// Below this size the O(nm) product is faster than three transforms
+const NAIVE_THRESHOLD: usize = 64;
+
+/// Smallest primitive root modulo the prime `M`
+pub fn primitive_root<const M: u32>() -> ModInt<M> {
+    if M == 2 {
+        return ModInt::new(1);
+    }
+    // prime factors of M - 1
+    let mut factors = Vec::new();
+    let mut rest = M - 1;
+    let mut p = 2;
+    while p * p <= rest {
+        if rest.is_multiple_of(p) {
+            factors.push(p);
+            while rest.is_multiple_of(p) {
+                rest /= p;
+            }
+        }
+        p += 1;
+    }
+    if rest > 1 {
+        factors.push(rest);
+    }
+    (2..M)
+        .map(|g| ModInt::new(g as u64))
+        .find(|g: &ModInt<M>| {
+            factors
+                .iter()
+                .all(|&q| g.pow(((M - 1) / q) as u64).value() != 1)
+        })
+        .unwrap()
+}
+
+/// True if `M` supports transforms of length `len` (a power of two)
+pub fn ntt_friendly<const M: u32>(len: usize) -> bool {
+    len.is_power_of_two() && (M - 1).trailing_zeros() >= len.trailing_zeros()
+}
+
+/// In-place transform of `a`, whose length must be a power of two dividing
+/// `M - 1`. With `invert` it computes the inverse transform, scaled by 1/n.
+pub fn ntt<const M: u32>(a: &mut [ModInt<M>], invert: bool) {
+    let n = a.len();
+    assert!(ntt_friendly::<M>(n), "Unsupported transform length");
+    // bit-reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            a.swap(i, j);
+        }
+    }
+
+    let root = primitive_root::<M>();
+    let mut len = 2;
+    while len <= n {
+        let mut w_len = root.pow(((M - 1) as usize / len) as u64);
+        if invert {
+            w_len = w_len.inv();
+        }
+        for chunk in a.chunks_mut(len) {
+            let (lo, hi) = chunk.split_at_mut(len / 2);
+            let mut w = ModInt::new(1);
+            for (x, y) in lo.iter_mut().zip(hi.iter_mut()) {
+                let (u, v) = (*x, *y * w);
+                *x = u + v;
+                *y = u - v;
+                w *= w_len;
+            }
+        }
+        len <<= 1;
+    }
+
+    if invert {
+        let n_inv = ModInt::new(n as u64).inv();
+        a.iter_mut().for_each(|x| *x *= n_inv);
+    }
+}
+
+/// Coefficients of the product of the polynomials `a` and `b`
+pub fn convolution<const M: u32>(a: &[ModInt<M>], b: &[ModInt<M>]) -> Vec<ModInt<M>> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let result_len = a.len() + b.len() - 1;
+    let size = result_len.next_power_of_two();
+    if a.len().min(b.len()) <= NAIVE_THRESHOLD || !ntt_friendly::<M>(size) {
+        return convolution_naive(a, b);
+    }
+    let mut fa = a.to_vec();
+    fa.resize(size, ModInt::new(0));
+    let mut fb = b.to_vec();
+    fb.resize(size, ModInt::new(0));
+    ntt(&mut fa, false);
+    ntt(&mut fb, false);
+    fa.iter_mut().zip(&fb).for_each(|(x, &y)| *x *= y);
+    ntt(&mut fa, true);
+    fa.truncate(result_len);
+    fa
+}
+
+/// Schoolbook product in O(|a| |b|), valid for any modulus
+pub fn convolution_naive<const M: u32>(a: &[ModInt<M>], b: &[ModInt<M>]) -> Vec<ModInt<M>> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut result = vec![ModInt::new(0); a.len() + b.len() - 1];
+    for (i, &x) in a.iter().enumerate() {
+        for (j, &y) in b.iter().enumerate() {
+            result[i + j] += x * y;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modint::{Mod1000000007, Mod998244353};
+    use crate::rng::Rng;
+
+    fn random_poly<const M: u32>(rng: &mut Rng, len: usize) -> Vec<ModInt<M>> {
+        (0..len).map(|_| ModInt::new(rng.next_u64())).collect()
+    }
+
+    #[test]
+    fn test_primitive_roots() {
+        assert_eq!(primitive_root::<998_244_353>().value(), 3);
+        assert_eq!(primitive_root::<1_000_000_007>().value(), 5);
+        assert_eq!(primitive_root::<7>().value(), 3);
+    }
+
+    #[test]
+    fn test_ntt_roundtrip() {
+        let mut rng = Rng::new(28);
+        let original: Vec<Mod998244353> = random_poly(&mut rng, 256);
+        let mut a = original.clone();
+        ntt(&mut a, false);
+        ntt(&mut a, true);
+        assert_eq!(a, original);
+    }
+
+    #[test]
+    fn test_convolution_against_naive() {
+        let mut rng = Rng::new(29);
+        for (n, m) in [(1, 1), (3, 70), (100, 100), (513, 200)] {
+            let a: Vec<Mod998244353> = random_poly(&mut rng, n);
+            let b = random_poly(&mut rng, m);
+            assert_eq!(convolution(&a, &b), convolution_naive(&a, &b));
+            // not NTT friendly, falls back to the schoolbook product
+            let c: Vec<Mod1000000007> = random_poly(&mut rng, n);
+            let d = random_poly(&mut rng, m);
+            assert_eq!(convolution(&c, &d), convolution_naive(&c, &d));
+        }
+        assert!(convolution::<998_244_353>(&[], &[ModInt::new(1)]).is_empty());
+    }
+}
@@ -0,0 +1,193 @@
+// ------- DIGIT DP -------
+// Counts the integers in [L, R] whose decimal representation (without leading
+// zeros) is accepted by a user-defined automaton. Bounds are decimal strings,
+// so they can be far larger than any machine integer.
+
+use std::hash::Hash;
+
+use crate::hash::FastHashMap;
+
+/// Number of integers in `[lo, hi]`, modulo `modulus`, whose digits lead the
+/// automaton from `initial` to a state satisfying `accept`.
+/// - `transition(state, digit)` returns the next state, or None to reject
+///   every number with that prefix;
+/// - the digits are fed from the most significant one, without leading zeros
+///   (the number 0 is the single digit 0).
+///
+/// Runs in O(digits * 10 * reachable states).
+pub fn digit_dp_count<S, T, A>(
+    lo: &str,
+    hi: &str,
+    modulus: u64,
+    initial: S,
+    transition: T,
+    accept: A,
+) -> u64
+where
+    S: Clone + Eq + Hash,
+    T: Fn(&S, u8) -> Option<S>,
+    A: Fn(&S) -> bool,
+{
+    let (lo, hi) = (parse_digits(lo), parse_digits(hi));
+    assert!(
+        (lo.len(), &lo) <= (hi.len(), &hi),
+        "Lower bound greater than upper bound"
+    );
+    let up_to_hi = count_up_to(&hi, modulus, &initial, &transition, &accept);
+    let up_to_lo = count_up_to(&lo, modulus, &initial, &transition, &accept);
+    let lo_itself = lo
+        .iter()
+        .try_fold(initial, |state, &d| transition(&state, d))
+        .is_some_and(|state| accept(&state));
+    let below_lo = add_mod(up_to_lo, modulus - u64::from(lo_itself) % modulus, modulus);
+    add_mod(up_to_hi, modulus - below_lo, modulus)
+}
+
+fn add_mod(a: u64, b: u64, modulus: u64) -> u64 {
+    ((a as u128 + b as u128) % modulus as u128) as u64
+}
+
+// Digits of a decimal string without leading zeros ("0" stays [0])
+fn parse_digits(s: &str) -> Vec<u8> {
+    assert!(
+        !s.is_empty() && s.bytes().all(|c| c.is_ascii_digit()),
+        "Bounds must be decimal strings"
+    );
+    let digits: Vec<u8> = s
+        .bytes()
+        .map(|c| c - b'0')
+        .skip_while(|&d| d == 0)
+        .collect();
+    if digits.is_empty() {
+        vec![0]
+    } else {
+        digits
+    }
+}
+
+// Accepted numbers in [0, bound]
+fn count_up_to<S, T, A>(bound: &[u8], modulus: u64, initial: &S, transition: &T, accept: &A) -> u64
+where
+    S: Clone + Eq + Hash,
+    T: Fn(&S, u8) -> Option<S>,
+    A: Fn(&S) -> bool,
+{
+    // counts of the prefixes already below the bound, None = no digit yet
+    let mut free: FastHashMap<Option<S>, u64> = FastHashMap::default();
+    // state of the prefix equal to the bound, None once rejected
+    let mut tight = Some(initial.clone());
+    for (i, &limit) in bound.iter().enumerate() {
+        let mut next: FastHashMap<Option<S>, u64> = FastHashMap::default();
+        let mut push = |from: &Option<S>, d: u8, count: u64| {
+            let to = match from {
+                None if d == 0 => None,
+                None => Some(transition(initial, d)),
+                Some(state) => Some(transition(state, d)),
+            };
+            let key = match to {
+                None => None,
+                Some(None) => return,
+                Some(Some(state)) => Some(state),
+            };
+            let entry = next.entry(key).or_insert(0);
+            *entry = add_mod(*entry, count, modulus);
+        };
+        for (state, &count) in &free {
+            for d in 0..10 {
+                push(state, d, count);
+            }
+        }
+        if let Some(state) = &tight {
+            // the tight prefix has started unless the bound itself is 0
+            let from = if i == 0 { None } else { Some(state.clone()) };
+            for d in 0..limit {
+                push(&from, d, 1);
+            }
+            tight = if i == 0 {
+                transition(initial, limit)
+            } else {
+                transition(state, limit)
+            };
+        }
+        free = next;
+    }
+
+    let mut total = u64::from(tight.is_some_and(|state| accept(&state)));
+    for (state, count) in free {
+        let accepted = match state {
+            Some(state) => accept(&state),
+            None => transition(initial, 0).is_some_and(|state| accept(&state)),
+        };
+        if accepted {
+            total = add_mod(total, count, modulus);
+        }
+    }
+    total % modulus
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn digits_of(mut x: u64) -> Vec<u8> {
+        let mut digits = vec![(x % 10) as u8];
+        x /= 10;
+        while x > 0 {
+            digits.push((x % 10) as u8);
+            x /= 10;
+        }
+        digits.reverse();
+        digits
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = Rng::new(26);
+        let sum_mod_7 = |s: &u8, d: u8| Some((s + d) % 7);
+        let no_equal_neighbours = |s: &u8, d: u8| (*s != d).then_some(d);
+        for _ in 0..50 {
+            let a = rng.gen_range(0, 3000);
+            let b = rng.gen_range(a, 3000);
+            let (lo, hi) = (a.to_string(), b.to_string());
+
+            let expected =
+                (a..=b).filter(|&x| digits_of(x).iter().map(|&d| d as u32).sum::<u32>() % 7 == 0);
+            let count = digit_dp_count(&lo, &hi, 1_000_000_007, 0u8, sum_mod_7, |&s| s == 0);
+            assert_eq!(count, expected.count() as u64);
+
+            let expected = (a..=b).filter(|&x| digits_of(x).windows(2).all(|w| w[0] != w[1]));
+            // 10 is not a digit, so the first digit is always allowed
+            let count =
+                digit_dp_count(&lo, &hi, 1_000_000_007, 10u8, no_equal_neighbours, |_| true);
+            assert_eq!(count, expected.count() as u64);
+        }
+    }
+
+    #[test]
+    fn test_huge_bounds_and_modulus() {
+        // every number in [0, 10^30 - 1] is accepted
+        let hi = "9".repeat(30);
+        let all = digit_dp_count("0", &hi, 998_244_353, (), |_, _| Some(()), |_| true);
+        let mut expected = 1u64;
+        for _ in 0..30 {
+            expected = expected * 10 % 998_244_353;
+        }
+        assert_eq!(all, expected);
+
+        // numbers made only of the digits 1 and 2 with up to 20 digits
+        let ones_twos = digit_dp_count(
+            "1",
+            "99999999999999999999",
+            1_000_000_007,
+            (),
+            |_, d| (d == 1 || d == 2).then_some(()),
+            |_| true,
+        );
+        assert_eq!(ones_twos, (1..=20).map(|k| 1u64 << k).sum::<u64>());
+        assert_eq!(
+            digit_dp_count("007", "7", 10, (), |_, _| Some(()), |_| true),
+            1
+        );
+    }
+}
@@ -0,0 +1,126 @@
+// ------- RANGE DISTINCT COUNT -------
+// Number of distinct values in arr[l..=r]. A value is counted at its first
+// occurrence in the range, i.e. at the positions j whose previous occurrence
+// prev[j] is before l. Offline, queries sorted by r keep a 1 on the last
+// occurrence of each value in a Fenwick tree; online, a persistent segment
+// tree over prev answers each query in O(log n). Ranges are 0-based and
+// inclusive.
+
+use crate::fenwick::FenwickTree;
+use crate::hash::FastHashMap;
+use crate::persistent::PersistentSegmentTree;
+use crate::segment_tree::Sum;
+use std::hash::Hash;
+
+// For every position, 1 + the index of the previous occurrence of its value,
+// 0 if it is the first one
+fn previous_occurrences<T: Hash + Eq>(arr: &[T]) -> Vec<usize> {
+    let mut last = FastHashMap::default();
+    arr.iter()
+        .enumerate()
+        .map(|(i, x)| last.insert(x, i).map_or(0, |p| p + 1))
+        .collect()
+}
+
+/// Answers all the `(l, r)` queries in O((n + q) log n)
+pub fn distinct_counts_offline<T: Hash + Eq>(arr: &[T], queries: &[(usize, usize)]) -> Vec<usize> {
+    for &(l, r) in queries {
+        assert!(l <= r && r < arr.len(), "Invalid range");
+    }
+    let prev = previous_occurrences(arr);
+    let mut order: Vec<usize> = (0..queries.len()).collect();
+    order.sort_unstable_by_key(|&i| queries[i].1);
+
+    let mut marks = FenwickTree::<i64>::new(arr.len());
+    let mut answers = vec![0; queries.len()];
+    let mut next = 0; // Positions before `next` are marked
+    for i in order {
+        let (l, r) = queries[i];
+        while next <= r {
+            // only the last occurrence so far of each value stays marked
+            if prev[next] > 0 {
+                marks.add(prev[next] - 1, -1);
+            }
+            marks.add(next, 1);
+            next += 1;
+        }
+        answers[i] = marks.sum(l, r) as usize;
+    }
+    answers
+}
+
+pub struct DistinctCounter {
+    n: usize,
+    // Version i counts, for the positions before i, each value of prev
+    prev_counts: PersistentSegmentTree<Sum<usize>>,
+}
+
+impl DistinctCounter {
+    pub fn new<T: Hash + Eq>(arr: &[T]) -> Self {
+        let n = arr.len();
+        let mut prev_counts = PersistentSegmentTree::new(&vec![Sum(0); n + 1]);
+        for (i, p) in previous_occurrences(arr).into_iter().enumerate() {
+            // version i + 1 is built on top of version i
+            prev_counts.update(i, p, |c| Sum(c.0 + 1));
+        }
+        DistinctCounter { n, prev_counts }
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Number of distinct values in `arr[l..=r]`
+    pub fn count(&self, l: usize, r: usize) -> usize {
+        assert!(l <= r && r < self.n, "Invalid range");
+        // positions in [l, r] whose previous occurrence, shifted by one, is <= l
+        let counts = &self.prev_counts;
+        counts.query(r + 1, 0, l).0 - counts.query(l, 0, l).0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::FastHashSet;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_example() {
+        let text = b"abracadabra";
+        let queries = [(0, 10), (0, 0), (1, 3), (3, 7), (7, 10)];
+        let expected = vec![5, 1, 3, 3, 3];
+        assert_eq!(distinct_counts_offline(text, &queries), expected);
+        let counter = DistinctCounter::new(text);
+        let online: Vec<usize> = queries.iter().map(|&(l, r)| counter.count(l, r)).collect();
+        assert_eq!(online, expected);
+    }
+
+    #[test]
+    fn test_random_against_naive() {
+        let mut rng = Rng::new(53);
+        for _ in 0..20 {
+            let n = rng.gen_range(1, 80) as usize;
+            let values = rng.gen_range(1, 20);
+            let arr: Vec<u64> = (0..n).map(|_| rng.gen_range(0, values)).collect();
+            let queries: Vec<(usize, usize)> = (0..100)
+                .map(|_| {
+                    let a = rng.gen_range(0, n as u64 - 1) as usize;
+                    let b = rng.gen_range(0, n as u64 - 1) as usize;
+                    (a.min(b), a.max(b))
+                })
+                .collect();
+            let offline = distinct_counts_offline(&arr, &queries);
+            let counter = DistinctCounter::new(&arr);
+            for (&(l, r), &answer) in queries.iter().zip(&offline) {
+                let naive = arr[l..=r].iter().collect::<FastHashSet<_>>().len();
+                assert_eq!(answer, naive);
+                assert_eq!(counter.count(l, r), naive);
+            }
+        }
+    }
+}
@@ -0,0 +1,233 @@
+// ------- DOMINATOR TREE -------
+// In a directed graph with a source s, d dominates v if every path from s to
+// v goes through d. The immediate dominator of v is its closest strict
+// dominator, and these form a tree rooted at s over the vertices reachable
+// from s. Lengauer-Tarjan computes it in O(m log n) from a DFS tree: the
+// semidominators come from the predecessors in decreasing preorder, with a
+// path-compressed forest, and fix the immediate dominators in a last pass.
+
+use crate::graph::Graph;
+
+const NONE: usize = usize::MAX;
+
+pub struct DominatorTree {
+    idom: Vec<Option<usize>>, // Immediate dominator, None for s and unreachable vertices
+    tin: Vec<usize>,          // Entry and exit times in the dominator tree
+    tout: Vec<usize>,
+}
+
+impl DominatorTree {
+    pub fn new(graph: &Graph, source: usize) -> Self {
+        let n = graph.len();
+        assert!(source < n, "Index out of range");
+        // preorder numbering, everything below works on preorder indices
+        let mut num = vec![NONE; n];
+        let mut vertex = Vec::new();
+        let mut parent = Vec::new();
+        let mut stack = vec![(source, NONE)];
+        while let Some((v, p)) = stack.pop() {
+            if num[v] != NONE {
+                continue;
+            }
+            num[v] = vertex.len();
+            vertex.push(v);
+            parent.push(p);
+            for &(w, _) in graph.neighbors(v).iter().rev() {
+                if num[w] == NONE {
+                    stack.push((w, num[v]));
+                }
+            }
+        }
+        let k = vertex.len();
+        let mut preds = vec![Vec::new(); k];
+        for (i, &v) in vertex.iter().enumerate() {
+            for &(w, _) in graph.neighbors(v) {
+                preds[num[w]].push(i);
+            }
+        }
+
+        let mut semi: Vec<usize> = (0..k).collect();
+        let mut label: Vec<usize> = (0..k).collect();
+        let mut ancestor = vec![NONE; k];
+        let mut idom = vec![NONE; k];
+        let mut bucket = vec![Vec::new(); k];
+        for w in (1..k).rev() {
+            for &v in &preds[w] {
+                let u = eval(v, &mut ancestor, &mut label, &semi);
+                semi[w] = semi[w].min(semi[u]);
+            }
+            bucket[semi[w]].push(w);
+            let p = parent[w];
+            ancestor[w] = p;
+            for v in std::mem::take(&mut bucket[p]) {
+                let u = eval(v, &mut ancestor, &mut label, &semi);
+                idom[v] = if semi[u] < semi[v] { u } else { p };
+            }
+        }
+        for w in 1..k {
+            if idom[w] != semi[w] {
+                idom[w] = idom[idom[w]];
+            }
+        }
+
+        let mut result = vec![None; n];
+        let mut children = vec![Vec::new(); n];
+        for w in 1..k {
+            result[vertex[w]] = Some(vertex[idom[w]]);
+            children[vertex[idom[w]]].push(vertex[w]);
+        }
+        let (mut tin, mut tout) = (vec![NONE; n], vec![NONE; n]);
+        let mut timer = 0;
+        let mut stack = vec![(source, false)];
+        while let Some((v, exiting)) = stack.pop() {
+            if exiting {
+                tout[v] = timer;
+                continue;
+            }
+            tin[v] = timer;
+            timer += 1;
+            stack.push((v, true));
+            stack.extend(children[v].iter().map(|&c| (c, false)));
+        }
+        DominatorTree {
+            idom: result,
+            tin,
+            tout,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.idom.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.idom.is_empty()
+    }
+
+    /// Immediate dominator of `v`, None for the source and for the vertices
+    /// it does not reach
+    pub fn idom(&self, v: usize) -> Option<usize> {
+        self.idom[v]
+    }
+
+    /// The immediate dominators of all vertices
+    pub fn idoms(&self) -> &[Option<usize>] {
+        &self.idom
+    }
+
+    pub fn is_reachable(&self, v: usize) -> bool {
+        self.tin[v] != NONE
+    }
+
+    /// Children lists of the dominator tree, empty for unreachable vertices
+    pub fn tree(&self) -> Vec<Vec<usize>> {
+        let mut children = vec![Vec::new(); self.len()];
+        for (v, &d) in self.idom.iter().enumerate() {
+            if let Some(d) = d {
+                children[d].push(v);
+            }
+        }
+        children
+    }
+
+    /// True if `d` dominates `v` (every vertex dominates itself), for `v`
+    /// reachable from the source
+    pub fn dominates(&self, d: usize, v: usize) -> bool {
+        assert!(self.is_reachable(v), "Vertex not reachable from the source");
+        self.is_reachable(d) && self.tin[d] <= self.tin[v] && self.tout[v] <= self.tout[d]
+    }
+}
+
+// Vertex of minimum semidominator on the forest path from v up to, excluding,
+// its root, compressing the path on the way
+fn eval(v: usize, ancestor: &mut [usize], label: &mut [usize], semi: &[usize]) -> usize {
+    if ancestor[v] == NONE {
+        return v;
+    }
+    let mut path = Vec::new();
+    let mut x = v;
+    while ancestor[ancestor[x]] != NONE {
+        path.push(x);
+        x = ancestor[x];
+    }
+    for &y in path.iter().rev() {
+        let a = ancestor[y];
+        if semi[label[a]] < semi[label[y]] {
+            label[y] = label[a];
+        }
+        ancestor[y] = ancestor[a];
+    }
+    label[v]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    // Vertices reachable from the source without going through `removed`
+    fn reach(graph: &Graph, source: usize, removed: usize) -> Vec<bool> {
+        let mut seen = vec![false; graph.len()];
+        if source == removed {
+            return seen;
+        }
+        seen[source] = true;
+        let mut todo = vec![source];
+        while let Some(v) = todo.pop() {
+            for &(w, _) in graph.neighbors(v) {
+                if w != removed && !seen[w] {
+                    seen[w] = true;
+                    todo.push(w);
+                }
+            }
+        }
+        seen
+    }
+
+    #[test]
+    fn test_against_removal() {
+        let mut rng = Rng::new(81);
+        for _ in 0..100 {
+            let n = rng.gen_range(1, 25) as usize;
+            let mut graph = Graph::new(n);
+            for _ in 0..rng.gen_range(0, 3 * n as u64) {
+                let u = rng.gen_range(0, n as u64 - 1) as usize;
+                let v = rng.gen_range(0, n as u64 - 1) as usize;
+                graph.add_edge(u, v, 1);
+            }
+            let source = rng.gen_range(0, n as u64 - 1) as usize;
+            let tree = DominatorTree::new(&graph, source);
+            let reachable = reach(&graph, source, NONE);
+            // dominators[v] = strict dominators of v
+            let mut dominators = vec![Vec::new(); n];
+            for d in 0..n {
+                let without = reach(&graph, source, d);
+                for v in 0..n {
+                    if v != d && reachable[v] && !without[v] {
+                        dominators[v].push(d);
+                    }
+                }
+            }
+            for v in 0..n {
+                assert_eq!(tree.is_reachable(v), reachable[v]);
+                // the immediate dominator is the deepest strict dominator
+                let expected = dominators[v]
+                    .iter()
+                    .max_by_key(|&&d| dominators[d].len())
+                    .copied();
+                assert_eq!(tree.idom(v), expected);
+                if reachable[v] {
+                    for d in 0..n {
+                        let dominates = d == v || dominators[v].contains(&d);
+                        assert_eq!(tree.dominates(d, v), dominates);
+                    }
+                }
+            }
+            let children = tree.tree();
+            assert_eq!(
+                children.iter().map(Vec::len).sum::<usize>(),
+                reachable.iter().filter(|&&r| r).count() - 1
+            );
+        }
+    }
+}
@@ -0,0 +1,244 @@
+// ------- DP UTILITIES -------
+// Divide-and-conquer optimization, convex hull trick on top of the Li Chao
+// tree, knapsack variants and longest common subsequence.
+
+use crate::li_chao::LiChaoTree;
+
+/// Value used for unreachable DP states
+pub const INF: i64 = i64::MAX / 4;
+
+/// One layer of a partition DP with the divide-and-conquer optimization:
+/// `result[i] = min over k < i of prev[k] + cost(k, i)`, with `result[0] = INF`.
+/// Requires the optimal `k` to be non-decreasing in `i` (e.g. `cost` satisfies
+/// the quadrangle inequality). Runs O(n log n) evaluations of `cost`.
+pub fn dc_layer<F: Fn(usize, usize) -> i64>(prev: &[i64], cost: F) -> Vec<i64> {
+    let n = prev.len();
+    let mut result = vec![INF; n];
+    if n > 1 {
+        dc_solve(prev, &cost, &mut result, 1, n - 1, 0, n - 2);
+    }
+    result
+}
+
+// Solve result[lo..=hi] knowing that their optimal k lies in [opt_lo, opt_hi]
+fn dc_solve<F: Fn(usize, usize) -> i64>(
+    prev: &[i64],
+    cost: &F,
+    result: &mut [i64],
+    lo: usize,
+    hi: usize,
+    opt_lo: usize,
+    opt_hi: usize,
+) {
+    let mid = (lo + hi) / 2;
+    let mut best = (INF, opt_lo);
+    let last = opt_hi.min(mid - 1);
+    for (k, &p) in prev.iter().enumerate().take(last + 1).skip(opt_lo) {
+        if p >= INF {
+            continue;
+        }
+        let value = p + cost(k, mid);
+        if value < best.0 {
+            best = (value, k);
+        }
+    }
+    result[mid] = best.0;
+    if lo < mid {
+        dc_solve(prev, cost, result, lo, mid - 1, opt_lo, best.1);
+    }
+    if mid < hi {
+        dc_solve(prev, cost, result, mid + 1, hi, best.1, opt_hi);
+    }
+}
+
+/// Minimum total cost of splitting `n` items into `groups` non-empty contiguous
+/// groups, where `cost(l, r)` is the cost of the group of items `l..r`
+pub fn partition_dp<F: Fn(usize, usize) -> i64>(n: usize, groups: usize, cost: F) -> i64 {
+    let mut dp = vec![INF; n + 1];
+    dp[0] = 0;
+    for _ in 0..groups {
+        dp = dc_layer(&dp, &cost);
+    }
+    dp[n]
+}
+
+/// Convex hull trick answering min or max of a set of lines at fixed query
+/// points. Lines can be added in any order, so the monotone slope orders a
+/// deque-based hull relies on are handled as well.
+pub struct ConvexHullTrick {
+    tree: LiChaoTree,
+    maximize: bool,
+}
+
+impl ConvexHullTrick {
+    pub fn new_min(xs: &[i64]) -> Self {
+        ConvexHullTrick {
+            tree: LiChaoTree::new(xs),
+            maximize: false,
+        }
+    }
+
+    pub fn new_max(xs: &[i64]) -> Self {
+        ConvexHullTrick {
+            tree: LiChaoTree::new(xs),
+            maximize: true,
+        }
+    }
+
+    /// Adds the line y = a * x + b
+    pub fn add_line(&mut self, a: i64, b: i64) {
+        if self.maximize {
+            // max of lines is minus the min of the negated lines
+            self.tree.add_line(-a, -b);
+        } else {
+            self.tree.add_line(a, b);
+        }
+    }
+
+    pub fn query(&self, x: i64) -> Option<i64> {
+        let value = self.tree.query(x)?;
+        Some(if self.maximize { -value } else { value })
+    }
+}
+
+/// Best value reachable with total weight at most `capacity`, using every
+/// `(weight, value)` item any number of times
+pub fn knapsack_unbounded(items: &[(usize, i64)], capacity: usize) -> i64 {
+    let mut best = vec![0; capacity + 1];
+    for c in 1..=capacity {
+        for &(weight, value) in items {
+            if weight <= c {
+                best[c] = best[c].max(best[c - weight] + value);
+            }
+        }
+    }
+    best[capacity]
+}
+
+/// Best value reachable with total weight at most `capacity`, using each
+/// `(weight, value, count)` item at most `count` times. Counts are split in
+/// powers of two, so the cost is O(capacity * sum of log(count)).
+pub fn knapsack_bounded(items: &[(usize, i64, usize)], capacity: usize) -> i64 {
+    let mut best = vec![0; capacity + 1];
+    for &(weight, value, count) in items {
+        let mut remaining = count;
+        let mut chunk = 1;
+        while remaining > 0 {
+            let take = chunk.min(remaining);
+            remaining -= take;
+            chunk *= 2;
+            // a 0/1 item made of `take` copies
+            let (w, v) = (weight * take, value * take as i64);
+            for c in (w..=capacity).rev() {
+                best[c] = best[c].max(best[c - w] + v);
+            }
+        }
+    }
+    best[capacity]
+}
+
+/// Length of the longest common subsequence of `a` and `b`, in O(|a| |b|) time
+/// and O(|b|) memory
+pub fn lcs_length<T: Eq>(a: &[T], b: &[T]) -> usize {
+    let mut row = vec![0; b.len() + 1];
+    for x in a {
+        let mut diagonal = 0;
+        for (j, y) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if x == y {
+                diagonal + 1
+            } else {
+                above.max(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prefix_sum::PrefixSum;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_partition_dp_against_quadratic() {
+        let mut rng = Rng::new(160);
+        let values: Vec<i64> = (0..40).map(|_| rng.gen_range_i64(0, 20)).collect();
+        let sums = PrefixSum::new(&values);
+        // squared group sums satisfy the quadrangle inequality
+        let cost = |l: usize, r: usize| {
+            let s = sums.sum(l, r - 1);
+            s * s
+        };
+        for groups in 1..6 {
+            let mut dp = vec![INF; values.len() + 1];
+            dp[0] = 0;
+            for _ in 0..groups {
+                dp = (0..=values.len())
+                    .map(|i| {
+                        (0..i)
+                            .filter(|&k| dp[k] < INF)
+                            .map(|k| dp[k] + cost(k, i))
+                            .min()
+                            .unwrap_or(INF)
+                    })
+                    .collect();
+            }
+            assert_eq!(partition_dp(values.len(), groups, cost), dp[values.len()]);
+        }
+    }
+
+    #[test]
+    fn test_convex_hull_trick() {
+        let xs: Vec<i64> = (-10..=10).collect();
+        let mut min = ConvexHullTrick::new_min(&xs);
+        let mut max = ConvexHullTrick::new_max(&xs);
+        let lines = [(1, 0), (-1, 3), (2, -5), (0, 1)];
+        for &(a, b) in &lines {
+            min.add_line(a, b);
+            max.add_line(a, b);
+        }
+        for &x in &xs {
+            let values = lines.iter().map(|&(a, b)| a * x + b);
+            assert_eq!(min.query(x), values.clone().min());
+            assert_eq!(max.query(x), values.max());
+        }
+    }
+
+    #[test]
+    fn test_knapsacks() {
+        let items = [(3, 4), (4, 5), (2, 3)];
+        assert_eq!(knapsack_unbounded(&items, 7), 10); // 2 + 2 + 3
+        assert_eq!(knapsack_unbounded(&items, 1), 0);
+
+        // brute force over the copies of each item
+        let bounded = [(3, 4, 2), (4, 5, 1), (2, 3, 3)];
+        for capacity in 0..20 {
+            let mut best = 0;
+            for a in 0..=2 {
+                for b in 0..=1 {
+                    for c in 0..=3 {
+                        if 3 * a + 4 * b + 2 * c <= capacity {
+                            best = best.max(4 * a as i64 + 5 * b as i64 + 3 * c as i64);
+                        }
+                    }
+                }
+            }
+            assert_eq!(
+                knapsack_bounded(&bounded, capacity),
+                best,
+                "capacity {}",
+                capacity
+            );
+        }
+    }
+
+    #[test]
+    fn test_lcs_length() {
+        assert_eq!(lcs_length(b"ABCBDAB", b"BDCABA"), 4);
+        assert_eq!(lcs_length(b"", b"abc"), 0);
+        assert_eq!(lcs_length(&[1, 2, 3], &[1, 2, 3]), 3);
+    }
+}
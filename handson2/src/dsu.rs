@@ -0,0 +1,230 @@
+// ------- DISJOINT SET UNION -------
+// Union-find with union by size. `Dsu` also compresses paths for amortized
+// inverse-Ackermann operations; `RollbackDsu` keeps the trees intact so the
+// last unions can be undone, at O(log n) per find.
+
+/// Union-find with path compression and union by size
+#[derive(Clone, Debug)]
+pub struct Dsu {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    components: usize,
+}
+
+impl Dsu {
+    /// `n` singleton sets
+    pub fn new(n: usize) -> Self {
+        Dsu {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            components: n,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// Representative of the set containing `v`
+    pub fn find(&mut self, v: usize) -> usize {
+        assert!(v < self.len(), "Index out of range");
+        let mut root = v;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        let mut v = v;
+        while self.parent[v] != root {
+            let next = self.parent[v];
+            self.parent[v] = root;
+            v = next;
+        }
+        root
+    }
+
+    pub fn same(&mut self, u: usize, v: usize) -> bool {
+        self.find(u) == self.find(v)
+    }
+
+    /// Merges the sets of `u` and `v`, false if they were already the same
+    pub fn union(&mut self, u: usize, v: usize) -> bool {
+        let (mut a, mut b) = (self.find(u), self.find(v));
+        if a == b {
+            return false;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.components -= 1;
+        true
+    }
+
+    /// Size of the set containing `v`
+    pub fn size(&mut self, v: usize) -> usize {
+        let root = self.find(v);
+        self.size[root]
+    }
+
+    /// Number of disjoint sets
+    pub fn components(&self) -> usize {
+        self.components
+    }
+}
+
+/// Union-find whose unions can be undone in LIFO order
+#[derive(Clone, Debug)]
+pub struct RollbackDsu {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    components: usize,
+    history: Vec<Option<usize>>, // root attached by each union, None if no-op
+}
+
+impl RollbackDsu {
+    /// `n` singleton sets
+    pub fn new(n: usize) -> Self {
+        RollbackDsu {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            components: n,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// Representative of the set containing `v`, O(log n)
+    pub fn find(&self, mut v: usize) -> usize {
+        assert!(v < self.len(), "Index out of range");
+        while self.parent[v] != v {
+            v = self.parent[v];
+        }
+        v
+    }
+
+    pub fn same(&self, u: usize, v: usize) -> bool {
+        self.find(u) == self.find(v)
+    }
+
+    /// Merges the sets of `u` and `v`, false if they were already the same.
+    /// No-op unions are recorded too, so every call is undone by one step.
+    pub fn union(&mut self, u: usize, v: usize) -> bool {
+        let (mut a, mut b) = (self.find(u), self.find(v));
+        if a == b {
+            self.history.push(None);
+            return false;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.components -= 1;
+        self.history.push(Some(b));
+        true
+    }
+
+    pub fn size(&self, v: usize) -> usize {
+        self.size[self.find(v)]
+    }
+
+    pub fn components(&self) -> usize {
+        self.components
+    }
+
+    /// Current version, to be passed to `rollback`
+    pub fn snapshot(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Undoes every union made after `snapshot` was taken
+    pub fn rollback(&mut self, snapshot: usize) {
+        assert!(snapshot <= self.history.len(), "Invalid snapshot");
+        while self.history.len() > snapshot {
+            if let Some(b) = self.history.pop().unwrap() {
+                let a = self.parent[b];
+                self.parent[b] = b;
+                self.size[a] -= self.size[b];
+                self.components += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    // labels[v] is the component of v, merged by relabelling
+    fn naive_union(labels: &mut [usize], u: usize, v: usize) {
+        let (from, to) = (labels[v], labels[u]);
+        labels
+            .iter_mut()
+            .filter(|l| **l == from)
+            .for_each(|l| *l = to);
+    }
+
+    #[test]
+    fn test_dsu_against_labels() {
+        let mut rng = Rng::new(38);
+        let n = 30;
+        let mut dsu = Dsu::new(n);
+        let mut labels: Vec<usize> = (0..n).collect();
+        for _ in 0..500 {
+            let u = rng.gen_range(0, n as u64 - 1) as usize;
+            let v = rng.gen_range(0, n as u64 - 1) as usize;
+            assert_eq!(dsu.union(u, v), labels[u] != labels[v]);
+            naive_union(&mut labels, u, v);
+            let w = rng.gen_range(0, n as u64 - 1) as usize;
+            assert_eq!(dsu.same(u, w), labels[u] == labels[w]);
+            assert_eq!(
+                dsu.size(w),
+                labels.iter().filter(|&&l| l == labels[w]).count()
+            );
+        }
+    }
+
+    #[test]
+    fn test_rollback() {
+        let mut rng = Rng::new(39);
+        let n = 20;
+        let mut dsu = RollbackDsu::new(n);
+        // stack of (snapshot, labels at that snapshot)
+        let mut saved = vec![(dsu.snapshot(), (0..n).collect::<Vec<_>>())];
+        let mut labels: Vec<usize> = (0..n).collect();
+        for _ in 0..500 {
+            if rng.gen_bool(0.3) && saved.len() > 1 {
+                let (snapshot, old) = saved.pop().unwrap();
+                dsu.rollback(snapshot);
+                labels = old;
+            } else {
+                if rng.gen_bool(0.3) {
+                    saved.push((dsu.snapshot(), labels.clone()));
+                }
+                let u = rng.gen_range(0, n as u64 - 1) as usize;
+                let v = rng.gen_range(0, n as u64 - 1) as usize;
+                assert_eq!(dsu.union(u, v), labels[u] != labels[v]);
+                naive_union(&mut labels, u, v);
+            }
+            for u in 0..n {
+                assert_eq!(dsu.same(u, 0), labels[u] == labels[0]);
+            }
+            let mut distinct = labels.clone();
+            distinct.sort();
+            distinct.dedup();
+            assert_eq!(dsu.components(), distinct.len());
+        }
+    }
+}
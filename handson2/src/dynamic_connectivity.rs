@@ -0,0 +1,101 @@
+// ------- OFFLINE DYNAMIC CONNECTIVITY -------
+// Connectivity queries on a graph whose edges are inserted and deleted, all
+// known in advance: the offline deletion driver runs the unions on a rollback
+// DSU over a segment tree on time. O((n + q) log q log n) overall.
+
+use crate::dsu::RollbackDsu;
+use crate::offline_delete::{offline_delete, Operation};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// Inserts the undirected edge (u, v); parallel edges are allowed
+    Add(usize, usize),
+    /// Deletes one copy of the edge (u, v), which must be present
+    Remove(usize, usize),
+    /// Asks whether u and v are connected right now
+    Query(usize, usize),
+}
+
+/// Answers the `Query` events on a graph with `n` vertices, in order
+pub fn dynamic_connectivity(n: usize, events: &[Event]) -> Vec<bool> {
+    let operations: Vec<Operation<(usize, usize), (usize, usize)>> = events
+        .iter()
+        .map(|&event| {
+            let (Event::Add(u, v) | Event::Remove(u, v) | Event::Query(u, v)) = event;
+            assert!(u < n && v < n, "Index out of range");
+            // both orientations of an edge are the same item
+            let edge = (u.min(v), u.max(v));
+            match event {
+                Event::Add(..) => Operation::Insert(edge),
+                Event::Remove(..) => Operation::Delete(edge),
+                Event::Query(..) => Operation::Query((u, v)),
+            }
+        })
+        .collect();
+    offline_delete(&mut RollbackDsu::new(n), &operations, |dsu, &(u, v)| {
+        dsu.same(u, v)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsu::Dsu;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_example() {
+        use Event::*;
+        let events = [
+            Add(0, 1),
+            Add(1, 2),
+            Query(0, 2),
+            Remove(1, 0),
+            Query(0, 2),
+            Add(0, 2),
+            Query(1, 0),
+            Query(3, 3),
+            Query(0, 3),
+        ];
+        assert_eq!(
+            dynamic_connectivity(4, &events),
+            vec![true, false, true, true, false]
+        );
+        assert!(dynamic_connectivity(3, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_against_rebuild() {
+        let mut rng = Rng::new(40);
+        for _ in 0..30 {
+            let n = rng.gen_range(1, 8) as usize;
+            let mut edges: Vec<(usize, usize)> = Vec::new();
+            let mut events = Vec::new();
+            let mut expected = Vec::new();
+            for _ in 0..rng.gen_range(0, 80) {
+                let u = rng.gen_range(0, n as u64 - 1) as usize;
+                let v = rng.gen_range(0, n as u64 - 1) as usize;
+                match rng.gen_range(0, 2) {
+                    0 => {
+                        edges.push((u, v));
+                        events.push(Event::Add(u, v));
+                    }
+                    1 if !edges.is_empty() => {
+                        let i = rng.gen_range(0, edges.len() as u64 - 1) as usize;
+                        let (a, b) = edges.swap_remove(i);
+                        events.push(Event::Remove(b, a));
+                    }
+                    _ => {
+                        let mut dsu = Dsu::new(n);
+                        for &(a, b) in &edges {
+                            dsu.union(a, b);
+                        }
+                        expected.push(dsu.same(u, v));
+                        events.push(Event::Query(u, v));
+                    }
+                }
+            }
+            assert_eq!(dynamic_connectivity(n, &events), expected);
+        }
+    }
+}
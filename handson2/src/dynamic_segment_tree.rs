@@ -0,0 +1,247 @@
+// ------- DYNAMIC SEGMENT TREE -------
+// Segment trees over positions [0, n) that only allocate the nodes on the
+// paths of the updated positions. All trees live in a shared arena so they
+// can be merged node by node, as in subtree DP over colors: merging the trees
+// of a whole rooted tree costs O(total nodes created) = O(updates * log n).
+
+use crate::segment_tree::Monoid;
+
+/// Root of a tree in the arena, `EMPTY` is the tree with no updates
+pub type Root = usize;
+pub const EMPTY: Root = 0;
+
+#[derive(Clone)]
+struct Node<T> {
+    value: T,
+    left: usize,
+    right: usize,
+}
+
+pub struct DynamicSegmentTree<T: Monoid> {
+    n: usize,
+    nodes: Vec<Node<T>>, // Node 0 is the shared empty node, its own children
+}
+
+impl<T: Monoid> DynamicSegmentTree<T> {
+    /// An arena of trees over the positions [0, n), every position holding
+    /// the identity until updated. With n = 0 every tree is `EMPTY`
+    pub fn new(n: usize) -> Self {
+        DynamicSegmentTree {
+            n,
+            nodes: vec![Node {
+                value: T::identity(),
+                left: EMPTY,
+                right: EMPTY,
+            }],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Number of nodes allocated so far over all trees, the sentinel excluded
+    pub fn node_count(&self) -> usize {
+        self.nodes.len() - 1
+    }
+
+    fn push(&mut self, value: T) -> usize {
+        self.nodes.push(Node {
+            value,
+            left: EMPTY,
+            right: EMPTY,
+        });
+        self.nodes.len() - 1
+    }
+
+    fn pull(&mut self, node: usize) {
+        let Node { left, right, .. } = self.nodes[node];
+        self.nodes[node].value = self.nodes[left].value.combine(&self.nodes[right].value);
+    }
+
+    /// Sets position `i` of the tree `root`, returns the root of the result
+    pub fn set(&mut self, root: Root, i: usize, value: T) -> Root {
+        self.update(root, i, |_| value)
+    }
+
+    /// Replaces position `i` of the tree `root` by `f(old value)`, returns
+    /// the root of the result (a new one only if `root` was `EMPTY`)
+    pub fn update<F: FnOnce(&T) -> T>(&mut self, root: Root, i: usize, f: F) -> Root {
+        assert!(i < self.n, "Index out of range");
+        self.update_recursive(root, 0, self.n - 1, i, f)
+    }
+
+    fn update_recursive<F: FnOnce(&T) -> T>(
+        &mut self,
+        node: usize,
+        start: usize,
+        end: usize,
+        i: usize,
+        f: F,
+    ) -> usize {
+        let node = if node == EMPTY {
+            self.push(T::identity())
+        } else {
+            node
+        };
+        if start == end {
+            self.nodes[node].value = f(&self.nodes[node].value);
+            return node;
+        }
+        let mid = (start + end) / 2;
+        if i <= mid {
+            let left = self.update_recursive(self.nodes[node].left, start, mid, i, f);
+            self.nodes[node].left = left;
+        } else {
+            let right = self.update_recursive(self.nodes[node].right, mid + 1, end, i, f);
+            self.nodes[node].right = right;
+        }
+        self.pull(node);
+        node
+    }
+
+    /// Combines the values in `[l, r]` of the tree `root`
+    pub fn query(&self, root: Root, l: usize, r: usize) -> T {
+        assert!(l <= r && r < self.n, "Invalid range");
+        self.query_recursive(root, 0, self.n - 1, l, r)
+    }
+
+    fn query_recursive(&self, node: usize, start: usize, end: usize, l: usize, r: usize) -> T {
+        if node == EMPTY {
+            return T::identity();
+        }
+        if l <= start && end <= r {
+            return self.nodes[node].value.clone();
+        }
+        let mid = (start + end) / 2;
+        let mut result = T::identity();
+        if l <= mid {
+            result = self.query_recursive(self.nodes[node].left, start, mid, l, r);
+        }
+        if r > mid {
+            let right = self.query_recursive(self.nodes[node].right, mid + 1, end, l, r);
+            result = result.combine(&right);
+        }
+        result
+    }
+
+    /// Combination of all the positions of the tree `root`
+    pub fn all(&self, root: Root) -> T {
+        self.nodes[root].value.clone()
+    }
+
+    /// Merges the tree `b` into the tree `a`, combining the leaves present
+    /// in both with `combine`. Destructive: both inputs are consumed and the
+    /// result reuses their nodes. The cost is the number of nodes the two
+    /// trees have in common. Each of them in `b` becomes unreachable for good
+    /// (the arena never reclaims it), hence the O(total nodes) bound over any
+    /// sequence of merges.
+    pub fn merge(&mut self, a: Root, b: Root) -> Root {
+        if a == EMPTY || b == EMPTY {
+            return a.max(b);
+        }
+        self.merge_recursive(a, b, 0, self.n - 1)
+    }
+
+    fn merge_recursive(&mut self, a: usize, b: usize, start: usize, end: usize) -> usize {
+        if a == EMPTY || b == EMPTY {
+            return a.max(b);
+        }
+        if start == end {
+            self.nodes[a].value = self.nodes[a].value.combine(&self.nodes[b].value);
+            return a;
+        }
+        let mid = (start + end) / 2;
+        let left = self.merge_recursive(self.nodes[a].left, self.nodes[b].left, start, mid);
+        let right = self.merge_recursive(self.nodes[a].right, self.nodes[b].right, mid + 1, end);
+        self.nodes[a].left = left;
+        self.nodes[a].right = right;
+        self.pull(a);
+        a
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use crate::segment_tree::{Max, Sum};
+
+    #[test]
+    fn test_point_updates() {
+        let mut arena = DynamicSegmentTree::new(1_000_000_000);
+        let mut root = EMPTY;
+        root = arena.set(root, 999_999_999, Max(5));
+        root = arena.set(root, 3, Max(7));
+        root = arena.update(root, 500, |_| Max(2));
+        assert_eq!(arena.query(root, 0, 999_999_999), Max(7));
+        assert_eq!(arena.query(root, 4, 999_999_999), Max(5));
+        assert_eq!(arena.query(root, 4, 499), Max(i64::MIN));
+        assert!(arena.node_count() <= 3 * 31);
+    }
+
+    #[test]
+    fn test_empty_arena() {
+        let mut arena = DynamicSegmentTree::<Sum<i64>>::new(0);
+        assert!(arena.is_empty());
+        assert_eq!(arena.merge(EMPTY, EMPTY), EMPTY);
+        assert_eq!(arena.all(EMPTY), Sum(0));
+        assert_eq!(arena.node_count(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Index out of range")]
+    fn test_empty_arena_update_panics() {
+        DynamicSegmentTree::<Sum<i64>>::new(0).set(EMPTY, 0, Sum(1));
+    }
+
+    #[test]
+    fn test_subtree_color_counts() {
+        let mut rng = Rng::new(42);
+        let (n, colors) = (300, 40);
+        // vertex v > 0 hangs below parent[v] < v
+        let parent: Vec<usize> = (0..n)
+            .map(|v| {
+                if v == 0 {
+                    0
+                } else {
+                    rng.gen_range(0, v as u64 - 1) as usize
+                }
+            })
+            .collect();
+        let color: Vec<usize> = (0..n)
+            .map(|_| rng.gen_range(0, colors as u64 - 1) as usize)
+            .collect();
+
+        let mut arena = DynamicSegmentTree::new(colors);
+        let mut roots: Vec<Root> = (0..n)
+            .map(|v| arena.update(EMPTY, color[v], |_| Sum(1u64)))
+            .collect();
+        let allocated = arena.node_count();
+        let mut subtree: Vec<Vec<usize>> = (0..n).map(|v| vec![color[v]]).collect();
+        for v in (1..n).rev() {
+            // children have larger indices, so v is complete here
+            let (l, r) = (rng.gen_range(0, colors as u64 - 1) as usize, colors - 1);
+            let expected = subtree[v].iter().filter(|&&c| l <= c && c <= r).count();
+            assert_eq!(arena.query(roots[v], l, r), Sum(expected as u64));
+            assert_eq!(arena.all(roots[v]), Sum(subtree[v].len() as u64));
+
+            let p = parent[v];
+            roots[p] = arena.merge(roots[p], roots[v]);
+            let moved = std::mem::take(&mut subtree[v]);
+            subtree[p].extend(moved);
+        }
+        assert_eq!(arena.all(roots[0]), Sum(n as u64));
+        for c in 0..colors {
+            let expected = color.iter().filter(|&&x| x == c).count() as u64;
+            assert_eq!(arena.query(roots[0], c, c), Sum(expected));
+        }
+        // merging never allocates, and each tree had depth + 1 nodes
+        assert_eq!(arena.node_count(), allocated);
+        assert!(allocated <= n * 7);
+    }
+}
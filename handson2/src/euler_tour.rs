@@ -0,0 +1,171 @@
+// ------- EULER TOUR FLATTENING -------
+// Numbers the vertices of a rooted tree in DFS order, so that every subtree
+// becomes a contiguous range `[tin[v], tout[v]]` of a flat array. Subtree
+// queries are then range queries, and adding a value on the whole path from a
+// vertex to the root is a point update read back with a subtree query.
+
+use crate::segment_tree::{Monoid, SegmentTree};
+
+pub struct EulerTourFlatten {
+    tin: Vec<usize>,   // DFS entry time of each vertex
+    tout: Vec<usize>,  // Largest entry time inside the subtree of each vertex
+    order: Vec<usize>, // order[t] = vertex entered at time t
+}
+
+impl EulerTourFlatten {
+    /// Flattens the tree given by the adjacency lists `adj`, rooted at `root`
+    pub fn new(adj: &[Vec<usize>], root: usize) -> Self {
+        let n = adj.len();
+        assert!(root < n, "Index out of range");
+        let mut tin = vec![usize::MAX; n];
+        let mut tout = vec![0; n];
+        let mut order = Vec::with_capacity(n);
+        // (vertex, index of the next neighbour to visit)
+        let mut stack = vec![(root, 0)];
+        tin[root] = 0;
+        order.push(root);
+        while let Some(&mut (v, ref mut next)) = stack.last_mut() {
+            if let Some(&u) = adj[v].get(*next) {
+                *next += 1;
+                if tin[u] == usize::MAX {
+                    tin[u] = order.len();
+                    order.push(u);
+                    stack.push((u, 0));
+                }
+            } else {
+                tout[v] = order.len() - 1;
+                stack.pop();
+            }
+        }
+        assert_eq!(order.len(), n, "The graph is not a connected tree");
+        EulerTourFlatten { tin, tout, order }
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    pub fn tin(&self, v: usize) -> usize {
+        self.tin[v]
+    }
+
+    pub fn tout(&self, v: usize) -> usize {
+        self.tout[v]
+    }
+
+    /// Vertices in DFS order
+    pub fn order(&self) -> &[usize] {
+        &self.order
+    }
+
+    /// Flat range `[tin[v], tout[v]]` covered by the subtree of `v`
+    pub fn subtree_range(&self, v: usize) -> (usize, usize) {
+        (self.tin[v], self.tout[v])
+    }
+
+    /// True if `u` is an ancestor of `v` (a vertex is its own ancestor)
+    pub fn is_ancestor(&self, u: usize, v: usize) -> bool {
+        self.tin[u] <= self.tin[v] && self.tin[v] <= self.tout[u]
+    }
+
+    /// Reorders per-vertex `values` into DFS order
+    pub fn flatten<T: Clone>(&self, values: &[T]) -> Vec<T> {
+        assert_eq!(values.len(), self.len(), "One value per vertex expected");
+        self.order.iter().map(|&v| values[v].clone()).collect()
+    }
+
+    /// A segment tree over the per-vertex `values` laid out in DFS order
+    pub fn segment_tree<T: Monoid>(&self, values: &[T]) -> SegmentTree<T> {
+        SegmentTree::new(&self.flatten(values))
+    }
+
+    /// Combination of the values in the subtree of `v`
+    pub fn subtree_query<T: Monoid>(&self, tree: &SegmentTree<T>, v: usize) -> T {
+        tree.query(self.tin[v], self.tout[v])
+    }
+
+    /// Combines `value` into every vertex on the path from `v` to the root.
+    /// The update is stored at `v` only; read it back at `u` with
+    /// `subtree_query(tree, u)`, which collects the updates made below `u`.
+    pub fn path_to_root_update<T: Monoid>(&self, tree: &mut SegmentTree<T>, v: usize, value: T) {
+        let pos = self.tin[v];
+        let combined = tree.get(pos).combine(&value);
+        tree.set(pos, combined);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use crate::segment_tree::{Max, Sum};
+
+    // random tree where parent[v] < v, as adjacency lists
+    fn random_tree(rng: &mut Rng, n: usize) -> (Vec<usize>, Vec<Vec<usize>>) {
+        let mut parent = vec![0; n];
+        let mut adj = vec![Vec::new(); n];
+        for v in 1..n {
+            parent[v] = rng.gen_range(0, v as u64 - 1) as usize;
+            adj[v].push(parent[v]);
+            adj[parent[v]].push(v);
+        }
+        (parent, adj)
+    }
+
+    fn ancestors(parent: &[usize], mut v: usize) -> Vec<usize> {
+        let mut path = vec![v];
+        while v != 0 {
+            v = parent[v];
+            path.push(v);
+        }
+        path
+    }
+
+    #[test]
+    fn test_subtree_queries() {
+        let mut rng = Rng::new(22);
+        let n = 200;
+        let (parent, adj) = random_tree(&mut rng, n);
+        let tour = EulerTourFlatten::new(&adj, 0);
+        let mut values: Vec<u32> = (0..n).map(|_| rng.gen_range(0, 1000) as u32).collect();
+        let mut tree = tour.segment_tree(&values.iter().map(|&v| Max(v)).collect::<Vec<_>>());
+        for _ in 0..300 {
+            let v = rng.gen_range(0, n as u64 - 1) as usize;
+            values[v] = rng.gen_range(0, 1000) as u32;
+            tree.set(tour.tin(v), Max(values[v]));
+
+            let u = rng.gen_range(0, n as u64 - 1) as usize;
+            let expected = (0..n)
+                .filter(|&w| ancestors(&parent, w).contains(&u))
+                .map(|w| values[w])
+                .max()
+                .unwrap();
+            assert_eq!(tour.subtree_query(&tree, u).0, expected);
+            assert_eq!(tour.is_ancestor(u, v), ancestors(&parent, v).contains(&u));
+        }
+    }
+
+    #[test]
+    fn test_path_to_root_updates() {
+        let mut rng = Rng::new(23);
+        let n = 100;
+        let (parent, adj) = random_tree(&mut rng, n);
+        let tour = EulerTourFlatten::new(&adj, 0);
+        let mut tree = tour.segment_tree(&vec![Sum(0u64); n]);
+        let mut naive = vec![0u64; n];
+        for _ in 0..300 {
+            let v = rng.gen_range(0, n as u64 - 1) as usize;
+            let x = rng.gen_range(1, 50);
+            tour.path_to_root_update(&mut tree, v, Sum(x));
+            for u in ancestors(&parent, v) {
+                naive[u] += x;
+            }
+            let u = rng.gen_range(0, n as u64 - 1) as usize;
+            assert_eq!(tour.subtree_query(&tree, u).0, naive[u]);
+        }
+    }
+}
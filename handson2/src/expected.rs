@@ -0,0 +1,188 @@
+// ------- EXPECTED VALUES MODULO A PRIME -------
+// Probabilities and expectations are rationals p / q, which problems ask for
+// as p * q^-1 modulo a prime such as 998244353. Expected-value DPs then run
+// over `ModInt` directly; when the states depend on each other cyclically, as
+// in random walks on graphs, the expectations solve a linear system, solved
+// here by Gaussian elimination over `ModInt`.
+
+use crate::graph::Graph;
+use crate::matrix::Matrix;
+use crate::modint::ModInt;
+
+/// The fraction `p / q` modulo `M`
+///
+/// # Panics
+/// Panics if `q` is a multiple of `M`.
+pub fn fraction<const M: u32>(p: u64, q: u64) -> ModInt<M> {
+    ModInt::new(p) / ModInt::new(q)
+}
+
+/// Expected value of an outcome drawn with probability proportional to its
+/// weight, from `(weight, value)` pairs
+pub fn expectation<const M: u32, I>(outcomes: I) -> ModInt<M>
+where
+    I: IntoIterator<Item = (u64, ModInt<M>)>,
+{
+    let (total, sum) =
+        outcomes
+            .into_iter()
+            .fold((ModInt::new(0), ModInt::new(0)), |(total, sum), (w, v)| {
+                let w = ModInt::new(w);
+                (total + w, sum + w * v)
+            });
+    sum / total
+}
+
+/// The solution `x` of `a x = b` for a square `a`, in O(n^3). None if `a` is
+/// singular modulo `M`, which can happen even when it is not over the
+/// rationals, with probability about n / M for "random" systems.
+pub fn solve_linear<const M: u32>(
+    a: &Matrix<ModInt<M>>,
+    b: &[ModInt<M>],
+) -> Option<Vec<ModInt<M>>> {
+    let n = a.rows();
+    assert_eq!(a.cols(), n, "Only square systems are supported");
+    assert_eq!(b.len(), n, "Dimension mismatch");
+    // augmented rows [a | b]
+    let mut rows: Vec<Vec<ModInt<M>>> = (0..n)
+        .map(|i| {
+            let mut row: Vec<ModInt<M>> = (0..n).map(|j| a[(i, j)]).collect();
+            row.push(b[i]);
+            row
+        })
+        .collect();
+    for col in 0..n {
+        let pivot = (col..n).find(|&i| rows[i][col].value() != 0)?;
+        rows.swap(col, pivot);
+        let inv = rows[col][col].inv();
+        for x in rows[col].iter_mut() {
+            *x *= inv;
+        }
+        let pivot_row = rows[col].clone();
+        for (i, row) in rows.iter_mut().enumerate() {
+            let factor = row[col];
+            if i != col && factor.value() != 0 {
+                for (x, &p) in row.iter_mut().zip(&pivot_row).skip(col) {
+                    *x -= factor * p;
+                }
+            }
+        }
+    }
+    Some(rows.into_iter().map(|row| row[n]).collect())
+}
+
+/// Expected number of steps for a random walk from every vertex to reach one
+/// of `targets`, each step following an edge chosen uniformly at random. None
+/// if some vertex cannot reach a target, or if the system is singular
+/// modulo `M`.
+pub fn hitting_times<const M: u32>(graph: &Graph, targets: &[usize]) -> Option<Vec<ModInt<M>>> {
+    let n = graph.len();
+    let mut is_target = vec![false; n];
+    for &t in targets {
+        assert!(t < n, "Index out of range");
+        is_target[t] = true;
+    }
+    // vertices reaching a target, walking the edges backwards
+    let mut reverse = vec![Vec::new(); n];
+    for v in 0..n {
+        for &(u, _) in graph.neighbors(v) {
+            reverse[u].push(v);
+        }
+    }
+    let mut reaches = is_target.clone();
+    let mut stack: Vec<usize> = (0..n).filter(|&v| is_target[v]).collect();
+    while let Some(u) = stack.pop() {
+        for &v in &reverse[u] {
+            if !reaches[v] {
+                reaches[v] = true;
+                stack.push(v);
+            }
+        }
+    }
+    if reaches.contains(&false) {
+        return None;
+    }
+
+    // E[t] = 0 for targets, and deg(v) E[v] - sum of E[u] over edges = deg(v)
+    let mut a = Matrix::new(n, n);
+    let mut b = vec![ModInt::new(0); n];
+    for v in 0..n {
+        if is_target[v] {
+            a[(v, v)] = ModInt::new(1);
+            continue;
+        }
+        let degree = ModInt::from(graph.degree(v));
+        a[(v, v)] += degree;
+        for &(u, _) in graph.neighbors(v) {
+            a[(v, u)] -= ModInt::new(1);
+        }
+        b[v] = degree;
+    }
+    solve_linear(&a, &b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modint::Mod998244353;
+    use crate::rng::Rng;
+
+    type Mint = Mod998244353;
+
+    #[test]
+    fn test_fractions_and_expectation() {
+        let half: Mint = fraction(1, 2);
+        assert_eq!(half * Mint::new(2), Mint::new(1));
+        assert_eq!(half.value(), 499_122_177);
+        // a fair die, and a die showing 6 with probability 1/2
+        let fair = expectation((1..=6).map(|v| (1, Mint::new(v))));
+        assert_eq!(fair, fraction(7, 2));
+        let loaded = expectation((1..=6).map(|v| (if v == 6 { 5 } else { 1 }, Mint::new(v))));
+        assert_eq!(loaded, fraction(45, 10));
+    }
+
+    #[test]
+    fn test_solve_random_systems() {
+        let mut rng = Rng::new(62);
+        for _ in 0..30 {
+            let n = rng.gen_range(1, 8) as usize;
+            let mut a = Matrix::new(n, n);
+            for i in 0..n {
+                for j in 0..n {
+                    a[(i, j)] = Mint::new(rng.next_u64());
+                }
+            }
+            let x: Vec<Mint> = (0..n).map(|_| Mint::new(rng.next_u64())).collect();
+            let b = a.apply(&x);
+            assert_eq!(solve_linear(&a, &b), Some(x));
+        }
+        // the second row is twice the first
+        let singular = Matrix::from_rows(&[
+            vec![Mint::new(1), Mint::new(2)],
+            vec![Mint::new(2), Mint::new(4)],
+        ]);
+        assert_eq!(solve_linear(&singular, &[Mint::new(1), Mint::new(2)]), None);
+    }
+
+    #[test]
+    fn test_hitting_times() {
+        // from k on a cycle of n vertices, 0 is reached after k (n - k) steps
+        let n = 9;
+        let edges: Vec<(usize, usize, i64)> = (0..n).map(|v| (v, (v + 1) % n, 1)).collect();
+        let cycle = Graph::from_undirected_edges(n, &edges);
+        let times = hitting_times::<998_244_353>(&cycle, &[0]).unwrap();
+        for (k, &t) in times.iter().enumerate() {
+            assert_eq!(t, Mint::from(k * (n - k)));
+        }
+        // a star: each leaf is left to the centre, from which 1 of 3 leaves is the target
+        let star = Graph::from_undirected_edges(4, &[(0, 1, 1), (0, 2, 1), (0, 3, 1)]);
+        let times = hitting_times::<998_244_353>(&star, &[1]).unwrap();
+        assert_eq!(
+            times,
+            vec![Mint::new(5), Mint::new(0), Mint::new(6), Mint::new(6)]
+        );
+        // vertex 2 is isolated
+        let split = Graph::from_undirected_edges(3, &[(0, 1, 1)]);
+        assert_eq!(hitting_times::<998_244_353>(&split, &[0]), None);
+    }
+}
@@ -0,0 +1,122 @@
+// ------- FENWICK TREE -------
+// Point additions and prefix sums in O(log n). Positions are 0-based, ranges
+// are inclusive.
+
+use crate::prefix_sum::Summable;
+
+pub struct FenwickTree<T> {
+    tree: Vec<T>, // 1-based internally: tree[i] covers (i - lowbit(i), i]
+}
+
+impl<T: Summable> FenwickTree<T> {
+    /// A tree of `len` zeros
+    pub fn new(len: usize) -> Self {
+        FenwickTree {
+            tree: vec![T::default(); len + 1],
+        }
+    }
+
+    /// Builds the tree from `values` in O(n)
+    pub fn from_values(values: &[T]) -> Self {
+        let mut tree = vec![T::default(); values.len() + 1];
+        tree[1..].copy_from_slice(values);
+        for i in 1..tree.len() {
+            let parent = i + (i & i.wrapping_neg());
+            if parent < tree.len() {
+                tree[parent] = tree[parent] + tree[i];
+            }
+        }
+        FenwickTree { tree }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds `value` at position `i`
+    pub fn add(&mut self, i: usize, value: T) {
+        assert!(i < self.len(), "Index out of range");
+        let mut i = i + 1;
+        while i < self.tree.len() {
+            self.tree[i] = self.tree[i] + value;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// Sum of the first `count` values
+    pub fn prefix(&self, count: usize) -> T {
+        assert!(count <= self.len(), "Prefix out of range");
+        let mut i = count;
+        let mut result = T::default();
+        while i > 0 {
+            result = result + self.tree[i];
+            i -= i & i.wrapping_neg();
+        }
+        result
+    }
+
+    /// Sum of the values in `[l, r]`
+    pub fn sum(&self, l: usize, r: usize) -> T {
+        assert!(l <= r && r < self.len(), "Invalid range");
+        self.prefix(r + 1) - self.prefix(l)
+    }
+}
+
+impl<T: Summable + Ord> FenwickTree<T> {
+    /// Largest `count` such that `prefix(count) <= target`, assuming all the
+    /// values are non-negative. Runs in O(log n) by binary lifting.
+    pub fn max_prefix_within(&self, target: T) -> usize {
+        let mut pos = 0;
+        let mut remaining = target;
+        let mut step = self.len().next_power_of_two();
+        while step > 0 {
+            let next = pos + step;
+            if next < self.tree.len() && self.tree[next] <= remaining {
+                pos = next;
+                remaining = remaining - self.tree[next];
+            }
+            step /= 2;
+        }
+        pos
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_against_plain_array() {
+        let mut rng = Rng::new(12);
+        let n = 50;
+        let mut arr: Vec<i64> = (0..n).map(|_| rng.gen_range(0, 9) as i64 - 4).collect();
+        let mut fenwick = FenwickTree::from_values(&arr);
+        for _ in 0..500 {
+            let i = rng.gen_range(0, n as u64 - 1) as usize;
+            let v = rng.gen_range(0, 9) as i64 - 4;
+            arr[i] += v;
+            fenwick.add(i, v);
+            let a = rng.gen_range(0, n as u64 - 1) as usize;
+            let b = rng.gen_range(0, n as u64 - 1) as usize;
+            let (l, r) = (a.min(b), a.max(b));
+            assert_eq!(fenwick.sum(l, r), arr[l..=r].iter().sum::<i64>());
+        }
+        assert_eq!(fenwick.prefix(0), 0);
+    }
+
+    #[test]
+    fn test_max_prefix_within() {
+        let fenwick = FenwickTree::from_values(&[2u32, 0, 3, 1, 0, 4]);
+        assert_eq!(fenwick.max_prefix_within(0), 0);
+        assert_eq!(fenwick.max_prefix_within(1), 0);
+        assert_eq!(fenwick.max_prefix_within(2), 2);
+        assert_eq!(fenwick.max_prefix_within(5), 3);
+        assert_eq!(fenwick.max_prefix_within(6), 5);
+        assert_eq!(fenwick.max_prefix_within(100), 6);
+    }
+}
@@ -0,0 +1,340 @@
+// ------- FLOWS AND CUTS -------
+// Stoer-Wagner finds a global minimum cut of an undirected graph with
+// non-negative weights in O(n^3) without any flow: each phase adds the vertex
+// most tightly connected to the growing set until the last two vertices s and
+// t, whose cut-of-the-phase is a minimum s-t cut, then merges s and t.
+// Dinic's algorithm computes maximum flows in O(V^2 E), saturating blocking
+// flows along BFS levels. Gusfield's algorithm answers all-pairs minimum cuts
+// with n - 1 maximum flows, building a tree where the minimum s-t cut is the
+// lightest edge on the path between s and t.
+
+use crate::graph::Graph;
+use std::collections::VecDeque;
+
+/// Maximum flow network with integer capacities
+#[derive(Clone, Debug)]
+pub struct MaxFlow {
+    adj: Vec<Vec<usize>>, // Ids of the arcs leaving each vertex
+    to: Vec<usize>,       // Arc 2i is the i-th edge, arc 2i + 1 its reverse
+    cap: Vec<i64>,        // Residual capacity of each arc
+    original: Vec<i64>,
+    level: Vec<usize>,
+    next_arc: Vec<usize>,
+}
+
+impl MaxFlow {
+    pub fn new(n: usize) -> Self {
+        MaxFlow {
+            adj: vec![Vec::new(); n],
+            to: Vec::new(),
+            cap: Vec::new(),
+            original: Vec::new(),
+            level: vec![0; n],
+            next_arc: vec![0; n],
+        }
+    }
+
+    /// A network with an arc of capacity w for every edge (u, v, w) stored
+    /// in `graph`, so both directions of its undirected edges
+    pub fn from_graph(graph: &Graph) -> Self {
+        let mut network = MaxFlow::new(graph.len());
+        for v in 0..graph.len() {
+            for &(u, w) in graph.neighbors(v) {
+                network.add_edge(v, u, w);
+            }
+        }
+        network
+    }
+
+    pub fn len(&self) -> usize {
+        self.adj.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.adj.is_empty()
+    }
+
+    /// Adds an arc from `u` to `v` and returns its id
+    pub fn add_edge(&mut self, u: usize, v: usize, capacity: i64) -> usize {
+        assert!(u < self.len() && v < self.len(), "Index out of range");
+        assert!(capacity >= 0, "Negative capacity");
+        let id = self.to.len() / 2;
+        self.adj[u].push(self.to.len());
+        self.to.push(v);
+        self.cap.push(capacity);
+        self.adj[v].push(self.to.len());
+        self.to.push(u);
+        self.cap.push(0);
+        self.original.extend([capacity, 0]);
+        id
+    }
+
+    /// Flow on the arc `id` after the last `max_flow`
+    pub fn flow(&self, id: usize) -> i64 {
+        self.original[2 * id] - self.cap[2 * id]
+    }
+
+    /// Removes every flow, restoring the original capacities
+    pub fn reset(&mut self) {
+        self.cap.copy_from_slice(&self.original);
+    }
+
+    /// Pushes a maximum flow from `s` to `t` on top of the current one and
+    /// returns the amount pushed
+    pub fn max_flow(&mut self, s: usize, t: usize) -> i64 {
+        assert!(s < self.len() && t < self.len(), "Index out of range");
+        assert_ne!(s, t, "Source and sink must differ");
+        let mut total = 0;
+        while self.bfs(s, t) {
+            self.next_arc.fill(0);
+            loop {
+                let pushed = self.dfs(s, t, i64::MAX);
+                if pushed == 0 {
+                    break;
+                }
+                total += pushed;
+            }
+        }
+        total
+    }
+
+    /// The vertices reachable from `s` in the residual network, the source
+    /// side of a minimum cut after `max_flow(s, t)`
+    pub fn min_cut_side(&self, s: usize) -> Vec<bool> {
+        let mut seen = vec![false; self.len()];
+        seen[s] = true;
+        let mut stack = vec![s];
+        while let Some(v) = stack.pop() {
+            for &arc in &self.adj[v] {
+                let u = self.to[arc];
+                if self.cap[arc] > 0 && !seen[u] {
+                    seen[u] = true;
+                    stack.push(u);
+                }
+            }
+        }
+        seen
+    }
+
+    // Levels by BFS from s over residual arcs; whether t is reachable
+    fn bfs(&mut self, s: usize, t: usize) -> bool {
+        self.level.fill(usize::MAX);
+        self.level[s] = 0;
+        let mut queue = VecDeque::from([s]);
+        while let Some(v) = queue.pop_front() {
+            for &arc in &self.adj[v] {
+                let u = self.to[arc];
+                if self.cap[arc] > 0 && self.level[u] == usize::MAX {
+                    self.level[u] = self.level[v] + 1;
+                    queue.push_back(u);
+                }
+            }
+        }
+        self.level[t] != usize::MAX
+    }
+
+    // Pushes at most `limit` along one path of increasing levels
+    fn dfs(&mut self, v: usize, t: usize, limit: i64) -> i64 {
+        if v == t {
+            return limit;
+        }
+        while self.next_arc[v] < self.adj[v].len() {
+            let arc = self.adj[v][self.next_arc[v]];
+            let u = self.to[arc];
+            if self.cap[arc] > 0 && self.level[u] == self.level[v] + 1 {
+                let pushed = self.dfs(u, t, limit.min(self.cap[arc]));
+                if pushed > 0 {
+                    self.cap[arc] -= pushed;
+                    self.cap[arc ^ 1] += pushed;
+                    return pushed;
+                }
+            }
+            self.next_arc[v] += 1;
+        }
+        0
+    }
+}
+
+/// Edges `(v, parent, min cut)` of a tree on the vertices of the undirected
+/// `graph`, where the minimum cut between any two vertices is the lightest
+/// edge on their path. Vertex 0 is the root.
+pub fn gomory_hu(graph: &Graph) -> Vec<(usize, usize, i64)> {
+    let n = graph.len();
+    let mut network = MaxFlow::from_graph(graph);
+    let mut parent = vec![0; n];
+    let mut edges = Vec::with_capacity(n.saturating_sub(1));
+    for v in 1..n {
+        network.reset();
+        let cut = network.max_flow(v, parent[v]);
+        let side = network.min_cut_side(v);
+        // later vertices on v's side of the cut now hang below v
+        for u in v + 1..n {
+            if side[u] && parent[u] == parent[v] {
+                parent[u] = v;
+            }
+        }
+        edges.push((v, parent[v], cut));
+    }
+    edges
+}
+
+/// Weight of a minimum cut of the undirected `graph` with non-negative
+/// weights, and the vertices of one side of it
+pub fn global_min_cut(graph: &Graph) -> (i64, Vec<usize>) {
+    let n = graph.len();
+    assert!(n >= 2, "Need at least two vertices");
+    let mut weight = vec![vec![0i64; n]; n];
+    for (v, row) in weight.iter_mut().enumerate() {
+        for &(u, w) in graph.neighbors(v) {
+            assert!(w >= 0, "Negative weight");
+            if u != v {
+                row[u] += w;
+            }
+        }
+    }
+    // vertices merged into each remaining vertex
+    let mut members: Vec<Vec<usize>> = (0..n).map(|v| vec![v]).collect();
+    let mut alive: Vec<usize> = (0..n).collect();
+    let mut best = (i64::MAX, Vec::new());
+    while alive.len() > 1 {
+        let mut added = vec![false; n];
+        let mut connection = vec![0i64; n];
+        let (mut prev, mut last) = (alive[0], alive[0]);
+        for _ in 0..alive.len() {
+            let next = *alive
+                .iter()
+                .filter(|&&v| !added[v])
+                .max_by_key(|&&v| connection[v])
+                .unwrap();
+            added[next] = true;
+            prev = last;
+            last = next;
+            for &v in alive.iter().filter(|&&v| !added[v]) {
+                connection[v] += weight[next][v];
+            }
+        }
+        if connection[last] < best.0 {
+            best = (connection[last], members[last].clone());
+        }
+        // merge last into prev
+        let moved = std::mem::take(&mut members[last]);
+        members[prev].extend(moved);
+        for &v in &alive {
+            weight[prev][v] += weight[last][v];
+            weight[v][prev] = weight[prev][v];
+        }
+        weight[prev][prev] = 0;
+        alive.retain(|&v| v != last);
+    }
+    best.1.sort_unstable();
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_min_cut_against_bipartitions() {
+        // two triangles joined by a light edge
+        let edges = [
+            (0, 1, 5),
+            (1, 2, 5),
+            (2, 0, 5),
+            (3, 4, 5),
+            (4, 5, 5),
+            (5, 3, 5),
+            (2, 3, 1),
+        ];
+        let (cut, side) = global_min_cut(&Graph::from_undirected_edges(6, &edges));
+        assert_eq!(cut, 1);
+        assert!(side == vec![0, 1, 2] || side == vec![3, 4, 5]);
+
+        let mut rng = Rng::new(66);
+        for _ in 0..100 {
+            let n = rng.gen_range(2, 9) as usize;
+            let mut edges = Vec::new();
+            for _ in 0..rng.gen_range(0, 20) {
+                let u = rng.gen_range(0, n as u64 - 1) as usize;
+                let v = rng.gen_range(0, n as u64 - 1) as usize;
+                edges.push((u, v, rng.gen_range_i64(0, 10)));
+            }
+            let crossing = |inside: &dyn Fn(usize) -> bool| -> i64 {
+                edges
+                    .iter()
+                    .filter(|&&(u, v, _)| inside(u) != inside(v))
+                    .map(|e| e.2)
+                    .sum()
+            };
+            let brute = (1..(1u32 << n) - 1)
+                .map(|mask| crossing(&|v| mask >> v & 1 == 1))
+                .min()
+                .unwrap();
+            let (cut, side) = global_min_cut(&Graph::from_undirected_edges(n, &edges));
+            assert_eq!(cut, brute);
+            assert!(!side.is_empty() && side.len() < n);
+            assert_eq!(crossing(&|v| side.contains(&v)), cut);
+        }
+    }
+
+    #[test]
+    fn test_flows_and_gomory_hu_against_cuts() {
+        // two paths limited by the arcs leaving the source, and a cross arc
+        let mut network = MaxFlow::new(4);
+        let a = network.add_edge(0, 1, 3);
+        network.add_edge(1, 3, 5);
+        let b = network.add_edge(0, 2, 2);
+        network.add_edge(2, 3, 4);
+        network.add_edge(1, 2, 4);
+        assert_eq!(network.max_flow(0, 3), 5);
+        assert_eq!((network.flow(a), network.flow(b)), (3, 2));
+        assert_eq!(network.min_cut_side(0), vec![true, false, false, false]);
+        network.reset();
+        assert_eq!(network.max_flow(1, 2), 4);
+
+        let mut rng = Rng::new(67);
+        for _ in 0..60 {
+            let n = rng.gen_range(2, 8) as usize;
+            let mut edges = Vec::new();
+            for _ in 0..rng.gen_range(0, 16) {
+                let u = rng.gen_range(0, n as u64 - 1) as usize;
+                let v = rng.gen_range(0, n as u64 - 1) as usize;
+                edges.push((u, v, rng.gen_range_i64(0, 10)));
+            }
+            let graph = Graph::from_undirected_edges(n, &edges);
+            let tree = Graph::from_undirected_edges(n, &gomory_hu(&graph));
+            let mut network = MaxFlow::from_graph(&graph);
+            for s in 0..n {
+                // lightest edge on the tree path from s to every vertex
+                let mut lightest = vec![None; n];
+                lightest[s] = Some(i64::MAX);
+                let mut stack = vec![s];
+                while let Some(v) = stack.pop() {
+                    for &(u, w) in tree.neighbors(v) {
+                        if lightest[u].is_none() {
+                            lightest[u] = Some(lightest[v].unwrap().min(w));
+                            stack.push(u);
+                        }
+                    }
+                }
+                for t in (0..n).filter(|&t| t != s) {
+                    let brute = (0..1u32 << n)
+                        .filter(|&mask| mask >> s & 1 == 1 && mask >> t & 1 == 0)
+                        .map(|mask| {
+                            edges
+                                .iter()
+                                .filter(|&&(u, v, _)| (mask >> u & 1) != (mask >> v & 1))
+                                .map(|e| e.2)
+                                .sum::<i64>()
+                        })
+                        .min()
+                        .unwrap();
+                    network.reset();
+                    assert_eq!(network.max_flow(s, t), brute);
+                    assert_eq!(lightest[t], Some(brute));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,194 @@
+// ------- IMPARTIAL GAMES -------
+// Sprague-Grundy theory: a position of an impartial game under the normal
+// play convention is lost for the player to move iff its Grundy number, the
+// mex of the Grundy numbers of its moves, is 0, and a sum of games has the
+// XOR of their Grundy numbers. Grundy numbers are memoized over states
+// described by a transition closure; Nim and Wythoff's game have closed forms.
+
+use crate::hash::{FastHashMap, FastHashSet};
+use std::hash::Hash;
+
+/// Smallest non-negative integer not in `values`
+pub fn mex<I: IntoIterator<Item = usize>>(values: I) -> usize {
+    let mut seen = Vec::new();
+    for v in values {
+        if v >= seen.len() {
+            seen.resize(v + 1, false);
+        }
+        seen[v] = true;
+    }
+    seen.iter().position(|&s| !s).unwrap_or(seen.len())
+}
+
+/// Memoized Grundy numbers of the states of a finite acyclic game, whose
+/// moves are listed by `moves`
+pub struct Grundy<S, F> {
+    moves: F,
+    memo: FastHashMap<S, usize>,
+}
+
+impl<S: Hash + Eq + Clone, F: FnMut(&S) -> Vec<S>> Grundy<S, F> {
+    pub fn new(moves: F) -> Self {
+        Grundy {
+            moves,
+            memo: FastHashMap::default(),
+        }
+    }
+
+    /// Number of states evaluated so far
+    pub fn len(&self) -> usize {
+        self.memo.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.memo.is_empty()
+    }
+
+    /// Grundy number of `state`; an explicit stack replaces recursion, so
+    /// long chains of moves do not overflow
+    pub fn value(&mut self, state: S) -> usize {
+        if let Some(&g) = self.memo.get(&state) {
+            return g;
+        }
+        let mut in_progress = FastHashSet::default();
+        // (state, its moves) whose moves are being evaluated
+        let mut stack = vec![(state.clone(), (self.moves)(&state))];
+        in_progress.insert(state.clone());
+        while let Some((current, next)) = stack.last() {
+            match next.iter().find(|s| !self.memo.contains_key(*s)).cloned() {
+                Some(child) => {
+                    assert!(in_progress.insert(child.clone()), "Cyclic game graph");
+                    let moves = (self.moves)(&child);
+                    stack.push((child, moves));
+                }
+                None => {
+                    let g = mex(next.iter().map(|s| self.memo[s]));
+                    let current = current.clone();
+                    in_progress.remove(&current);
+                    self.memo.insert(current, g);
+                    stack.pop();
+                }
+            }
+        }
+        self.memo[&state]
+    }
+}
+
+/// Grundy numbers of the positions 0..=n of the game removing any amount in
+/// `moves` from a pile
+pub fn subtraction_game(n: usize, moves: &[usize]) -> Vec<usize> {
+    let mut grundy = Vec::with_capacity(n + 1);
+    for i in 0..=n {
+        let g = mex(moves.iter().filter(|&&m| m <= i).map(|&m| grundy[i - m]));
+        grundy.push(g);
+    }
+    grundy
+}
+
+/// Whether the player to move wins Nim with these piles
+pub fn nim_wins(piles: &[u64]) -> bool {
+    piles.iter().fold(0, |x, &p| x ^ p) != 0
+}
+
+/// A winning Nim move as (pile, new size of the pile), None if the position
+/// is lost
+pub fn nim_winning_move(piles: &[u64]) -> Option<(usize, u64)> {
+    let x = piles.iter().fold(0, |x, &p| x ^ p);
+    if x == 0 {
+        return None;
+    }
+    // a pile with the highest bit of x set can be lowered to p ^ x
+    piles
+        .iter()
+        .position(|&p| p ^ x < p)
+        .map(|i| (i, piles[i] ^ x))
+}
+
+/// Whether (a, b) is lost for the player to move in Wythoff's game, where a
+/// move removes any amount from one pile or the same amount from both. The
+/// lost positions are (floor(k phi), floor(k phi^2)), computed exactly.
+pub fn wythoff_loses(a: u64, b: u64) -> bool {
+    let (a, b) = (a.min(b), a.max(b));
+    let k = (b - a) as u128;
+    // floor(k phi) = floor((k + sqrt(5 k^2)) / 2) = (k + isqrt(5 k^2)) / 2
+    (k + (5 * k * k).isqrt()) / 2 == a as u128
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mex_and_subtraction_game() {
+        assert_eq!(mex([]), 0);
+        assert_eq!(mex([1, 0, 3, 0]), 2);
+        assert_eq!(mex([0, 1, 2]), 3);
+        // removing 1, 3 or 4 stones
+        let g = subtraction_game(10, &[1, 3, 4]);
+        assert_eq!(g, vec![0, 1, 0, 1, 2, 3, 2, 0, 1, 0, 1]);
+    }
+
+    #[test]
+    fn test_nim_against_grundy() {
+        // states are sorted pile sizes, a move lowers one pile
+        let mut grundy = Grundy::new(|piles: &Vec<u64>| {
+            let mut next = Vec::new();
+            for i in 0..piles.len() {
+                for smaller in 0..piles[i] {
+                    let mut state = piles.clone();
+                    state[i] = smaller;
+                    state.sort_unstable();
+                    next.push(state);
+                }
+            }
+            next
+        });
+        for a in 0..6 {
+            for b in a..6 {
+                for c in b..6 {
+                    let piles = vec![a, b, c];
+                    assert_eq!(grundy.value(piles.clone()) as u64, a ^ b ^ c);
+                    assert_eq!(nim_wins(&piles), a ^ b ^ c != 0);
+                    if let Some((i, size)) = nim_winning_move(&piles) {
+                        let mut next = piles.clone();
+                        assert!(size < next[i]);
+                        next[i] = size;
+                        assert!(!nim_wins(&next));
+                    }
+                }
+            }
+        }
+        assert!(!grundy.is_empty());
+    }
+
+    #[test]
+    fn test_wythoff_against_grundy() {
+        let mut grundy = Grundy::new(|&(a, b): &(u64, u64)| {
+            let mut next = Vec::new();
+            for k in 1..=a {
+                next.push((a - k, b));
+            }
+            for k in 1..=b {
+                next.push((a, b - k));
+            }
+            for k in 1..=a.min(b) {
+                next.push((a - k, b - k));
+            }
+            next
+        });
+        for a in 0..30 {
+            for b in 0..30 {
+                assert_eq!(wythoff_loses(a, b), grundy.value((a, b)) == 0);
+            }
+        }
+        // k = 10^12, far beyond the precision of floating point phi
+        assert!(wythoff_loses(1_618_033_988_749, 2_618_033_988_749));
+        assert!(!wythoff_loses(1_618_033_988_750, 2_618_033_988_750));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cyclic game graph")]
+    fn test_cycle_detected() {
+        Grundy::new(|&s: &u8| vec![(s + 1) % 3]).value(0);
+    }
+}
@@ -0,0 +1,692 @@
+// ------- GEOMETRY -------
+// Plane geometry over f64 points. Predicates compare against EPS, so inputs
+// are expected to be of moderate magnitude (up to about 1e6). Polygons are
+// lists of vertices in counterclockwise order.
+// Half-plane intersection sorts the half-planes by angle and keeps the useful
+// ones in a deque, in O(n log n); the region must be bounded, which adding
+// `HalfPlane::bounding_box` guarantees. Tangency is decided with EPS on
+// squared distances, so touching circles and lines give a single point.
+// Convex polygons answer point location and extreme vertex queries by binary
+// search in O(log n), and Minkowski sums merge the edges of both polygons by
+// angle in O(n + m): two convex shapes A and B intersect iff A + (-B)
+// contains the origin.
+
+use std::collections::VecDeque;
+use std::ops::{Add, Mul, Sub};
+
+pub const EPS: f64 = 1e-9;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    pub fn new(x: f64, y: f64) -> Self {
+        Point { x, y }
+    }
+
+    pub fn dot(self, other: Point) -> f64 {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// Positive if `other` is counterclockwise from `self`
+    pub fn cross(self, other: Point) -> f64 {
+        self.x * other.y - self.y * other.x
+    }
+
+    pub fn norm(self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn dist(self, other: Point) -> f64 {
+        (self - other).norm()
+    }
+
+    pub fn angle(self) -> f64 {
+        self.y.atan2(self.x)
+    }
+
+    /// `self` rotated by 90 degrees counterclockwise
+    pub fn perp(self) -> Point {
+        Point::new(-self.y, self.x)
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, other: Point) -> Point {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<f64> for Point {
+    type Output = Point;
+
+    fn mul(self, k: f64) -> Point {
+        Point::new(self.x * k, self.y * k)
+    }
+}
+
+/// Twice the signed area of the triangle abc, positive if counterclockwise
+pub fn orient(a: Point, b: Point, c: Point) -> f64 {
+    (b - a).cross(c - a)
+}
+
+/// Signed area of a polygon, positive if counterclockwise
+pub fn polygon_area(polygon: &[Point]) -> f64 {
+    let n = polygon.len();
+    (0..n)
+        .map(|i| polygon[i].cross(polygon[(i + 1) % n]))
+        .sum::<f64>()
+        / 2.0
+}
+
+/// Intersection of the lines through `a`, `b` and through `c`, `d`; None if
+/// they are parallel
+pub fn line_intersection(a: Point, b: Point, c: Point, d: Point) -> Option<Point> {
+    let denom = (b - a).cross(d - c);
+    if denom.abs() < EPS {
+        return None;
+    }
+    Some(a + (b - a) * ((c - a).cross(d - c) / denom))
+}
+
+/// The points on the left of the directed line through `p` with direction `d`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HalfPlane {
+    pub p: Point,
+    pub d: Point,
+}
+
+impl HalfPlane {
+    /// The half-plane left of the line from `a` to `b`
+    pub fn through(a: Point, b: Point) -> Self {
+        HalfPlane { p: a, d: b - a }
+    }
+
+    /// Whether `q` is strictly outside
+    pub fn out(&self, q: Point) -> bool {
+        self.d.cross(q - self.p) < -EPS
+    }
+
+    /// The square |x|, |y| <= limit as four half-planes
+    pub fn bounding_box(limit: f64) -> [HalfPlane; 4] {
+        let corners = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+            .map(|(x, y)| Point::new(x * limit, y * limit));
+        [0, 1, 2, 3].map(|i| HalfPlane::through(corners[i], corners[(i + 1) % 4]))
+    }
+
+    fn intersection(&self, other: &HalfPlane) -> Point {
+        self.p + self.d * ((other.p - self.p).cross(other.d) / self.d.cross(other.d))
+    }
+}
+
+/// The part of `polygon` inside the half-plane `h`
+pub fn clip_polygon(polygon: &[Point], h: &HalfPlane) -> Vec<Point> {
+    let n = polygon.len();
+    let mut result = Vec::new();
+    for i in 0..n {
+        let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+        if !h.out(a) {
+            result.push(a);
+        }
+        if h.out(a) != h.out(b) {
+            let edge = HalfPlane::through(a, b);
+            if edge.d.cross(h.d).abs() > EPS {
+                result.push(edge.intersection(h));
+            }
+        }
+    }
+    result
+}
+
+/// Vertices of the bounded intersection of the half-planes, in
+/// counterclockwise order. Empty if the intersection has zero area.
+pub fn half_plane_intersection(planes: &[HalfPlane]) -> Vec<Point> {
+    let mut planes = planes.to_vec();
+    planes.sort_by(|a, b| a.d.angle().total_cmp(&b.d.angle()));
+    let mut deque: VecDeque<HalfPlane> = VecDeque::new();
+    for h in planes {
+        // drop the half-planes made useless by h, at both ends
+        while deque.len() > 1 && h.out(deque[deque.len() - 1].intersection(&deque[deque.len() - 2]))
+        {
+            deque.pop_back();
+        }
+        while deque.len() > 1 && h.out(deque[0].intersection(&deque[1])) {
+            deque.pop_front();
+        }
+        if let Some(last) = deque.back() {
+            if h.d.cross(last.d).abs() < EPS {
+                // opposite parallel half-planes left next to each other do
+                // not overlap
+                if h.d.dot(last.d) < 0.0 {
+                    return Vec::new();
+                }
+                // of two parallel half-planes, keep the inner one
+                if !h.out(last.p) {
+                    continue;
+                }
+                deque.pop_back();
+            }
+        }
+        deque.push_back(h);
+    }
+    while deque.len() > 2
+        && deque[0].out(deque[deque.len() - 1].intersection(&deque[deque.len() - 2]))
+    {
+        deque.pop_back();
+    }
+    while deque.len() > 2 && deque[deque.len() - 1].out(deque[0].intersection(&deque[1])) {
+        deque.pop_front();
+    }
+    if deque.len() < 3 {
+        return Vec::new();
+    }
+    let n = deque.len();
+    let vertices: Vec<Point> = (0..n)
+        .map(|i| deque[i].intersection(&deque[(i + 1) % n]))
+        .collect();
+    if polygon_area(&vertices) < EPS {
+        return Vec::new();
+    }
+    vertices
+}
+
+/// Convex hull in counterclockwise order without collinear vertices, from
+/// the lowest-leftmost point
+pub fn convex_hull(points: &[Point]) -> Vec<Point> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    sorted.dedup();
+    if sorted.len() < 3 {
+        return sorted;
+    }
+    // lower hull left to right, then upper hull right to left
+    let mut hull: Vec<Point> = Vec::with_capacity(2 * sorted.len());
+    let turns_left = |hull: &[Point], p: Point| {
+        let k = hull.len();
+        orient(hull[k - 2], hull[k - 1], p) > EPS
+    };
+    for &p in &sorted {
+        while hull.len() >= 2 && !turns_left(&hull, p) {
+            hull.pop();
+        }
+        hull.push(p);
+    }
+    let lower = hull.len() + 1;
+    for &p in sorted.iter().rev().skip(1) {
+        while hull.len() >= lower && !turns_left(&hull, p) {
+            hull.pop();
+        }
+        hull.push(p);
+    }
+    hull.pop();
+    hull
+}
+
+/// Whether `p` is inside the convex `polygon` or on its boundary, in
+/// O(log n)
+pub fn in_convex_polygon(polygon: &[Point], p: Point) -> bool {
+    let n = polygon.len();
+    match n {
+        0 => return false,
+        1 => return polygon[0].dist(p) < EPS,
+        2 => {
+            let (a, b) = (polygon[0], polygon[1]);
+            return orient(a, b, p).abs() < EPS && (p - a).dot(p - b) <= EPS;
+        }
+        _ => {}
+    }
+    let origin = polygon[0];
+    if orient(origin, polygon[1], p) < -EPS || orient(origin, polygon[n - 1], p) > EPS {
+        return false;
+    }
+    // the fan triangle (origin, polygon[lo], polygon[lo + 1]) containing p
+    let (mut lo, mut hi) = (1, n - 1);
+    while hi - lo > 1 {
+        let mid = (lo + hi) / 2;
+        if orient(origin, polygon[mid], p) >= -EPS {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    orient(polygon[lo], polygon[lo + 1], p) >= -EPS
+}
+
+/// Index of a vertex of the convex `polygon`, without collinear vertices,
+/// maximizing the dot product with `dir`, in O(log n)
+pub fn extreme_vertex(polygon: &[Point], dir: Point) -> usize {
+    let n = polygon.len();
+    assert!(n > 0, "Empty polygon");
+    // sign of the progress in direction dir from vertex j to vertex i
+    let cmp = |i: usize, j: usize| {
+        let step = dir.dot(polygon[i % n] - polygon[j % n]);
+        if step > EPS {
+            1
+        } else if step < -EPS {
+            -1
+        } else {
+            0
+        }
+    };
+    // vertex i is extreme if the edge into it goes forward and the edge out
+    // of it does not
+    let extreme = |i: usize| cmp(i + 1, i) <= 0 && cmp(i, i + n - 1) > 0;
+    if n < 3 {
+        return (0..n)
+            .max_by(|&i, &j| dir.dot(polygon[i]).total_cmp(&dir.dot(polygon[j])))
+            .unwrap();
+    }
+    if extreme(0) {
+        return 0;
+    }
+    let (mut lo, mut hi) = (0, n);
+    while lo + 1 < hi {
+        let mid = (lo + hi) / 2;
+        if extreme(mid) {
+            return mid;
+        }
+        let (lo_step, mid_step) = (cmp(lo + 1, lo), cmp(mid + 1, mid));
+        if lo_step > mid_step || (lo_step == mid_step && lo_step == cmp(lo, mid)) {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+    lo
+}
+
+/// Minkowski sum {a + b} of two convex polygons, which may keep collinear
+/// vertices
+pub fn minkowski_sum(a: &[Point], b: &[Point]) -> Vec<Point> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    // both from their lowest-leftmost vertex, with the first two repeated
+    let reorder = |polygon: &[Point]| {
+        let start = (0..polygon.len())
+            .min_by(|&i, &j| {
+                let (p, q) = (polygon[i], polygon[j]);
+                p.y.total_cmp(&q.y).then(p.x.total_cmp(&q.x))
+            })
+            .unwrap();
+        (0..polygon.len() + 2)
+            .map(|k| polygon[(start + k) % polygon.len()])
+            .collect::<Vec<Point>>()
+    };
+    let (p, q) = (reorder(a), reorder(b));
+    let (mut i, mut j) = (0, 0);
+    let mut result = Vec::with_capacity(a.len() + b.len());
+    while i < a.len() || j < b.len() {
+        result.push(p[i] + q[j]);
+        let turn = (p[i + 1] - p[i]).cross(q[j + 1] - q[j]);
+        if turn >= -EPS && i < a.len() {
+            i += 1;
+        }
+        if turn <= EPS && j < b.len() {
+            j += 1;
+        }
+    }
+    result
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Circle {
+    pub c: Point,
+    pub r: f64,
+}
+
+impl Circle {
+    pub fn new(c: Point, r: f64) -> Self {
+        Circle { c, r }
+    }
+
+    /// Intersections with the line through `a` and `b`, ordered from `a`
+    /// towards `b`
+    pub fn line_intersection(&self, a: Point, b: Point) -> Vec<Point> {
+        let ab = b - a;
+        let len2 = ab.dot(ab);
+        let foot = a + ab * ((self.c - a).dot(ab) / len2);
+        let h2 = self.r * self.r - orient(a, b, self.c).powi(2) / len2;
+        if h2 < -EPS {
+            return Vec::new();
+        }
+        if h2 < EPS {
+            return vec![foot];
+        }
+        let h = ab * (h2.sqrt() / len2.sqrt());
+        vec![foot - h, foot + h]
+    }
+
+    /// Intersections with `other`, the first one on the right of the line
+    /// from this centre to the other; none for concentric circles
+    pub fn circle_intersection(&self, other: &Circle) -> Vec<Point> {
+        let d = other.c - self.c;
+        let d2 = d.dot(d);
+        if d2 < EPS {
+            return Vec::new();
+        }
+        let (sum, diff) = (self.r + other.r, self.r - other.r);
+        if sum * sum < d2 - EPS || diff * diff > d2 + EPS {
+            return Vec::new();
+        }
+        // the chord crosses the line of centres at distance p |d| from here
+        let p = (d2 + self.r * self.r - other.r * other.r) / (2.0 * d2);
+        let h2 = self.r * self.r - p * p * d2;
+        let mid = self.c + d * p;
+        if h2 < EPS {
+            return vec![mid];
+        }
+        let offset = d.perp() * (h2 / d2).sqrt();
+        vec![mid - offset, mid + offset]
+    }
+
+    /// Common tangents as pairs of touching points (on self, on other): the
+    /// outer ones, then the inner ones. A circle of radius 0 is a point, so
+    /// this also gives the tangents from a point.
+    pub fn tangents(&self, other: &Circle) -> Vec<(Point, Point)> {
+        let mut result = Vec::new();
+        for other_r in [other.r, -other.r] {
+            let d = other.c - self.c;
+            let d2 = d.dot(d);
+            let dr = self.r - other_r;
+            let h2 = d2 - dr * dr;
+            if d2 < EPS || h2 < -EPS {
+                continue;
+            }
+            let h = h2.max(0.0).sqrt();
+            let signs: &[f64] = if h2 < EPS { &[1.0] } else { &[-1.0, 1.0] };
+            for &sign in signs {
+                // unit normal of the tangent line, from the centres to it
+                let v = (d * dr + d.perp() * (h * sign)) * (1.0 / d2);
+                result.push((self.c + v * self.r, other.c + v * other_r));
+            }
+            // a point has no distinct inner tangents
+            if self.r == 0.0 || other.r == 0.0 {
+                break;
+            }
+        }
+        result
+    }
+
+    /// Area of the intersection with a polygon, in O(n)
+    pub fn polygon_area(&self, polygon: &[Point]) -> f64 {
+        let r2 = self.r * self.r;
+        let arg = |p: Point, q: Point| p.cross(q).atan2(p.dot(q));
+        // signed area of the circle intersected with the triangle (0, p, q),
+        // with the centre moved to the origin
+        let triangle = |p: Point, q: Point| {
+            let d = q - p;
+            let len2 = d.dot(d);
+            if len2 < EPS {
+                return 0.0;
+            }
+            // the segment p + t d is inside the circle for t in [s, t]
+            let a = d.dot(p) / len2;
+            let b = (p.dot(p) - r2) / len2;
+            let det = a * a - b;
+            if det <= 0.0 {
+                return arg(p, q) * r2 / 2.0;
+            }
+            let (s, t) = ((-a - det.sqrt()).max(0.0), (-a + det.sqrt()).min(1.0));
+            if t < 0.0 || s >= 1.0 {
+                return arg(p, q) * r2 / 2.0;
+            }
+            let (u, v) = (p + d * s, p + d * t);
+            arg(p, u) * r2 / 2.0 + u.cross(v) / 2.0 + arg(v, q) * r2 / 2.0
+        };
+        let n = polygon.len();
+        (0..n)
+            .map(|i| triangle(polygon[i] - self.c, polygon[(i + 1) % n] - self.c))
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_basics() {
+        let (a, b) = (Point::new(0.0, 0.0), Point::new(4.0, 4.0));
+        let (c, d) = (Point::new(0.0, 4.0), Point::new(4.0, 0.0));
+        assert_eq!(line_intersection(a, b, c, d), Some(Point::new(2.0, 2.0)));
+        assert_eq!(line_intersection(a, b, c, c + (b - a)), None);
+        assert!(orient(a, d, b) > 0.0);
+        let square = [a, d, b, c];
+        assert_eq!(polygon_area(&square), 16.0);
+        let half = clip_polygon(&square, &HalfPlane::through(d, c));
+        assert!((polygon_area(&half) - 8.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_half_planes_against_clipping() {
+        let mut rng = Rng::new(68);
+        let random_point = |rng: &mut Rng| {
+            Point::new(
+                rng.gen_range_i64(-10, 10) as f64,
+                rng.gen_range_i64(-10, 10) as f64,
+            )
+        };
+        for _ in 0..500 {
+            let mut planes = HalfPlane::bounding_box(20.0).to_vec();
+            for _ in 0..rng.gen_range(0, 8) {
+                let a = random_point(&mut rng);
+                let b = random_point(&mut rng);
+                if a != b {
+                    planes.push(HalfPlane::through(a, b));
+                }
+            }
+            let box_corners = [(-20.0, -20.0), (20.0, -20.0), (20.0, 20.0), (-20.0, 20.0)];
+            let mut expected: Vec<Point> =
+                box_corners.iter().map(|&(x, y)| Point::new(x, y)).collect();
+            for h in &planes {
+                expected = clip_polygon(&expected, h);
+            }
+            let region = half_plane_intersection(&planes);
+            let area = if expected.len() < 3 {
+                0.0
+            } else {
+                polygon_area(&expected)
+            };
+            if area < 1e-6 {
+                assert!(region.is_empty());
+                continue;
+            }
+            assert!((polygon_area(&region) - area).abs() < 1e-6);
+            for v in &region {
+                assert!(planes.iter().all(|h| h.d.cross(*v - h.p) > -1e-6));
+            }
+        }
+        // x >= 1 and x <= -1
+        let apart = [
+            HalfPlane::through(Point::new(1.0, 1.0), Point::new(1.0, 0.0)),
+            HalfPlane::through(Point::new(-1.0, 0.0), Point::new(-1.0, 1.0)),
+        ];
+        let mut planes = HalfPlane::bounding_box(5.0).to_vec();
+        planes.extend(apart);
+        assert!(half_plane_intersection(&planes).is_empty());
+    }
+
+    fn close(a: Point, b: Point) -> bool {
+        a.dist(b) < 1e-9
+    }
+
+    #[test]
+    fn test_circle_intersections() {
+        let circle = Circle::new(Point::new(0.0, 0.0), 5.0);
+        let y = |y: f64| (Point::new(-10.0, y), Point::new(10.0, y));
+        let (a, b) = y(3.0);
+        let points = circle.line_intersection(a, b);
+        assert!(close(points[0], Point::new(-4.0, 3.0)) && close(points[1], Point::new(4.0, 3.0)));
+        let (a, b) = y(5.0);
+        assert_eq!(circle.line_intersection(a, b), vec![Point::new(0.0, 5.0)]);
+        let (a, b) = y(6.0);
+        assert!(circle.line_intersection(a, b).is_empty());
+
+        let other = Circle::new(Point::new(8.0, 0.0), 5.0);
+        let points = circle.circle_intersection(&other);
+        assert!(close(points[0], Point::new(4.0, -3.0)) && close(points[1], Point::new(4.0, 3.0)));
+        // touching from outside and from inside
+        let small = Circle::new(Point::new(0.0, 0.0), 2.0);
+        let points = small.circle_intersection(&Circle::new(Point::new(5.0, 0.0), 3.0));
+        assert_eq!(points.len(), 1);
+        assert!(close(points[0], Point::new(2.0, 0.0)));
+        let points = circle.circle_intersection(&Circle::new(Point::new(3.0, 0.0), 2.0));
+        assert_eq!(points.len(), 1);
+        assert!(close(points[0], Point::new(5.0, 0.0)));
+        assert!(circle.circle_intersection(&small).is_empty());
+        assert!(circle
+            .circle_intersection(&Circle::new(Point::new(20.0, 0.0), 1.0))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_tangents() {
+        // two unit circles 4 apart: y = 1, y = -1, and two through (2, 0)
+        let a = Circle::new(Point::new(0.0, 0.0), 1.0);
+        let b = Circle::new(Point::new(4.0, 0.0), 1.0);
+        let tangents = a.tangents(&b);
+        assert_eq!(tangents.len(), 4);
+        assert!(
+            close(tangents[0].0, Point::new(0.0, -1.0))
+                && close(tangents[0].1, Point::new(4.0, -1.0))
+        );
+        assert!(
+            close(tangents[1].0, Point::new(0.0, 1.0))
+                && close(tangents[1].1, Point::new(4.0, 1.0))
+        );
+        for &(p, q) in &tangents {
+            // the touching points are on the circles, and the line is
+            // perpendicular to both radii
+            assert!((p.dist(a.c) - 1.0).abs() < 1e-9 && (q.dist(b.c) - 1.0).abs() < 1e-9);
+            assert!((q - p).dot(p - a.c).abs() < 1e-9 && (q - p).dot(q - b.c).abs() < 1e-9);
+        }
+        let crossing = &tangents[2..];
+        for &(p, q) in crossing {
+            assert!(close(
+                line_intersection(p, q, a.c, b.c).unwrap(),
+                Point::new(2.0, 0.0)
+            ));
+        }
+        // externally touching circles share one inner tangent
+        assert_eq!(a.tangents(&Circle::new(Point::new(2.0, 0.0), 1.0)).len(), 3);
+        // nested circles have none
+        assert!(Circle::new(Point::new(0.0, 0.0), 5.0)
+            .tangents(&a)
+            .is_empty());
+        // from the point (2, 0) to the unit circle
+        let point = Circle::new(Point::new(2.0, 0.0), 0.0);
+        let tangents = point.tangents(&a);
+        assert_eq!(tangents.len(), 2);
+        let h = 3f64.sqrt() / 2.0;
+        assert!(
+            close(tangents[0].1, Point::new(0.5, h)) || close(tangents[0].1, Point::new(0.5, -h))
+        );
+        assert!(tangents.iter().all(|&(p, _)| p == point.c));
+    }
+
+    #[test]
+    fn test_circle_polygon_area() {
+        use std::f64::consts::PI;
+        let square = |lo: f64, hi: f64| {
+            [(lo, lo), (hi, lo), (hi, hi), (lo, hi)].map(|(x, y)| Point::new(x, y))
+        };
+        let unit = Circle::new(Point::new(0.0, 0.0), 1.0);
+        // the circle inside the polygon, and the polygon inside the circle
+        assert!((unit.polygon_area(&square(-1.0, 1.0)) - PI).abs() < 1e-9);
+        assert!(
+            (Circle::new(Point::new(0.0, 0.0), 10.0).polygon_area(&square(-1.0, 1.0)) - 4.0).abs()
+                < 1e-9
+        );
+        // a quarter, and a half of the circle
+        assert!((unit.polygon_area(&square(0.0, 2.0)) - PI / 4.0).abs() < 1e-9);
+        let upper =
+            [(-2.0, 0.0), (2.0, 0.0), (2.0, 2.0), (-2.0, 2.0)].map(|(x, y)| Point::new(x, y));
+        assert!((unit.polygon_area(&upper) - PI / 2.0).abs() < 1e-9);
+        // the strip 0 <= y <= 1/2: two triangles and a sector of pi / 3
+        let strip =
+            [(-1.0, 0.0), (1.0, 0.0), (1.0, 0.5), (-1.0, 0.5)].map(|(x, y)| Point::new(x, y));
+        let expected = 3f64.sqrt() / 4.0 + PI / 6.0;
+        assert!((unit.polygon_area(&strip) - expected).abs() < 1e-9);
+        // clockwise polygons give negative areas
+        let mut clockwise = square(0.0, 2.0);
+        clockwise.reverse();
+        assert!((unit.polygon_area(&clockwise) + PI / 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_convex_polygon_queries() {
+        let mut rng = Rng::new(69);
+        let random_points = |rng: &mut Rng, n: usize| -> Vec<Point> {
+            (0..n)
+                .map(|_| {
+                    Point::new(
+                        rng.gen_range_i64(-20, 20) as f64,
+                        rng.gen_range_i64(-20, 20) as f64,
+                    )
+                })
+                .collect()
+        };
+        for _ in 0..200 {
+            let n = rng.gen_range(1, 30) as usize;
+            let hull = convex_hull(&random_points(&mut rng, n));
+            // every edge turns left, and every point is on the inner side
+            let m = hull.len();
+            if m >= 3 {
+                for i in 0..m {
+                    assert!(orient(hull[i], hull[(i + 1) % m], hull[(i + 2) % m]) > 0.0);
+                }
+            }
+            for _ in 0..50 {
+                let q = random_points(&mut rng, 1)[0];
+                let brute = if m >= 3 {
+                    (0..m).all(|i| orient(hull[i], hull[(i + 1) % m], q) >= 0.0)
+                } else {
+                    hull.contains(&q)
+                        || (m == 2
+                            && orient(hull[0], hull[1], q) == 0.0
+                            && (q - hull[0]).dot(q - hull[1]) <= 0.0)
+                };
+                assert_eq!(in_convex_polygon(&hull, q), brute);
+                let dir = Point::new(
+                    rng.gen_range_i64(-5, 5) as f64,
+                    rng.gen_range_i64(-5, 5) as f64,
+                );
+                let best = hull.iter().map(|&h| dir.dot(h)).fold(f64::MIN, f64::max);
+                assert_eq!(dir.dot(hull[extreme_vertex(&hull, dir)]), best);
+            }
+
+            let k = rng.gen_range(1, 10) as usize;
+            let other = convex_hull(&random_points(&mut rng, k));
+            let sum = minkowski_sum(&hull, &other);
+            let sums: Vec<Point> = hull
+                .iter()
+                .flat_map(|&a| other.iter().map(move |&b| a + b))
+                .collect();
+            assert_eq!(convex_hull(&sum), convex_hull(&sums));
+        }
+        // a unit square and a unit diamond: the square [-1, 2]^2 without
+        // four corner triangles of area 1 / 2
+        let square =
+            [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)].map(|(x, y)| Point::new(x, y));
+        let diamond =
+            [(0.0, -1.0), (1.0, 0.0), (0.0, 1.0), (-1.0, 0.0)].map(|(x, y)| Point::new(x, y));
+        let octagon = minkowski_sum(&square, &diamond);
+        assert_eq!(octagon.len(), 8);
+        assert_eq!(polygon_area(&octagon), 7.0);
+    }
+}
@@ -0,0 +1,97 @@
+// ------- GRAPH -------
+// Weighted graph stored as adjacency lists of (neighbour, weight) pairs.
+// Vertices are 0-based; undirected edges are stored once per direction.
+
+#[derive(Clone, Debug, Default)]
+pub struct Graph {
+    adj: Vec<Vec<(usize, i64)>>,
+    edges: usize, // Number of add_edge calls, undirected edges count twice
+}
+
+impl Graph {
+    /// A graph with `n` vertices and no edges
+    pub fn new(n: usize) -> Self {
+        Graph {
+            adj: vec![Vec::new(); n],
+            edges: 0,
+        }
+    }
+
+    /// A graph with `n` vertices and the undirected edges `(u, v, weight)`
+    pub fn from_undirected_edges(n: usize, edges: &[(usize, usize, i64)]) -> Self {
+        let mut graph = Graph::new(n);
+        for &(u, v, weight) in edges {
+            graph.add_undirected_edge(u, v, weight);
+        }
+        graph
+    }
+
+    /// Number of vertices
+    pub fn len(&self) -> usize {
+        self.adj.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.adj.is_empty()
+    }
+
+    /// Number of directed edges
+    pub fn edge_count(&self) -> usize {
+        self.edges
+    }
+
+    /// Adds the directed edge `u -> v`
+    pub fn add_edge(&mut self, u: usize, v: usize, weight: i64) {
+        assert!(u < self.len() && v < self.len(), "Index out of range");
+        self.adj[u].push((v, weight));
+        self.edges += 1;
+    }
+
+    /// Adds the edges `u -> v` and `v -> u` with the same weight
+    pub fn add_undirected_edge(&mut self, u: usize, v: usize, weight: i64) {
+        self.add_edge(u, v, weight);
+        self.add_edge(v, u, weight);
+    }
+
+    /// The `(neighbour, weight)` pairs of the edges leaving `v`
+    pub fn neighbors(&self, v: usize) -> &[(usize, i64)] {
+        &self.adj[v]
+    }
+
+    pub fn degree(&self, v: usize) -> usize {
+        self.adj[v].len()
+    }
+
+    /// Parents and DFS preorder of the tree rooted at `root`, neighbours
+    /// visited in insertion order: every vertex comes after its parent and
+    /// every subtree is contiguous. The root is its own parent.
+    pub fn rooted_order(&self, root: usize) -> (Vec<usize>, Vec<usize>) {
+        rooted_order(self.len(), root, |v| self.adj[v].iter().map(|&(u, _)| u))
+    }
+}
+
+// `Graph::rooted_order` for trees given by any neighbour lists
+pub(crate) fn rooted_order<I, N>(n: usize, root: usize, neighbors: N) -> (Vec<usize>, Vec<usize>)
+where
+    I: IntoIterator<Item = usize>,
+    I::IntoIter: DoubleEndedIterator,
+    N: Fn(usize) -> I,
+{
+    assert!(root < n, "Index out of range");
+    let mut parent = vec![usize::MAX; n];
+    let mut order = Vec::with_capacity(n);
+    parent[root] = root;
+    let mut stack = vec![root];
+    while let Some(v) = stack.pop() {
+        order.push(v);
+        // reversed, so the first neighbour is popped first
+        for u in neighbors(v).into_iter().rev() {
+            if parent[u] == usize::MAX {
+                parent[u] = v;
+                stack.push(u);
+            }
+        }
+    }
+    assert_eq!(order.len(), n, "The graph is not a connected tree");
+    (parent, order)
+}
@@ -0,0 +1,313 @@
+// ------- GREEDY INTERVAL AND SCHEDULING PROBLEMS -------
+// Classic exchange-argument greedies on closed intervals [l, r]. Intervals
+// sharing an endpoint overlap. Every solver returns the indices of the chosen
+// intervals in the order they were picked.
+// Scheduling on one machine: unit jobs with deadlines are placed by profit in
+// the latest free slot, found with a DSU; Smith's rule orders jobs by
+// time / weight to minimize the weighted completion time; and Moore-Hodgson
+// keeps the most jobs on time by dropping the longest one, from a heap,
+// whenever the earliest-deadline-first schedule runs late.
+
+use crate::dsu::Dsu;
+use std::collections::BinaryHeap;
+
+/// Largest set of pairwise disjoint intervals: repeatedly take the interval
+/// ending first among those starting after the last chosen one
+pub fn max_non_overlapping(intervals: &[(i64, i64)]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..intervals.len()).collect();
+    order.sort_by_key(|&i| intervals[i].1);
+    let mut chosen = Vec::new();
+    let mut last_end = None;
+    for i in order {
+        let (l, r) = intervals[i];
+        if last_end.is_none_or(|end| l > end) {
+            chosen.push(i);
+            last_end = Some(r);
+        }
+    }
+    chosen
+}
+
+/// Fewest points hitting every interval. The points are the right endpoints
+/// of the returned intervals: each one is placed at the end of the first
+/// interval not hit yet.
+pub fn min_stabbing_points(intervals: &[(i64, i64)]) -> Vec<usize> {
+    // an interval is hit by the last point iff it starts at or before it,
+    // which is exactly the complement of the disjointness test above
+    max_non_overlapping(intervals)
+}
+
+/// Fewest intervals whose union covers the segment `[lo, hi]`, seen as real
+/// segments (so [0, 2] and [2, 5] cover [0, 5]). None if it cannot be covered.
+pub fn min_interval_cover(intervals: &[(i64, i64)], lo: i64, hi: i64) -> Option<Vec<usize>> {
+    assert!(lo <= hi, "Invalid range");
+    let mut order: Vec<usize> = (0..intervals.len()).collect();
+    order.sort_by_key(|&i| intervals[i].0);
+    let mut chosen = Vec::new();
+    let mut covered = lo; // [lo, covered] is covered once something is chosen
+    let mut next = 0;
+    loop {
+        // among the intervals starting inside the covered part, reach furthest
+        let mut best: Option<usize> = None;
+        while next < order.len() && intervals[order[next]].0 <= covered {
+            let i = order[next];
+            if best.is_none_or(|b| intervals[i].1 > intervals[b].1) {
+                best = Some(i);
+            }
+            next += 1;
+        }
+        let best = best?;
+        if intervals[best].1 < covered || (intervals[best].1 == covered && !chosen.is_empty()) {
+            return None;
+        }
+        chosen.push(best);
+        covered = intervals[best].1;
+        if covered >= hi {
+            return Some(chosen);
+        }
+    }
+}
+
+/// Most profitable set of unit-time jobs, given as (deadline, profit), each
+/// finishing by its deadline: the total profit and the (job, slot) pairs,
+/// slot t covering the time [t - 1, t]
+pub fn job_sequencing(jobs: &[(usize, i64)]) -> (i64, Vec<(usize, usize)>) {
+    let slots = jobs
+        .iter()
+        .map(|&(d, _)| d)
+        .max()
+        .unwrap_or(0)
+        .min(jobs.len());
+    let mut order: Vec<usize> = (0..jobs.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(jobs[i].1));
+    // slots are merged with the free slots below them, latest[root] being
+    // the latest free slot of a component (0 for none)
+    let mut dsu = Dsu::new(slots + 1);
+    let mut latest: Vec<usize> = (0..=slots).collect();
+    let (mut total, mut schedule) = (0, Vec::new());
+    for i in order {
+        let (deadline, profit) = jobs[i];
+        let slot = latest[dsu.find(deadline.min(slots))];
+        if slot == 0 || profit < 0 {
+            continue;
+        }
+        total += profit;
+        schedule.push((i, slot));
+        let below = latest[dsu.find(slot - 1)];
+        dsu.union(slot, slot - 1);
+        let root = dsu.find(slot);
+        latest[root] = below;
+    }
+    (total, schedule)
+}
+
+/// Order of the jobs, given as (time, weight), minimizing the sum of
+/// weight * completion time, and that sum
+pub fn min_weighted_completion(jobs: &[(i64, i64)]) -> (i64, Vec<usize>) {
+    let mut order: Vec<usize> = (0..jobs.len()).collect();
+    // i before j iff t_i / w_i < t_j / w_j, compared without division
+    order.sort_by(|&i, &j| {
+        let ((ti, wi), (tj, wj)) = (jobs[i], jobs[j]);
+        (ti as i128 * wj as i128).cmp(&(tj as i128 * wi as i128))
+    });
+    let (mut time, mut total) = (0, 0);
+    for &i in &order {
+        time += jobs[i].0;
+        total += jobs[i].1 * time;
+    }
+    (total, order)
+}
+
+/// Largest set of jobs, given as (time, deadline), that can all finish by
+/// their deadlines, in the order they should run
+pub fn max_jobs_on_time(jobs: &[(i64, i64)]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..jobs.len()).collect();
+    order.sort_by_key(|&i| jobs[i].1);
+    let mut kept = BinaryHeap::new(); // (time, job)
+    let mut time = 0;
+    for i in order {
+        let (duration, deadline) = jobs[i];
+        kept.push((duration, i));
+        time += duration;
+        if time > deadline {
+            let (longest, _) = kept.pop().unwrap();
+            time -= longest;
+        }
+    }
+    let mut chosen: Vec<usize> = kept.into_iter().map(|(_, i)| i).collect();
+    chosen.sort_by_key(|&i| (jobs[i].1, i));
+    chosen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combinatorics::permutations;
+    use crate::rng::Rng;
+
+    fn random_intervals(rng: &mut Rng, n: usize) -> Vec<(i64, i64)> {
+        (0..n)
+            .map(|_| {
+                let l = rng.gen_range_i64(0, 20);
+                (l, l + rng.gen_range_i64(0, 6))
+            })
+            .collect()
+    }
+
+    fn disjoint(a: (i64, i64), b: (i64, i64)) -> bool {
+        a.1 < b.0 || b.1 < a.0
+    }
+
+    #[test]
+    fn test_against_subsets() {
+        let mut rng = Rng::new(36);
+        for _ in 0..200 {
+            let n = rng.gen_range(0, 10) as usize;
+            let intervals = random_intervals(&mut rng, n);
+            let subsets = || {
+                (0..1usize << n).map(|mask| {
+                    (0..n)
+                        .filter(move |&i| mask >> i & 1 == 1)
+                        .collect::<Vec<_>>()
+                })
+            };
+
+            let chosen = max_non_overlapping(&intervals);
+            for (k, &a) in chosen.iter().enumerate() {
+                for &b in &chosen[k + 1..] {
+                    assert!(disjoint(intervals[a], intervals[b]));
+                }
+            }
+            let best = subsets()
+                .filter(|s| {
+                    s.iter().all(|&a| {
+                        s.iter()
+                            .all(|&b| a == b || disjoint(intervals[a], intervals[b]))
+                    })
+                })
+                .map(|s| s.len())
+                .max()
+                .unwrap();
+            assert_eq!(chosen.len(), best);
+
+            // stabbing: every interval is hit, and by the duality no fewer
+            // points than disjoint intervals can do
+            let points: Vec<i64> = min_stabbing_points(&intervals)
+                .iter()
+                .map(|&i| intervals[i].1)
+                .collect();
+            assert!(intervals
+                .iter()
+                .all(|&(l, r)| points.iter().any(|&p| l <= p && p <= r)));
+            assert_eq!(points.len(), best);
+
+            let (lo, hi) = (rng.gen_range_i64(0, 10), rng.gen_range_i64(10, 25));
+            let covers = |s: &Vec<usize>| {
+                let mut parts: Vec<(i64, i64)> = s.iter().map(|&i| intervals[i]).collect();
+                parts.sort();
+                let mut reach = None;
+                for (l, r) in parts {
+                    match reach {
+                        None if l <= lo && lo <= r => reach = Some(r),
+                        Some(end) if l <= end => reach = Some(end.max(r)),
+                        _ => {}
+                    }
+                }
+                reach.is_some_and(|end| end >= hi)
+            };
+            let expected = subsets().filter(covers).map(|s| s.len()).min();
+            let cover = min_interval_cover(&intervals, lo, hi);
+            assert_eq!(cover.as_ref().map(Vec::len), expected);
+            if let Some(cover) = cover {
+                assert!(covers(&cover));
+            }
+        }
+    }
+
+    #[test]
+    fn test_cover_examples() {
+        let intervals = [(0, 2), (2, 5), (1, 4), (5, 8)];
+        assert_eq!(min_interval_cover(&intervals, 0, 8), Some(vec![0, 1, 3]));
+        assert_eq!(min_interval_cover(&intervals, 3, 3), Some(vec![1]));
+        assert_eq!(min_interval_cover(&intervals, 0, 9), None);
+        assert_eq!(min_interval_cover(&[(0, 1), (2, 3)], 0, 3), None);
+    }
+
+    #[test]
+    fn test_scheduling_against_brute_force() {
+        let mut rng = Rng::new(86);
+        for _ in 0..200 {
+            let n = rng.gen_range(0, 8) as usize;
+            let subsets = (0..1usize << n).map(|mask| {
+                (0..n)
+                    .filter(move |&i| mask >> i & 1 == 1)
+                    .collect::<Vec<_>>()
+            });
+
+            // unit jobs: a set fits iff, by deadline, the k-th job has deadline >= k
+            let jobs: Vec<(usize, i64)> = (0..n)
+                .map(|_| (rng.gen_range(0, 6) as usize, rng.gen_range_i64(-5, 30)))
+                .collect();
+            let fits = |s: &Vec<usize>| {
+                let mut deadlines: Vec<usize> = s.iter().map(|&i| jobs[i].0).collect();
+                deadlines.sort();
+                deadlines.iter().enumerate().all(|(k, &d)| d > k)
+            };
+            let best = subsets
+                .clone()
+                .filter(fits)
+                .map(|s| s.iter().map(|&i| jobs[i].1).sum::<i64>())
+                .max()
+                .unwrap();
+            let (total, schedule) = job_sequencing(&jobs);
+            assert_eq!(total, best);
+            assert_eq!(schedule.iter().map(|&(i, _)| jobs[i].1).sum::<i64>(), total);
+            let mut slots: Vec<usize> = schedule.iter().map(|&(_, t)| t).collect();
+            assert!(schedule.iter().all(|&(i, t)| 1 <= t && t <= jobs[i].0));
+            slots.sort();
+            slots.dedup();
+            assert_eq!(slots.len(), schedule.len());
+
+            // weighted completion time over every order
+            let jobs: Vec<(i64, i64)> = (0..n)
+                .map(|_| (rng.gen_range_i64(1, 10), rng.gen_range_i64(0, 10)))
+                .collect();
+            let cost = |order: &[usize]| {
+                let mut time = 0;
+                order.iter().fold(0, |acc, &i| {
+                    time += jobs[i].0;
+                    acc + jobs[i].1 * time
+                })
+            };
+            let indices: Vec<usize> = (0..n).collect();
+            let best = permutations(&indices).map(|p| cost(&p)).min().unwrap();
+            let (total, order) = min_weighted_completion(&jobs);
+            assert_eq!(total, best);
+            assert_eq!(cost(&order), best);
+
+            // jobs on time: a set fits iff earliest deadline first meets them all
+            let jobs: Vec<(i64, i64)> = (0..n)
+                .map(|_| (rng.gen_range_i64(1, 6), rng.gen_range_i64(0, 15)))
+                .collect();
+            let on_time = |order: &[usize]| {
+                let mut time = 0;
+                order.iter().all(|&i| {
+                    time += jobs[i].0;
+                    time <= jobs[i].1
+                })
+            };
+            let best = subsets
+                .filter(|s| {
+                    let mut s = s.clone();
+                    s.sort_by_key(|&i| jobs[i].1);
+                    on_time(&s)
+                })
+                .map(|s| s.len())
+                .max()
+                .unwrap();
+            let chosen = max_jobs_on_time(&jobs);
+            assert_eq!(chosen.len(), best);
+            assert!(on_time(&chosen));
+        }
+    }
+}
@@ -0,0 +1,293 @@
+// ------- GRID -------
+// Character grids as found in maze problems: parsing, bounds-checked 4 and 8
+// neighbourhoods, (multi-source) BFS distances with path reconstruction and
+// flood fill labelling of connected components. Cells are (row, column).
+
+use std::collections::VecDeque;
+use std::ops::{Index, IndexMut};
+
+pub type Pos = (usize, usize);
+
+// A value for some of the cells, indexed [row][column]
+type CellMap<T> = Vec<Vec<Option<T>>>;
+
+/// Up, right, down, left
+pub const DIRS4: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+/// The four sides followed by the four corners
+pub const DIRS8: [(isize, isize); 8] = [
+    (-1, 0),
+    (0, 1),
+    (1, 0),
+    (0, -1),
+    (-1, -1),
+    (-1, 1),
+    (1, 1),
+    (1, -1),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid {
+    rows: usize,
+    cols: usize,
+    cells: Vec<u8>, // Row-major
+}
+
+impl Grid {
+    /// Parses the non-empty lines of `s`, trimmed, which must have the same
+    /// length
+    pub fn parse(s: &str) -> Self {
+        let lines: Vec<&[u8]> = s
+            .lines()
+            .map(|l| l.trim().as_bytes())
+            .filter(|l| !l.is_empty())
+            .collect();
+        let cols = lines.first().map_or(0, |l| l.len());
+        assert!(
+            lines.iter().all(|l| l.len() == cols),
+            "Rows of different lengths"
+        );
+        Grid {
+            rows: lines.len(),
+            cols,
+            cells: lines.concat(),
+        }
+    }
+
+    /// A `rows` x `cols` grid filled with `fill`
+    pub fn new(rows: usize, cols: usize, fill: u8) -> Self {
+        Grid {
+            rows,
+            cols,
+            cells: vec![fill; rows * cols],
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Number of cells
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// The cell at `pos` moved by `(dr, dc)`, None if outside the grid
+    pub fn step(&self, (r, c): Pos, (dr, dc): (isize, isize)) -> Option<Pos> {
+        let r = r.checked_add_signed(dr)?;
+        let c = c.checked_add_signed(dc)?;
+        (r < self.rows && c < self.cols).then_some((r, c))
+    }
+
+    /// The cells adjacent to `pos` along `dirs` (usually `DIRS4` or `DIRS8`)
+    pub fn neighbors<'a>(
+        &'a self,
+        pos: Pos,
+        dirs: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = Pos> + 'a {
+        dirs.iter().filter_map(move |&d| self.step(pos, d))
+    }
+
+    /// Positions of every cell equal to `c`, row by row
+    pub fn find_all(&self, c: u8) -> Vec<Pos> {
+        (0..self.cells.len())
+            .filter(|&i| self.cells[i] == c)
+            .map(|i| (i / self.cols, i % self.cols))
+            .collect()
+    }
+
+    /// Position of the first cell equal to `c`
+    pub fn find(&self, c: u8) -> Option<Pos> {
+        let i = self.cells.iter().position(|&x| x == c)?;
+        Some((i / self.cols, i % self.cols))
+    }
+
+    /// Distance of every cell from the closest source, moving along `dirs`
+    /// through the cells accepted by `passable`. Sources are always entered.
+    pub fn bfs<F: Fn(u8) -> bool>(
+        &self,
+        sources: &[Pos],
+        dirs: &[(isize, isize)],
+        passable: F,
+    ) -> Vec<Vec<Option<usize>>> {
+        self.bfs_with_parents(sources, dirs, passable).0
+    }
+
+    // BFS returning the distances and the cell each one was reached from
+    fn bfs_with_parents<F: Fn(u8) -> bool>(
+        &self,
+        sources: &[Pos],
+        dirs: &[(isize, isize)],
+        passable: F,
+    ) -> (CellMap<usize>, CellMap<Pos>) {
+        let mut dist = vec![vec![None; self.cols]; self.rows];
+        let mut from = vec![vec![None; self.cols]; self.rows];
+        let mut queue = VecDeque::new();
+        for &(r, c) in sources {
+            assert!(r < self.rows && c < self.cols, "Index out of range");
+            if dist[r][c].is_none() {
+                dist[r][c] = Some(0);
+                queue.push_back((r, c));
+            }
+        }
+        while let Some((r, c)) = queue.pop_front() {
+            let d = dist[r][c].unwrap();
+            for (nr, nc) in self.neighbors((r, c), dirs) {
+                if dist[nr][nc].is_none() && passable(self[(nr, nc)]) {
+                    dist[nr][nc] = Some(d + 1);
+                    from[nr][nc] = Some((r, c));
+                    queue.push_back((nr, nc));
+                }
+            }
+        }
+        (dist, from)
+    }
+
+    /// A shortest path from `start` to `end`, both included, None if `end` is
+    /// unreachable
+    pub fn shortest_path<F: Fn(u8) -> bool>(
+        &self,
+        start: Pos,
+        end: Pos,
+        dirs: &[(isize, isize)],
+        passable: F,
+    ) -> Option<Vec<Pos>> {
+        let (dist, from) = self.bfs_with_parents(&[start], dirs, passable);
+        dist[end.0][end.1]?;
+        let mut path = vec![end];
+        while let Some(prev) = from[path.last().unwrap().0][path.last().unwrap().1] {
+            path.push(prev);
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Flood fill: the component label of every cell accepted by `passable`,
+    /// labels numbered from 0 in row-major order of their first cell, and the
+    /// number of components
+    pub fn components<F: Fn(u8) -> bool>(
+        &self,
+        dirs: &[(isize, isize)],
+        passable: F,
+    ) -> (Vec<Vec<Option<usize>>>, usize) {
+        let mut label = vec![vec![None; self.cols]; self.rows];
+        let mut count = 0;
+        let mut stack = Vec::new();
+        for r in 0..self.rows {
+            for c in 0..self.cols {
+                if label[r][c].is_some() || !passable(self[(r, c)]) {
+                    continue;
+                }
+                label[r][c] = Some(count);
+                stack.push((r, c));
+                while let Some(pos) = stack.pop() {
+                    for (nr, nc) in self.neighbors(pos, dirs) {
+                        if label[nr][nc].is_none() && passable(self[(nr, nc)]) {
+                            label[nr][nc] = Some(count);
+                            stack.push((nr, nc));
+                        }
+                    }
+                }
+                count += 1;
+            }
+        }
+        (label, count)
+    }
+}
+
+impl Index<Pos> for Grid {
+    type Output = u8;
+
+    fn index(&self, (r, c): Pos) -> &u8 {
+        assert!(r < self.rows && c < self.cols, "Index out of range");
+        &self.cells[r * self.cols + c]
+    }
+}
+
+impl IndexMut<Pos> for Grid {
+    fn index_mut(&mut self, (r, c): Pos) -> &mut u8 {
+        assert!(r < self.rows && c < self.cols, "Index out of range");
+        &mut self.cells[r * self.cols + c]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAZE: &str = "
+        S.#.....
+        .##.###.
+        ...#..#E
+        .#......
+    ";
+
+    #[test]
+    fn test_parse_and_neighbors() {
+        let grid = Grid::parse(
+            "#..
+             .S.",
+        );
+        assert_eq!((grid.rows(), grid.cols()), (2, 3));
+        assert_eq!(grid.find(b'S'), Some((1, 1)));
+        assert_eq!(
+            grid.neighbors((0, 0), &DIRS4).collect::<Vec<_>>(),
+            vec![(0, 1), (1, 0)]
+        );
+        assert_eq!(grid.neighbors((1, 1), &DIRS8).count(), 5);
+        assert_eq!(grid.find_all(b'.'), vec![(0, 1), (0, 2), (1, 0), (1, 2)]);
+    }
+
+    #[test]
+    fn test_shortest_path() {
+        let grid = Grid::parse(MAZE);
+        let (start, end) = (grid.find(b'S').unwrap(), grid.find(b'E').unwrap());
+        let path = grid
+            .shortest_path(start, end, &DIRS4, |c| c != b'#')
+            .unwrap();
+        assert_eq!(path.len() - 1, 11);
+        assert_eq!((path[0], *path.last().unwrap()), (start, end));
+        for w in path.windows(2) {
+            assert_eq!(w[0].0.abs_diff(w[1].0) + w[0].1.abs_diff(w[1].1), 1);
+            assert_ne!(grid[w[1]], b'#');
+        }
+        // corners may be cut with 8 directions
+        let dist = grid.bfs(&[start], &DIRS8, |c| c != b'#');
+        assert_eq!(dist[end.0][end.1], Some(8));
+
+        // walling off the exit
+        let mut walled = grid.clone();
+        walled[(3, 7)] = b'#';
+        assert_eq!(
+            walled.shortest_path(start, end, &DIRS4, |c| c != b'#'),
+            None
+        );
+    }
+
+    #[test]
+    fn test_multi_source_and_components() {
+        let grid = Grid::parse(MAZE);
+        let dist = grid.bfs(&[(0, 0), (3, 7)], &DIRS4, |c| c != b'#');
+        assert_eq!(dist[0][0], Some(0));
+        assert_eq!(dist[2][2], Some(4));
+        assert_eq!(dist[2][7], Some(1));
+        assert_eq!(dist[0][2], None); // a wall
+
+        let (label, count) = grid.components(&DIRS4, |c| c == b'#');
+        assert_eq!(count, 4);
+        assert_eq!(label[0][2], label[1][1]);
+        assert_eq!(label[1][4], label[2][6]);
+        assert_eq!(label[0][0], None);
+        // diagonally touching walls merge
+        assert_eq!(grid.components(&DIRS8, |c| c == b'#').1, 2);
+        let (_, open) = grid.components(&DIRS4, |c| c != b'#');
+        assert_eq!(open, 1);
+    }
+}
@@ -0,0 +1,96 @@
+// ------- FAST HASHING -------
+// FxHash-style hasher: one rotate, xor and multiply per word. It is much
+// faster than the default SipHash but it is not resistant to crafted inputs,
+// so use it on judges where no one can build anti-hash tests against you.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasherDefault, Hasher};
+
+const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+#[derive(Clone, Copy, Default)]
+pub struct FxHasher {
+    hash: u64,
+}
+
+impl FxHasher {
+    fn add_to_hash(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(SEED);
+    }
+}
+
+impl Hasher for FxHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        let mut chunks = bytes.chunks_exact(8);
+        for chunk in &mut chunks {
+            self.add_to_hash(u64::from_le_bytes(chunk.try_into().unwrap()));
+        }
+        for &byte in chunks.remainder() {
+            self.add_to_hash(byte as u64);
+        }
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.add_to_hash(i);
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+pub type FxBuildHasher = BuildHasherDefault<FxHasher>;
+
+/// `HashMap` using `FxHasher`, create it with `FastHashMap::default()`
+pub type FastHashMap<K, V> = HashMap<K, V, FxBuildHasher>;
+
+/// `HashSet` using `FxHasher`, create it with `FastHashSet::default()`
+pub type FastHashSet<T> = HashSet<T, FxBuildHasher>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::hash::{BuildHasher, Hash};
+
+    fn hash_of<T: Hash>(value: &T) -> u64 {
+        FxBuildHasher::default().hash_one(value)
+    }
+
+    #[test]
+    fn test_hash_is_deterministic() {
+        assert_eq!(hash_of(&42u32), hash_of(&42u32));
+        assert_eq!(hash_of(&"contest"), hash_of(&"contest"));
+        assert_ne!(hash_of(&1u64), hash_of(&2u64));
+        assert_ne!(hash_of(&(1u32, 2u32)), hash_of(&(2u32, 1u32)));
+    }
+
+    #[test]
+    fn test_containers() {
+        let mut map: FastHashMap<u64, usize> = FastHashMap::default();
+        for i in 0..10_000u64 {
+            *map.entry(i % 1000).or_insert(0) += 1;
+        }
+        assert_eq!(map.len(), 1000);
+        assert!(map.values().all(|&c| c == 10));
+
+        let set: FastHashSet<String> = ["a", "b", "a"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(set.len(), 2);
+        assert!(set.contains("b"));
+    }
+}
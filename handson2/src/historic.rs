@@ -0,0 +1,245 @@
+// ------- HISTORIC MAXIMUM SEGMENT TREE -------
+// Range add and range assign on i64 values, answering both the current range
+// maximum and the historic one, the largest value any position of the range
+// has ever held. Every lazy tag remembers, besides the pending add and assign,
+// the largest add prefix and assigned value seen since it was pushed, so that
+// children learn the peaks they missed. Ranges are 0-based and inclusive.
+
+use crate::stats;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Tag {
+    add: i64,         // Pending add, applied before `set`
+    max_add: i64,     // Largest partial sum of the adds, at least 0
+    set: Option<i64>, // Pending assignment, including the adds that followed it
+    max_set: i64,     // Largest value reached after the first assignment
+}
+
+impl Tag {
+    const NONE: Tag = Tag {
+        add: 0,
+        max_add: 0,
+        set: None,
+        max_set: i64::MIN,
+    };
+
+    fn add(value: i64) -> Tag {
+        Tag {
+            add: value,
+            max_add: value.max(0),
+            ..Tag::NONE
+        }
+    }
+
+    fn assign(value: i64) -> Tag {
+        Tag {
+            set: Some(value),
+            max_set: value,
+            ..Tag::NONE
+        }
+    }
+
+    // The tag doing `self` and then `next`
+    fn then(mut self, next: Tag) -> Tag {
+        match self.set {
+            Some(set) => {
+                self.max_set = self.max_set.max(set + next.max_add);
+                self.set = Some(set + next.add);
+            }
+            None => {
+                self.max_add = self.max_add.max(self.add + next.max_add);
+                self.add += next.add;
+            }
+        }
+        if let Some(set) = next.set {
+            self.max_set = match self.set {
+                Some(_) => self.max_set.max(next.max_set),
+                None => next.max_set,
+            };
+            self.set = Some(set);
+        }
+        self
+    }
+}
+
+pub struct HistoricMaxTree {
+    n: usize,
+    max: Vec<i64>,      // Current maximum of each node
+    historic: Vec<i64>, // Historic maximum of each node
+    lazy: Vec<Tag>,     // Tag still to be pushed to the children
+}
+
+impl HistoricMaxTree {
+    pub fn new(values: &[i64]) -> Self {
+        let n = values.len();
+        let mut tree = HistoricMaxTree {
+            n,
+            max: vec![i64::MIN; 4 * n.max(1)],
+            historic: vec![i64::MIN; 4 * n.max(1)],
+            lazy: vec![Tag::NONE; 4 * n.max(1)],
+        };
+        if n > 0 {
+            tree.build(values, 1, 0, n - 1);
+        }
+        tree
+    }
+
+    fn build(&mut self, values: &[i64], node: usize, start: usize, end: usize) {
+        if start == end {
+            self.max[node] = values[start];
+        } else {
+            let mid = (start + end) / 2;
+            self.build(values, 2 * node, start, mid);
+            self.build(values, 2 * node + 1, mid + 1, end);
+            self.max[node] = self.max[2 * node].max(self.max[2 * node + 1]);
+        }
+        self.historic[node] = self.max[node];
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Adds `value` to every position in `[l, r]`
+    pub fn range_add(&mut self, l: usize, r: usize, value: i64) {
+        self.check_range(l, r);
+        self.update(1, 0, self.n - 1, l, r, Tag::add(value));
+    }
+
+    /// Sets every position in `[l, r]` to `value`
+    pub fn range_assign(&mut self, l: usize, r: usize, value: i64) {
+        self.check_range(l, r);
+        self.update(1, 0, self.n - 1, l, r, Tag::assign(value));
+    }
+
+    /// Current maximum in `[l, r]`
+    pub fn range_max(&mut self, l: usize, r: usize) -> i64 {
+        self.check_range(l, r);
+        self.query(1, 0, self.n - 1, l, r).0
+    }
+
+    /// Largest value ever held by a position in `[l, r]`
+    pub fn range_historic_max(&mut self, l: usize, r: usize) -> i64 {
+        self.check_range(l, r);
+        self.query(1, 0, self.n - 1, l, r).1
+    }
+
+    fn check_range(&self, l: usize, r: usize) {
+        assert!(l <= r && r < self.n, "Invalid range");
+    }
+
+    // Apply `tag` to the whole subtree of `node`
+    fn apply(&mut self, node: usize, tag: Tag) {
+        let historic = &mut self.historic[node];
+        *historic = (*historic).max(self.max[node] + tag.max_add);
+        self.max[node] += tag.add;
+        if let Some(set) = tag.set {
+            *historic = (*historic).max(tag.max_set);
+            self.max[node] = set;
+        }
+        self.lazy[node] = self.lazy[node].then(tag);
+    }
+
+    fn push(&mut self, node: usize) {
+        let tag = std::mem::replace(&mut self.lazy[node], Tag::NONE);
+        if tag != Tag::NONE {
+            stats::lazy_push();
+            self.apply(2 * node, tag);
+            self.apply(2 * node + 1, tag);
+        }
+    }
+
+    fn update(&mut self, node: usize, start: usize, end: usize, l: usize, r: usize, tag: Tag) {
+        stats::visit();
+        if r < start || end < l {
+            return;
+        }
+        if l <= start && end <= r {
+            self.apply(node, tag);
+            return;
+        }
+        self.push(node);
+        let mid = (start + end) / 2;
+        self.update(2 * node, start, mid, l, r, tag);
+        self.update(2 * node + 1, mid + 1, end, l, r, tag);
+        self.max[node] = self.max[2 * node].max(self.max[2 * node + 1]);
+        self.historic[node] = self.historic[2 * node].max(self.historic[2 * node + 1]);
+    }
+
+    // (current maximum, historic maximum) of [l, r] within [start, end]
+    fn query(&mut self, node: usize, start: usize, end: usize, l: usize, r: usize) -> (i64, i64) {
+        stats::visit();
+        if r < start || end < l {
+            return (i64::MIN, i64::MIN);
+        }
+        if l <= start && end <= r {
+            return (self.max[node], self.historic[node]);
+        }
+        self.push(node);
+        let mid = (start + end) / 2;
+        let left = self.query(2 * node, start, mid, l, r);
+        let right = self.query(2 * node + 1, mid + 1, end, l, r);
+        (left.0.max(right.0), left.1.max(right.1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_example() {
+        let mut tree = HistoricMaxTree::new(&[1, 5, 2]);
+        tree.range_add(0, 2, 3); // 4 8 5
+        tree.range_assign(1, 2, 0); // 4 0 0
+        tree.range_add(0, 1, -10); // -6 -10 0
+        assert_eq!(tree.range_max(0, 1), -6);
+        assert_eq!(tree.range_historic_max(0, 1), 8);
+        assert_eq!(tree.range_historic_max(2, 2), 5);
+        tree.range_assign(2, 2, 7);
+        assert_eq!(tree.range_historic_max(2, 2), 7);
+    }
+
+    #[test]
+    fn test_random_against_naive() {
+        let mut rng = Rng::new(54);
+        for _ in 0..20 {
+            let n = rng.gen_range(1, 40) as usize;
+            let mut values: Vec<i64> = (0..n).map(|_| rng.gen_range_i64(-20, 20)).collect();
+            let mut historic = values.clone();
+            let mut tree = HistoricMaxTree::new(&values);
+            for _ in 0..300 {
+                let a = rng.gen_range(0, n as u64 - 1) as usize;
+                let b = rng.gen_range(0, n as u64 - 1) as usize;
+                let (l, r) = (a.min(b), a.max(b));
+                let v = rng.gen_range_i64(-20, 20);
+                match rng.gen_range(0, 3) {
+                    0 => {
+                        tree.range_add(l, r, v);
+                        values[l..=r].iter_mut().for_each(|x| *x += v);
+                    }
+                    1 => {
+                        tree.range_assign(l, r, v);
+                        values[l..=r].iter_mut().for_each(|x| *x = v);
+                    }
+                    2 => {
+                        let max = values[l..=r].iter().max().unwrap();
+                        assert_eq!(tree.range_max(l, r), *max);
+                    }
+                    _ => {
+                        let max = historic[l..=r].iter().max().unwrap();
+                        assert_eq!(tree.range_historic_max(l, r), *max);
+                    }
+                }
+                for (h, &x) in historic.iter_mut().zip(&values) {
+                    *h = (*h).max(x);
+                }
+            }
+        }
+    }
+}
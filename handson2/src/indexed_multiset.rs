@@ -0,0 +1,177 @@
+// ------- INDEXED MULTISET -------
+// Order-statistic multiset over a universe of values known in advance: a
+// Fenwick tree counts the occurrences of each compressed value, so insert,
+// erase, k-th smallest and rank all take O(log n). Implements OrderedSetOps,
+// like the AVL multiset which needs no universe.
+
+use crate::avl::OrderedSetOps;
+use crate::fenwick::FenwickTree;
+
+pub struct IndexedMultiset<T> {
+    universe: Vec<T>,         // Sorted distinct values that can be stored
+    counts: FenwickTree<i64>, // Occurrences of each value of the universe
+    len: usize,
+}
+
+impl<T: Ord + Clone> IndexedMultiset<T> {
+    /// An empty multiset able to store any of the values in `universe`
+    pub fn new(universe: &[T]) -> Self {
+        let mut universe = universe.to_vec();
+        universe.sort_unstable();
+        universe.dedup();
+        let counts = FenwickTree::new(universe.len());
+        IndexedMultiset {
+            universe,
+            counts,
+            len: 0,
+        }
+    }
+
+    /// Number of stored elements, counting duplicates
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn index_of(&self, x: &T) -> Option<usize> {
+        self.universe.binary_search(x).ok()
+    }
+
+    /// Adds one occurrence of `x`
+    ///
+    /// # Panics
+    /// Panics if `x` is not part of the universe.
+    pub fn insert(&mut self, x: T) {
+        let idx = self.index_of(&x).expect("Value outside the universe");
+        self.counts.add(idx, 1);
+        self.len += 1;
+    }
+
+    /// Removes one occurrence of `x`, returns false if `x` was not present
+    pub fn erase_one(&mut self, x: &T) -> bool {
+        match self.index_of(x) {
+            Some(idx) if self.counts.sum(idx, idx) > 0 => {
+                self.counts.add(idx, -1);
+                self.len -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Number of occurrences of `x`
+    pub fn count(&self, x: &T) -> usize {
+        self.index_of(x)
+            .map_or(0, |idx| self.counts.sum(idx, idx) as usize)
+    }
+
+    pub fn contains(&self, x: &T) -> bool {
+        self.count(x) > 0
+    }
+
+    /// Number of elements strictly smaller than `x`
+    pub fn count_less(&self, x: &T) -> usize {
+        let idx = self.universe.partition_point(|v| v < x);
+        self.counts.prefix(idx) as usize
+    }
+
+    /// The k-th smallest element, k starting from 0
+    pub fn kth(&self, k: usize) -> Option<&T> {
+        if k >= self.len {
+            return None;
+        }
+        // the first value whose prefix count exceeds k
+        let idx = self.counts.max_prefix_within(k as i64);
+        Some(&self.universe[idx])
+    }
+
+    pub fn min(&self) -> Option<&T> {
+        self.kth(0)
+    }
+
+    pub fn max(&self) -> Option<&T> {
+        self.len.checked_sub(1).and_then(|k| self.kth(k))
+    }
+}
+
+impl<T: Ord + Clone> OrderedSetOps<T> for IndexedMultiset<T> {
+    fn len(&self) -> usize {
+        IndexedMultiset::len(self)
+    }
+
+    fn insert(&mut self, x: T) {
+        IndexedMultiset::insert(self, x)
+    }
+
+    fn erase_one(&mut self, x: &T) -> bool {
+        IndexedMultiset::erase_one(self, x)
+    }
+
+    fn count(&self, x: &T) -> usize {
+        IndexedMultiset::count(self, x)
+    }
+
+    fn count_less(&self, x: &T) -> usize {
+        IndexedMultiset::count_less(self, x)
+    }
+
+    fn kth(&self, k: usize) -> Option<&T> {
+        IndexedMultiset::kth(self, k)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_basic_operations() {
+        let mut set = IndexedMultiset::new(&[10, 20, 30, 40]);
+        set.insert(30);
+        set.insert(10);
+        set.insert(30);
+        assert_eq!(set.len(), 3);
+        assert_eq!(set.kth(0), Some(&10));
+        assert_eq!(set.kth(1), Some(&30));
+        assert_eq!(set.kth(2), Some(&30));
+        assert_eq!(set.kth(3), None);
+        assert_eq!(set.count_less(&30), 1);
+        assert_eq!(set.count_less(&35), 3);
+        assert_eq!(set.count(&30), 2);
+        assert!(set.erase_one(&30));
+        assert!(!set.erase_one(&20));
+        assert!(!set.erase_one(&25));
+        assert_eq!(set.max(), Some(&30));
+        assert_eq!(set.min(), Some(&10));
+    }
+
+    #[test]
+    fn test_against_sorted_vec() {
+        let mut rng = Rng::new(21);
+        let universe: Vec<i32> = (-20..20).collect();
+        let mut set = IndexedMultiset::new(&universe);
+        let mut sorted: Vec<i32> = Vec::new();
+        for _ in 0..2000 {
+            let x = rng.gen_range(0, 39) as i32 - 20;
+            if rng.gen_range(0, 2) == 0 {
+                let pos = sorted.iter().position(|&v| v == x);
+                assert_eq!(set.erase_one(&x), pos.is_some());
+                if let Some(pos) = pos {
+                    sorted.remove(pos);
+                }
+            } else {
+                set.insert(x);
+                let pos = sorted.partition_point(|&v| v < x);
+                sorted.insert(pos, x);
+            }
+            assert_eq!(set.len(), sorted.len());
+            assert_eq!(set.count_less(&x), sorted.partition_point(|&v| v < x));
+            let k = rng.gen_range(0, sorted.len() as u64) as usize;
+            assert_eq!(set.kth(k), sorted.get(k));
+        }
+    }
+}
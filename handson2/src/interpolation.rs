@@ -0,0 +1,123 @@
+// ------- LAGRANGE INTERPOLATION -------
+// The polynomial of degree < n through n points, evaluated at x modulo a
+// prime: O(n^2) for arbitrary distinct abscissas, and O(n) when they are
+// 0, 1, ..., n - 1, as the Lagrange basis then only needs prefix and suffix
+// products of (x - i) and factorials. Sums of polynomial sequences, such as
+// 1^k + ... + n^k of degree k + 1, are evaluated that way from few terms.
+
+use crate::modint::ModInt;
+
+/// Value at `x` of the polynomial through the `(x_i, y_i)` points, whose
+/// abscissas must be distinct
+pub fn interpolate<const M: u32>(points: &[(ModInt<M>, ModInt<M>)], x: ModInt<M>) -> ModInt<M> {
+    let mut result = ModInt::new(0);
+    for (i, &(xi, yi)) in points.iter().enumerate() {
+        let (mut num, mut den) = (ModInt::new(1), ModInt::new(1));
+        for (j, &(xj, _)) in points.iter().enumerate() {
+            if i != j {
+                num *= x - xj;
+                den *= xi - xj;
+            }
+        }
+        result += yi * num / den;
+    }
+    result
+}
+
+/// Value at `x` of the polynomial through `(i, values[i])`, in O(n), for
+/// fewer than `M` values
+pub fn interpolate_consecutive<const M: u32>(values: &[ModInt<M>], x: u64) -> ModInt<M> {
+    let n = values.len();
+    if (x as u128) < n as u128 {
+        return values[x as usize];
+    }
+    let x = ModInt::<M>::new(x);
+    // prefix[i] = (x - 0) ... (x - (i - 1)), suffix[i] = (x - i) ... (x - (n - 1))
+    let mut prefix = vec![ModInt::new(1); n + 1];
+    let mut suffix = vec![ModInt::new(1); n + 1];
+    for i in 0..n {
+        prefix[i + 1] = prefix[i] * (x - ModInt::from(i));
+    }
+    for i in (0..n).rev() {
+        suffix[i] = suffix[i + 1] * (x - ModInt::from(i));
+    }
+    let mut inv_factorial = vec![ModInt::new(1); n];
+    let factorial: ModInt<M> = (1..n).map(ModInt::from).product();
+    if n > 0 {
+        inv_factorial[n - 1] = factorial.inv();
+    }
+    for i in (1..n).rev() {
+        inv_factorial[i - 1] = inv_factorial[i] * ModInt::from(i);
+    }
+    // the denominator of the i-th basis polynomial is i! (n - 1 - i)! (-1)^(n-1-i)
+    let mut result = ModInt::new(0);
+    for (i, &y) in values.iter().enumerate() {
+        let term = y * prefix[i] * suffix[i + 1] * inv_factorial[i] * inv_factorial[n - 1 - i];
+        if (n - 1 - i) % 2 == 1 {
+            result -= term;
+        } else {
+            result += term;
+        }
+    }
+    result
+}
+
+/// 1^k + 2^k + ... + n^k, in O(k log k)
+pub fn power_sum<const M: u32>(n: u64, k: u64) -> ModInt<M> {
+    // a polynomial of degree k + 1 in n, known at n = 0, ..., k + 1
+    let mut values = Vec::with_capacity(k as usize + 2);
+    values.push(ModInt::new(0));
+    for i in 1..=k + 1 {
+        let last = *values.last().unwrap();
+        values.push(last + ModInt::new(i).pow(k));
+    }
+    interpolate_consecutive(&values, n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modint::Mod998244353;
+    use crate::rng::Rng;
+
+    type Mint = Mod998244353;
+
+    #[test]
+    fn test_against_evaluation() {
+        let mut rng = Rng::new(75);
+        for degree in 0..12 {
+            let coeffs: Vec<Mint> = (0..=degree).map(|_| Mint::new(rng.next_u64())).collect();
+            let eval = |x: Mint| {
+                coeffs
+                    .iter()
+                    .rev()
+                    .fold(Mint::new(0), |acc, &c| acc * x + c)
+            };
+            let points: Vec<(Mint, Mint)> = (0..=degree)
+                .map(|_| Mint::new(rng.next_u64()))
+                .map(|x| (x, eval(x)))
+                .collect();
+            let values: Vec<Mint> = (0..=degree as u64).map(|i| eval(Mint::new(i))).collect();
+            for _ in 0..10 {
+                let x = rng.next_u64();
+                assert_eq!(interpolate(&points, Mint::new(x)), eval(Mint::new(x)));
+                assert_eq!(interpolate_consecutive(&values, x), eval(Mint::new(x)));
+            }
+            assert_eq!(interpolate_consecutive(&values, 0), values[0]);
+        }
+    }
+
+    #[test]
+    fn test_power_sums() {
+        assert_eq!(power_sum::<998_244_353>(10, 0), Mint::new(10));
+        assert_eq!(power_sum::<998_244_353>(100, 1), Mint::new(5050));
+        assert_eq!(power_sum::<998_244_353>(10, 2), Mint::new(385));
+        assert_eq!(power_sum::<998_244_353>(0, 3), Mint::new(0));
+        // (n (n + 1) / 2)^2 for n = 10^12
+        let n = 1_000_000_000_000u64;
+        let triangle = Mint::new(n) * Mint::new(n + 1) / Mint::new(2);
+        assert_eq!(power_sum::<998_244_353>(n, 3), triangle * triangle);
+        let brute: Mint = (1..=1000u64).map(|i| Mint::new(i).pow(7)).sum();
+        assert_eq!(power_sum::<998_244_353>(1000, 7), brute);
+    }
+}
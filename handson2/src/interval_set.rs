@@ -0,0 +1,226 @@
+// ------- INTERVAL SET -------
+// Ordered map of disjoint inclusive intervals [l, r], each carrying a value
+// (use `()` for a plain set). Range assignment collapses every piece inside the
+// range into a single interval, as in the Chtholly tree, so under random range
+// assignments the number of stored intervals stays small. Any i64 can be an
+// endpoint: the point after r is only looked at when r < i64::MAX.
+
+use std::collections::BTreeMap;
+
+pub struct IntervalSet<V> {
+    intervals: BTreeMap<i64, (i64, V)>, // start -> (end, value)
+}
+
+impl<V: Clone + PartialEq> Default for IntervalSet<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: Clone + PartialEq> IntervalSet<V> {
+    pub fn new() -> Self {
+        IntervalSet {
+            intervals: BTreeMap::new(),
+        }
+    }
+
+    /// Number of stored intervals
+    pub fn len(&self) -> usize {
+        self.intervals.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Returns the interval containing `point` with its value, if any
+    pub fn covering(&self, point: i64) -> Option<(i64, i64, &V)> {
+        let (&start, (end, value)) = self.intervals.range(..=point).next_back()?;
+        (*end >= point).then_some((start, *end, value))
+    }
+
+    /// Iterates over the intervals in increasing order as `(l, r, value)`
+    pub fn iter(&self) -> impl Iterator<Item = (i64, i64, &V)> {
+        self.intervals.iter().map(|(&l, (r, v))| (l, *r, v))
+    }
+
+    /// Makes sure that no interval crosses the boundary between `pos - 1` and `pos`
+    pub fn split(&mut self, pos: i64) {
+        // an interval starting before pos has start < pos, so pos - 1 is valid
+        if let Some((&start, &(end, _))) = self.intervals.range(..pos).next_back() {
+            if end >= pos {
+                let value = self.intervals.get(&start).unwrap().1.clone();
+                self.intervals.insert(start, (pos - 1, value.clone()));
+                self.intervals.insert(pos, (end, value));
+            }
+        }
+    }
+
+    // split(r + 1), nothing to split past i64::MAX
+    fn split_after(&mut self, r: i64) {
+        if let Some(next) = r.checked_add(1) {
+            self.split(next);
+        }
+    }
+
+    /// Sets `value` on every point of `[l, r]`, replacing all the intervals
+    /// inside the range with one and merging it with equal neighbours
+    pub fn assign(&mut self, l: i64, r: i64, value: V) {
+        assert!(l <= r, "Invalid interval");
+        self.remove(l, r);
+
+        let (mut start, mut end) = (l, r);
+        if let Some((&prev_start, (prev_end, prev_value))) = self.intervals.range(..l).next_back() {
+            if *prev_end + 1 == l && *prev_value == value {
+                start = prev_start;
+            }
+        }
+        if let Some(next) = r.checked_add(1) {
+            if let Some((next_end, next_value)) = self.intervals.get(&next) {
+                if *next_value == value {
+                    end = *next_end;
+                    self.intervals.remove(&next);
+                }
+            }
+        }
+        self.intervals.insert(start, (end, value));
+    }
+
+    /// Removes every point of `[l, r]` from the set
+    pub fn remove(&mut self, l: i64, r: i64) {
+        assert!(l <= r, "Invalid interval");
+        self.split(l);
+        self.split_after(r);
+        let inside: Vec<i64> = self.intervals.range(l..=r).map(|(&s, _)| s).collect();
+        for start in inside {
+            self.intervals.remove(&start);
+        }
+    }
+
+    /// Calls `f(l, r, value)` on every piece of an interval inside `[l, r]`,
+    /// splitting the intervals crossing the range borders first
+    pub fn for_each_in<F: FnMut(i64, i64, &mut V)>(&mut self, l: i64, r: i64, mut f: F) {
+        assert!(l <= r, "Invalid interval");
+        self.split(l);
+        self.split_after(r);
+        for (&start, (end, value)) in self.intervals.range_mut(l..=r) {
+            f(start, *end, value);
+        }
+    }
+}
+
+impl IntervalSet<()> {
+    /// Adds every point of `[l, r]` to the set, merging touching intervals
+    pub fn insert(&mut self, l: i64, r: i64) {
+        self.assign(l, r, ());
+    }
+
+    /// Returns true if `point` belongs to the set
+    pub fn contains(&self, point: i64) -> bool {
+        self.covering(point).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_plain_set() {
+        let mut set = IntervalSet::new();
+        set.insert(1, 3);
+        set.insert(7, 9);
+        set.insert(4, 5);
+        assert_eq!(
+            set.iter().map(|(l, r, _)| (l, r)).collect::<Vec<_>>(),
+            vec![(1, 5), (7, 9)]
+        );
+        set.insert(6, 6);
+        assert_eq!(set.len(), 1);
+        set.remove(3, 4);
+        assert_eq!(
+            set.iter().map(|(l, r, _)| (l, r)).collect::<Vec<_>>(),
+            vec![(1, 2), (5, 9)]
+        );
+        assert!(set.contains(2) && !set.contains(3) && set.contains(9));
+        assert_eq!(set.covering(6).map(|(l, r, _)| (l, r)), Some((5, 9)));
+        assert_eq!(set.covering(10), None);
+    }
+
+    #[test]
+    fn test_extreme_endpoints() {
+        let (min, max) = (i64::MIN, i64::MAX);
+        let mut set = IntervalSet::new();
+        set.insert(min, max);
+        assert_eq!(set.covering(0).map(|(l, r, _)| (l, r)), Some((min, max)));
+        set.remove(max, max);
+        set.remove(min, min);
+        assert!(!set.contains(min) && !set.contains(max));
+        assert_eq!(
+            set.covering(0).map(|(l, r, _)| (l, r)),
+            Some((min + 1, max - 1))
+        );
+        set.insert(max, max);
+        set.insert(min, min);
+        assert_eq!(set.len(), 1);
+
+        let mut values = IntervalSet::new();
+        values.assign(min, max, 0);
+        values.assign(max - 1, max, 1);
+        values.assign(min, min + 1, 1);
+        values.for_each_in(max, max, |_, _, v| *v += 1);
+        values.for_each_in(min, min, |_, _, v| *v += 1);
+        let pieces: Vec<_> = values.iter().map(|(l, r, &v)| (l, r, v)).collect();
+        assert_eq!(
+            pieces,
+            vec![
+                (min, min, 2),
+                (min + 1, min + 1, 1),
+                (min + 2, max - 2, 0),
+                (max - 1, max - 1, 1),
+                (max, max, 2)
+            ]
+        );
+    }
+
+    #[test]
+    // Chtholly operations compared with a plain array
+    fn test_assign_and_add_against_array() {
+        let mut rng = Rng::new(8);
+        let n = 60;
+        let mut arr: Vec<Option<i64>> = vec![None; n];
+        let mut set = IntervalSet::new();
+
+        for _ in 0..3000 {
+            let a = rng.gen_range(0, n as u64 - 1) as i64;
+            let b = rng.gen_range(0, n as u64 - 1) as i64;
+            let (l, r) = (a.min(b), a.max(b));
+            let value = rng.gen_range(0, 3) as i64;
+            match rng.gen_range(0, 2) {
+                0 => {
+                    set.assign(l, r, value);
+                    (l..=r).for_each(|i| arr[i as usize] = Some(value));
+                }
+                1 => {
+                    set.remove(l, r);
+                    (l..=r).for_each(|i| arr[i as usize] = None);
+                }
+                _ => {
+                    set.for_each_in(l, r, |_, _, v| *v += value);
+                    (l..=r).for_each(|i| {
+                        if let Some(x) = arr[i as usize].as_mut() {
+                            *x += value
+                        }
+                    });
+                }
+            }
+            for (i, expected) in arr.iter().enumerate() {
+                assert_eq!(set.covering(i as i64).map(|(_, _, &v)| v), *expected);
+            }
+            // intervals are disjoint and sorted
+            let pieces: Vec<_> = set.iter().map(|(l, r, _)| (l, r)).collect();
+            assert!(pieces.windows(2).all(|w| w[0].1 < w[1].0));
+        }
+    }
+}
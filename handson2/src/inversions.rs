@@ -0,0 +1,148 @@
+// ------- INVERSIONS -------
+// Counting pairs i < j with a[i] > a[j] by a Fenwick tree over the ranks,
+// the adjacent swaps needed to turn one sequence into another, and the
+// number of permutations with a given number of inversions.
+
+use crate::fenwick::FenwickTree;
+use crate::modint::ModInt;
+
+/// Number of pairs i < j with `values[i] > values[j]`, O(n log n)
+pub fn count_inversions<T: Ord>(values: &[T]) -> u64 {
+    // rank of each value among the distinct ones
+    let mut sorted: Vec<&T> = values.iter().collect();
+    sorted.sort_unstable();
+    sorted.dedup();
+    let mut seen = FenwickTree::new(sorted.len());
+    let mut inversions = 0;
+    for (i, v) in values.iter().enumerate() {
+        let rank = sorted.binary_search(&v).unwrap();
+        // earlier values that are <= v are not inversions
+        inversions += i as u64 - seen.prefix(rank + 1);
+        seen.add(rank, 1u64);
+    }
+    inversions
+}
+
+/// Fewest swaps of adjacent elements sorting `values`, which is the number
+/// of inversions
+pub fn min_adjacent_swaps_to_sort<T: Ord>(values: &[T]) -> u64 {
+    count_inversions(values)
+}
+
+/// Fewest swaps of adjacent elements turning `from` into `to`, None if they
+/// are not permutations of each other. Equal values keep their relative
+/// order, which is always optimal.
+pub fn min_adjacent_swaps<T: Ord>(from: &[T], to: &[T]) -> Option<u64> {
+    if from.len() != to.len() {
+        return None;
+    }
+    let sorted_positions = |s: &[T]| {
+        let mut order: Vec<usize> = (0..s.len()).collect();
+        order.sort_by(|&i, &j| s[i].cmp(&s[j])); // stable, so ties by index
+        order
+    };
+    let (a, b) = (sorted_positions(from), sorted_positions(to));
+    let mut target = vec![0; from.len()];
+    for (&i, &j) in a.iter().zip(&b) {
+        if from[i] != to[j] {
+            return None;
+        }
+        target[i] = j; // the k-th copy of a value goes to the k-th slot
+    }
+    Some(count_inversions(&target))
+}
+
+/// `result[k]` = number of permutations of n elements with exactly k
+/// inversions, for k up to `max_k`, modulo M. O(n max_k).
+pub fn permutations_by_inversions<const M: u32>(n: usize, max_k: usize) -> Vec<ModInt<M>> {
+    let mut dp = vec![ModInt::<M>::new(0); max_k + 1];
+    dp[0] = ModInt::new(1);
+    // inserting the i-th element adds between 0 and i - 1 inversions
+    for i in 2..=n {
+        let mut prefix = ModInt::<M>::new(0);
+        let mut next = vec![ModInt::new(0); max_k + 1];
+        for k in 0..=max_k {
+            prefix += dp[k];
+            if k >= i {
+                prefix -= dp[k - i];
+            }
+            next[k] = prefix;
+        }
+        dp = next;
+    }
+    dp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modint::Mod998244353;
+    use crate::rng::Rng;
+
+    fn naive_inversions<T: Ord>(values: &[T]) -> u64 {
+        let mut count = 0;
+        for (i, a) in values.iter().enumerate() {
+            count += values[i + 1..].iter().filter(|&b| a > b).count() as u64;
+        }
+        count
+    }
+
+    #[test]
+    fn test_against_naive() {
+        let mut rng = Rng::new(45);
+        for _ in 0..200 {
+            let n = rng.gen_range(0, 40) as usize;
+            let values: Vec<u64> = (0..n).map(|_| rng.gen_range(0, 10)).collect();
+            assert_eq!(count_inversions(&values), naive_inversions(&values));
+
+            // bubble sort performs exactly the minimum number of swaps
+            let mut shuffled = values.clone();
+            rng.shuffle(&mut shuffled);
+            let mut bubbled = shuffled.clone();
+            let mut swaps = 0;
+            for end in (1..n).rev() {
+                for j in 0..end {
+                    if bubbled[j] > bubbled[j + 1] {
+                        bubbled.swap(j, j + 1);
+                        swaps += 1;
+                    }
+                }
+            }
+            assert_eq!(min_adjacent_swaps_to_sort(&shuffled), swaps);
+            assert_eq!(min_adjacent_swaps(&shuffled, &bubbled), Some(swaps));
+            assert_eq!(
+                min_adjacent_swaps(&values, &shuffled),
+                min_adjacent_swaps(&shuffled, &values)
+            );
+        }
+        assert_eq!(min_adjacent_swaps(&[1, 2], &[2, 2]), None);
+        assert_eq!(min_adjacent_swaps(&[1, 2, 1], &[1, 1, 2]), Some(1));
+    }
+
+    #[test]
+    fn test_permutations_by_inversions() {
+        // brute force over the permutations of 6 elements
+        let n = 6;
+        let mut counts = vec![0u32; n * (n - 1) / 2 + 1];
+        let mut perm: Vec<usize> = (0..n).collect();
+        loop {
+            counts[naive_inversions(&perm) as usize] += 1;
+            // next permutation in lexicographic order
+            let Some(i) = (0..n - 1).rev().find(|&i| perm[i] < perm[i + 1]) else {
+                break;
+            };
+            let j = (i + 1..n).rev().find(|&j| perm[j] > perm[i]).unwrap();
+            perm.swap(i, j);
+            perm[i + 1..].reverse();
+        }
+        let dp = permutations_by_inversions::<998_244_353>(n, counts.len() + 2);
+        for (k, &c) in counts.iter().enumerate() {
+            assert_eq!(dp[k].value(), c);
+        }
+        assert_eq!(dp[counts.len()], Mod998244353::new(0));
+        assert_eq!(
+            permutations_by_inversions::<998_244_353>(0, 1)[0].value(),
+            1
+        );
+    }
+}
@@ -0,0 +1,162 @@
+// ------- INPUT PARSING -------
+// Token scanner for judge style input: values are read one whitespace
+// separated token at a time regardless of line breaks, as typed tuples or as
+// the rest of a line. The `try_` variants return None at end of input instead
+// of panicking, for inputs terminated by EOF rather than by a count.
+// Use `Scanner::new(std::io::stdin().lock())` to read the standard input.
+
+use std::io::BufRead;
+use std::str::FromStr;
+
+pub struct Scanner<R> {
+    reader: R,
+    tokens: Vec<String>, // Unread tokens of the current line, reversed
+}
+
+impl<R: BufRead> Scanner<R> {
+    pub fn new(reader: R) -> Self {
+        Scanner {
+            reader,
+            tokens: Vec::new(),
+        }
+    }
+
+    // Reads lines until one has a token, false at end of input
+    fn fill(&mut self) -> bool {
+        while self.tokens.is_empty() {
+            let mut line = String::new();
+            if self
+                .reader
+                .read_line(&mut line)
+                .expect("Failed to read input")
+                == 0
+            {
+                return false;
+            }
+            self.tokens = line.split_whitespace().rev().map(String::from).collect();
+        }
+        true
+    }
+
+    /// The next token, None at end of input
+    pub fn token(&mut self) -> Option<String> {
+        if !self.fill() {
+            return None;
+        }
+        self.tokens.pop()
+    }
+
+    /// The next token parsed as `T`, None at end of input
+    pub fn try_read<T: FromStr>(&mut self) -> Option<T> {
+        self.token().map(|token| parse(&token))
+    }
+
+    pub fn read<T: FromStr>(&mut self) -> T {
+        self.try_read().expect("Unexpected end of input")
+    }
+
+    /// The next `n` tokens parsed as `T`
+    pub fn read_vec<T: FromStr>(&mut self, n: usize) -> Vec<T> {
+        (0..n).map(|_| self.read()).collect()
+    }
+
+    /// The next tokens parsed as a tuple, e.g. `read_tuple::<(usize, u32)>()`.
+    /// None if the input ends before the first element; ending after it
+    /// panics.
+    pub fn try_read_tuple<T: FromTokens>(&mut self) -> Option<T> {
+        T::from_tokens(self)
+    }
+
+    pub fn read_tuple<T: FromTokens>(&mut self) -> T {
+        self.try_read_tuple().expect("Unexpected end of input")
+    }
+
+    /// The unread tokens of the current line parsed as `T`, or those of the
+    /// next non-blank line if the current one is used up. None at end of
+    /// input.
+    pub fn try_read_line<T: FromStr>(&mut self) -> Option<Vec<T>> {
+        if !self.fill() {
+            return None;
+        }
+        let tokens = std::mem::take(&mut self.tokens);
+        Some(tokens.iter().rev().map(|token| parse(token)).collect())
+    }
+
+    pub fn read_line<T: FromStr>(&mut self) -> Vec<T> {
+        self.try_read_line().expect("Unexpected end of input")
+    }
+}
+
+// Parses `token`, panicking with the token on failure
+fn parse<T: FromStr>(token: &str) -> T {
+    token
+        .parse()
+        .unwrap_or_else(|_| panic!("Failed to parse token {:?}", token))
+}
+
+/// Values made of a fixed number of consecutive tokens
+pub trait FromTokens: Sized {
+    /// None if the input ends before the first token
+    fn from_tokens<R: BufRead>(scanner: &mut Scanner<R>) -> Option<Self>;
+}
+
+macro_rules! impl_from_tokens_for_tuples {
+    ($(($first:ident $(, $rest:ident)*)),*) => {$(
+        impl<$first: FromStr $(, $rest: FromStr)*> FromTokens for ($first, $($rest,)*) {
+            fn from_tokens<R: BufRead>(scanner: &mut Scanner<R>) -> Option<Self> {
+                Some((scanner.try_read::<$first>()?, $(scanner.read::<$rest>(),)*))
+            }
+        }
+    )*};
+}
+
+impl_from_tokens_for_tuples!(
+    (A),
+    (A, B),
+    (A, B, C),
+    (A, B, C, D),
+    (A, B, C, D, E),
+    (A, B, C, D, E, F)
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_across_lines() {
+        let input = "3 2\n\n  10 20\n30\n1 2 x\n";
+        let mut scanner = Scanner::new(input.as_bytes());
+        let (n, m) = scanner.read_tuple::<(usize, usize)>();
+        assert_eq!((n, m), (3, 2));
+        assert_eq!(scanner.read_vec::<u32>(n), vec![10, 20, 30]);
+        assert_eq!(scanner.read::<i64>(), 1);
+        assert_eq!(scanner.token().as_deref(), Some("2"));
+        assert_eq!(scanner.try_read::<String>().as_deref(), Some("x"));
+        assert_eq!(scanner.try_read::<u32>(), None);
+        assert_eq!(scanner.try_read_tuple::<(u32, u32)>(), None);
+    }
+
+    #[test]
+    fn test_lines_and_eof() {
+        let input = "0 1 4 7\n1 2 3\n\n5\n8 9";
+        let mut scanner = Scanner::new(input.as_bytes());
+        assert_eq!(scanner.read::<u32>(), 0);
+        // the rest of the current line, then whole lines
+        assert_eq!(scanner.read_line::<u32>(), vec![1, 4, 7]);
+        assert_eq!(scanner.read_line::<u32>(), vec![1, 2, 3]);
+        assert_eq!(scanner.read_line::<u32>(), vec![5]);
+        let mut pairs = Vec::new();
+        while let Some(pair) = scanner.try_read_tuple::<(u8, u8)>() {
+            pairs.push(pair);
+        }
+        assert_eq!(pairs, vec![(8, 9)]);
+        assert_eq!(scanner.try_read_line::<u32>(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed to parse token \"x\"")]
+    fn test_parse_error() {
+        Scanner::new("1 x".as_bytes()).read_tuple::<(u32, u32)>();
+    }
+}
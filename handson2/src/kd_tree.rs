@@ -0,0 +1,211 @@
+// ------- K-D TREE -------
+// Static 2D tree over integer points, split alternately on x and y at the
+// median. Nearest neighbor queries prune subtrees by bounding box and take
+// O(log n) on typical inputs; rectangle counting takes O(sqrt(n)).
+
+pub type Point = (i64, i64);
+
+#[derive(Clone, Copy, Debug)]
+struct BoundingBox {
+    min_x: i64,
+    max_x: i64,
+    min_y: i64,
+    max_y: i64,
+}
+
+impl BoundingBox {
+    // Squared distance from p to the closest point of the box
+    fn dist2(&self, (x, y): Point) -> i64 {
+        let dx = (self.min_x - x).max(0).max(x - self.max_x);
+        let dy = (self.min_y - y).max(0).max(y - self.max_y);
+        dx * dx + dy * dy
+    }
+}
+
+/// The subtree on positions [lo, hi) of `points` has its root at the middle
+/// position, the left subtree before it and the right one after it
+pub struct KdTree {
+    points: Vec<(Point, usize)>, // Points with their index in the input
+    boxes: Vec<BoundingBox>,     // Bounding box of the subtree rooted at each position
+}
+
+fn dist2(a: Point, b: Point) -> i64 {
+    (a.0 - b.0).pow(2) + (a.1 - b.1).pow(2)
+}
+
+impl KdTree {
+    pub fn new(points: &[Point]) -> Self {
+        let mut tree = KdTree {
+            points: points.iter().copied().zip(0..).collect(),
+            boxes: vec![
+                BoundingBox {
+                    min_x: 0,
+                    max_x: 0,
+                    min_y: 0,
+                    max_y: 0,
+                };
+                points.len()
+            ],
+        };
+        tree.build(0, points.len(), 0);
+        tree
+    }
+
+    fn build(&mut self, lo: usize, hi: usize, depth: usize) {
+        if lo >= hi {
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        let slice = &mut self.points[lo..hi];
+        if depth & 1 == 0 {
+            slice.select_nth_unstable_by_key(mid - lo, |&((x, _), _)| x);
+        } else {
+            slice.select_nth_unstable_by_key(mid - lo, |&((_, y), _)| y);
+        }
+        let bbox = slice.iter().fold(
+            BoundingBox {
+                min_x: i64::MAX,
+                max_x: i64::MIN,
+                min_y: i64::MAX,
+                max_y: i64::MIN,
+            },
+            |b, &((x, y), _)| BoundingBox {
+                min_x: b.min_x.min(x),
+                max_x: b.max_x.max(x),
+                min_y: b.min_y.min(y),
+                max_y: b.max_y.max(y),
+            },
+        );
+        self.boxes[mid] = bbox;
+        self.build(lo, mid, depth + 1);
+        self.build(mid + 1, hi, depth + 1);
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Index of a point closest to `q` with its squared distance, None if
+    /// the tree is empty
+    pub fn nearest(&self, q: Point) -> Option<(usize, i64)> {
+        let mut best = None;
+        self.nearest_recursive(0, self.len(), 0, q, &mut best);
+        best
+    }
+
+    fn nearest_recursive(
+        &self,
+        lo: usize,
+        hi: usize,
+        depth: usize,
+        q: Point,
+        best: &mut Option<(usize, i64)>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        if best.is_some_and(|(_, d)| self.boxes[mid].dist2(q) >= d) {
+            return;
+        }
+        let (p, index) = self.points[mid];
+        let d = dist2(p, q);
+        if best.is_none_or(|(_, bd)| d < bd) {
+            *best = Some((index, d));
+        }
+        // descend first on the side of the split containing q
+        let go_left = if depth & 1 == 0 { q.0 < p.0 } else { q.1 < p.1 };
+        let (first, second) = if go_left {
+            ((lo, mid), (mid + 1, hi))
+        } else {
+            ((mid + 1, hi), (lo, mid))
+        };
+        self.nearest_recursive(first.0, first.1, depth + 1, q, best);
+        self.nearest_recursive(second.0, second.1, depth + 1, q, best);
+    }
+
+    /// Number of points with `x1 <= x <= x2` and `y1 <= y <= y2`
+    pub fn count_in_rect(&self, x1: i64, x2: i64, y1: i64, y2: i64) -> usize {
+        assert!(x1 <= x2 && y1 <= y2, "Invalid range");
+        let rect = BoundingBox {
+            min_x: x1,
+            max_x: x2,
+            min_y: y1,
+            max_y: y2,
+        };
+        self.count_recursive(0, self.len(), &rect)
+    }
+
+    fn count_recursive(&self, lo: usize, hi: usize, rect: &BoundingBox) -> usize {
+        if lo >= hi {
+            return 0;
+        }
+        let mid = (lo + hi) / 2;
+        let b = &self.boxes[mid];
+        if b.max_x < rect.min_x
+            || rect.max_x < b.min_x
+            || b.max_y < rect.min_y
+            || rect.max_y < b.min_y
+        {
+            return 0;
+        }
+        if rect.min_x <= b.min_x
+            && b.max_x <= rect.max_x
+            && rect.min_y <= b.min_y
+            && b.max_y <= rect.max_y
+        {
+            return hi - lo;
+        }
+        let ((x, y), _) = self.points[mid];
+        let inside = rect.min_x <= x && x <= rect.max_x && rect.min_y <= y && y <= rect.max_y;
+        inside as usize
+            + self.count_recursive(lo, mid, rect)
+            + self.count_recursive(mid + 1, hi, rect)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = Rng::new(43);
+        for round in 0..30 {
+            let n = rng.gen_range(0, 200) as usize;
+            let coord = if round % 2 == 0 { 20 } else { 1_000_000 };
+            let point = |rng: &mut Rng| {
+                (
+                    rng.gen_range_i64(-coord, coord),
+                    rng.gen_range_i64(-coord, coord),
+                )
+            };
+            let points: Vec<Point> = (0..n).map(|_| point(&mut rng)).collect();
+            let tree = KdTree::new(&points);
+            assert_eq!(tree.len(), n);
+            for _ in 0..50 {
+                let q = point(&mut rng);
+                let expected = points.iter().map(|&p| dist2(p, q)).min();
+                let found = tree.nearest(q);
+                assert_eq!(found.map(|(_, d)| d), expected);
+                if let Some((i, d)) = found {
+                    assert_eq!(dist2(points[i], q), d);
+                }
+
+                let (a, b) = (point(&mut rng), point(&mut rng));
+                let (x1, x2) = (a.0.min(b.0), a.0.max(b.0));
+                let (y1, y2) = (a.1.min(b.1), a.1.max(b.1));
+                let expected = points
+                    .iter()
+                    .filter(|&&(x, y)| x1 <= x && x <= x2 && y1 <= y && y <= y2)
+                    .count();
+                assert_eq!(tree.count_in_rect(x1, x2, y1, y2), expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,224 @@
+// ------- LOWEST COMMON ANCESTOR -------
+// O(1) LCA queries after O(n log n) preprocessing. In DFS order, the LCA of
+// u != v with tin[u] < tin[v] is the parent of the shallowest vertex entered
+// in (tin[u], tin[v]], found with a sparse table over the DFS order.
+// When all the queries are known in advance, Tarjan's offline algorithm
+// answers them in O((n + q) α(n)) with a DSU: vertices are finished in reverse
+// preorder and each finished set is merged into its parent's, so the set of
+// any finished vertex is labeled by its deepest unfinished ancestor.
+
+use crate::dsu::Dsu;
+use crate::graph::Graph;
+use crate::sparse_table::SparseTable;
+
+pub struct Lca {
+    root: usize,
+    parent: Vec<usize>,                 // The root is its own parent
+    depth: Vec<usize>,                  // Number of edges from the root
+    dist: Vec<i64>,                     // Weighted distance from the root
+    tin: Vec<usize>,                    // DFS entry time
+    tout: Vec<usize>,                   // Largest entry time inside the subtree
+    table: SparseTable<(usize, usize)>, // (depth, vertex) by entry time
+}
+
+impl Lca {
+    /// Preprocesses the tree `graph` rooted at `root`
+    pub fn new(graph: &Graph, root: usize) -> Self {
+        let n = graph.len();
+        let (parent, order) = graph.rooted_order(root);
+        let mut depth = vec![0; n];
+        let mut dist = vec![0; n];
+        let mut tin = vec![0; n];
+        for (t, &v) in order.iter().enumerate() {
+            tin[v] = t;
+            for &(u, weight) in graph.neighbors(v) {
+                if u != root && parent[u] == v {
+                    depth[u] = depth[v] + 1;
+                    dist[u] = dist[v] + weight;
+                }
+            }
+        }
+        // subtrees are contiguous in preorder
+        let mut size = vec![1; n];
+        for &v in order.iter().rev().filter(|&&v| v != root) {
+            size[parent[v]] += size[v];
+        }
+        let tout: Vec<usize> = (0..n).map(|v| tin[v] + size[v] - 1).collect();
+        let by_time: Vec<(usize, usize)> = order.iter().map(|&v| (depth[v], v)).collect();
+        Lca {
+            root,
+            parent,
+            depth,
+            dist,
+            tin,
+            tout,
+            table: SparseTable::min(&by_time),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    pub fn root(&self) -> usize {
+        self.root
+    }
+
+    /// Parent of `v`, None for the root
+    pub fn parent(&self, v: usize) -> Option<usize> {
+        (v != self.root).then(|| self.parent[v])
+    }
+
+    pub fn depth(&self, v: usize) -> usize {
+        self.depth[v]
+    }
+
+    /// DFS entry time of `v`, the root entering at 0
+    pub fn tin(&self, v: usize) -> usize {
+        self.tin[v]
+    }
+
+    /// Whether `u` is an ancestor of `v`, every vertex being its own ancestor
+    pub fn is_ancestor(&self, u: usize, v: usize) -> bool {
+        self.tin[u] <= self.tin[v] && self.tin[v] <= self.tout[u]
+    }
+
+    pub fn lca(&self, u: usize, v: usize) -> usize {
+        if u == v {
+            return u;
+        }
+        let (a, b) = (self.tin[u].min(self.tin[v]), self.tin[u].max(self.tin[v]));
+        let (_, shallowest) = self.table.query(a + 1, b);
+        self.parent[shallowest]
+    }
+
+    /// Weighted length of the path between `u` and `v`
+    pub fn dist(&self, u: usize, v: usize) -> i64 {
+        self.dist[u] + self.dist[v] - 2 * self.dist[self.lca(u, v)]
+    }
+}
+
+/// The LCA of every pair in `queries` on the tree `graph` rooted at `root`,
+/// the same as `Lca::lca` for each pair
+pub fn offline_lca(graph: &Graph, root: usize, queries: &[(usize, usize)]) -> Vec<usize> {
+    let n = graph.len();
+    let (parent, order) = graph.rooted_order(root);
+    // queries by endpoint, as (other endpoint, query index)
+    let mut at = vec![Vec::new(); n];
+    for (i, &(u, v)) in queries.iter().enumerate() {
+        assert!(u < n && v < n, "Index out of range");
+        at[u].push((v, i));
+        at[v].push((u, i));
+    }
+    let mut dsu = Dsu::new(n);
+    let mut ancestor: Vec<usize> = (0..n).collect(); // Label of each DSU root
+    let mut finished = vec![false; n];
+    let mut answers = vec![0; queries.len()];
+    // a subtree is finished before its root, as in a DFS
+    for &v in order.iter().rev() {
+        finished[v] = true;
+        for &(other, i) in &at[v] {
+            if finished[other] {
+                answers[i] = ancestor[dsu.find(other)];
+            }
+        }
+        if v != root {
+            let p = parent[v];
+            dsu.union(v, p);
+            let label = dsu.find(p);
+            ancestor[label] = p;
+        }
+    }
+    answers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_against_naive() {
+        let mut rng = Rng::new(47);
+        for _ in 0..30 {
+            let n = rng.gen_range(1, 80) as usize;
+            let mut parent = vec![0; n];
+            let mut edges = Vec::new();
+            for (v, p) in parent.iter_mut().enumerate().skip(1) {
+                *p = rng.gen_range(0, v as u64 - 1) as usize;
+                edges.push((*p, v, rng.gen_range_i64(1, 9)));
+            }
+            // edges go from smaller to larger labels, but the root is random
+            let root = rng.gen_range(0, n as u64 - 1) as usize;
+            let graph = Graph::from_undirected_edges(n, &edges);
+            let lca = Lca::new(&graph, root);
+
+            // naive: parents by BFS from the root, then climb
+            let mut up = vec![usize::MAX; n];
+            let mut weight_up = vec![0; n];
+            let mut queue = std::collections::VecDeque::from([root]);
+            up[root] = root;
+            while let Some(v) = queue.pop_front() {
+                for &(u, w) in graph.neighbors(v) {
+                    if up[u] == usize::MAX {
+                        up[u] = v;
+                        weight_up[u] = w;
+                        queue.push_back(u);
+                    }
+                }
+            }
+            let path = |mut v: usize| {
+                let mut path = vec![(v, 0)];
+                let mut total = 0;
+                while v != root {
+                    total += weight_up[v];
+                    v = up[v];
+                    path.push((v, total));
+                }
+                path
+            };
+            for _ in 0..100 {
+                let u = rng.gen_range(0, n as u64 - 1) as usize;
+                let v = rng.gen_range(0, n as u64 - 1) as usize;
+                let (pu, pv) = (path(u), path(v));
+                let (w, du) = *pu
+                    .iter()
+                    .find(|(a, _)| pv.iter().any(|(b, _)| a == b))
+                    .unwrap();
+                let dv = pv.iter().find(|(b, _)| *b == w).unwrap().1;
+                assert_eq!(lca.lca(u, v), w);
+                assert_eq!(lca.dist(u, v), du + dv);
+                assert_eq!(lca.depth(u), pu.len() - 1);
+                assert!(lca.is_ancestor(w, v));
+                assert_eq!(lca.is_ancestor(u, v), w == u);
+            }
+            assert_eq!(lca.parent(root), None);
+        }
+    }
+
+    #[test]
+    fn test_offline_against_online() {
+        let mut rng = Rng::new(71);
+        for _ in 0..30 {
+            let n = rng.gen_range(1, 200) as usize;
+            let edges: Vec<(usize, usize, i64)> = (1..n)
+                .map(|v| (rng.gen_range(0, v as u64 - 1) as usize, v, 1))
+                .collect();
+            let graph = Graph::from_undirected_edges(n, &edges);
+            let root = rng.gen_range(0, n as u64 - 1) as usize;
+            let queries: Vec<(usize, usize)> = (0..300)
+                .map(|_| {
+                    let u = rng.gen_range(0, n as u64 - 1) as usize;
+                    (u, rng.gen_range(0, n as u64 - 1) as usize)
+                })
+                .collect();
+            let lca = Lca::new(&graph, root);
+            let expected: Vec<usize> = queries.iter().map(|&(u, v)| lca.lca(u, v)).collect();
+            assert_eq!(offline_lca(&graph, root, &queries), expected);
+        }
+    }
+}
@@ -0,0 +1,119 @@
+// ------- LI CHAO TREE -------
+// Stores lines y = a * x + b and answers the minimum value at a query point in
+// O(log n), where the possible query points are fixed at construction.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Line {
+    pub a: i64,
+    pub b: i64,
+}
+
+impl Line {
+    pub fn eval(&self, x: i64) -> i64 {
+        self.a * x + self.b
+    }
+}
+
+pub struct LiChaoTree {
+    xs: Vec<i64>,             // Sorted distinct query points
+    lines: Vec<Option<Line>>, // Line winning at the midpoint of each node
+}
+
+impl LiChaoTree {
+    /// A tree answering queries on the points `xs`
+    pub fn new(xs: &[i64]) -> Self {
+        let mut xs = xs.to_vec();
+        xs.sort_unstable();
+        xs.dedup();
+        let lines = vec![None; 4 * xs.len().max(1)];
+        LiChaoTree { xs, lines }
+    }
+
+    /// Adds the line y = a * x + b
+    pub fn add_line(&mut self, a: i64, b: i64) {
+        if self.xs.is_empty() {
+            return;
+        }
+        let mut line = Line { a, b };
+        let (mut node, mut start, mut end) = (0, 0, self.xs.len() - 1);
+        loop {
+            let mid = (start + end) / 2;
+            let current = match self.lines[node] {
+                Some(current) => current,
+                None => {
+                    self.lines[node] = Some(line);
+                    return;
+                }
+            };
+            // keep the line winning at mid, push the loser to the side where it may still win
+            let (x_start, x_mid) = (self.xs[start], self.xs[mid]);
+            let wins_start = line.eval(x_start) < current.eval(x_start);
+            let wins_mid = line.eval(x_mid) < current.eval(x_mid);
+            if wins_mid {
+                self.lines[node] = Some(line);
+                line = current;
+            }
+            if start == end {
+                return;
+            }
+            if wins_start != wins_mid {
+                node = 2 * node + 1;
+                end = mid;
+            } else {
+                node = 2 * node + 2;
+                start = mid + 1;
+            }
+        }
+    }
+
+    /// Minimum value at `x` over all the lines, None if no line was added
+    ///
+    /// # Panics
+    /// Panics if `x` is not one of the query points given at construction.
+    pub fn query(&self, x: i64) -> Option<i64> {
+        let idx = self.xs.binary_search(&x).expect("Unknown query point");
+        let (mut node, mut start, mut end) = (0, 0, self.xs.len() - 1);
+        let mut best: Option<i64> = None;
+        loop {
+            if let Some(line) = self.lines[node] {
+                let y = line.eval(x);
+                best = Some(best.map_or(y, |b| b.min(y)));
+            }
+            if start == end {
+                return best;
+            }
+            let mid = (start + end) / 2;
+            if idx <= mid {
+                node = 2 * node + 1;
+                end = mid;
+            } else {
+                node = 2 * node + 2;
+                start = mid + 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = Rng::new(16);
+        let xs: Vec<i64> = (0..60).map(|_| rng.gen_range_i64(-100, 100)).collect();
+        let mut tree = LiChaoTree::new(&xs);
+        let mut lines = Vec::new();
+        assert_eq!(tree.query(xs[0]), None);
+        for _ in 0..100 {
+            let (a, b) = (rng.gen_range_i64(-50, 50), rng.gen_range_i64(-1000, 1000));
+            tree.add_line(a, b);
+            lines.push(Line { a, b });
+            for &x in &xs {
+                let expected = lines.iter().map(|l| l.eval(x)).min();
+                assert_eq!(tree.query(x), expected);
+            }
+        }
+    }
+}
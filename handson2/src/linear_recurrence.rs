@@ -0,0 +1,211 @@
+// ------- LINEAR RECURRENCES -------
+// N-th term of a_n = c_1 a_{n-1} + ... + c_d a_{n-d} modulo a prime, either by
+// companion matrix power in O(d^3 log n) or by Bostan-Mori in
+// O(M(d) log n), M(d) being the cost of one polynomial product.
+// Berlekamp-Massey recovers the shortest recurrence generating a sequence in
+// O(N^2); 2d terms determine a recurrence of order d, so a sequence known to
+// be linearly recurrent can be extended from a prefix to its 10^18-th term.
+
+use crate::convolution::convolution;
+use crate::matrix::Matrix;
+use crate::modint::ModInt;
+
+/// N-th term (0-based) of the recurrence with coefficients `coeffs` =
+/// [c_1, ..., c_d] and first terms `init` = [a_0, ..., a_{d-1}], by
+/// Bostan-Mori: a_n is the n-th coefficient of P(x) / Q(x), with
+/// Q(x) = 1 - c_1 x - ... - c_d x^d and P = A Q mod x^d
+pub fn linear_recurrence<const M: u32>(
+    coeffs: &[ModInt<M>],
+    init: &[ModInt<M>],
+    n: u64,
+) -> ModInt<M> {
+    let d = coeffs.len();
+    assert_eq!(init.len(), d, "One initial term per coefficient expected");
+    if (n as u128) < d as u128 {
+        return init[n as usize];
+    }
+    if d == 0 {
+        return ModInt::new(0);
+    }
+    let mut q = Vec::with_capacity(d + 1);
+    q.push(ModInt::new(1));
+    q.extend(coeffs.iter().map(|&c| -c));
+    let mut p = convolution(init, &q);
+    p.truncate(d);
+
+    let mut n = n;
+    while n > 0 {
+        // P(x)/Q(x) = P(x)Q(-x) / Q(x)Q(-x), and the denominator is even
+        let q_neg: Vec<ModInt<M>> = q
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| if i & 1 == 1 { -c } else { c })
+            .collect();
+        let u = convolution(&p, &q_neg);
+        let v = convolution(&q, &q_neg);
+        p = u.into_iter().skip((n & 1) as usize).step_by(2).collect();
+        q = v.into_iter().step_by(2).collect();
+        n >>= 1;
+    }
+    p.first().copied().unwrap_or_default() / q[0]
+}
+
+/// Same as `linear_recurrence`, by raising the d x d companion matrix
+pub fn linear_recurrence_matrix<const M: u32>(
+    coeffs: &[ModInt<M>],
+    init: &[ModInt<M>],
+    n: u64,
+) -> ModInt<M> {
+    let d = coeffs.len();
+    assert_eq!(init.len(), d, "One initial term per coefficient expected");
+    if (n as u128) < d as u128 {
+        return init[n as usize];
+    }
+    if d == 0 {
+        return ModInt::new(0);
+    }
+    // state (a_{k+d-1}, ..., a_k) -> (a_{k+d}, ..., a_{k+1})
+    let mut companion = Matrix::new(d, d);
+    for (j, &c) in coeffs.iter().enumerate() {
+        companion[(0, j)] = c;
+    }
+    for i in 1..d {
+        companion[(i, i - 1)] = ModInt::new(1);
+    }
+    let state: Vec<ModInt<M>> = init.iter().rev().copied().collect();
+    let last = companion.pow(n - (d as u64 - 1)).apply(&state);
+    last[0]
+}
+
+/// Coefficients [c_1, ..., c_d] of a shortest linear recurrence generating
+/// `terms`
+pub fn berlekamp_massey<const M: u32>(terms: &[ModInt<M>]) -> Vec<ModInt<M>> {
+    let zero = ModInt::new(0);
+    // current connection polynomial, and the one before the last length change
+    let mut current = vec![ModInt::new(1)];
+    let mut previous = vec![ModInt::new(1)];
+    let mut len = 0;
+    let mut shift = 1;
+    let mut previous_discrepancy = ModInt::new(1);
+    for n in 0..terms.len() {
+        let discrepancy: ModInt<M> = (0..=len).map(|i| current[i] * terms[n - i]).sum();
+        if discrepancy == zero {
+            shift += 1;
+            continue;
+        }
+        let factor = discrepancy / previous_discrepancy;
+        let before = current.clone();
+        if current.len() < previous.len() + shift {
+            current.resize(previous.len() + shift, zero);
+        }
+        for (i, &b) in previous.iter().enumerate() {
+            current[i + shift] -= factor * b;
+        }
+        if 2 * len <= n {
+            len = n + 1 - len;
+            previous = before;
+            previous_discrepancy = discrepancy;
+            shift = 1;
+        } else {
+            shift += 1;
+        }
+    }
+    current.resize(len + 1, zero);
+    current[1..].iter().map(|&c| -c).collect()
+}
+
+/// N-th term (0-based) of the sequence starting with `terms`, assuming it
+/// follows a linear recurrence of order at most `terms.len() / 2`
+pub fn guess_nth_term<const M: u32>(terms: &[ModInt<M>], n: u64) -> ModInt<M> {
+    if (n as u128) < terms.len() as u128 {
+        return terms[n as usize];
+    }
+    let coeffs = berlekamp_massey(terms);
+    linear_recurrence(&coeffs, &terms[..coeffs.len()], n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modint::{Mod1000000007, Mod998244353};
+    use crate::rng::Rng;
+
+    fn iterate<const M: u32>(
+        coeffs: &[ModInt<M>],
+        init: &[ModInt<M>],
+        count: usize,
+    ) -> Vec<ModInt<M>> {
+        let mut terms = init.to_vec();
+        while terms.len() < count {
+            let k = terms.len();
+            let next = coeffs
+                .iter()
+                .enumerate()
+                .map(|(i, &c)| c * terms[k - 1 - i])
+                .sum();
+            terms.push(next);
+        }
+        terms
+    }
+
+    #[test]
+    fn test_fibonacci() {
+        type Mint = Mod1000000007;
+        let coeffs = [Mint::new(1), Mint::new(1)];
+        let init = [Mint::new(0), Mint::new(1)];
+        assert_eq!(linear_recurrence(&coeffs, &init, 10), Mint::new(55));
+        assert_eq!(linear_recurrence(&coeffs, &init, 0), Mint::new(0));
+        let n = 1_000_000_000_000_000_000;
+        assert_eq!(linear_recurrence(&coeffs, &init, n).value(), 209_783_453);
+        assert_eq!(
+            linear_recurrence_matrix(&coeffs, &init, n).value(),
+            209_783_453
+        );
+    }
+
+    #[test]
+    fn test_random_recurrences() {
+        let mut rng = Rng::new(30);
+        for d in [1, 2, 5, 17, 100] {
+            let coeffs: Vec<Mod998244353> = (0..d).map(|_| ModInt::new(rng.next_u64())).collect();
+            let init: Vec<Mod998244353> = (0..d).map(|_| ModInt::new(rng.next_u64())).collect();
+            let terms = iterate(&coeffs, &init, 300);
+            for n in [0, d - 1, d, d + 1, 150, 299] {
+                assert_eq!(linear_recurrence(&coeffs, &init, n as u64), terms[n]);
+                assert_eq!(linear_recurrence_matrix(&coeffs, &init, n as u64), terms[n]);
+            }
+            // the d^3 matrix product gets slow past a few dozen terms
+            let big = rng.next_u64() >> 4;
+            if d > 20 {
+                continue;
+            }
+            assert_eq!(
+                linear_recurrence(&coeffs, &init, big),
+                linear_recurrence_matrix(&coeffs, &init, big)
+            );
+        }
+    }
+
+    #[test]
+    fn test_berlekamp_massey() {
+        type Mint = Mod998244353;
+        let mut rng = Rng::new(74);
+        for d in [1, 2, 7, 30] {
+            let coeffs: Vec<Mint> = (0..d).map(|_| ModInt::new(rng.next_u64())).collect();
+            let init: Vec<Mint> = (0..d).map(|_| ModInt::new(rng.next_u64())).collect();
+            let terms = iterate(&coeffs, &init, 200);
+            assert_eq!(berlekamp_massey(&terms[..2 * d]), coeffs);
+            assert_eq!(guess_nth_term(&terms[..2 * d], 199), terms[199]);
+        }
+        // n^3 follows a recurrence of order 4, found from 8 terms
+        let cubes: Vec<Mint> = (0..8u64).map(|n| Mint::new(n * n * n)).collect();
+        assert_eq!(berlekamp_massey(&cubes).len(), 4);
+        let n = 1_000_000_000_000_000_000u64;
+        assert_eq!(guess_nth_term(&cubes, n), Mint::new(n).pow(3));
+        // the shortest recurrence of a sequence with a long zero prefix
+        let late: Vec<Mint> = [0, 0, 0, 1, 2, 4, 8, 16].map(Mint::new).to_vec();
+        let coeffs = berlekamp_massey(&late);
+        assert_eq!(iterate(&coeffs, &late[..coeffs.len()], 8), late);
+        assert!(berlekamp_massey::<998_244_353>(&[]).is_empty());
+    }
+}
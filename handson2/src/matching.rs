@@ -0,0 +1,309 @@
+// ------- MATCHING -------
+// The Hungarian algorithm solves the assignment problem on a dense cost
+// matrix in O(n^2 m): each row in turn is added to the matching along a
+// shortest augmenting path under reduced costs, keeping the potentials of
+// rows and columns feasible, as Dijkstra does in min-cost flow but without
+// building the n m edges of a flow network. Edmonds' blossom algorithm finds
+// a maximum matching in a general graph in O(n^3): a BFS for augmenting paths
+// from each free vertex contracts every odd cycle it closes into its base.
+
+use crate::graph::Graph;
+
+const NONE: usize = usize::MAX;
+
+/// Minimum total cost of assigning every row of `cost` to a distinct column,
+/// with `rows <= cols`, and the column assigned to each row
+pub fn hungarian(cost: &[Vec<i64>]) -> (i64, Vec<usize>) {
+    let n = cost.len();
+    let m = cost.first().map_or(0, Vec::len);
+    assert!(
+        cost.iter().all(|row| row.len() == m),
+        "Rows of different lengths"
+    );
+    assert!(n <= m, "More rows than columns");
+    // 1-based rows and columns, column 0 being a virtual one holding the
+    // row being added
+    let mut u = vec![0i64; n + 1];
+    let mut v = vec![0i64; m + 1];
+    let mut owner = vec![0usize; m + 1]; // Row matched to each column, 0 if none
+    let mut way = vec![0usize; m + 1];
+    for row in 1..=n {
+        owner[0] = row;
+        let mut col = 0;
+        let mut min_slack = vec![i64::MAX; m + 1];
+        let mut used = vec![false; m + 1];
+        // grow the tree of alternating paths until it reaches a free column
+        loop {
+            used[col] = true;
+            let i = owner[col];
+            let mut delta = i64::MAX;
+            let mut next = 0;
+            for j in 1..=m {
+                if used[j] {
+                    continue;
+                }
+                let slack = cost[i - 1][j - 1] - u[i] - v[j];
+                if slack < min_slack[j] {
+                    min_slack[j] = slack;
+                    way[j] = col;
+                }
+                if min_slack[j] < delta {
+                    delta = min_slack[j];
+                    next = j;
+                }
+            }
+            for j in 0..=m {
+                if used[j] {
+                    u[owner[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    min_slack[j] -= delta;
+                }
+            }
+            col = next;
+            if owner[col] == 0 {
+                break;
+            }
+        }
+        // flip the augmenting path back to the virtual column
+        while col != 0 {
+            let prev = way[col];
+            owner[col] = owner[prev];
+            col = prev;
+        }
+    }
+
+    let mut assignment = vec![0; n];
+    for j in 1..=m {
+        if owner[j] != 0 {
+            assignment[owner[j] - 1] = j - 1;
+        }
+    }
+    let total = (0..n).map(|i| cost[i][assignment[i]]).sum();
+    (total, assignment)
+}
+
+/// Maximum matching of the undirected `graph`, as the vertex matched to
+/// each vertex
+pub fn max_matching(graph: &Graph) -> Vec<Option<usize>> {
+    let n = graph.len();
+    let mut blossom = Blossom {
+        graph,
+        mate: vec![NONE; n],
+        parent: vec![NONE; n],
+        base: (0..n).collect(),
+        used: vec![false; n],
+        in_blossom: vec![false; n],
+        queue: Vec::new(),
+    };
+    for root in 0..n {
+        if blossom.mate[root] != NONE {
+            continue;
+        }
+        // flip the matched and unmatched edges along the augmenting path
+        let mut v = blossom.find_path(root);
+        while v != NONE {
+            let pv = blossom.parent[v];
+            let next = blossom.mate[pv];
+            blossom.mate[v] = pv;
+            blossom.mate[pv] = v;
+            v = next;
+        }
+    }
+    blossom
+        .mate
+        .iter()
+        .map(|&m| (m != NONE).then_some(m))
+        .collect()
+}
+
+// State of the search for augmenting paths
+struct Blossom<'a> {
+    graph: &'a Graph,
+    mate: Vec<usize>,
+    parent: Vec<usize>, // Predecessor of each odd vertex in the BFS tree
+    base: Vec<usize>,   // Base of the contracted blossom of each vertex
+    used: Vec<bool>,    // Even vertices, those entered in the queue
+    in_blossom: Vec<bool>,
+    queue: Vec<usize>,
+}
+
+impl Blossom<'_> {
+    // Lowest common ancestor of the bases of a and b in the BFS tree
+    fn lca(&self, mut a: usize, mut b: usize) -> usize {
+        let mut on_path = vec![false; self.mate.len()];
+        loop {
+            a = self.base[a];
+            on_path[a] = true;
+            if self.mate[a] == NONE {
+                break;
+            }
+            a = self.parent[self.mate[a]];
+        }
+        loop {
+            b = self.base[b];
+            if on_path[b] {
+                return b;
+            }
+            b = self.parent[self.mate[b]];
+        }
+    }
+
+    // Marks the blossom vertices from v up to base b, pointing the odd ones
+    // back along the cycle through `child`
+    fn mark_path(&mut self, mut v: usize, b: usize, mut child: usize) {
+        while self.base[v] != b {
+            self.in_blossom[self.base[v]] = true;
+            self.in_blossom[self.base[self.mate[v]]] = true;
+            self.parent[v] = child;
+            child = self.mate[v];
+            v = self.parent[self.mate[v]];
+        }
+    }
+
+    // BFS from the free vertex root; returns the free vertex ending an
+    // augmenting path, or NONE
+    fn find_path(&mut self, root: usize) -> usize {
+        let n = self.mate.len();
+        self.used.fill(false);
+        self.parent.fill(NONE);
+        for (i, b) in self.base.iter_mut().enumerate() {
+            *b = i;
+        }
+        self.used[root] = true;
+        self.queue.clear();
+        self.queue.push(root);
+        let mut head = 0;
+        while head < self.queue.len() {
+            let v = self.queue[head];
+            head += 1;
+            for &(to, _) in self.graph.neighbors(v) {
+                if self.base[v] == self.base[to] || self.mate[v] == to {
+                    continue;
+                }
+                if to == root || (self.mate[to] != NONE && self.parent[self.mate[to]] != NONE) {
+                    // an edge between two even vertices closes a blossom
+                    let current = self.lca(v, to);
+                    self.in_blossom.fill(false);
+                    self.mark_path(v, current, to);
+                    self.mark_path(to, current, v);
+                    for i in 0..n {
+                        if self.in_blossom[self.base[i]] {
+                            self.base[i] = current;
+                            if !self.used[i] {
+                                self.used[i] = true;
+                                self.queue.push(i);
+                            }
+                        }
+                    }
+                } else if self.parent[to] == NONE {
+                    self.parent[to] = v;
+                    if self.mate[to] == NONE {
+                        return to;
+                    }
+                    self.used[self.mate[to]] = true;
+                    self.queue.push(self.mate[to]);
+                }
+            }
+        }
+        NONE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combinatorics::permutations;
+    use crate::rng::Rng;
+
+    // Size of a maximum matching by DP over subsets, matching the lowest
+    // vertex of each subset or leaving it out
+    fn brute_matching(n: usize, adjacent: &[Vec<bool>]) -> usize {
+        let mut best = vec![0; 1 << n];
+        for mask in 1usize..1 << n {
+            let v = mask.trailing_zeros() as usize;
+            let rest = mask & !(1 << v);
+            best[mask] = best[rest];
+            for u in (0..n).filter(|&u| rest >> u & 1 == 1 && adjacent[v][u]) {
+                best[mask] = best[mask].max(best[rest & !(1 << u)] + 1);
+            }
+        }
+        best[(1 << n) - 1]
+    }
+
+    #[test]
+    fn test_hungarian_against_permutations() {
+        let cost = [vec![4, 1, 3], vec![2, 0, 5], vec![3, 2, 2]];
+        assert_eq!(hungarian(&cost), (5, vec![1, 0, 2]));
+        assert_eq!(hungarian(&[]), (0, vec![]));
+
+        let mut rng = Rng::new(64);
+        for _ in 0..100 {
+            let n = rng.gen_range(1, 5) as usize;
+            let m = rng.gen_range(n as u64, 6) as usize;
+            let cost: Vec<Vec<i64>> = (0..n)
+                .map(|_| (0..m).map(|_| rng.gen_range_i64(-20, 50)).collect())
+                .collect();
+            let (total, assignment) = hungarian(&cost);
+            let columns: Vec<usize> = (0..m).collect();
+            // every injection of the rows into the columns, as the first n
+            // entries of a permutation of the columns
+            let best = permutations(&columns)
+                .map(|p| (0..n).map(|i| cost[i][p[i]]).sum::<i64>())
+                .min()
+                .unwrap();
+            assert_eq!(total, best);
+            let mut seen = vec![false; m];
+            for &j in &assignment {
+                assert!(!seen[j]);
+                seen[j] = true;
+            }
+        }
+    }
+
+    #[test]
+    fn test_blossom_against_brute_force() {
+        // a triangle with a tail needs the odd cycle contracted
+        let graph = Graph::from_undirected_edges(
+            6,
+            &[
+                (0, 1, 1),
+                (1, 2, 1),
+                (2, 0, 1),
+                (2, 3, 1),
+                (3, 4, 1),
+                (4, 5, 1),
+            ],
+        );
+        assert_eq!(max_matching(&graph).iter().flatten().count(), 6);
+
+        let mut rng = Rng::new(65);
+        for _ in 0..200 {
+            let n = rng.gen_range(1, 11) as usize;
+            let mut edges = Vec::new();
+            for u in 0..n {
+                for v in u + 1..n {
+                    if rng.gen_bool(0.3) {
+                        edges.push((u, v, 1));
+                    }
+                }
+            }
+            let mut adjacent = vec![vec![false; n]; n];
+            for &(u, v, _) in &edges {
+                adjacent[u][v] = true;
+                adjacent[v][u] = true;
+            }
+            let mate = max_matching(&Graph::from_undirected_edges(n, &edges));
+            for (v, &m) in mate.iter().enumerate() {
+                if let Some(u) = m {
+                    assert!(adjacent[v][u]);
+                    assert_eq!(mate[u], Some(v));
+                }
+            }
+            assert_eq!(
+                mate.iter().flatten().count() / 2,
+                brute_matching(n, &adjacent)
+            );
+        }
+    }
+}
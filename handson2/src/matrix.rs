@@ -0,0 +1,312 @@
+// ------- MATRIX -------
+// Dense row-major matrices with multiplication and fast exponentiation over
+// any semiring: a `Monoid` from the segment tree, playing the role of the
+// addition, with a multiplication distributing over it. Integers under the
+// segment tree's `Sum` and `ModInt` are semirings as usual, while the segment tree's `Min` and `Max`
+// with + as the multiplication give the min-plus and max-plus (tropical)
+// semirings, where the k-th power of the weight matrix of a graph holds the
+// lightest (heaviest) walks of exactly k edges.
+
+use crate::graph::Graph;
+use crate::modint::ModInt;
+use crate::segment_tree::{Max, Min, Monoid, Sum};
+use std::ops::{Index, IndexMut, Mul};
+
+/// A `Monoid`, the addition with identity zero, with an associative `mul`
+/// with identity `one` that distributes over it and is absorbed by zero
+pub trait Semiring: Monoid + Copy {
+    fn one() -> Self;
+    fn mul(&self, other: &Self) -> Self;
+}
+
+macro_rules! impl_numeric_semirings {
+    ($($t:ty),*) => {$(
+        impl Semiring for Sum<$t> {
+            fn one() -> Self {
+                Sum(1)
+            }
+            #[cfg(any(debug_assertions, feature = "checked-arith"))]
+            fn mul(&self, other: &Self) -> Self {
+                Sum(self.0.checked_mul(other.0).expect("Product overflow"))
+            }
+            #[cfg(not(any(debug_assertions, feature = "checked-arith")))]
+            fn mul(&self, other: &Self) -> Self {
+                Sum(self.0.wrapping_mul(other.0))
+            }
+        }
+    )*};
+}
+
+// Products are overflow-checked exactly when the sums are
+impl_numeric_semirings!(u32, u64, usize, i32, i64, i128);
+
+macro_rules! impl_tropical_semirings {
+    ($($t:ty),*) => {$(
+        // the identity of the monoid, MAX for Min and MIN for Max, is the
+        // infinity absorbing every sum
+        impl Semiring for Min<$t> {
+            fn one() -> Self {
+                Min(0)
+            }
+            fn mul(&self, other: &Self) -> Self {
+                if self.0 == <$t>::MAX || other.0 == <$t>::MAX {
+                    Min(<$t>::MAX)
+                } else {
+                    Min(self.0 + other.0)
+                }
+            }
+        }
+
+        impl Semiring for Max<$t> {
+            fn one() -> Self {
+                Max(0)
+            }
+            fn mul(&self, other: &Self) -> Self {
+                if self.0 == <$t>::MIN || other.0 == <$t>::MIN {
+                    Max(<$t>::MIN)
+                } else {
+                    Max(self.0 + other.0)
+                }
+            }
+        }
+    )*};
+}
+
+impl_tropical_semirings!(i32, i64, i128);
+
+impl<const M: u32> Monoid for ModInt<M> {
+    fn identity() -> Self {
+        ModInt::new(0)
+    }
+    fn combine(&self, other: &Self) -> Self {
+        *self + *other
+    }
+}
+
+impl<const M: u32> Semiring for ModInt<M> {
+    fn one() -> Self {
+        ModInt::new(1)
+    }
+    fn mul(&self, other: &Self) -> Self {
+        *self * *other
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Matrix<T> {
+    rows: usize,
+    cols: usize,
+    data: Vec<T>,
+}
+
+impl<T: Semiring> Matrix<T> {
+    /// A `rows` x `cols` matrix of zeros
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Matrix {
+            rows,
+            cols,
+            data: vec![T::identity(); rows * cols],
+        }
+    }
+
+    pub fn identity(n: usize) -> Self {
+        let mut result = Matrix::new(n, n);
+        for i in 0..n {
+            result[(i, i)] = T::one();
+        }
+        result
+    }
+
+    /// Builds a matrix from its rows, which must have the same length
+    pub fn from_rows(rows: &[Vec<T>]) -> Self {
+        let cols = rows.first().map_or(0, Vec::len);
+        assert!(
+            rows.iter().all(|r| r.len() == cols),
+            "Rows of different lengths"
+        );
+        Matrix {
+            rows: rows.len(),
+            cols,
+            data: rows.concat(),
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// `self` raised to `exp` by repeated squaring, O(n^3 log exp)
+    pub fn pow(&self, mut exp: u64) -> Self {
+        assert_eq!(self.rows, self.cols, "Only square matrices have powers");
+        let mut base = self.clone();
+        let mut result = Matrix::identity(self.rows);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = &result * &base;
+            }
+            base = &base * &base;
+            exp >>= 1;
+        }
+        result
+    }
+
+    /// Product with the column vector `v`
+    pub fn apply(&self, v: &[T]) -> Vec<T> {
+        assert_eq!(v.len(), self.cols, "Dimension mismatch");
+        (0..self.rows)
+            .map(|i| {
+                let row = &self.data[i * self.cols..(i + 1) * self.cols];
+                row.iter()
+                    .zip(v)
+                    .fold(T::identity(), |acc, (a, b)| acc.combine(&a.mul(b)))
+            })
+            .collect()
+    }
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+
+    fn index(&self, (i, j): (usize, usize)) -> &T {
+        assert!(i < self.rows && j < self.cols, "Index out of range");
+        &self.data[i * self.cols + j]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut T {
+        assert!(i < self.rows && j < self.cols, "Index out of range");
+        &mut self.data[i * self.cols + j]
+    }
+}
+
+impl<T: Semiring> Mul for &Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn mul(self, other: &Matrix<T>) -> Matrix<T> {
+        assert_eq!(self.cols, other.rows, "Dimension mismatch");
+        let mut result: Matrix<T> = Matrix::new(self.rows, other.cols);
+        // i-k-j order walks both operands row by row
+        for i in 0..self.rows {
+            for k in 0..self.cols {
+                let a = self[(i, k)];
+                for j in 0..other.cols {
+                    let cell = &mut result.data[i * other.cols + j];
+                    *cell = cell.combine(&a.mul(&other.data[k * other.cols + j]));
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Weights of the lightest walks of exactly `k` edges between every pair of
+/// vertices of the directed `graph`, i64::MAX where there is none
+pub fn shortest_walks(graph: &Graph, k: u64) -> Matrix<Min<i64>> {
+    let n = graph.len();
+    let mut weights: Matrix<Min<i64>> = Matrix::new(n, n);
+    for u in 0..n {
+        for &(v, w) in graph.neighbors(u) {
+            weights[(u, v)] = weights[(u, v)].combine(&Min(w));
+        }
+    }
+    weights.pow(k)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modint::Mod1000000007;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_product_and_power() {
+        let sums = |rows: &[&[i64]]| -> Matrix<Sum<i64>> {
+            let rows: Vec<Vec<Sum<i64>>> = rows
+                .iter()
+                .map(|row| row.iter().map(|&x| Sum(x)).collect())
+                .collect();
+            Matrix::from_rows(&rows)
+        };
+        let a = sums(&[&[1, 2, 3], &[4, 5, 6]]);
+        let b = sums(&[&[7, 8], &[9, 10], &[11, 12]]);
+        assert_eq!(&a * &b, sums(&[&[58, 64], &[139, 154]]));
+        assert_eq!(a.apply(&[Sum(1), Sum(0), Sum(-1)]), vec![Sum(-2), Sum(-2)]);
+
+        let fib = sums(&[&[1, 1], &[1, 0]]);
+        assert_eq!(fib.pow(0), Matrix::identity(2));
+        assert_eq!(fib.pow(10)[(0, 1)], Sum(55));
+    }
+
+    #[test]
+    #[should_panic(expected = "overflow")]
+    #[cfg(any(debug_assertions, feature = "checked-arith"))]
+    fn test_overflow_checked() {
+        let fib = Matrix::from_rows(&[vec![Sum(1u64), Sum(1)], vec![Sum(1), Sum(0)]]);
+        // F(94) no longer fits in a u64
+        fib.pow(94);
+    }
+
+    #[test]
+    fn test_modint_entries() {
+        type Mint = Mod1000000007;
+        let fib = Matrix::from_rows(&[
+            vec![Mint::new(1), Mint::new(1)],
+            vec![Mint::new(1), Mint::new(0)],
+        ]);
+        // F(10^18) mod 10^9 + 7
+        assert_eq!(
+            fib.pow(1_000_000_000_000_000_000)[(0, 1)].value(),
+            209_783_453
+        );
+    }
+
+    #[test]
+    fn test_tropical_walks() {
+        let mut rng = Rng::new(79);
+        for _ in 0..30 {
+            let n = rng.gen_range(1, 7) as usize;
+            let mut graph = Graph::new(n);
+            for _ in 0..rng.gen_range(0, 15) {
+                let (u, v) = (
+                    rng.gen_range(0, n as u64 - 1),
+                    rng.gen_range(0, n as u64 - 1),
+                );
+                graph.add_edge(u as usize, v as usize, rng.gen_range_i64(-5, 20));
+            }
+            // best[v] over walks of exactly k edges from `source`, by DP
+            for k in 0..6u64 {
+                let walks = shortest_walks(&graph, k);
+                for source in 0..n {
+                    let mut best = vec![None; n];
+                    best[source] = Some(0i64);
+                    for _ in 0..k {
+                        let mut next: Vec<Option<i64>> = vec![None; n];
+                        for (u, &d) in best.iter().enumerate() {
+                            if let Some(d) = d {
+                                for &(v, w) in graph.neighbors(u) {
+                                    next[v] = Some(next[v].map_or(d + w, |x| x.min(d + w)));
+                                }
+                            }
+                        }
+                        best = next;
+                    }
+                    for (v, &b) in best.iter().enumerate() {
+                        assert_eq!(walks[(source, v)].0, b.unwrap_or(i64::MAX));
+                    }
+                }
+            }
+        }
+
+        // heaviest walks of 3 edges in max-plus
+        let a = Matrix::from_rows(&[vec![Max(1i64), Max(4)], vec![Max(i64::MIN), Max(2)]]);
+        let cube = a.pow(3);
+        assert_eq!(cube[(0, 1)], Max(8));
+        assert_eq!(cube[(1, 0)], Max(i64::MIN));
+        assert_eq!(a.apply(&[Max(0), Max(0)]), vec![Max(4), Max(2)]);
+    }
+}
@@ -0,0 +1,131 @@
+// ------- MEET IN THE MIDDLE -------
+// For n up to about 40 items, the 2^n subsets are out of reach but the 2^(n/2)
+// subsets of each half are not. The driver enumerates the aggregates of every
+// subset of each half (sums, products, xors, ...), one per mask, and queries
+// combine a subset of the left half with the best or all matching subsets of
+// the sorted right half, by binary search or two pointers, in O(2^(n/2) n).
+
+/// Aggregates of all subsets of `items`, the one at index `mask` for the
+/// items at the set bits of `mask`
+pub fn subset_aggregates<T, A, F>(items: &[T], empty: A, add: F) -> Vec<A>
+where
+    A: Clone,
+    F: Fn(&A, &T) -> A,
+{
+    assert!(items.len() < 32, "Too many items");
+    let mut result = Vec::with_capacity(1 << items.len());
+    result.push(empty);
+    for item in items {
+        // the subsets with this item are the previous ones extended by it
+        for mask in 0..result.len() {
+            let extended = add(&result[mask], item);
+            result.push(extended);
+        }
+    }
+    result
+}
+
+/// Subset aggregates of the first and second halves of `items`
+pub fn split_aggregates<T, A, F>(items: &[T], empty: A, add: F) -> (Vec<A>, Vec<A>)
+where
+    A: Clone,
+    F: Fn(&A, &T) -> A,
+{
+    let (left, right) = items.split_at(items.len() / 2);
+    (
+        subset_aggregates(left, empty.clone(), &add),
+        subset_aggregates(right, empty, &add),
+    )
+}
+
+fn subset_sums(items: &[i64]) -> (Vec<i64>, Vec<i64>) {
+    let (left, mut right) = split_aggregates(items, 0, |&sum, &x| sum + x);
+    right.sort_unstable();
+    (left, right)
+}
+
+/// Subset sum closest to `target`, the smaller one on ties
+pub fn closest_subset_sum(items: &[i64], target: i64) -> i64 {
+    let (left, right) = subset_sums(items);
+    let mut best: Option<i64> = None;
+    let mut consider = |sum: i64| {
+        let key = |s: i64| ((s - target).abs(), s);
+        if best.is_none_or(|b| key(sum) < key(b)) {
+            best = Some(sum);
+        }
+    };
+    for &a in &left {
+        // the right sums just below and at least target - a
+        let i = right.partition_point(|&b| a + b < target);
+        if i > 0 {
+            consider(a + right[i - 1]);
+        }
+        if i < right.len() {
+            consider(a + right[i]);
+        }
+    }
+    best.unwrap()
+}
+
+/// Number of subsets, the empty one included, with sum at most `limit`
+pub fn count_subsets_at_most(items: &[i64], limit: i64) -> u64 {
+    let (mut left, right) = subset_sums(items);
+    left.sort_unstable();
+    // as a grows, the right sums fitting with it shrink to a prefix
+    let mut j = right.len();
+    let mut count = 0;
+    for &a in &left {
+        while j > 0 && a + right[j - 1] > limit {
+            j -= 1;
+        }
+        count += j as u64;
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = Rng::new(84);
+        for _ in 0..100 {
+            let n = rng.gen_range(0, 12) as usize;
+            let items: Vec<i64> = (0..n).map(|_| rng.gen_range_i64(-50, 100)).collect();
+            let sums = subset_aggregates(&items, 0, |&s, &x| s + x);
+            assert_eq!(sums.len(), 1 << n);
+            for (mask, &sum) in sums.iter().enumerate() {
+                let brute: i64 = (0..n)
+                    .filter(|&i| mask >> i & 1 == 1)
+                    .map(|i| items[i])
+                    .sum();
+                assert_eq!(sum, brute);
+            }
+            let target = rng.gen_range_i64(-200, 600);
+            let closest = sums
+                .iter()
+                .copied()
+                .min_by_key(|&s| ((s - target).abs(), s))
+                .unwrap();
+            assert_eq!(closest_subset_sum(&items, target), closest);
+            let count = sums.iter().filter(|&&s| s <= target).count() as u64;
+            assert_eq!(count_subsets_at_most(&items, target), count);
+        }
+    }
+
+    #[test]
+    fn test_forty_items() {
+        let items: Vec<i64> = (0..40).map(|i| 1 << i).collect();
+        // every sum below 2^40 is reachable, exactly once
+        assert_eq!(closest_subset_sum(&items, 123_456_789_012), 123_456_789_012);
+        assert_eq!(count_subsets_at_most(&items, 999_999), 1_000_000);
+        assert_eq!(count_subsets_at_most(&items, i64::MAX / 2), 1 << 40);
+
+        // products of the halves, by the generic driver
+        let (left, right) = split_aggregates(&[2u64, 3, 5, 7, 11], 1, |&p, &x| p * x);
+        assert_eq!(left, vec![1, 2, 3, 6]);
+        assert_eq!(right.iter().max(), Some(&385));
+    }
+}
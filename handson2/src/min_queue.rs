@@ -0,0 +1,176 @@
+// ------- MIN STACK AND MIN QUEUE -------
+// Stack and queue answering their current minimum in O(1); the queue is two
+// min-stacks with amortized O(1) operations. Wrap values in
+// `std::cmp::Reverse` to track the maximum instead.
+
+/// Stack storing next to each element the minimum of the elements below it
+#[derive(Clone, Debug)]
+pub struct MinStack<T> {
+    items: Vec<(T, T)>, // (value, minimum of the stack up to this value)
+}
+
+impl<T: Ord + Clone> Default for MinStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + Clone> MinStack<T> {
+    pub fn new() -> Self {
+        MinStack { items: Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn push(&mut self, value: T) {
+        let min = match self.items.last() {
+            Some((_, min)) if *min < value => min.clone(),
+            _ => value.clone(),
+        };
+        self.items.push((value, min));
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.items.pop().map(|(value, _)| value)
+    }
+
+    pub fn top(&self) -> Option<&T> {
+        self.items.last().map(|(value, _)| value)
+    }
+
+    pub fn min(&self) -> Option<&T> {
+        self.items.last().map(|(_, min)| min)
+    }
+}
+
+/// FIFO queue made of two min-stacks: pushes go on `back`, pops come from
+/// `front`, which is refilled by reversing `back` when it runs empty
+#[derive(Clone, Debug)]
+pub struct MinQueue<T> {
+    front: MinStack<T>,
+    back: MinStack<T>,
+}
+
+impl<T: Ord + Clone> Default for MinQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + Clone> MinQueue<T> {
+    pub fn new() -> Self {
+        MinQueue {
+            front: MinStack::new(),
+            back: MinStack::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.front.len() + self.back.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn push(&mut self, value: T) {
+        self.back.push(value);
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.refill();
+        self.front.pop()
+    }
+
+    /// Oldest element, the next one to be popped
+    pub fn front(&mut self) -> Option<&T> {
+        self.refill();
+        self.front.top()
+    }
+
+    pub fn min(&self) -> Option<&T> {
+        match (self.front.min(), self.back.min()) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    fn refill(&mut self) {
+        if self.front.is_empty() {
+            while let Some(value) = self.back.pop() {
+                self.front.push(value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use std::cmp::Reverse;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn test_min_stack() {
+        let mut stack = MinStack::new();
+        assert_eq!(stack.min(), None);
+        for v in [5, 3, 7, 3, 1] {
+            stack.push(v);
+        }
+        assert_eq!(stack.min(), Some(&1));
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.min(), Some(&3));
+        stack.pop();
+        assert_eq!(stack.min(), Some(&3));
+        stack.pop();
+        stack.pop();
+        assert_eq!((stack.top(), stack.min()), (Some(&5), Some(&5)));
+    }
+
+    #[test]
+    fn test_min_queue_against_deque() {
+        let mut rng = Rng::new(37);
+        let mut queue = MinQueue::new();
+        let mut max_queue = MinQueue::new();
+        let mut naive = VecDeque::new();
+        for _ in 0..2000 {
+            if rng.gen_bool(0.55) {
+                let v = rng.gen_range(0, 100);
+                queue.push(v);
+                max_queue.push(Reverse(v));
+                naive.push_back(v);
+            } else {
+                assert_eq!(queue.front().copied(), naive.front().copied());
+                assert_eq!(queue.pop(), naive.pop_front());
+                max_queue.pop();
+            }
+            assert_eq!(queue.len(), naive.len());
+            assert_eq!(queue.min().copied(), naive.iter().min().copied());
+            assert_eq!(max_queue.min().map(|r| r.0), naive.iter().max().copied());
+        }
+    }
+
+    #[test]
+    fn test_sliding_window_minimum() {
+        let values = [4, 2, 12, 11, -5, 7, 8, 1];
+        let mut window = MinQueue::new();
+        let mut minima = Vec::new();
+        for &v in &values {
+            window.push(v);
+            if window.len() > 3 {
+                window.pop();
+            }
+            if window.len() == 3 {
+                minima.push(*window.min().unwrap());
+            }
+        }
+        assert_eq!(minima, vec![2, 2, -5, -5, -5, 1]);
+    }
+}
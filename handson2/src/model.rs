@@ -0,0 +1,328 @@
+// ------- MODEL-BASED TESTING -------
+// Random operation sequences run on MaxSegmentTree and on a plain array model,
+// comparing every query answer. Ranges are drawn next to the previous one half
+// of the time, since lazy tags go wrong at the boundaries between updates. A
+// failing case is shrunk: operations are dropped in halving chunks, then one
+// by one, while the case still fails, then the array is cut to the positions
+// used and its values lowered, in rounds until nothing changes. The minimal
+// case prints as a Rust test to paste next to the failing one.
+
+use crate::rng::Rng;
+use crate::stress::{stress, Mismatch};
+use crate::MaxSegmentTree;
+use std::fmt;
+
+/// An operation on a MaxSegmentTree, ranges 0-based and inclusive
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    /// Replace every value x in [start, end] with min(x, value)
+    Chmin {
+        start: usize,
+        end: usize,
+        value: u32,
+    },
+    /// Replace every value x in [start, end] with max(x, value)
+    Chmax {
+        start: usize,
+        end: usize,
+        value: u32,
+    },
+    /// Set every value in [start, end] to value
+    Assign {
+        start: usize,
+        end: usize,
+        value: u32,
+    },
+    /// Maximum of [start, end]
+    Max { start: usize, end: usize },
+}
+
+impl Op {
+    // Last position the operation touches
+    fn end(self) -> usize {
+        match self {
+            Op::Chmin { end, .. }
+            | Op::Chmax { end, .. }
+            | Op::Assign { end, .. }
+            | Op::Max { end, .. } => end,
+        }
+    }
+
+    // Run on the plain array, the answer of a Max
+    fn apply_to(self, values: &mut [u32]) -> Option<u32> {
+        match self {
+            Op::Chmin { start, end, value } => values[start..=end]
+                .iter_mut()
+                .for_each(|x| *x = (*x).min(value)),
+            Op::Chmax { start, end, value } => values[start..=end]
+                .iter_mut()
+                .for_each(|x| *x = (*x).max(value)),
+            Op::Assign { start, end, value } => values[start..=end].fill(value),
+            Op::Max { start, end } => return values[start..=end].iter().max().copied(),
+        }
+        None
+    }
+}
+
+/// The answers of the Max operations on an array holding `data`
+pub fn run_model(data: &[u32], ops: &[Op]) -> Vec<u32> {
+    let mut values = data.to_vec();
+    ops.iter()
+        .filter_map(|op| op.apply_to(&mut values))
+        .collect()
+}
+
+/// The answers of the Max operations on a MaxSegmentTree built from `data`
+pub fn run_tree(data: &[u32], ops: &[Op]) -> Vec<u32> {
+    let mut tree = MaxSegmentTree::new(data);
+    let mut answers = Vec::new();
+    for &op in ops {
+        match op {
+            Op::Chmin { start, end, value } => tree.update(start, end, value),
+            Op::Chmax { start, end, value } => tree.chmax(start, end, value),
+            Op::Assign { start, end, value } => tree.assign(start, end, value),
+            Op::Max { start, end } => answers.push(tree.query(start, end)),
+        }
+    }
+    answers
+}
+
+/// A random case of up to `max_n` values and `max_ops` operations
+pub fn gen_case(rng: &mut Rng, max_n: usize, max_ops: usize) -> (Vec<u32>, Vec<Op>) {
+    let n = rng.gen_range(1, max_n as u64) as usize;
+    let data: Vec<u32> = (0..n).map(|_| rng.gen_range(0, 100) as u32).collect();
+    let mut ops = Vec::new();
+    let mut last_end = 0;
+    for _ in 0..rng.gen_range(1, max_ops as u64) {
+        let (start, end) = if rng.gen_bool(0.5) && last_end + 1 < n {
+            // right after the previous range
+            let start = last_end + 1;
+            (start, rng.gen_range(start as u64, n as u64 - 1) as usize)
+        } else {
+            let a = rng.gen_range(0, n as u64 - 1) as usize;
+            let b = rng.gen_range(0, n as u64 - 1) as usize;
+            (a.min(b), a.max(b))
+        };
+        last_end = end;
+        let value = rng.gen_range(0, 100) as u32;
+        // queries half of the time, the three updates equally often otherwise
+        ops.push(match rng.gen_range(0, 5) {
+            0..=2 => Op::Max { start, end },
+            3 => Op::Chmin { start, end, value },
+            4 => Op::Chmax { start, end, value },
+            _ => Op::Assign { start, end, value },
+        });
+    }
+    (data, ops)
+}
+
+/// Removes items of `items` while `fails` keeps holding, first in halving
+/// chunks, then one at a time until no single item can go
+pub fn shrink<T: Clone>(items: &[T], mut fails: impl FnMut(&[T]) -> bool) -> Vec<T> {
+    let mut items = items.to_vec();
+    let mut chunk = items.len().div_ceil(2);
+    while chunk > 0 {
+        let mut changed = false;
+        let mut i = 0;
+        while i < items.len() {
+            let mut candidate = items[..i].to_vec();
+            candidate.extend_from_slice(&items[(i + chunk).min(items.len())..]);
+            if fails(&candidate) {
+                items = candidate;
+                changed = true;
+            } else {
+                i += chunk;
+            }
+        }
+        if chunk > 1 || !changed {
+            chunk /= 2;
+        }
+    }
+    items
+}
+
+/// A minimal case on which an implementation disagrees with the model
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Failure {
+    /// Seed of the `Rng` the original case was generated with
+    pub seed: u64,
+    pub data: Vec<u32>,
+    pub ops: Vec<Op>,
+}
+
+/// The case as a Rust test asserting the model answers on a MaxSegmentTree
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "// shrunk from the case of seed {}", self.seed)?;
+        writeln!(f, "#[test]")?;
+        writeln!(f, "fn test_seed_{}() {{", self.seed)?;
+        writeln!(
+            f,
+            "    let mut tree = MaxSegmentTree::new(&{:?});",
+            self.data
+        )?;
+        let mut values = self.data.clone();
+        for &op in &self.ops {
+            let answer = op.apply_to(&mut values);
+            match op {
+                Op::Chmin { start, end, value } => {
+                    writeln!(f, "    tree.update({}, {}, {});", start, end, value)?
+                }
+                Op::Chmax { start, end, value } => {
+                    writeln!(f, "    tree.chmax({}, {}, {});", start, end, value)?
+                }
+                Op::Assign { start, end, value } => {
+                    writeln!(f, "    tree.assign({}, {}, {});", start, end, value)?
+                }
+                Op::Max { start, end } => writeln!(
+                    f,
+                    "    assert_eq!(tree.query({}, {}), {});",
+                    start,
+                    end,
+                    answer.unwrap()
+                )?,
+            }
+        }
+        write!(f, "}}")
+    }
+}
+
+// The smallest failing case found from (data, ops). Smaller values can make
+// more operations useless and the other way round, so both are shrunk in
+// turn until neither changes
+fn shrink_case<R>(data: &[u32], ops: &[Op], run: &mut R) -> (Vec<u32>, Vec<Op>)
+where
+    R: FnMut(&[u32], &[Op]) -> Vec<u32>,
+{
+    let mut fails = |data: &[u32], ops: &[Op]| run(data, ops) != run_model(data, ops);
+    let (mut data, mut ops) = (data.to_vec(), ops.to_vec());
+    loop {
+        let before = (data.clone(), ops.clone());
+        ops = shrink(&ops, |ops| fails(&data, ops));
+        // only the positions up to the last one used
+        let used = ops.iter().map(|&op| op.end() + 1).max().unwrap_or(1);
+        if fails(&data[..used], &ops) {
+            data.truncate(used);
+        }
+        // values as small as possible: 0, else the smallest failing power of 2
+        for i in 0..data.len() {
+            let mut value = 0;
+            while value < data[i] {
+                let old = std::mem::replace(&mut data[i], value);
+                if fails(&data, &ops) {
+                    break;
+                }
+                data[i] = old;
+                value = (value * 2).max(1);
+            }
+        }
+        if (&data, &ops) == (&before.0, &before.1) {
+            return (data, ops);
+        }
+    }
+}
+
+/// Stress tests `run` against the model on `iterations` random cases with
+/// seeds `first_seed`, `first_seed + 1`, ..., and returns the first failing
+/// one, shrunk
+pub fn check_against_model<R>(
+    first_seed: u64,
+    iterations: usize,
+    max_n: usize,
+    max_ops: usize,
+    mut run: R,
+) -> Result<(), Failure>
+where
+    R: FnMut(&[u32], &[Op]) -> Vec<u32>,
+{
+    type Case = (Vec<u32>, Vec<Op>);
+    let mismatch = stress(
+        first_seed,
+        iterations,
+        |rng| gen_case(rng, max_n, max_ops),
+        |(data, ops): &Case| run_model(data, ops),
+        |(data, ops): &Case| run(data, ops),
+    );
+    match mismatch {
+        Ok(()) => Ok(()),
+        Err(Mismatch {
+            seed,
+            input: (data, ops),
+            ..
+        }) => {
+            let (data, ops) = shrink_case(&data, &ops, &mut run);
+            Err(Failure { seed, data, ops })
+        }
+    }
+}
+
+/// Like `check_against_model`, but panics with the reproducer, for use in
+/// tests
+pub fn assert_model<R>(first_seed: u64, iterations: usize, max_n: usize, max_ops: usize, run: R)
+where
+    R: FnMut(&[u32], &[Op]) -> Vec<u32>,
+{
+    if let Err(failure) = check_against_model(first_seed, iterations, max_n, max_ops, run) {
+        panic!("{}", failure);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tree_matches_model() {
+        assert_model(95, 2000, 40, 60, run_tree);
+    }
+
+    #[test]
+    fn test_shrinks_to_minimal_reproducer() {
+        // forgets an update whose range starts right after the previous one
+        let buggy = |data: &[u32], ops: &[Op]| {
+            let mut kept = Vec::new();
+            let mut last_end = None;
+            for &op in ops {
+                let forgotten = match op {
+                    Op::Chmin { start, .. }
+                    | Op::Chmax { start, .. }
+                    | Op::Assign { start, .. } => last_end.is_some_and(|e: usize| e + 1 == start),
+                    Op::Max { .. } => false,
+                };
+                if !forgotten {
+                    kept.push(op);
+                }
+                last_end = Some(op.end());
+            }
+            run_model(data, &kept)
+        };
+        let failure = check_against_model(96, 1000, 40, 60, buggy).unwrap_err();
+        // an adjacent range, the forgotten update and a query seeing it
+        assert_eq!(failure.ops.len(), 3, "{}", failure);
+        let (start, end) = match failure.ops[2] {
+            Op::Max { start, end } => (start, end),
+            _ => panic!("{}", failure),
+        };
+        // cut right after the last position used
+        let used = failure.ops.iter().map(|op| op.end()).max().unwrap() + 1;
+        assert_eq!(failure.data.len(), used, "{}", failure);
+        assert!(failure.data[start..=end].iter().filter(|&&x| x > 0).count() <= 1);
+        assert!(!matches!(failure.ops[1], Op::Max { .. }));
+        assert_ne!(
+            buggy(&failure.data, &failure.ops),
+            run_model(&failure.data, &failure.ops)
+        );
+
+        let code = failure.to_string();
+        assert!(code.contains("let mut tree = MaxSegmentTree::new(&["));
+        assert!(["tree.update(", "tree.chmax(", "tree.assign("]
+            .iter()
+            .any(|call| code.contains(call)));
+        assert!(code.contains("assert_eq!(tree.query("));
+
+        assert_eq!(
+            shrink(&[5, 1, 7, 3, 9, 2], |s| s.contains(&7) && s.contains(&2)),
+            vec![7, 2]
+        );
+    }
+}
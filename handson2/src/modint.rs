@@ -0,0 +1,203 @@
+// ------- MODULAR INTEGERS -------
+// Integers modulo a compile-time prime `M`, always kept reduced in [0, M).
+// Division uses Fermat's little theorem, so `M` must be prime for `inv`.
+
+use std::fmt;
+use std::iter::{Product, Sum};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ModInt<const M: u32>(u32);
+
+pub type Mod998244353 = ModInt<998_244_353>;
+pub type Mod1000000007 = ModInt<1_000_000_007>;
+
+impl<const M: u32> ModInt<M> {
+    pub const fn modulus() -> u32 {
+        M
+    }
+
+    /// `value` reduced modulo `M`
+    pub const fn new(value: u64) -> Self {
+        ModInt((value % M as u64) as u32)
+    }
+
+    /// The representative in [0, M)
+    pub const fn value(self) -> u32 {
+        self.0
+    }
+
+    pub fn pow(self, mut exp: u64) -> Self {
+        let mut base = self;
+        let mut result = ModInt(1 % M);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result *= base;
+            }
+            base *= base;
+            exp >>= 1;
+        }
+        result
+    }
+
+    /// Multiplicative inverse, for a prime `M`
+    ///
+    /// # Panics
+    /// Panics on zero.
+    pub fn inv(self) -> Self {
+        assert!(self.0 != 0, "Zero has no inverse");
+        self.pow(M as u64 - 2)
+    }
+}
+
+impl<const M: u32> From<u32> for ModInt<M> {
+    fn from(value: u32) -> Self {
+        ModInt::new(value as u64)
+    }
+}
+
+impl<const M: u32> From<u64> for ModInt<M> {
+    fn from(value: u64) -> Self {
+        ModInt::new(value)
+    }
+}
+
+impl<const M: u32> From<usize> for ModInt<M> {
+    fn from(value: usize) -> Self {
+        ModInt::new(value as u64)
+    }
+}
+
+impl<const M: u32> From<i64> for ModInt<M> {
+    fn from(value: i64) -> Self {
+        ModInt(value.rem_euclid(M as i64) as u32)
+    }
+}
+
+impl<const M: u32> Add for ModInt<M> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let sum = self.0 as u64 + other.0 as u64;
+        ModInt(if sum >= M as u64 { sum - M as u64 } else { sum } as u32)
+    }
+}
+
+impl<const M: u32> Sub for ModInt<M> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        if self.0 >= other.0 {
+            ModInt(self.0 - other.0)
+        } else {
+            ModInt(M - other.0 + self.0)
+        }
+    }
+}
+
+impl<const M: u32> Mul for ModInt<M> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        ModInt((self.0 as u64 * other.0 as u64 % M as u64) as u32)
+    }
+}
+
+impl<const M: u32> Div for ModInt<M> {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, other: Self) -> Self {
+        self * other.inv()
+    }
+}
+
+impl<const M: u32> Neg for ModInt<M> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        ModInt(0) - self
+    }
+}
+
+macro_rules! impl_assign_ops {
+    ($($trait:ident $method:ident $op:tt),*) => {$(
+        impl<const M: u32> $trait for ModInt<M> {
+            fn $method(&mut self, other: Self) {
+                *self = *self $op other;
+            }
+        }
+    )*};
+}
+
+impl_assign_ops!(AddAssign add_assign +, SubAssign sub_assign -, MulAssign mul_assign *, DivAssign div_assign /);
+
+impl<const M: u32> Sum for ModInt<M> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(ModInt(0), |a, b| a + b)
+    }
+}
+
+impl<const M: u32> Product for ModInt<M> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(ModInt(1 % M), |a, b| a * b)
+    }
+}
+
+impl<const M: u32> fmt::Display for ModInt<M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl<const M: u32> fmt::Debug for ModInt<M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    type Mint = Mod998244353;
+
+    #[test]
+    fn test_arithmetic_against_u64() {
+        let mut rng = Rng::new(27);
+        let p = Mint::modulus() as u64;
+        for _ in 0..1000 {
+            let (a, b) = (rng.gen_range(0, p - 1), rng.gen_range(1, p - 1));
+            let (x, y) = (Mint::new(a), Mint::new(b));
+            assert_eq!((x + y).value() as u64, (a + b) % p);
+            assert_eq!((x - y).value() as u64, (a + p - b) % p);
+            assert_eq!((x * y).value() as u64, a * b % p);
+            assert_eq!((x / y) * y, x);
+            assert_eq!(x + (-x), Mint::new(0));
+        }
+        assert_eq!(Mint::from(-1i64).value(), 998_244_352);
+        assert_eq!(Mint::new(3).pow(0), Mint::new(1));
+        assert_eq!(
+            Mod1000000007::new(2).pow(1_000_000_006),
+            Mod1000000007::new(1)
+        );
+    }
+
+    #[test]
+    fn test_sum_product_and_assign() {
+        let values: Vec<Mint> = (1..=10u32).map(Mint::from).collect();
+        assert_eq!(values.iter().copied().sum::<Mint>(), Mint::new(55));
+        assert_eq!(
+            values.iter().copied().product::<Mint>(),
+            Mint::new(3_628_800)
+        );
+        let mut x = Mint::new(10);
+        x += Mint::new(5);
+        x *= Mint::new(2);
+        x -= Mint::new(1);
+        x /= Mint::new(29);
+        assert_eq!(x, Mint::new(1));
+        assert_eq!(format!("{}", Mint::new(42)), "42");
+    }
+}
@@ -0,0 +1,317 @@
+// ------- NUMBER THEORY -------
+// Modular arithmetic with a runtime modulus: extended gcd and inverses,
+// Chinese remainder theorem for any moduli, discrete logarithm by
+// baby-step giant-step and square roots modulo a prime by Tonelli-Shanks.
+// Floor sums reduce like the Euclidean algorithm in O(log m), and the O(sqrt n)
+// distinct values of n / i come with the ranges of i sharing them.
+
+use crate::hash::FastHashMap;
+
+pub fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// `(g, x, y)` with `g = gcd(a, b) = a x + b y` and `g >= 0`
+pub fn ext_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// Inverse of `a` modulo `m` in [0, m), None if they are not coprime
+pub fn mod_inverse(a: i64, m: i64) -> Option<i64> {
+    assert!(m > 0, "Modulus must be positive");
+    let (g, x, _) = ext_gcd(a.rem_euclid(m), m);
+    (g == 1).then(|| x.rem_euclid(m))
+}
+
+pub fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    (a as u128 * b as u128 % m as u128) as u64
+}
+
+pub fn pow_mod(mut base: u64, mut exp: u64, m: u64) -> u64 {
+    let mut result = 1 % m;
+    base %= m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exp >>= 1;
+    }
+    result
+}
+
+/// Solves the system x = r_i (mod m_i). Returns `(r, m)` with `m` the lcm of
+/// the moduli and `r` in [0, m) the unique solution modulo `m`, or None if
+/// the congruences contradict each other. The moduli need not be coprime.
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    let (mut r, mut m): (i128, i128) = (0, 1);
+    for &(ri, mi) in congruences {
+        assert!(mi > 0, "Modulus must be positive");
+        let (ri, mi) = ((ri as i128).rem_euclid(mi as i128), mi as i128);
+        // r + m t = ri (mod mi)  <=>  m t = ri - r (mod mi)
+        let (g, p, _) = ext_gcd(m as i64, mi as i64);
+        let g = g as i128;
+        if (ri - r) % g != 0 {
+            return None;
+        }
+        let step = mi / g;
+        let t = ((ri - r) / g % step * p as i128).rem_euclid(step);
+        r += m * t;
+        m *= step;
+        assert!(m <= i64::MAX as i128, "Modulus overflow");
+        r = r.rem_euclid(m);
+    }
+    Some((r as i64, m as i64))
+}
+
+/// Smallest `x >= 0` with `a^x = b (mod m)`, None if there is none.
+/// Works for any modulus in O(sqrt(m)) expected time.
+pub fn discrete_log(a: u64, b: u64, m: u64) -> Option<u64> {
+    assert!(m > 0, "Modulus must be positive");
+    let (a, b) = (a % m, b % m);
+    if b == 1 % m {
+        return Some(0);
+    }
+    // strip the common factors of a and m, keeping a^k * coef in front
+    let mut coef = 1 % m;
+    let mut k = 0;
+    let mut b = b;
+    let mut m = m;
+    loop {
+        let g = gcd(a, m);
+        if g == 1 {
+            break;
+        }
+        if b % g != 0 {
+            return None;
+        }
+        b /= g;
+        m /= g;
+        k += 1;
+        coef = mul_mod(coef, a / g, m);
+        if coef == b {
+            return Some(k);
+        }
+    }
+
+    // now a is invertible: coef * a^(n s - j) = b, with giant steps of size s
+    let s = (m as f64).sqrt().ceil() as u64 + 1;
+    let mut baby: FastHashMap<u64, u64> = FastHashMap::default();
+    let mut value = b;
+    for j in 0..s {
+        baby.insert(value, j); // b * a^j, later j overwrite earlier ones
+        value = mul_mod(value, a, m);
+    }
+    let giant = pow_mod(a, s, m);
+    let mut current = coef;
+    for i in 1..=s {
+        current = mul_mod(current, giant, m);
+        if let Some(&j) = baby.get(&current) {
+            return Some(i * s - j + k);
+        }
+    }
+    None
+}
+
+/// A square root of `a` modulo the prime `p`, None if `a` is not a quadratic
+/// residue. The other root is `p - x`.
+pub fn sqrt_mod(a: u64, p: u64) -> Option<u64> {
+    let a = a % p;
+    if p == 2 || a == 0 {
+        return Some(a);
+    }
+    // Euler's criterion
+    if pow_mod(a, (p - 1) / 2, p) != 1 {
+        return None;
+    }
+    if p % 4 == 3 {
+        return Some(pow_mod(a, p.div_ceil(4), p));
+    }
+    // p - 1 = q 2^s with q odd
+    let s = (p - 1).trailing_zeros();
+    let q = (p - 1) >> s;
+    let z = (2..p)
+        .find(|&z| pow_mod(z, (p - 1) / 2, p) == p - 1)
+        .unwrap();
+    let mut m = s;
+    let mut c = pow_mod(z, q, p);
+    let mut t = pow_mod(a, q, p);
+    let mut r = pow_mod(a, q.div_ceil(2), p);
+    while t != 1 {
+        // least i with t^(2^i) = 1
+        let mut i = 0;
+        let mut t2 = t;
+        while t2 != 1 {
+            t2 = mul_mod(t2, t2, p);
+            i += 1;
+        }
+        let b = pow_mod(c, 1 << (m - i - 1), p);
+        m = i;
+        c = mul_mod(b, b, p);
+        t = mul_mod(t, c, p);
+        r = mul_mod(r, b, p);
+    }
+    Some(r)
+}
+
+/// Sum of floor((a i + b) / m) for i in [0, n), assuming it fits in an i64
+pub fn floor_sum(n: i64, m: i64, a: i64, b: i64) -> i64 {
+    assert!(n >= 0 && m > 0, "Invalid arguments");
+    let pairs = n as i128 * (n as i128 - 1) / 2;
+    // reduce a and b into [0, m), moving their quotients out of the floor
+    let (qa, ra) = (a.div_euclid(m), a.rem_euclid(m));
+    let (qb, rb) = (b.div_euclid(m), b.rem_euclid(m));
+    let mut total = pairs * qa as i128 + n as i128 * qb as i128;
+    let (mut n, mut m, mut a, mut b) = (n as u128, m as u128, ra as u128, rb as u128);
+    loop {
+        if a >= m {
+            total += (n * n.saturating_sub(1) / 2 * (a / m)) as i128;
+            a %= m;
+        }
+        if b >= m {
+            total += (n * (b / m)) as i128;
+            b %= m;
+        }
+        // count the lattice points under the line with the axes swapped
+        let y_max = a * n + b;
+        if y_max < m {
+            break;
+        }
+        n = y_max / m;
+        b = y_max % m;
+        std::mem::swap(&mut m, &mut a);
+    }
+    total as i64
+}
+
+/// The ranges `(lo, hi, q)` of i in [1, n] where n / i = q, by increasing i
+pub fn floor_div_ranges(n: u64) -> impl Iterator<Item = (u64, u64, u64)> {
+    let mut lo = 1;
+    std::iter::from_fn(move || {
+        if lo > n {
+            return None;
+        }
+        let q = n / lo;
+        let hi = n / q;
+        let range = (lo, hi, q);
+        lo = hi + 1;
+        Some(range)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_ext_gcd_and_inverse() {
+        let mut rng = Rng::new(31);
+        for _ in 0..1000 {
+            let a = rng.gen_range_i64(-1_000_000, 1_000_000);
+            let b = rng.gen_range_i64(-1_000_000, 1_000_000);
+            let (g, x, y) = ext_gcd(a, b);
+            assert_eq!(g as u64, gcd(a.unsigned_abs(), b.unsigned_abs()));
+            assert_eq!(a * x + b * y, g);
+        }
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(6, 9), None);
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(1, 4), (3, 6)]), Some((9, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[]), Some((0, 1)));
+        let mut rng = Rng::new(32);
+        for _ in 0..300 {
+            let moduli: Vec<i64> = (0..3).map(|_| rng.gen_range_i64(1, 30)).collect();
+            let x = rng.gen_range_i64(0, 10_000);
+            let system: Vec<(i64, i64)> = moduli.iter().map(|&m| (x % m, m)).collect();
+            let (r, m) = crt(&system).unwrap();
+            assert_eq!(r, x % m);
+        }
+    }
+
+    #[test]
+    fn test_discrete_log_against_brute_force() {
+        let mut rng = Rng::new(33);
+        for _ in 0..300 {
+            let m = rng.gen_range(1, 200);
+            let a = rng.gen_range(0, m - 1);
+            let b = rng.gen_range(0, m - 1);
+            let expected = (0..2 * m).find(|&x| pow_mod(a, x, m) == b % m);
+            assert_eq!(discrete_log(a, b, m), expected, "{}^x = {} mod {}", a, b, m);
+        }
+        assert_eq!(
+            discrete_log(3, 13, 998_244_353).map(|x| pow_mod(3, x, 998_244_353)),
+            Some(13)
+        );
+    }
+
+    #[test]
+    fn test_sqrt_mod() {
+        for p in [2, 3, 5, 13, 17, 97, 998_244_353] {
+            let mut rng = Rng::new(p);
+            for _ in 0..200 {
+                let a = rng.gen_range(0, p - 1);
+                match sqrt_mod(a, p) {
+                    Some(r) => assert_eq!(mul_mod(r, r, p), a),
+                    None if p < 1000 => assert!((0..p).all(|x| mul_mod(x, x, p) != a)),
+                    None => assert_eq!(pow_mod(a, (p - 1) / 2, p), p - 1),
+                }
+            }
+        }
+        assert_eq!(sqrt_mod(3, 7), None);
+    }
+
+    #[test]
+    fn test_floor_sum_and_ranges() {
+        let mut rng = Rng::new(76);
+        for _ in 0..500 {
+            let n = rng.gen_range_i64(0, 50);
+            let m = rng.gen_range_i64(1, 30);
+            let a = rng.gen_range_i64(-100, 100);
+            let b = rng.gen_range_i64(-100, 100);
+            let brute: i64 = (0..n).map(|i| (a * i + b).div_euclid(m)).sum();
+            assert_eq!(floor_sum(n, m, a, b), brute);
+        }
+        // sum of i / 7 for i < 10^9, in closed form
+        let n = 1_000_000_000;
+        let full = n / 7;
+        let expected = 7 * full * (full - 1) / 2 + full * (n - 7 * full);
+        assert_eq!(floor_sum(n, 7, 1, 0), expected);
+
+        for n in (0..200).chain([1_000_000_007]) {
+            let ranges: Vec<(u64, u64, u64)> = floor_div_ranges(n).collect();
+            assert!(ranges.len() as u64 <= 2 * (n as f64).sqrt() as u64 + 1);
+            let mut next = 1;
+            for &(lo, hi, q) in &ranges {
+                assert_eq!(lo, next);
+                assert!(lo <= hi);
+                assert!(n / lo == q && n / hi == q);
+                next = hi + 1;
+            }
+            assert_eq!(next, n + 1);
+        }
+    }
+}
@@ -0,0 +1,228 @@
+// ------- OFFLINE DELETIONS -------
+// Turns an insert-only structure whose insertions can be undone in LIFO order
+// into one supporting deletions, when all the operations are known in
+// advance. Every item lives on an interval of time, which is split over the
+// nodes of a segment tree on time; a DFS over that tree inserts the items of
+// each node and rolls them back on the way up, so every item is inserted
+// O(log q) times.
+
+use crate::dsu::RollbackDsu;
+use crate::hash::FastHashMap;
+use crate::xor_basis::XorBasis;
+use std::hash::Hash;
+
+/// An insert-only structure with LIFO undo
+pub trait Rollback {
+    type Item;
+    fn insert(&mut self, item: &Self::Item);
+    /// Current version, to be passed to `rollback`
+    fn snapshot(&self) -> usize;
+    /// Undoes every insertion made after `snapshot` was taken
+    fn rollback(&mut self, snapshot: usize);
+}
+
+/// Edges `(u, v)` are unions
+impl Rollback for RollbackDsu {
+    type Item = (usize, usize);
+
+    fn insert(&mut self, &(u, v): &(usize, usize)) {
+        self.union(u, v);
+    }
+
+    fn snapshot(&self) -> usize {
+        RollbackDsu::snapshot(self)
+    }
+
+    fn rollback(&mut self, snapshot: usize) {
+        RollbackDsu::rollback(self, snapshot)
+    }
+}
+
+impl Rollback for XorBasis {
+    type Item = u64;
+
+    fn insert(&mut self, &x: &u64) {
+        XorBasis::insert(self, x);
+    }
+
+    fn snapshot(&self) -> usize {
+        XorBasis::snapshot(self)
+    }
+
+    fn rollback(&mut self, snapshot: usize) {
+        XorBasis::rollback(self, snapshot)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation<T, Q> {
+    /// Inserts one copy of the item
+    Insert(T),
+    /// Deletes one copy of the item, which must be present
+    Delete(T),
+    /// Asks a question about the items present right now
+    Query(Q),
+}
+
+/// Runs `operations` on `structure`, which must hold the items present
+/// before the first one, and answers the queries in order with `answer`.
+/// The structure is left as it was given.
+pub fn offline_delete<S, Q, A, F>(
+    structure: &mut S,
+    operations: &[Operation<S::Item, Q>],
+    mut answer: F,
+) -> Vec<A>
+where
+    S: Rollback,
+    S::Item: Clone + Hash + Eq,
+    F: FnMut(&S, &Q) -> A,
+{
+    let time = operations.len();
+    let mut tree: Vec<Vec<S::Item>> = vec![Vec::new(); 4 * time.max(1)];
+    // start times of the copies of each item currently present
+    let mut open: FastHashMap<S::Item, Vec<usize>> = FastHashMap::default();
+    for (t, operation) in operations.iter().enumerate() {
+        match operation {
+            Operation::Insert(item) => open.entry(item.clone()).or_default().push(t),
+            Operation::Delete(item) => {
+                let start = open
+                    .get_mut(item)
+                    .and_then(Vec::pop)
+                    .expect("Deleting a missing item");
+                // alive for the operations strictly between the insertion and now
+                if start + 1 < t {
+                    insert(&mut tree, 1, 0, time - 1, start + 1, t - 1, item);
+                }
+            }
+            Operation::Query(_) => {}
+        }
+    }
+    for (item, starts) in &open {
+        for &start in starts {
+            if start + 1 < time {
+                insert(&mut tree, 1, 0, time - 1, start + 1, time - 1, item);
+            }
+        }
+    }
+
+    let mut answers = Vec::new();
+    if time > 0 {
+        let mut solver = Solver {
+            tree: &tree,
+            operations,
+            answer: &mut answer,
+            answers: &mut answers,
+        };
+        solver.solve(structure, 1, 0, time - 1);
+    }
+    answers
+}
+
+// Stores `item` on the O(log q) nodes exactly covering [l, r]
+fn insert<T: Clone>(
+    tree: &mut [Vec<T>],
+    node: usize,
+    lo: usize,
+    hi: usize,
+    l: usize,
+    r: usize,
+    item: &T,
+) {
+    if r < lo || hi < l {
+        return;
+    }
+    if l <= lo && hi <= r {
+        tree[node].push(item.clone());
+        return;
+    }
+    let mid = (lo + hi) / 2;
+    insert(tree, 2 * node, lo, mid, l, r, item);
+    insert(tree, 2 * node + 1, mid + 1, hi, l, r, item);
+}
+
+// State shared by the DFS over the segment tree on time
+struct Solver<'a, S: Rollback, Q, A, F> {
+    tree: &'a [Vec<S::Item>],
+    operations: &'a [Operation<S::Item, Q>],
+    answer: &'a mut F,
+    answers: &'a mut Vec<A>,
+}
+
+impl<S: Rollback, Q, A, F: FnMut(&S, &Q) -> A> Solver<'_, S, Q, A, F> {
+    fn solve(&mut self, structure: &mut S, node: usize, lo: usize, hi: usize) {
+        let snapshot = structure.snapshot();
+        for item in &self.tree[node] {
+            structure.insert(item);
+        }
+        if lo == hi {
+            if let Operation::Query(query) = &self.operations[lo] {
+                self.answers.push((self.answer)(structure, query));
+            }
+        } else {
+            let mid = (lo + hi) / 2;
+            self.solve(structure, 2 * node, lo, mid);
+            self.solve(structure, 2 * node + 1, mid + 1, hi);
+        }
+        structure.rollback(snapshot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_xor_basis_against_rebuild() {
+        let mut rng = Rng::new(57);
+        for _ in 0..30 {
+            let mut present: Vec<u64> = Vec::new();
+            let mut operations = Vec::new();
+            let mut expected = Vec::new();
+            for _ in 0..rng.gen_range(0, 60) {
+                match rng.gen_range(0, 2) {
+                    0 => {
+                        let x = rng.gen_range(0, 63);
+                        present.push(x);
+                        operations.push(Operation::Insert(x));
+                    }
+                    1 if !present.is_empty() => {
+                        let i = rng.gen_range(0, present.len() as u64 - 1) as usize;
+                        operations.push(Operation::Delete(present.swap_remove(i)));
+                    }
+                    _ => {
+                        let mut basis = XorBasis::new();
+                        for &x in &present {
+                            basis.insert(x);
+                        }
+                        let y = rng.gen_range(0, 63);
+                        expected.push((basis.max_xor(), basis.contains(y)));
+                        operations.push(Operation::Query(y));
+                    }
+                }
+            }
+            let mut basis = XorBasis::new();
+            let answers = offline_delete(&mut basis, &operations, |b, &y| {
+                (b.max_xor(), b.contains(y))
+            });
+            assert_eq!(answers, expected);
+            assert_eq!(basis.rank(), 0);
+        }
+    }
+
+    #[test]
+    fn test_initial_items_stay() {
+        // items inserted before the operations are never deleted
+        let mut dsu = RollbackDsu::new(4);
+        dsu.union(0, 1);
+        let operations = [
+            Operation::Insert((1, 2)),
+            Operation::Query(()),
+            Operation::Delete((1, 2)),
+            Operation::Query(()),
+        ];
+        let answers = offline_delete(&mut dsu, &operations, |d, _| d.components());
+        assert_eq!(answers, vec![2, 3]);
+        assert_eq!(dsu.components(), 3);
+    }
+}
@@ -0,0 +1,366 @@
+// ------- PERSISTENT SEGMENT TREE -------
+// Every point update creates a new version sharing all untouched nodes with
+// the old one, so each version costs O(log n) extra nodes. Ranges are 0-based
+// and inclusive. VersionedArray hides the versions behind a working copy with
+// Git-like commit and checkout.
+
+use crate::segment_tree::{Max, Monoid};
+use crate::RangeError;
+
+/// Identifier of a version of the tree, version 0 is the initial array
+pub type Version = usize;
+
+#[derive(Clone)]
+pub(crate) struct Node<T> {
+    pub(crate) value: T,
+    pub(crate) left: usize,
+    pub(crate) right: usize,
+}
+
+pub struct PersistentSegmentTree<T: Monoid> {
+    n: usize,
+    nodes: Vec<Node<T>>, // Nodes of every version, children are indices in this vector
+    roots: Vec<usize>,   // Root node of each version
+}
+
+impl<T: Monoid> PersistentSegmentTree<T> {
+    /// Builds version 0 from `values`. Without values, every version is a
+    /// single identity node and no range is valid
+    pub fn new(values: &[T]) -> Self {
+        let mut tree = PersistentSegmentTree {
+            n: values.len(),
+            nodes: Vec::with_capacity(2 * values.len().max(1)),
+            roots: Vec::new(),
+        };
+        let root = match values.len() {
+            0 => tree.push(T::identity(), 0, 0),
+            n => tree.build(values, 0, n - 1),
+        };
+        tree.roots.push(root);
+        tree
+    }
+
+    fn build(&mut self, values: &[T], start: usize, end: usize) -> usize {
+        if start == end {
+            return self.push(values[start].clone(), 0, 0);
+        }
+        let mid = (start + end) / 2;
+        let left = self.build(values, start, mid);
+        let right = self.build(values, mid + 1, end);
+        let value = self.nodes[left].value.combine(&self.nodes[right].value);
+        self.push(value, left, right)
+    }
+
+    fn push(&mut self, value: T, left: usize, right: usize) -> usize {
+        self.nodes.push(Node { value, left, right });
+        self.nodes.len() - 1
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Number of versions created so far
+    pub fn versions(&self) -> usize {
+        self.roots.len()
+    }
+
+    /// Creates a new version equal to `version` with position `i` set to `value`
+    pub fn set(&mut self, version: Version, i: usize, value: T) -> Version {
+        self.update(version, i, |_| value)
+    }
+
+    /// Creates a new version equal to `version` with position `i` replaced by
+    /// `f(old value)`
+    pub fn update<F: FnOnce(&T) -> T>(&mut self, version: Version, i: usize, f: F) -> Version {
+        assert!(i < self.n, "Index out of range");
+        let root = self.update_recursive(self.roots[version], 0, self.n - 1, i, f);
+        self.roots.push(root);
+        self.roots.len() - 1
+    }
+
+    fn update_recursive<F: FnOnce(&T) -> T>(
+        &mut self,
+        node: usize,
+        start: usize,
+        end: usize,
+        i: usize,
+        f: F,
+    ) -> usize {
+        if start == end {
+            let value = f(&self.nodes[node].value);
+            return self.push(value, 0, 0);
+        }
+        let mid = (start + end) / 2;
+        let Node { left, right, .. } = self.nodes[node];
+        let (left, right) = if i <= mid {
+            (self.update_recursive(left, start, mid, i, f), right)
+        } else {
+            (left, self.update_recursive(right, mid + 1, end, i, f))
+        };
+        let value = self.nodes[left].value.combine(&self.nodes[right].value);
+        self.push(value, left, right)
+    }
+
+    /// Combines the values in `[l, r]` as they were in `version`. Panics with
+    /// the message of the RangeError if the range is not valid, see try_query
+    pub fn query(&self, version: Version, l: usize, r: usize) -> T {
+        if let Err(error) = self.check_range(l, r) {
+            panic!("{}", error);
+        }
+        self.query_recursive(self.roots[version], 0, self.n - 1, l, r)
+    }
+
+    /// Checked query: the values in `[l, r]` combined as they were in
+    /// `version`, or the reason why the range is not valid for this tree
+    pub fn try_query(&self, version: Version, l: usize, r: usize) -> Result<T, RangeError> {
+        self.check_range(l, r)?;
+        Ok(self.query_recursive(self.roots[version], 0, self.n - 1, l, r))
+    }
+
+    fn check_range(&self, l: usize, r: usize) -> Result<(), RangeError> {
+        if self.n == 0 {
+            Err(RangeError::EmptyTree)
+        } else if l > r {
+            Err(RangeError::StartAfterEnd { start: l, end: r })
+        } else if r >= self.n {
+            Err(RangeError::OutOfBounds {
+                index: r,
+                len: self.n,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    fn query_recursive(&self, node: usize, start: usize, end: usize, l: usize, r: usize) -> T {
+        if l <= start && end <= r {
+            return self.nodes[node].value.clone();
+        }
+        let mid = (start + end) / 2;
+        let mut result = T::identity();
+        if l <= mid {
+            result = self.query_recursive(self.nodes[node].left, start, mid, l, r);
+        }
+        if r > mid {
+            let right = self.query_recursive(self.nodes[node].right, mid + 1, end, l, r);
+            result = result.combine(&right);
+        }
+        result
+    }
+
+    /// Returns the value at position `i` in `version`
+    pub fn get(&self, version: Version, i: usize) -> T {
+        self.query(version, i, i)
+    }
+
+    // Root node of a version, used by structures walking the tree directly
+    pub(crate) fn root(&self, version: Version) -> usize {
+        self.roots[version]
+    }
+
+    pub(crate) fn node(&self, node: usize) -> &Node<T> {
+        &self.nodes[node]
+    }
+}
+
+/// Identifier of a commit of a VersionedArray
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct VersionId(usize);
+
+/// Array with a working copy that point writes change, snapshots of it taken
+/// by `commit` and restored by `checkout`, and range maximum queries. Every
+/// write costs O(log n) time and memory, commits and checkouts O(1)
+pub struct VersionedArray<T: Copy>
+where
+    Max<T>: Monoid,
+{
+    tree: PersistentSegmentTree<Max<T>>,
+    working: Version,      // Version of the tree holding the working copy
+    commits: Vec<Version>, // Version of the tree of every commit
+}
+
+impl<T: Copy> VersionedArray<T>
+where
+    Max<T>: Monoid,
+{
+    /// An array holding `values`, committed as the first version
+    pub fn new(values: &[T]) -> Self {
+        let values: Vec<Max<T>> = values.iter().map(|&x| Max(x)).collect();
+        VersionedArray {
+            tree: PersistentSegmentTree::new(&values),
+            working: 0,
+            commits: vec![0],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// The commit made by `new`
+    pub fn initial(&self) -> VersionId {
+        VersionId(0)
+    }
+
+    /// Snapshot of the working copy
+    pub fn commit(&mut self) -> VersionId {
+        self.commits.push(self.working);
+        VersionId(self.commits.len() - 1)
+    }
+
+    /// Replaces the working copy with the snapshot `id`, dropping the writes
+    /// made since the last commit or checkout
+    pub fn checkout(&mut self, id: VersionId) {
+        self.working = self.commits[id.0];
+    }
+
+    /// False right after `id` is committed or checked out, true once the
+    /// working copy has been written since, even back to the same values
+    pub fn differs_from(&self, id: VersionId) -> bool {
+        self.working != self.commits[id.0]
+    }
+
+    pub fn set(&mut self, i: usize, value: T) {
+        self.working = self.tree.set(self.working, i, Max(value));
+    }
+
+    pub fn get(&self, i: usize) -> T {
+        self.tree.get(self.working, i).0
+    }
+
+    /// Maximum of the working copy in `[l, r]`
+    pub fn range_max(&self, l: usize, r: usize) -> T {
+        self.tree.query(self.working, l, r).0
+    }
+
+    /// Maximum in `[l, r]` of the snapshot `id`, without checking it out
+    pub fn range_max_at(&self, id: VersionId, l: usize, r: usize) -> T {
+        self.tree.query(self.commits[id.0], l, r).0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use crate::segment_tree::{Max, Sum};
+
+    #[test]
+    fn test_versions_are_independent() {
+        let mut tree = PersistentSegmentTree::new(&[Sum(1i64), Sum(2), Sum(3), Sum(4)]);
+        let v1 = tree.set(0, 1, Sum(10));
+        let v2 = tree.update(v1, 3, |old| Sum(old.0 * 2));
+        let v3 = tree.set(0, 0, Sum(0));
+
+        assert_eq!(tree.query(0, 0, 3), Sum(10));
+        assert_eq!(tree.query(v1, 0, 3), Sum(18));
+        assert_eq!(tree.query(v2, 0, 3), Sum(22));
+        assert_eq!(tree.query(v3, 0, 3), Sum(9));
+        assert_eq!(tree.get(v2, 3), Sum(8));
+        assert_eq!(tree.versions(), 4);
+    }
+
+    #[test]
+    fn test_empty_and_invalid_ranges() {
+        let tree = PersistentSegmentTree::<Sum<i64>>::new(&[]);
+        assert!(tree.is_empty());
+        assert_eq!(tree.versions(), 1);
+        assert_eq!(tree.try_query(0, 0, 0), Err(RangeError::EmptyTree));
+        let array = VersionedArray::<i64>::new(&[]);
+        assert_eq!(array.len(), 0);
+
+        let tree = PersistentSegmentTree::new(&[Sum(1i64), Sum(2), Sum(3)]);
+        assert_eq!(tree.try_query(0, 1, 2), Ok(Sum(5)));
+        assert_eq!(
+            tree.try_query(0, 2, 1),
+            Err(RangeError::StartAfterEnd { start: 2, end: 1 })
+        );
+        assert_eq!(
+            tree.try_query(0, 0, 3),
+            Err(RangeError::OutOfBounds { index: 3, len: 3 })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "the tree is empty")]
+    fn test_empty_query_panics() {
+        PersistentSegmentTree::<Sum<i64>>::new(&[]).query(0, 0, 0);
+    }
+
+    #[test]
+    fn test_versioned_array() {
+        let mut rng = Rng::new(94);
+        let n = 20;
+        let initial: Vec<i64> = (0..n).map(|_| rng.gen_range_i64(-50, 50)).collect();
+        let mut array = VersionedArray::new(&initial);
+        let mut working = initial.clone();
+        let mut commits = vec![(array.initial(), initial)];
+        for _ in 0..2000 {
+            match rng.gen_range(0, 9) {
+                0 => {
+                    let id = array.commit();
+                    commits.push((id, working.clone()));
+                }
+                1 => {
+                    let (id, snapshot) = rng.choose(&commits).unwrap().clone();
+                    array.checkout(id);
+                    assert!(!array.differs_from(id));
+                    working = snapshot;
+                }
+                _ => {
+                    let i = rng.gen_range(0, n as u64 - 1) as usize;
+                    let value = rng.gen_range_i64(-50, 50);
+                    array.set(i, value);
+                    working[i] = value;
+                }
+            }
+            let a = rng.gen_range(0, n as u64 - 1) as usize;
+            let b = rng.gen_range(0, n as u64 - 1) as usize;
+            let (l, r) = (a.min(b), a.max(b));
+            assert_eq!(array.range_max(l, r), *working[l..=r].iter().max().unwrap());
+            assert_eq!(array.get(a), working[a]);
+            let (id, snapshot) = rng.choose(&commits).unwrap();
+            assert_eq!(
+                array.range_max_at(*id, l, r),
+                *snapshot[l..=r].iter().max().unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_random_against_snapshots() {
+        let mut rng = Rng::new(1);
+        let n = 37;
+        let initial: Vec<i64> = (0..n).map(|_| rng.gen_range(0, 100) as i64).collect();
+        let mut snapshots = vec![initial.clone()];
+        let mut tree =
+            PersistentSegmentTree::new(&initial.iter().map(|&v| Max(v)).collect::<Vec<_>>());
+
+        for _ in 0..300 {
+            let base = rng.gen_range(0, snapshots.len() as u64 - 1) as usize;
+            let i = rng.gen_range(0, n as u64 - 1) as usize;
+            let value = rng.gen_range(0, 100) as i64;
+            let mut snapshot = snapshots[base].clone();
+            snapshot[i] = value;
+            assert_eq!(tree.set(base, i, Max(value)), snapshots.len());
+            snapshots.push(snapshot);
+        }
+
+        for (version, snapshot) in snapshots.iter().enumerate() {
+            let a = rng.gen_range(0, n as u64 - 1) as usize;
+            let b = rng.gen_range(0, n as u64 - 1) as usize;
+            let (l, r) = (a.min(b), a.max(b));
+            let expected = *snapshot[l..=r].iter().max().unwrap();
+            assert_eq!(tree.query(version, l, r), Max(expected));
+        }
+    }
+}
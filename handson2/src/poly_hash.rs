@@ -0,0 +1,233 @@
+// ------- RANDOMIZED POLYNOMIAL HASHING -------
+// Double polynomial hashes modulo the Mersenne prime 2^61 - 1, with bases
+// drawn at the first use in each process: no fixed input can be crafted
+// against them, as anti-hash tests on Codeforces do against fixed bases and
+// 2^64 overflow hashing. Values are hashed as x + 1 after a leading 1, so
+// neither zeros nor the length can be folded away: two different sequences
+// collide with probability about (n / 2^61)^2. Rooted trees are hashed as the product of
+// (r[height] + child hash) over the children, with random r per height.
+// Ranges are 0-based and inclusive.
+
+use crate::graph::Graph;
+use crate::rng::Rng;
+use std::sync::OnceLock;
+
+const MOD: u64 = (1 << 61) - 1;
+
+/// Two independent hashes; equal sequences always have equal hashes
+pub type HashValue = (u64, u64);
+
+fn mul(a: u64, b: u64) -> u64 {
+    ((a as u128 * b as u128) % MOD as u128) as u64
+}
+
+fn add(a: u64, b: u64) -> u64 {
+    (a + b) % MOD
+}
+
+// Seed of every random choice of this process
+fn process_seed() -> u64 {
+    static SEED: OnceLock<u64> = OnceLock::new();
+    *SEED.get_or_init(|| Rng::from_entropy().next_u64())
+}
+
+// The two polynomial bases, in [2^32, MOD)
+fn bases() -> [u64; 2] {
+    let mut rng = Rng::new(process_seed());
+    [(); 2].map(|_| rng.gen_range(1 << 32, MOD - 1))
+}
+
+// Coefficient of a value, never 0 and below MOD
+fn symbol<T: Into<u32>>(x: T) -> u64 {
+    x.into() as u64 + 1
+}
+
+/// Hash of the whole sequence, comparable with `PolyHash::hash` of any
+/// sequence in the same process
+pub fn hash_slice<T: Copy + Into<u32>>(values: &[T]) -> HashValue {
+    let [b0, b1] = bases();
+    values.iter().fold((1, 1), |(h0, h1), &x| {
+        let x = symbol(x);
+        (add(mul(h0, b0), x), add(mul(h1, b1), x))
+    })
+}
+
+/// Prefix hashes of a sequence, hashing any of its ranges in O(1)
+pub struct PolyHash {
+    prefix: Vec<[u64; 2]>, // prefix[i] hashes the first i values, no leading 1
+    powers: Vec<[u64; 2]>, // powers[i] = bases^i
+}
+
+impl PolyHash {
+    pub fn new<T: Copy + Into<u32>>(values: &[T]) -> Self {
+        let bases = bases();
+        let mut prefix = vec![[0, 0]];
+        let mut powers = vec![[1, 1]];
+        for &x in values {
+            let x = symbol(x);
+            let (h, p) = (prefix.last().unwrap(), powers.last().unwrap());
+            let next_hash = [0, 1].map(|k| add(mul(h[k], bases[k]), x));
+            let next_power = [0, 1].map(|k| mul(p[k], bases[k]));
+            prefix.push(next_hash);
+            powers.push(next_power);
+        }
+        PolyHash { prefix, powers }
+    }
+
+    pub fn len(&self) -> usize {
+        self.prefix.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Hash of the values in `[l, r]`, equal to `hash_slice` of that range
+    pub fn hash(&self, l: usize, r: usize) -> HashValue {
+        assert!(l <= r && r < self.len(), "Invalid range");
+        let [h0, h1] = [0, 1].map(|k| {
+            // the leading 1 is worth bases^len
+            let power = self.powers[r + 1 - l][k];
+            let shifted = mul(self.prefix[l][k], power);
+            add(add(self.prefix[r + 1][k], MOD - shifted), power)
+        });
+        (h0, h1)
+    }
+
+    /// Whether the ranges `a` and `b` hold the same values
+    pub fn equal(&self, a: (usize, usize), b: (usize, usize)) -> bool {
+        a.1 - a.0 == b.1 - b.0 && self.hash(a.0, a.1) == self.hash(b.0, b.1)
+    }
+
+    /// Length of the longest common prefix of the suffixes at `i` and `j`,
+    /// in O(log n)
+    pub fn lcp(&self, i: usize, j: usize) -> usize {
+        let (mut lo, mut hi) = (0, self.len() - i.max(j));
+        while lo < hi {
+            let mid = (lo + hi).div_ceil(2);
+            if self.equal((i, i + mid - 1), (j, j + mid - 1)) {
+                lo = mid;
+            } else {
+                hi = mid - 1;
+            }
+        }
+        lo
+    }
+}
+
+/// Hash of the subtree of every vertex of the tree `graph` rooted at `root`:
+/// isomorphic rooted subtrees have equal hashes, and hashes are comparable
+/// across the trees hashed in the same process
+pub fn tree_hashes(graph: &Graph, root: usize) -> Vec<HashValue> {
+    let n = graph.len();
+    // children come after their parent
+    let (parent, order) = graph.rooted_order(root);
+
+    let mut height = vec![0; n];
+    for &v in order.iter().rev().filter(|&&v| v != root) {
+        height[parent[v]] = height[parent[v]].max(height[v] + 1);
+    }
+    // one random pair per height, the same in every call
+    let mut rng = Rng::new(process_seed() ^ 0x9e37_79b9_7f4a_7c15);
+    let r: Vec<[u64; 2]> = (0..=height[root])
+        .map(|_| [(); 2].map(|_| rng.gen_range(1, MOD - 1)))
+        .collect();
+
+    let mut product = vec![[1, 1]; n];
+    let mut hashes = vec![(0, 0); n];
+    for &v in order.iter().rev() {
+        let [h0, h1] = product[v];
+        hashes[v] = (h0, h1);
+        if v != root {
+            let p = parent[v];
+            let factor = r[height[p]];
+            product[p][0] = mul(product[p][0], add(factor[0], h0));
+            product[p][1] = mul(product[p][1], add(factor[1], h1));
+        }
+    }
+    hashes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_shapes::TreeShapes;
+
+    #[test]
+    fn test_ranges_against_slices() {
+        let mut rng = Rng::new(58);
+        // a small alphabet, so many ranges are equal
+        let s: Vec<u8> = (0..200).map(|_| b'a' + rng.gen_range(0, 1) as u8).collect();
+        let hashes = PolyHash::new(&s);
+        for _ in 0..2000 {
+            let i = rng.gen_range(0, 199) as usize;
+            let j = rng.gen_range(0, 199) as usize;
+            let len = rng.gen_range(1, (200 - i.max(j)) as u64) as usize;
+            let (a, b) = ((i, i + len - 1), (j, j + len - 1));
+            assert_eq!(hashes.equal(a, b), s[a.0..=a.1] == s[b.0..=b.1]);
+            assert_eq!(hashes.hash(a.0, a.1), hash_slice(&s[a.0..=a.1]));
+            let lcp = s[i..]
+                .iter()
+                .zip(&s[j..])
+                .take_while(|(x, y)| x == y)
+                .count();
+            assert_eq!(hashes.lcp(i, j), lcp);
+        }
+        assert_eq!(hash_slice(b"abc"), PolyHash::new(b"xabc").hash(1, 3));
+    }
+
+    #[test]
+    fn test_zeros_and_lengths() {
+        // leading zeros, and the values at both ends of the u32 range
+        let sequences: [&[u32]; 7] = [&[], &[0], &[0, 0], &[0, 7], &[7], &[u32::MAX], &[1]];
+        for (i, a) in sequences.iter().enumerate() {
+            for b in &sequences[..i] {
+                assert_ne!(hash_slice(a), hash_slice(b), "{:?} {:?}", a, b);
+            }
+        }
+        let hashes = PolyHash::new(&[0u32, 0, 7]);
+        assert_eq!(hashes.hash(1, 2), hash_slice(&[0u32, 7]));
+        assert_ne!(hashes.hash(0, 2), hashes.hash(1, 2));
+        assert_ne!(hashes.hash(0, 0), hashes.hash(0, 1));
+    }
+
+    #[test]
+    fn test_tree_hashes() {
+        // 0 has two children with one leaf each, and 5 with a path of two
+        let edges = [
+            (0, 1, 1),
+            (0, 2, 1),
+            (1, 3, 1),
+            (2, 4, 1),
+            (0, 5, 1),
+            (5, 6, 1),
+            (6, 7, 1),
+        ];
+        let hashes = tree_hashes(&Graph::from_undirected_edges(8, &edges), 0);
+        assert_eq!(hashes[1], hashes[2]);
+        assert_eq!(hashes[3], hashes[7]);
+        assert_ne!(hashes[1], hashes[5]);
+        // the same shapes in other trees, with other labels
+        let path = Graph::from_undirected_edges(3, &[(2, 0, 1), (0, 1, 1)]);
+        assert_eq!(tree_hashes(&path, 2)[2], hashes[5]);
+        assert_eq!(tree_hashes(&path, 1)[0], hashes[1]);
+
+        // equal hashes exactly for the subtrees with the same AHU shape
+        let mut rng = Rng::new(59);
+        let mut shapes = TreeShapes::new();
+        for _ in 0..20 {
+            let n = rng.gen_range(1, 60) as usize;
+            let edges: Vec<(usize, usize, i64)> = (1..n)
+                .map(|v| (rng.gen_range(0, v as u64 - 1) as usize, v, 1))
+                .collect();
+            let graph = Graph::from_undirected_edges(n, &edges);
+            let hashes = tree_hashes(&graph, 0);
+            let ids = shapes.subtree_ids(&graph, 0);
+            for u in 0..n {
+                for v in 0..n {
+                    assert_eq!(hashes[u] == hashes[v], ids[u] == ids[v]);
+                }
+            }
+        }
+    }
+}
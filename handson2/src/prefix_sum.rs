@@ -0,0 +1,171 @@
+// ------- PREFIX SUMS AND DIFFERENCE ARRAYS -------
+// Static range sums in O(1) after O(n) preprocessing, and offline range
+// additions materialized in O(n). Ranges are 0-based and inclusive.
+
+use std::ops::{Add, Sub};
+
+/// Numeric types usable in prefix sums, `Default` is the zero value
+pub trait Summable: Copy + Default + Add<Output = Self> + Sub<Output = Self> {}
+
+impl<T: Copy + Default + Add<Output = T> + Sub<Output = T>> Summable for T {}
+
+pub struct PrefixSum<T> {
+    prefix: Vec<T>, // prefix[i] = sum of the first i values
+}
+
+impl<T: Summable> PrefixSum<T> {
+    pub fn new(values: &[T]) -> Self {
+        let mut prefix = Vec::with_capacity(values.len() + 1);
+        prefix.push(T::default());
+        for &v in values {
+            prefix.push(*prefix.last().unwrap() + v);
+        }
+        PrefixSum { prefix }
+    }
+
+    pub fn len(&self) -> usize {
+        self.prefix.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Sum of the values in `[l, r]`
+    pub fn sum(&self, l: usize, r: usize) -> T {
+        assert!(l <= r && r < self.len(), "Invalid range");
+        self.prefix[r + 1] - self.prefix[l]
+    }
+
+    /// Sum of all the values
+    pub fn total(&self) -> T {
+        *self.prefix.last().unwrap()
+    }
+}
+
+pub struct PrefixSum2D<T> {
+    cols: usize,
+    prefix: Vec<Vec<T>>, // prefix[i][j] = sum of the top-left i x j block
+}
+
+impl<T: Summable> PrefixSum2D<T> {
+    /// Builds the sums of a grid given as rows of equal length
+    pub fn new(grid: &[Vec<T>]) -> Self {
+        let cols = grid.first().map_or(0, |row| row.len());
+        let mut prefix = vec![vec![T::default(); cols + 1]; grid.len() + 1];
+        for (i, row) in grid.iter().enumerate() {
+            assert_eq!(row.len(), cols, "Rows must have the same length");
+            for (j, &v) in row.iter().enumerate() {
+                prefix[i + 1][j + 1] = prefix[i][j + 1] + prefix[i + 1][j] - prefix[i][j] + v;
+            }
+        }
+        PrefixSum2D { cols, prefix }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.prefix.len() - 1
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Sum of the rectangle with corners `(r1, c1)` and `(r2, c2)`, inclusive
+    pub fn sum(&self, r1: usize, c1: usize, r2: usize, c2: usize) -> T {
+        assert!(r1 <= r2 && r2 < self.rows(), "Invalid row range");
+        assert!(c1 <= c2 && c2 < self.cols, "Invalid column range");
+        // add before subtracting so that unsigned sums never go below zero
+        self.prefix[r2 + 1][c2 + 1] + self.prefix[r1][c1]
+            - self.prefix[r1][c2 + 1]
+            - self.prefix[r2 + 1][c1]
+    }
+}
+
+/// Collects range additions and materializes the resulting array at the end.
+/// Values added and removed are kept apart, so unsigned types work as long as
+/// every final value is non-negative.
+pub struct DiffArray<T> {
+    added: Vec<T>,   // added[i] = total added by ranges starting at i
+    removed: Vec<T>, // removed[i] = total added by ranges ending at i - 1
+}
+
+impl<T: Summable> DiffArray<T> {
+    /// An array of `len` zeros
+    pub fn new(len: usize) -> Self {
+        DiffArray {
+            added: vec![T::default(); len],
+            removed: vec![T::default(); len],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.added.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds `value` to every position of `[l, r]`
+    pub fn add(&mut self, l: usize, r: usize, value: T) {
+        assert!(l <= r && r < self.len(), "Invalid range");
+        self.added[l] = self.added[l] + value;
+        if r + 1 < self.len() {
+            self.removed[r + 1] = self.removed[r + 1] + value;
+        }
+    }
+
+    /// Returns the array after all the additions
+    pub fn build(&self) -> Vec<T> {
+        let mut current = T::default();
+        self.added
+            .iter()
+            .zip(&self.removed)
+            .map(|(&added, &removed)| {
+                current = current + added - removed;
+                current
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_sum() {
+        let ps = PrefixSum::new(&[3i64, -1, 4, 1, -5]);
+        assert_eq!(ps.sum(0, 4), 2);
+        assert_eq!(ps.sum(1, 3), 4);
+        assert_eq!(ps.sum(4, 4), -5);
+        assert_eq!(ps.total(), 2);
+        assert!(PrefixSum::<u32>::new(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_prefix_sum_2d() {
+        let grid = vec![vec![1u64, 2, 3], vec![4, 5, 6], vec![7, 8, 9]];
+        let ps = PrefixSum2D::new(&grid);
+        assert_eq!(ps.sum(0, 0, 2, 2), 45);
+        assert_eq!(ps.sum(1, 1, 2, 2), 28);
+        assert_eq!(ps.sum(0, 2, 1, 2), 9);
+        assert_eq!(ps.sum(2, 0, 2, 0), 7);
+        let sparse = PrefixSum2D::new(&[vec![0u32, 0], vec![0, 1]]);
+        assert_eq!(sparse.sum(1, 1, 1, 1), 1);
+    }
+
+    #[test]
+    fn test_diff_array() {
+        let mut diff = DiffArray::new(5);
+        diff.add(0, 2, 2i32);
+        diff.add(1, 4, -1);
+        diff.add(4, 4, 10);
+        assert_eq!(diff.build(), vec![2, 1, 1, -1, 9]);
+        // unsigned values work as long as the result never goes below zero
+        let mut diff = DiffArray::new(3);
+        diff.add(0, 2, 1u32);
+        diff.add(1, 1, 1);
+        assert_eq!(diff.build(), vec![1, 2, 1]);
+    }
+}
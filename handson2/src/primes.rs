@@ -0,0 +1,372 @@
+// ------- PRIMES -------
+// A linear sieve of smallest prime factors up to n in O(n), for primality and
+// factorization of small numbers. Larger numbers are tested by Miller-Rabin:
+// the seven bases of Jim Sinclair make it deterministic on u64, and the first
+// thirteen primes on u128 below 3.3 * 10^24 (a strong probable prime test
+// beyond, with multiplications by doubling to avoid overflow).
+// The sieve also tabulates multiplicative functions such as the Mobius
+// function and divisor counts and sums in O(n), from f(p^e) on prime powers.
+// Primes up to 10^11 are counted in O(n^(3/4)) by Lucy_Hedgehog's method.
+
+use crate::number_theory::{mul_mod, pow_mod};
+use std::ops::{Add, Mul};
+
+/// Smallest prime factors of the numbers up to a bound
+pub struct Sieve {
+    smallest: Vec<u32>, // smallest[x] = smallest prime factor of x >= 2
+    primes: Vec<u32>,
+}
+
+impl Sieve {
+    /// Sieves the numbers in [0, n]
+    pub fn new(n: usize) -> Self {
+        let mut smallest = vec![0u32; n + 1];
+        let mut primes = Vec::new();
+        for x in 2..=n {
+            if smallest[x] == 0 {
+                smallest[x] = x as u32;
+                primes.push(x as u32);
+            }
+            // every composite is crossed once, by its smallest prime factor
+            for &p in &primes {
+                if p > smallest[x] || x * p as usize > n {
+                    break;
+                }
+                smallest[x * p as usize] = p;
+            }
+        }
+        Sieve { smallest, primes }
+    }
+
+    /// Largest number sieved
+    pub fn limit(&self) -> usize {
+        self.smallest.len() - 1
+    }
+
+    pub fn is_prime(&self, x: usize) -> bool {
+        assert!(x <= self.limit(), "Index out of range");
+        x >= 2 && self.smallest[x] as usize == x
+    }
+
+    /// The primes up to the limit, in increasing order
+    pub fn primes(&self) -> &[u32] {
+        &self.primes
+    }
+
+    /// Prime factorization of `x >= 1` as (prime, exponent) pairs, in
+    /// increasing order of primes
+    pub fn factorize(&self, mut x: usize) -> Vec<(usize, u32)> {
+        assert!(x >= 1 && x <= self.limit(), "Index out of range");
+        let mut factors: Vec<(usize, u32)> = Vec::new();
+        while x > 1 {
+            let p = self.smallest[x] as usize;
+            match factors.last_mut() {
+                Some((q, e)) if *q == p => *e += 1,
+                _ => factors.push((p, 1)),
+            }
+            x /= p;
+        }
+        factors
+    }
+
+    /// Values on [0, limit] of the multiplicative function with f(p^e) given
+    /// by `prime_power(p, e)`, where f(1) = 1 (f(0) is set to 1 as well)
+    pub fn multiplicative<T, F>(&self, prime_power: F) -> Vec<T>
+    where
+        T: Copy + Mul<Output = T> + From<u8>,
+        F: Fn(usize, u32) -> T,
+    {
+        let n = self.limit();
+        let mut values = vec![T::from(1); n + 1];
+        // x = rest[x] p^exp[x] for its smallest prime p, p not dividing rest[x]
+        let mut rest = vec![1usize; n + 1];
+        let mut exp = vec![0u32; n + 1];
+        for x in 2..=n {
+            let p = self.smallest[x] as usize;
+            let y = x / p;
+            if self.smallest[y] as usize == p {
+                rest[x] = rest[y];
+                exp[x] = exp[y] + 1;
+            } else {
+                rest[x] = y;
+                exp[x] = 1;
+            }
+            values[x] = values[rest[x]] * prime_power(p, exp[x]);
+        }
+        values
+    }
+
+    /// Mobius function on [0, limit]
+    pub fn mobius(&self) -> Vec<i64> {
+        self.multiplicative(|_, e| if e == 1 { -1 } else { 0 })
+    }
+
+    /// Number of divisors of each number in [0, limit]
+    pub fn divisor_counts(&self) -> Vec<u64> {
+        self.multiplicative(|_, e| e as u64 + 1)
+    }
+
+    /// Sum of the divisors of each number in [0, limit]
+    pub fn divisor_sums(&self) -> Vec<u64> {
+        // 1 + p + ... + p^e
+        self.multiplicative(|p, e| (0..=e).map(|i| (p as u64).pow(i)).sum())
+    }
+}
+
+/// Dirichlet convolution h(n) = sum over d | n of f(d) g(n / d), on indices
+/// 1 to n in O(n log n) (index 0 is ignored and left as the default)
+pub fn dirichlet_convolution<T>(f: &[T], g: &[T]) -> Vec<T>
+where
+    T: Copy + Default + Add<Output = T> + Mul<Output = T>,
+{
+    assert_eq!(f.len(), g.len(), "Dimension mismatch");
+    let n = f.len();
+    let mut h = vec![T::default(); n];
+    for d in 1..n {
+        for (k, &gk) in g.iter().enumerate().take((n - 1) / d + 1).skip(1) {
+            h[d * k] = h[d * k] + f[d] * gk;
+        }
+    }
+    h
+}
+
+/// Number of primes <= n, in O(n^(3/4))
+pub fn prime_count(n: u64) -> u64 {
+    if n < 2 {
+        return 0;
+    }
+    let r = n.isqrt() as usize;
+    // small[v] = count for v, large[i] = count for n / i, of the numbers in
+    // [2, v] not crossed by the primes processed so far
+    let mut small: Vec<u64> = (0..=r as u64).map(|v| v.saturating_sub(1)).collect();
+    let mut large: Vec<u64> = (0..=r as u64).map(|i| (n / i.max(1)) - 1).collect();
+    for p in 2..=r {
+        if small[p] == small[p - 1] {
+            continue;
+        }
+        let below = small[p - 1];
+        let square = (p * p) as u64;
+        let last = r.min((n / square) as usize);
+        for i in 1..=last {
+            let d = i * p;
+            let crossed = if d <= r {
+                large[d]
+            } else {
+                small[(n / d as u64) as usize]
+            };
+            large[i] -= crossed - below;
+        }
+        for v in (p * p..=r).rev() {
+            small[v] -= small[v / p] - below;
+        }
+    }
+    large[1]
+}
+
+/// The `k`-th prime, 1-based (the first prime is 2)
+pub fn nth_prime(k: usize) -> u64 {
+    assert!(k >= 1, "Primes are counted from 1");
+    // p_k < k (ln k + ln ln k) for k >= 6
+    let k_f = k.max(6) as f64;
+    let bound = (k_f * (k_f.ln() + k_f.ln().ln())) as usize + 1;
+    Sieve::new(bound).primes()[k - 1] as u64
+}
+
+/// Deterministic primality test for u64
+pub fn is_prime(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for p in [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37] {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    [2, 325, 9375, 28178, 450775, 9780504, 1795265022]
+        .iter()
+        .all(|&a| {
+            let a = a % n;
+            if a == 0 {
+                return true;
+            }
+            let mut x = pow_mod(a, d, n);
+            if x == 1 || x == n - 1 {
+                return true;
+            }
+            for _ in 1..s {
+                x = mul_mod(x, x, n);
+                if x == n - 1 {
+                    return true;
+                }
+            }
+            false
+        })
+}
+
+// (a + b) mod m for a, b < m, without overflow
+fn add_mod_u128(a: u128, b: u128, m: u128) -> u128 {
+    if a >= m - b {
+        a - (m - b)
+    } else {
+        a + b
+    }
+}
+
+// a b mod m for a, b < m by doubling, without overflow
+fn mul_mod_u128(mut a: u128, mut b: u128, m: u128) -> u128 {
+    let mut result = 0;
+    while b > 0 {
+        if b & 1 == 1 {
+            result = add_mod_u128(result, a, m);
+        }
+        a = add_mod_u128(a, a, m);
+        b >>= 1;
+    }
+    result
+}
+
+fn pow_mod_u128(mut base: u128, mut exp: u128, m: u128) -> u128 {
+    let mut result = 1 % m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_mod_u128(result, base, m);
+        }
+        base = mul_mod_u128(base, base, m);
+        exp >>= 1;
+    }
+    result
+}
+
+/// Primality test for u128, deterministic below 3.3 * 10^24
+pub fn is_prime_u128(n: u128) -> bool {
+    if n <= u64::MAX as u128 {
+        return is_prime(n as u64);
+    }
+    let bases = [2u128, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+    if bases.iter().any(|&p| n.is_multiple_of(p)) {
+        return false;
+    }
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    bases.iter().all(|&a| {
+        let mut x = pow_mod_u128(a, d, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..s {
+            x = mul_mod_u128(x, x, n);
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+/// Smallest prime `>= n`
+///
+/// # Panics
+/// Panics past the largest u64 prime, 2^64 - 59.
+pub fn next_prime(n: u64) -> u64 {
+    (n.max(2)..=u64::MAX)
+        .find(|&x| is_prime(x))
+        .expect("No u64 prime that large")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_sieve() {
+        let sieve = Sieve::new(1000);
+        assert_eq!(&sieve.primes()[..10], &[2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+        assert_eq!(sieve.primes().len(), 168);
+        for x in 0..=1000 {
+            let trial = x >= 2 && (2..x).take_while(|d| d * d <= x).all(|d| x % d != 0);
+            assert_eq!(sieve.is_prime(x), trial);
+            assert_eq!(is_prime(x as u64), trial);
+            if x >= 1 {
+                let product: usize = sieve.factorize(x).iter().map(|&(p, e)| p.pow(e)).product();
+                assert_eq!(product, x);
+            }
+        }
+        assert_eq!(sieve.factorize(360), vec![(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(nth_prime(1), 2);
+        assert_eq!(nth_prime(168), 997);
+        assert_eq!(nth_prime(10_000), 104_729);
+    }
+
+    #[test]
+    fn test_multiplicative() {
+        let n = 2000;
+        let sieve = Sieve::new(n);
+        let (mobius, counts, sums) = (sieve.mobius(), sieve.divisor_counts(), sieve.divisor_sums());
+        for x in 1..=n {
+            let divisors: Vec<usize> = (1..=x).filter(|&d| x.is_multiple_of(d)).collect();
+            assert_eq!(counts[x], divisors.len() as u64);
+            assert_eq!(sums[x], divisors.iter().sum::<usize>() as u64);
+            let factors = sieve.factorize(x);
+            let expected = if factors.iter().any(|&(_, e)| e > 1) {
+                0
+            } else if factors.len().is_multiple_of(2) {
+                1
+            } else {
+                -1
+            };
+            assert_eq!(mobius[x], expected);
+        }
+        // mu * 1 is the identity of the convolution, 1 * 1 counts divisors
+        let ones = vec![1i64; n + 1];
+        let identity = dirichlet_convolution(&mobius, &ones);
+        assert!((1..=n).all(|x| identity[x] == (x == 1) as i64));
+        let divisors = dirichlet_convolution(&ones, &ones);
+        assert!((1..=n).all(|x| divisors[x] == counts[x] as i64));
+    }
+
+    #[test]
+    fn test_prime_count() {
+        let sieve = Sieve::new(100_000);
+        let mut count = 0;
+        for x in 0..=100_000 {
+            if sieve.is_prime(x) {
+                count += 1;
+            }
+            if x < 2000 || x.is_multiple_of(997) {
+                assert_eq!(prime_count(x as u64), count);
+            }
+        }
+        assert_eq!(prime_count(1_000_000_000), 50_847_534);
+        assert_eq!(prime_count(10_000_000_000), 455_052_511);
+    }
+
+    #[test]
+    fn test_large_primes() {
+        assert!(is_prime(998_244_353) && is_prime(1_000_000_007));
+        assert!(is_prime((1 << 61) - 1));
+        assert!(is_prime(u64::MAX - 58));
+        // strong pseudoprimes to many small bases
+        assert!(!is_prime(3_215_031_751));
+        assert!(!is_prime(3_825_123_056_546_413_051));
+        assert_eq!(next_prime(1_000_000_000), 1_000_000_007);
+        assert_eq!(next_prime(0), 2);
+        assert_eq!(next_prime(u64::MAX - 70), u64::MAX - 58);
+
+        assert!(is_prime_u128((1 << 89) - 1));
+        assert!(!is_prime_u128(((1 << 61) - 1) * 1_000_000_007));
+        // 2^64 + 13 is the first prime past u64
+        assert!(is_prime_u128((1 << 64) + 13));
+        assert!((1..13).all(|k| !is_prime_u128((1 << 64) + k)));
+
+        // products of two random primes are composite
+        let mut rng = Rng::new(78);
+        for _ in 0..20 {
+            let p = next_prime(rng.gen_range(1 << 40, 1 << 62)) as u128;
+            let q = next_prime(rng.gen_range(1 << 40, 1 << 62)) as u128;
+            assert!(!is_prime_u128(p * q));
+        }
+    }
+}
@@ -0,0 +1,123 @@
+// ------- PRUFER SEQUENCES -------
+// Bijection between the labeled trees on n >= 2 vertices and the sequences of
+// n - 2 labels in [0, n), both directions in O(n): repeatedly remove the
+// smallest leaf and record its neighbour. Cayley's formula n^(n - 2) follows.
+
+use crate::graph::Graph;
+
+/// Prufer sequence of the tree `graph` with at least two vertices
+pub fn prufer_encode(graph: &Graph) -> Vec<usize> {
+    let n = graph.len();
+    assert!(n >= 2, "Prufer sequences need at least two vertices");
+    assert_eq!(graph.edge_count(), 2 * (n - 1), "The graph is not a tree");
+    // parent of every vertex when rooted at n - 1, the vertex never removed
+    let (parent, _) = graph.rooted_order(n - 1);
+
+    let mut degree: Vec<usize> = (0..n).map(|v| graph.degree(v)).collect();
+    let mut sequence = Vec::with_capacity(n - 2);
+    // `ptr` scans for the smallest leaf; a removal can only create a smaller
+    // leaf at the parent, which is then taken right away
+    let mut ptr = (0..n).find(|&v| degree[v] == 1).unwrap();
+    let mut leaf = ptr;
+    for _ in 0..n - 2 {
+        let next = parent[leaf];
+        sequence.push(next);
+        degree[next] -= 1;
+        if degree[next] == 1 && next < ptr {
+            leaf = next;
+        } else {
+            ptr += 1;
+            while degree[ptr] != 1 {
+                ptr += 1;
+            }
+            leaf = ptr;
+        }
+    }
+    sequence
+}
+
+/// Edges of the tree on `sequence.len() + 2` vertices with Prufer sequence
+/// `sequence`
+pub fn prufer_decode_edges(sequence: &[usize]) -> Vec<(usize, usize)> {
+    let n = sequence.len() + 2;
+    let mut degree = vec![1; n];
+    for &v in sequence {
+        assert!(v < n, "Index out of range");
+        degree[v] += 1;
+    }
+    let mut edges = Vec::with_capacity(n - 1);
+    let mut ptr = (0..n).find(|&v| degree[v] == 1).unwrap();
+    let mut leaf = ptr;
+    for &v in sequence {
+        edges.push((leaf, v));
+        degree[v] -= 1;
+        if degree[v] == 1 && v < ptr {
+            leaf = v;
+        } else {
+            ptr += 1;
+            while degree[ptr] != 1 {
+                ptr += 1;
+            }
+            leaf = ptr;
+        }
+    }
+    // the two vertices left are the current leaf and n - 1
+    edges.push((leaf, n - 1));
+    edges
+}
+
+/// The tree with Prufer sequence `sequence`, with unit edge weights
+pub fn prufer_decode(sequence: &[usize]) -> Graph {
+    let edges: Vec<(usize, usize, i64)> = prufer_decode_edges(sequence)
+        .into_iter()
+        .map(|(u, v)| (u, v, 1))
+        .collect();
+    Graph::from_undirected_edges(sequence.len() + 2, &edges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_example() {
+        // 0 - 3 - 1, 2 - 3 - 4 - 5
+        let graph = Graph::from_undirected_edges(
+            6,
+            &[(0, 3, 1), (1, 3, 1), (2, 3, 1), (3, 4, 1), (4, 5, 1)],
+        );
+        assert_eq!(prufer_encode(&graph), vec![3, 3, 3, 4]);
+        let mut edges = prufer_decode_edges(&[3, 3, 3, 4]);
+        edges.sort_unstable();
+        assert_eq!(edges, vec![(0, 3), (1, 3), (2, 3), (3, 4), (4, 5)]);
+        assert_eq!(prufer_decode_edges(&[]), vec![(0, 1)]);
+    }
+
+    #[test]
+    fn test_round_trip_and_cayley() {
+        let mut rng = Rng::new(51);
+        for _ in 0..200 {
+            let n = rng.gen_range(2, 30) as usize;
+            let sequence: Vec<usize> = (0..n - 2)
+                .map(|_| rng.gen_range(0, n as u64 - 1) as usize)
+                .collect();
+            assert_eq!(prufer_encode(&prufer_decode(&sequence)), sequence);
+        }
+
+        // every sequence of length 3 over 5 labels gives a distinct tree: 5^3
+        let mut trees = Vec::new();
+        for code in 0..125 {
+            let sequence = [code / 25, code / 5 % 5, code % 5];
+            let mut edges: Vec<(usize, usize)> = prufer_decode_edges(&sequence)
+                .into_iter()
+                .map(|(u, v)| (u.min(v), u.max(v)))
+                .collect();
+            edges.sort_unstable();
+            trees.push(edges);
+        }
+        trees.sort();
+        trees.dedup();
+        assert_eq!(trees.len(), 125);
+    }
+}
@@ -0,0 +1,134 @@
+// ------- RANGE K-TH SMALLEST -------
+// Static order statistics on ranges of an array. Version i of a persistent
+// segment tree over the compressed values counts the occurrences of each value
+// in the prefix arr[0..i], so the counts of a range [l, r] are the difference
+// between versions r + 1 and l. Ranges are 0-based and inclusive.
+
+use crate::persistent::PersistentSegmentTree;
+use crate::segment_tree::Sum;
+
+pub struct RangeKth {
+    n: usize,
+    values: Vec<u32>, // Sorted distinct values of the array
+    counts: Option<PersistentSegmentTree<Sum<usize>>>, // None for an empty array
+}
+
+impl RangeKth {
+    pub fn new(arr: &[u32]) -> Self {
+        let mut values = arr.to_vec();
+        values.sort_unstable();
+        values.dedup();
+        if values.is_empty() {
+            return RangeKth {
+                n: 0,
+                values,
+                counts: None,
+            };
+        }
+
+        let mut counts = PersistentSegmentTree::new(&vec![Sum(0); values.len()]);
+        for (i, x) in arr.iter().enumerate() {
+            let idx = values.binary_search(x).unwrap();
+            // version i + 1 is built on top of version i
+            counts.update(i, idx, |c| Sum(c.0 + 1));
+        }
+        RangeKth {
+            n: arr.len(),
+            values,
+            counts: Some(counts),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Returns the k-th smallest value (k starting from 0) in `arr[l..=r]`
+    ///
+    /// # Panics
+    /// Panics if the range is invalid or `k > r - l`.
+    pub fn kth(&self, l: usize, r: usize, k: usize) -> u32 {
+        self.check_range(l, r);
+        assert!(k <= r - l, "k is larger than the range");
+        let counts = self.counts.as_ref().unwrap();
+
+        // walk down both versions at once, going left while the left half
+        // contains more than k elements of the range
+        let (mut hi, mut lo) = (counts.root(r + 1), counts.root(l));
+        let (mut start, mut end) = (0, self.values.len() - 1);
+        let mut k = k;
+        while start < end {
+            let mid = (start + end) / 2;
+            let (hi_node, lo_node) = (counts.node(hi), counts.node(lo));
+            let in_left = counts.node(hi_node.left).value.0 - counts.node(lo_node.left).value.0;
+            if k < in_left {
+                hi = hi_node.left;
+                lo = lo_node.left;
+                end = mid;
+            } else {
+                k -= in_left;
+                hi = hi_node.right;
+                lo = lo_node.right;
+                start = mid + 1;
+            }
+        }
+        self.values[start]
+    }
+
+    /// Counts the values strictly smaller than `x` in `arr[l..=r]`
+    pub fn count_less(&self, l: usize, r: usize, x: u32) -> usize {
+        self.check_range(l, r);
+        let idx = self.values.partition_point(|&v| v < x);
+        if idx == 0 {
+            return 0;
+        }
+        let counts = self.counts.as_ref().unwrap();
+        counts.query(r + 1, 0, idx - 1).0 - counts.query(l, 0, idx - 1).0
+    }
+
+    fn check_range(&self, l: usize, r: usize) {
+        assert!(l <= r && r < self.n, "Invalid range");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_small_example() {
+        let rk = RangeKth::new(&[5, 1, 4, 1, 3, 9]);
+        assert_eq!(rk.kth(0, 5, 0), 1);
+        assert_eq!(rk.kth(0, 5, 1), 1);
+        assert_eq!(rk.kth(0, 5, 5), 9);
+        assert_eq!(rk.kth(2, 4, 1), 3);
+        assert_eq!(rk.count_less(0, 5, 4), 3);
+        assert_eq!(rk.count_less(0, 5, 0), 0);
+        assert_eq!(rk.count_less(0, 5, 100), 6);
+        assert_eq!(rk.count_less(1, 3, 2), 2);
+        assert!(RangeKth::new(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_random_against_sorting() {
+        let mut rng = Rng::new(77);
+        let arr: Vec<u32> = (0..150).map(|_| rng.gen_range(0, 40) as u32).collect();
+        let rk = RangeKth::new(&arr);
+        for _ in 0..500 {
+            let a = rng.gen_range(0, 149) as usize;
+            let b = rng.gen_range(0, 149) as usize;
+            let (l, r) = (a.min(b), a.max(b));
+            let mut sorted = arr[l..=r].to_vec();
+            sorted.sort_unstable();
+            let k = rng.gen_range(0, (r - l) as u64) as usize;
+            assert_eq!(rk.kth(l, r, k), sorted[k]);
+            let x = rng.gen_range(0, 41) as u32;
+            assert_eq!(rk.count_less(l, r, x), sorted.partition_point(|&v| v < x));
+        }
+    }
+}
@@ -0,0 +1,176 @@
+// ------- REACHABILITY -------
+// Transitive closure of a directed graph as one bitset row per strongly
+// connected component. Tarjan's algorithm finds the components in reverse
+// topological order, so every row is the OR of the rows of its successors,
+// already complete, in O(n m / 64) overall. The rows take c^2 / 8 bytes for
+// c components, about 300 MB for 5 * 10^4 vertices in the worst case.
+
+use crate::bitset::BitSet;
+use crate::graph::Graph;
+
+const NONE: usize = usize::MAX;
+
+pub struct Reachability {
+    comp: Vec<usize>,   // Component of each vertex
+    sizes: Vec<usize>,  // Number of vertices of each component
+    reach: Vec<BitSet>, // Components reachable from each component
+}
+
+impl Reachability {
+    pub fn new(graph: &Graph) -> Self {
+        let comp = strongly_connected_components(graph);
+        let count = comp.iter().map(|&c| c + 1).max().unwrap_or(0);
+        let mut sizes = vec![0; count];
+        let mut members = vec![Vec::new(); count];
+        for (v, &c) in comp.iter().enumerate() {
+            sizes[c] += 1;
+            members[c].push(v);
+        }
+        let mut reach: Vec<BitSet> = Vec::with_capacity(count);
+        // seen[d] = c once the row of d has been merged into the row of c
+        let mut seen = vec![NONE; count];
+        for (c, vertices) in members.iter().enumerate() {
+            let mut row = BitSet::new(count);
+            row.set(c, true);
+            for &v in vertices {
+                for &(w, _) in graph.neighbors(v) {
+                    let d = comp[w];
+                    if d != c && seen[d] != c {
+                        seen[d] = c;
+                        row |= &reach[d];
+                    }
+                }
+            }
+            reach.push(row);
+        }
+        Reachability { comp, sizes, reach }
+    }
+
+    pub fn len(&self) -> usize {
+        self.comp.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.comp.is_empty()
+    }
+
+    /// Strongly connected component of `v`, in reverse topological order
+    pub fn component(&self, v: usize) -> usize {
+        self.comp[v]
+    }
+
+    /// True if there is a path from `u` to `v`, every vertex reaching itself
+    pub fn reachable(&self, u: usize, v: usize) -> bool {
+        self.reach[self.comp[u]].get(self.comp[v])
+    }
+
+    /// Number of vertices reachable from `u`, itself included
+    pub fn reachable_count(&self, u: usize) -> usize {
+        self.reach[self.comp[u]].ones().map(|c| self.sizes[c]).sum()
+    }
+}
+
+/// Strongly connected component of every vertex, numbered in reverse
+/// topological order of the condensation (Tarjan, iterative)
+pub fn strongly_connected_components(graph: &Graph) -> Vec<usize> {
+    let n = graph.len();
+    let mut index = vec![NONE; n];
+    let mut low = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut comp = vec![NONE; n];
+    let (mut counter, mut count) = (0, 0);
+    for s in 0..n {
+        if index[s] != NONE {
+            continue;
+        }
+        // (vertex, next edge to explore)
+        let mut calls = vec![(s, 0)];
+        index[s] = counter;
+        low[s] = counter;
+        counter += 1;
+        stack.push(s);
+        on_stack[s] = true;
+        while let Some(&mut (v, ref mut next)) = calls.last_mut() {
+            if let Some(&(w, _)) = graph.neighbors(v).get(*next) {
+                *next += 1;
+                if index[w] == NONE {
+                    index[w] = counter;
+                    low[w] = counter;
+                    counter += 1;
+                    stack.push(w);
+                    on_stack[w] = true;
+                    calls.push((w, 0));
+                } else if on_stack[w] {
+                    low[v] = low[v].min(index[w]);
+                }
+                continue;
+            }
+            calls.pop();
+            if let Some(&(parent, _)) = calls.last() {
+                low[parent] = low[parent].min(low[v]);
+            }
+            if low[v] == index[v] {
+                loop {
+                    let w = stack.pop().unwrap();
+                    on_stack[w] = false;
+                    comp[w] = count;
+                    if w == v {
+                        break;
+                    }
+                }
+                count += 1;
+            }
+        }
+    }
+    comp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_against_dfs() {
+        let mut rng = Rng::new(80);
+        for _ in 0..40 {
+            let n = rng.gen_range(1, 120) as usize;
+            let mut graph = Graph::new(n);
+            for _ in 0..rng.gen_range(0, 2 * n as u64) {
+                let u = rng.gen_range(0, n as u64 - 1) as usize;
+                let v = rng.gen_range(0, n as u64 - 1) as usize;
+                graph.add_edge(u, v, 1);
+            }
+            let closure = Reachability::new(&graph);
+            assert_eq!(closure.len(), n);
+            for u in 0..n {
+                let mut seen = vec![false; n];
+                let mut todo = vec![u];
+                seen[u] = true;
+                while let Some(v) = todo.pop() {
+                    for &(w, _) in graph.neighbors(v) {
+                        if !seen[w] {
+                            seen[w] = true;
+                            todo.push(w);
+                        }
+                    }
+                }
+                for (v, &reached) in seen.iter().enumerate() {
+                    assert_eq!(closure.reachable(u, v), reached);
+                    // mutually reachable iff in the same component
+                    let same = closure.component(u) == closure.component(v);
+                    assert_eq!(same, reached && closure.reachable(v, u));
+                    // reachable components come earlier
+                    if reached {
+                        assert!(closure.component(v) <= closure.component(u));
+                    }
+                }
+                assert_eq!(
+                    closure.reachable_count(u),
+                    seen.iter().filter(|&&s| s).count()
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,131 @@
+// ------- OFFLINE RECTANGLE SUMS -------
+// Sums of weighted points inside axis-aligned rectangles, all queries known
+// in advance. A sweep over x inserts the points into a Fenwick tree indexed
+// by compressed y, and each rectangle is the difference of two prefixes in x.
+// O((n + q) log n).
+
+use crate::fenwick::FenwickTree;
+use crate::prefix_sum::Summable;
+
+/// For each rectangle `(x1, x2, y1, y2)`, the sum of the weights of the
+/// points `(x, y, w)` with `x1 <= x <= x2` and `y1 <= y <= y2`
+pub fn rectangle_sums<T: Summable>(
+    points: &[(i64, i64, T)],
+    rects: &[(i64, i64, i64, i64)],
+) -> Vec<T> {
+    let mut ys: Vec<i64> = points.iter().map(|&(_, y, _)| y).collect();
+    ys.sort_unstable();
+    ys.dedup();
+    let mut order: Vec<usize> = (0..points.len()).collect();
+    order.sort_by_key(|&i| points[i].0);
+
+    // (x, is_upper, rect): the prefix of the points with abscissa <= x for
+    // upper events, < x for lower ones, instead of <= x1 - 1 that overflows at
+    // i64::MIN. At equal x the lower events come first
+    let mut events = Vec::with_capacity(2 * rects.len());
+    for (i, &(x1, x2, y1, y2)) in rects.iter().enumerate() {
+        assert!(x1 <= x2 && y1 <= y2, "Invalid range");
+        events.push((x1, false, i));
+        events.push((x2, true, i));
+    }
+    events.sort_unstable();
+
+    let mut fenwick = FenwickTree::new(ys.len());
+    let mut lower = vec![T::default(); rects.len()];
+    let mut upper = vec![T::default(); rects.len()];
+    let mut next = 0;
+    for (x, is_upper, i) in events {
+        let in_prefix = |px: i64| px < x || is_upper && px == x;
+        while next < order.len() && in_prefix(points[order[next]].0) {
+            let (_, y, w) = points[order[next]];
+            fenwick.add(ys.partition_point(|&v| v < y), w);
+            next += 1;
+        }
+        let (_, _, y1, y2) = rects[i];
+        let (lo, hi) = (
+            ys.partition_point(|&v| v < y1),
+            ys.partition_point(|&v| v <= y2),
+        );
+        let sum = fenwick.prefix(hi) - fenwick.prefix(lo);
+        if is_upper {
+            upper[i] = sum;
+        } else {
+            lower[i] = sum;
+        }
+    }
+    upper.into_iter().zip(lower).map(|(u, l)| u - l).collect()
+}
+
+/// For each query `(qx, qy)`, the number of points `(x, y)` dominated by it,
+/// that is with `x <= qx` and `y <= qy`
+pub fn dominance_counts(points: &[(i64, i64)], queries: &[(i64, i64)]) -> Vec<u64> {
+    let weighted: Vec<(i64, i64, u64)> = points.iter().map(|&(x, y)| (x, y, 1)).collect();
+    let rects: Vec<(i64, i64, i64, i64)> = queries
+        .iter()
+        .map(|&(qx, qy)| (i64::MIN, qx, i64::MIN, qy))
+        .collect();
+    rectangle_sums(&weighted, &rects)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = Rng::new(44);
+        for _ in 0..50 {
+            let n = rng.gen_range(0, 60) as usize;
+            let points: Vec<(i64, i64, i64)> = (0..n)
+                .map(|_| {
+                    (
+                        rng.gen_range_i64(-15, 15),
+                        rng.gen_range_i64(-15, 15),
+                        rng.gen_range_i64(-100, 100),
+                    )
+                })
+                .collect();
+            let rects: Vec<(i64, i64, i64, i64)> = (0..40)
+                .map(|_| {
+                    let (x1, y1) = (rng.gen_range_i64(-20, 20), rng.gen_range_i64(-20, 20));
+                    (
+                        x1,
+                        x1 + rng.gen_range_i64(0, 20),
+                        y1,
+                        y1 + rng.gen_range_i64(0, 20),
+                    )
+                })
+                .collect();
+            let expected: Vec<i64> = rects
+                .iter()
+                .map(|&(x1, x2, y1, y2)| {
+                    points
+                        .iter()
+                        .filter(|&&(x, y, _)| x1 <= x && x <= x2 && y1 <= y && y <= y2)
+                        .map(|&(_, _, w)| w)
+                        .sum()
+                })
+                .collect();
+            assert_eq!(rectangle_sums(&points, &rects), expected);
+        }
+    }
+
+    #[test]
+    fn test_dominance_counts() {
+        let points = [(1, 1), (2, 3), (3, 2), (2, 3)];
+        let queries = [(0, 0), (2, 3), (3, 2), (3, 3), (i64::MAX, i64::MAX)];
+        assert_eq!(dominance_counts(&points, &queries), vec![0, 3, 2, 4, 4]);
+
+        // the extreme coordinates are counted as well
+        let (min, max) = (i64::MIN, i64::MAX);
+        let points = [(min, min), (min, 0), (0, min), (max, max)];
+        let queries = [(min, min), (min, max), (0, 0), (max, max)];
+        assert_eq!(dominance_counts(&points, &queries), vec![1, 2, 3, 4]);
+        let weighted = [(min, 0, 5), (max, 0, 7)];
+        assert_eq!(
+            rectangle_sums(&weighted, &[(min, min, 0, 0), (min, max, 0, 0)]),
+            vec![5, 12]
+        );
+    }
+}
@@ -0,0 +1,204 @@
+// ------- REROOTING -------
+// Computes a tree DP for every choice of root in O(n): one bottom-up pass from
+// an arbitrary root, then a top-down pass handing each child the value of the
+// rest of the tree through prefix/suffix combinations of its siblings.
+//
+// The maximum leaf-to-leaf path of handson1 is generalized here to weighted
+// n-ary trees, with the best path through every vertex as a by-product.
+
+use crate::graph::Graph;
+use crate::segment_tree::{Monoid, Top2};
+
+/// For every vertex `v` of the tree `graph`, combines over all neighbours `u`
+/// the value `lift(side, u, weight)`, where `side` is the DP of the component
+/// of `u` once the edge `(v, u)` is removed, rooted at `u`. That DP is itself
+/// the combination of the lifted values of the neighbours of `u` other than
+/// `v`. `combine` must be commutative.
+pub fn reroot<T, L>(graph: &Graph, lift: L) -> Vec<T>
+where
+    T: Monoid,
+    L: Fn(&T, usize, i64) -> T,
+{
+    let n = graph.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    // preorder from vertex 0 with parents
+    let (parent, order) = graph.rooted_order(0);
+    let is_child = |v: usize, u: usize| u != 0 && parent[u] == v;
+
+    // down[v]: DP of the subtree of v for the root 0
+    let mut down = vec![T::identity(); n];
+    for &v in order.iter().rev() {
+        for &(u, w) in graph.neighbors(v) {
+            if is_child(v, u) {
+                down[v] = down[v].combine(&lift(&down[u], u, w));
+            }
+        }
+    }
+
+    // up[v]: DP of the component of parent[v] without the subtree of v
+    let mut up = vec![T::identity(); n];
+    let mut result = vec![T::identity(); n];
+    for &v in &order {
+        // lifted value of every neighbour of v, as seen from v
+        let lifted: Vec<T> = graph
+            .neighbors(v)
+            .iter()
+            .map(|&(u, w)| {
+                if is_child(v, u) {
+                    lift(&down[u], u, w)
+                } else {
+                    lift(&up[v], u, w)
+                }
+            })
+            .collect();
+        let mut suffix = vec![T::identity(); lifted.len() + 1];
+        for i in (0..lifted.len()).rev() {
+            suffix[i] = lifted[i].combine(&suffix[i + 1]);
+        }
+        let mut prefix = T::identity();
+        for (i, &(u, _)) in graph.neighbors(v).iter().enumerate() {
+            if is_child(v, u) {
+                up[u] = prefix.combine(&suffix[i + 1]);
+            }
+            prefix = prefix.combine(&lifted[i]);
+        }
+        result[v] = suffix[0].clone();
+    }
+    result
+}
+
+/// Per-vertex answers of the maximum leaf-to-leaf path problem
+pub struct LeafPaths {
+    /// Heaviest path from each vertex to a leaf other than itself
+    pub farthest_leaf: Vec<Option<i64>>,
+    /// Heaviest path between two distinct leaves passing through each vertex
+    pub through: Vec<Option<i64>>,
+}
+
+impl LeafPaths {
+    /// Heaviest path between any two leaves of the tree
+    pub fn max_path(&self) -> Option<i64> {
+        self.through.iter().flatten().copied().max()
+    }
+}
+
+/// Solves the maximum path sum between two leaves on the weighted tree
+/// `graph`, where leaves are the vertices of degree one
+pub fn leaf_paths(graph: &Graph) -> LeafPaths {
+    // the two heaviest branches leaving each vertex, a branch going down
+    // to a leaf through its heaviest continuation (or stopping at a leaf)
+    let branches = reroot(graph, |side: &Top2<i64>, _, w| {
+        Top2::single(w + side.first.unwrap_or(0))
+    });
+    let farthest_leaf = branches.iter().map(|b| b.first).collect();
+    let through = branches
+        .iter()
+        .enumerate()
+        .map(|(v, b)| match graph.degree(v) {
+            0 => None,
+            1 => b.first, // v is a leaf itself
+            _ => Some(b.first? + b.second?),
+        })
+        .collect();
+    LeafPaths {
+        farthest_leaf,
+        through,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use crate::segment_tree::Sum;
+
+    fn distances(graph: &Graph, from: usize) -> Vec<i64> {
+        let mut dist = vec![i64::MIN; graph.len()];
+        dist[from] = 0;
+        let mut stack = vec![from];
+        while let Some(v) = stack.pop() {
+            for &(u, w) in graph.neighbors(v) {
+                if dist[u] == i64::MIN {
+                    dist[u] = dist[v] + w;
+                    stack.push(u);
+                }
+            }
+        }
+        dist
+    }
+
+    // (vertices, sum of their distances) of one side of an edge
+    #[derive(Clone)]
+    struct SizeDist(i64, i64);
+
+    impl Monoid for SizeDist {
+        fn identity() -> Self {
+            SizeDist(0, 0)
+        }
+        fn combine(&self, other: &Self) -> Self {
+            SizeDist(self.0 + other.0, self.1 + other.1)
+        }
+    }
+
+    #[test]
+    fn test_sum_of_distances_for_every_root() {
+        let graph = Graph::from_undirected_edges(5, &[(0, 1, 2), (1, 2, 1), (1, 3, 5), (3, 4, 1)]);
+        let sizes = reroot(&graph, |side: &Sum<usize>, _, _| Sum(side.0 + 1));
+        assert!(sizes.iter().all(|s| s.0 == 4));
+        // crossing the edge moves the side's vertices `w` further away
+        let sums = reroot(&graph, |side: &SizeDist, _, w| {
+            SizeDist(side.0 + 1, side.1 + w * (side.0 + 1))
+        });
+        for (v, sum) in sums.iter().enumerate() {
+            assert_eq!(sum.1, distances(&graph, v).iter().sum::<i64>());
+        }
+    }
+
+    #[test]
+    fn test_leaf_paths_against_brute_force() {
+        let mut rng = Rng::new(25);
+        for _ in 0..50 {
+            let n = rng.gen_range(1, 40) as usize;
+            let mut graph = Graph::new(n);
+            for v in 1..n {
+                let p = rng.gen_range(0, v as u64 - 1) as usize;
+                graph.add_undirected_edge(p, v, rng.gen_range_i64(1, 100));
+            }
+            let dist: Vec<Vec<i64>> = (0..n).map(|v| distances(&graph, v)).collect();
+            let leaves: Vec<usize> = (0..n).filter(|&v| graph.degree(v) == 1).collect();
+            let answers = leaf_paths(&graph);
+
+            for v in 0..n {
+                let farthest = leaves
+                    .iter()
+                    .filter(|&&l| l != v)
+                    .map(|&l| dist[v][l])
+                    .max();
+                assert_eq!(answers.farthest_leaf[v], farthest);
+                // with positive weights v lies on the path a-b iff distances add up
+                let mut through = None;
+                for &a in &leaves {
+                    for &b in &leaves {
+                        if a != b && dist[a][v] + dist[v][b] == dist[a][b] {
+                            through = through.max(Some(dist[a][b]));
+                        }
+                    }
+                }
+                assert_eq!(answers.through[v], through, "vertex {}", v);
+            }
+            let best = leaves
+                .iter()
+                .flat_map(|&a| {
+                    leaves
+                        .iter()
+                        .filter(move |&&b| b != a)
+                        .map(move |&b| (a, b))
+                })
+                .map(|(a, b)| dist[a][b])
+                .max();
+            assert_eq!(answers.max_path(), best);
+        }
+    }
+}
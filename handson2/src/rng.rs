@@ -0,0 +1,132 @@
+// ------- RANDOM NUMBERS -------
+// wyrand generator: a 64-bit counter mixed with one wide multiplication. Fast,
+// passes BigCrush and needs no external crate, which many judges disallow.
+
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// A generator producing always the same sequence for the same seed
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    /// A generator seeded from the clock and the process-wide random hasher
+    /// keys, different at every run
+    pub fn from_entropy() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        Rng::new(RandomState::new().hash_one(nanos))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0xa076_1d64_78bd_642f);
+        let t = (self.state as u128) * ((self.state ^ 0xe703_7ed1_a0b4_28db) as u128);
+        (t >> 64) as u64 ^ t as u64
+    }
+
+    /// Returns a value uniformly distributed in `[lo, hi]`
+    pub fn gen_range(&mut self, lo: u64, hi: u64) -> u64 {
+        assert!(lo <= hi, "Empty range");
+        let span = (hi - lo).wrapping_add(1);
+        if span == 0 {
+            return self.next_u64();
+        }
+        // Lemire's multiply-and-reject: unbiased without divisions in the fast path
+        let threshold = span.wrapping_neg() % span;
+        loop {
+            let m = (self.next_u64() as u128) * (span as u128);
+            if m as u64 >= threshold {
+                return lo + (m >> 64) as u64;
+            }
+        }
+    }
+
+    /// Returns a value uniformly distributed in `[lo, hi]`
+    pub fn gen_range_i64(&mut self, lo: i64, hi: i64) -> i64 {
+        assert!(lo <= hi, "Empty range");
+        let span = hi.wrapping_sub(lo) as u64;
+        lo.wrapping_add(self.gen_range(0, span) as i64)
+    }
+
+    /// Returns a float uniformly distributed in `[0, 1)`
+    pub fn gen_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns true with probability `p`
+    pub fn gen_bool(&mut self, p: f64) -> bool {
+        self.gen_f64() < p
+    }
+
+    /// Fisher-Yates shuffle
+    pub fn shuffle<T>(&mut self, slice: &mut [T]) {
+        for i in (1..slice.len()).rev() {
+            let j = self.gen_range(0, i as u64) as usize;
+            slice.swap(i, j);
+        }
+    }
+
+    /// A uniformly chosen element of `slice`, None if it is empty
+    pub fn choose<'a, T>(&mut self, slice: &'a [T]) -> Option<&'a T> {
+        if slice.is_empty() {
+            return None;
+        }
+        slice.get(self.gen_range(0, slice.len() as u64 - 1) as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeding_is_reproducible() {
+        let mut a = Rng::new(123);
+        let mut b = Rng::new(123);
+        let mut c = Rng::new(124);
+        let xs: Vec<u64> = (0..10).map(|_| a.next_u64()).collect();
+        let ys: Vec<u64> = (0..10).map(|_| b.next_u64()).collect();
+        let zs: Vec<u64> = (0..10).map(|_| c.next_u64()).collect();
+        assert_eq!(xs, ys);
+        assert_ne!(xs, zs);
+    }
+
+    #[test]
+    fn test_gen_range_bounds_and_uniformity() {
+        let mut rng = Rng::new(5);
+        let mut counts = [0usize; 6];
+        for _ in 0..60_000 {
+            let x = rng.gen_range(10, 15);
+            assert!((10..=15).contains(&x));
+            counts[(x - 10) as usize] += 1;
+        }
+        assert!(counts.iter().all(|&c| (9_000..11_000).contains(&c)));
+        assert_eq!(rng.gen_range(7, 7), 7);
+        let _ = rng.gen_range(0, u64::MAX);
+        for _ in 0..1000 {
+            let x = rng.gen_range_i64(-3, 3);
+            assert!((-3..=3).contains(&x));
+            let f = rng.gen_f64();
+            assert!((0.0..1.0).contains(&f));
+        }
+    }
+
+    #[test]
+    fn test_shuffle_is_permutation() {
+        let mut rng = Rng::new(9);
+        let mut values: Vec<u32> = (0..100).collect();
+        rng.shuffle(&mut values);
+        assert_ne!(values, (0..100).collect::<Vec<_>>());
+        values.sort_unstable();
+        assert_eq!(values, (0..100).collect::<Vec<_>>());
+        assert_eq!(rng.choose::<u32>(&[]), None);
+        assert_eq!(rng.choose(&[4]), Some(&4));
+    }
+}
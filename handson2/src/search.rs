@@ -0,0 +1,144 @@
+// ------- SEARCH -------
+// Binary search on monotone predicates and ternary search on unimodal
+// functions, over integers and floats. For a minimum, search the maximum of
+// the negated function.
+
+/// Smallest `x` in `[lo, hi]` with `pred(x)` true, None if there is none.
+/// `pred` must be monotone: false up to some point and true from there on.
+pub fn binary_search_first<F: FnMut(i64) -> bool>(lo: i64, hi: i64, mut pred: F) -> Option<i64> {
+    if lo > hi {
+        return None;
+    }
+    // invariant: the answer lies in [lo, end], end meaning "none". The bounds
+    // are i128 so that end and hi - lo do not overflow on the whole i64 range
+    let end = hi as i128 + 1;
+    let (mut lo, mut hi) = (lo as i128, end);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if pred(mid as i64) {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    (lo < end).then_some(lo as i64)
+}
+
+/// Largest `x` in `[lo, hi]` with `pred(x)` true, None if there is none.
+/// `pred` must be true up to some point and false from there on.
+pub fn binary_search_last<F: FnMut(i64) -> bool>(lo: i64, hi: i64, mut pred: F) -> Option<i64> {
+    match binary_search_first(lo, hi, |x| !pred(x)) {
+        Some(first_false) => (first_false > lo).then(|| first_false - 1),
+        None => (lo <= hi).then_some(hi),
+    }
+}
+
+/// Approximates the point where a monotone `pred` switches from false to
+/// true inside `[lo, hi]`, halving the interval `iterations` times
+pub fn bisect<F: FnMut(f64) -> bool>(lo: f64, hi: f64, iterations: usize, mut pred: F) -> f64 {
+    let (mut lo, mut hi) = (lo, hi);
+    for _ in 0..iterations {
+        let mid = lo + (hi - lo) / 2.0;
+        if pred(mid) {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+    hi
+}
+
+/// Position of the maximum of `f` over `[lo, hi]`, where `f` strictly
+/// increases up to the maximum and strictly decreases after it. The leftmost
+/// one is returned if the maximum is reached on a plateau at the top.
+pub fn ternary_search_int<T: PartialOrd, F: FnMut(i64) -> T>(lo: i64, hi: i64, mut f: F) -> i64 {
+    assert!(lo <= hi, "Invalid range");
+    if lo == hi {
+        return lo;
+    }
+    // the maximum is the first x where f stops increasing
+    binary_search_first(lo, hi - 1, |x| f(x) >= f(x + 1)).unwrap_or(hi)
+}
+
+/// Approximate position of the maximum of a unimodal `f` over `[lo, hi]`,
+/// shrinking the interval by a third `iterations` times
+pub fn ternary_search_float<F: FnMut(f64) -> f64>(
+    lo: f64,
+    hi: f64,
+    iterations: usize,
+    mut f: F,
+) -> f64 {
+    let (mut lo, mut hi) = (lo, hi);
+    for _ in 0..iterations {
+        let m1 = lo + (hi - lo) / 3.0;
+        let m2 = hi - (hi - lo) / 3.0;
+        if f(m1) < f(m2) {
+            lo = m1;
+        } else {
+            hi = m2;
+        }
+    }
+    lo + (hi - lo) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binary_search_integers() {
+        let values = [1, 3, 3, 5, 8, 13];
+        let first_at_least = |t: i64| binary_search_first(0, 5, |i| values[i as usize] >= t);
+        assert_eq!(first_at_least(0), Some(0));
+        assert_eq!(first_at_least(3), Some(1));
+        assert_eq!(first_at_least(4), Some(3));
+        assert_eq!(first_at_least(14), None);
+
+        let last_below = |t: i64| binary_search_last(0, 5, |i| values[i as usize] < t);
+        assert_eq!(last_below(0), None);
+        assert_eq!(last_below(4), Some(2));
+        assert_eq!(last_below(100), Some(5));
+
+        assert_eq!(binary_search_first(5, 4, |_| true), None);
+        assert_eq!(binary_search_first(-10, 10, |x| x * x * x >= -8), Some(-2));
+        assert_eq!(
+            binary_search_first(0, i64::MAX - 1, |x| x >= 1 << 40),
+            Some(1 << 40)
+        );
+    }
+
+    #[test]
+    fn test_binary_search_full_range() {
+        let (min, max) = (i64::MIN, i64::MAX);
+        for t in [min, min + 1, -1, 0, 1, max - 1, max] {
+            assert_eq!(binary_search_first(min, max, |x| x >= t), Some(t));
+            assert_eq!(binary_search_last(min, max, |x| x <= t), Some(t));
+        }
+        assert_eq!(binary_search_first(min, max, |_| false), None);
+        assert_eq!(binary_search_last(min, max, |_| true), Some(max));
+        assert_eq!(binary_search_last(min, max, |_| false), None);
+        assert_eq!(binary_search_first(max, max, |_| true), Some(max));
+        assert_eq!(ternary_search_int(min, max, |x| x), max);
+        assert_eq!(ternary_search_int(min, max, |x| -(x as i128 - 5).abs()), 5);
+        assert_eq!(ternary_search_int(min, min, |x| x), min);
+    }
+
+    #[test]
+    fn test_bisect_square_root() {
+        let root = bisect(0.0, 2.0, 60, |x| x * x >= 2.0);
+        assert!((root - 2f64.sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_ternary_search() {
+        assert_eq!(ternary_search_int(-100, 100, |x| -(x - 17) * (x - 17)), 17);
+        assert_eq!(ternary_search_int(0, 10, |x| x), 10);
+        assert_eq!(ternary_search_int(0, 10, |x| -x), 0);
+        assert_eq!(ternary_search_int(3, 3, |x| x), 3);
+        // plateau at the top
+        assert_eq!(ternary_search_int(0, 10, |x| x.min(4) - (x - 7).max(0)), 4);
+
+        let x = ternary_search_float(-10.0, 10.0, 200, |x| -(x - 1.5) * (x - 1.5));
+        assert!((x - 1.5).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,393 @@
+// ------- GENERIC SEGMENT TREE -------
+// Point update / range query segment tree over any monoid. Ranges are 0-based
+// and inclusive, as in `MaxSegmentTree::is_there`.
+
+use crate::stats;
+
+/// An associative combine operation with an identity element. `combine` does
+/// not need to be commutative: the tree always combines left before right.
+pub trait Monoid: Clone {
+    fn identity() -> Self;
+    fn combine(&self, other: &Self) -> Self;
+}
+
+pub struct SegmentTree<T: Monoid> {
+    n: usize,     // Number of elements
+    size: usize,  // Number of leaves, the smallest power of two >= n
+    tree: Vec<T>, // Node i has children 2i and 2i+1, leaves start at `size`
+}
+
+impl<T: Monoid> SegmentTree<T> {
+    pub fn new(values: &[T]) -> Self {
+        let n = values.len();
+        let size = n.next_power_of_two();
+        let mut tree = vec![T::identity(); 2 * size];
+        tree[size..size + n].clone_from_slice(values);
+        for i in (1..size).rev() {
+            tree[i] = tree[2 * i].combine(&tree[2 * i + 1]);
+        }
+        SegmentTree { n, size, tree }
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Returns the value stored at position `i`
+    pub fn get(&self, i: usize) -> &T {
+        assert!(i < self.n, "Index out of range");
+        &self.tree[self.size + i]
+    }
+
+    /// Replaces the value at position `i` and updates its ancestors
+    pub fn set(&mut self, i: usize, value: T) {
+        assert!(i < self.n, "Index out of range");
+        let mut node = self.size + i;
+        self.tree[node] = value;
+        while node > 1 {
+            node /= 2;
+            stats::visit();
+            self.tree[node] = self.tree[2 * node].combine(&self.tree[2 * node + 1]);
+        }
+    }
+
+    /// Combines the values in `[l, r]`, left to right
+    pub fn query(&self, l: usize, r: usize) -> T {
+        assert!(l <= r && r < self.n, "Invalid range");
+        let (mut lo, mut hi) = (l + self.size, r + self.size + 1);
+        let mut left = T::identity();
+        let mut right = T::identity();
+        while lo < hi {
+            if lo & 1 == 1 {
+                stats::visit();
+                left = left.combine(&self.tree[lo]);
+                lo += 1;
+            }
+            if hi & 1 == 1 {
+                hi -= 1;
+                stats::visit();
+                right = self.tree[hi].combine(&right);
+            }
+            lo /= 2;
+            hi /= 2;
+        }
+        left.combine(&right)
+    }
+
+    /// Combines all the values
+    pub fn all(&self) -> T {
+        self.tree[1].clone()
+    }
+}
+
+// ----- basic numeric monoids -----
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Max<T>(pub T);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Min<T>(pub T);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sum<T>(pub T);
+
+macro_rules! impl_numeric_monoids {
+    ($($t:ty),*) => {$(
+        impl Monoid for Max<$t> {
+            fn identity() -> Self {
+                Max(<$t>::MIN)
+            }
+            fn combine(&self, other: &Self) -> Self {
+                Max(self.0.max(other.0))
+            }
+        }
+
+        impl Monoid for Min<$t> {
+            fn identity() -> Self {
+                Min(<$t>::MAX)
+            }
+            fn combine(&self, other: &Self) -> Self {
+                Min(self.0.min(other.0))
+            }
+        }
+
+        impl Monoid for Sum<$t> {
+            fn identity() -> Self {
+                Sum(0)
+            }
+            #[cfg(any(debug_assertions, feature = "checked-arith"))]
+            fn combine(&self, other: &Self) -> Self {
+                Sum(self.0.checked_add(other.0).expect("Sum overflow"))
+            }
+            #[cfg(not(any(debug_assertions, feature = "checked-arith")))]
+            fn combine(&self, other: &Self) -> Self {
+                Sum(self.0.wrapping_add(other.0))
+            }
+        }
+    )*};
+}
+
+// Sums are overflow-checked in debug builds, and in release builds too with the
+// `checked-arith` feature; otherwise they wrap around like plain integers
+impl_numeric_monoids!(u32, u64, usize, i32, i64, i128);
+
+/// Range sums that cannot overflow for fewer than 2^32 values in the i32
+/// range, whose magnitude is at most 2^31
+pub type SumTree64 = SegmentTree<Sum<i64>>;
+/// Range sums that cannot overflow for fewer than 2^64 values in the i64
+/// range: 2^64 values of magnitude 2^63 would already sum to 2^127
+pub type SumTree128 = SegmentTree<Sum<i128>>;
+
+// ----- ready-made node types -----
+
+/// The two largest values of a range, duplicates count as distinct elements
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Top2<T> {
+    pub first: Option<T>,
+    pub second: Option<T>,
+}
+
+impl<T> Top2<T> {
+    pub fn single(value: T) -> Self {
+        Top2 {
+            first: Some(value),
+            second: None,
+        }
+    }
+}
+
+impl<T: Ord + Copy> Monoid for Top2<T> {
+    fn identity() -> Self {
+        Top2 {
+            first: None,
+            second: None,
+        }
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        // None sorts before Some, so the two largest of the four candidates win
+        let mut candidates = [self.first, self.second, other.first, other.second];
+        candidates.sort_unstable();
+        Top2 {
+            first: candidates[3],
+            second: candidates[2],
+        }
+    }
+}
+
+/// The maximum of a range together with the number of times it occurs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MaxCount<T> {
+    pub max: Option<T>,
+    pub count: usize,
+}
+
+impl<T> MaxCount<T> {
+    pub fn single(value: T) -> Self {
+        MaxCount {
+            max: Some(value),
+            count: 1,
+        }
+    }
+}
+
+impl<T: Ord + Copy> Monoid for MaxCount<T> {
+    fn identity() -> Self {
+        MaxCount {
+            max: None,
+            count: 0,
+        }
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        match self.max.cmp(&other.max) {
+            std::cmp::Ordering::Greater => *self,
+            std::cmp::Ordering::Less => *other,
+            std::cmp::Ordering::Equal => MaxCount {
+                max: self.max,
+                count: self.count + other.count,
+            },
+        }
+    }
+}
+
+/// The longest run of equal consecutive values in a range. Runs crossing the
+/// boundary between two segments are merged using the first/last values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LongestRun<T> {
+    pub len: usize,    // Number of elements in the segment
+    pub best: usize,   // Longest run inside the segment
+    pub prefix: usize, // Length of the run starting at the first element
+    pub suffix: usize, // Length of the run ending at the last element
+    pub first: Option<T>,
+    pub last: Option<T>,
+}
+
+impl<T> LongestRun<T> {
+    pub fn single(value: T) -> Self
+    where
+        T: Copy,
+    {
+        LongestRun {
+            len: 1,
+            best: 1,
+            prefix: 1,
+            suffix: 1,
+            first: Some(value),
+            last: Some(value),
+        }
+    }
+}
+
+impl<T: Eq + Copy> Monoid for LongestRun<T> {
+    fn identity() -> Self {
+        LongestRun {
+            len: 0,
+            best: 0,
+            prefix: 0,
+            suffix: 0,
+            first: None,
+            last: None,
+        }
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        if self.len == 0 {
+            return *other;
+        }
+        if other.len == 0 {
+            return *self;
+        }
+        let joined = self.last == other.first;
+        let mut best = self.best.max(other.best);
+        let mut prefix = self.prefix;
+        let mut suffix = other.suffix;
+        if joined {
+            best = best.max(self.suffix + other.prefix);
+            if self.prefix == self.len {
+                prefix += other.prefix;
+            }
+            if other.suffix == other.len {
+                suffix += self.suffix;
+            }
+        }
+        LongestRun {
+            len: self.len + other.len,
+            best,
+            prefix,
+            suffix,
+            first: self.first,
+            last: other.last,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn random_array(rng: &mut Rng, n: usize, max_value: u64) -> Vec<u32> {
+        (0..n).map(|_| rng.gen_range(0, max_value) as u32).collect()
+    }
+
+    #[test]
+    fn test_numeric_monoids() {
+        let arr = [5i64, -3, 8, 1];
+        let sum = SegmentTree::new(&arr.map(Sum));
+        let min = SegmentTree::new(&arr.map(Min));
+        let mut max = SegmentTree::new(&arr.map(Max));
+        assert_eq!(sum.query(0, 3), Sum(11));
+        assert_eq!(min.query(0, 1), Min(-3));
+        assert_eq!(max.query(1, 3), Max(8));
+        max.set(2, Max(0));
+        assert_eq!(max.query(1, 3), Max(1));
+        assert_eq!(max.all(), Max(5));
+        assert_eq!(sum.len(), 4);
+    }
+
+    #[test]
+    fn test_wide_sums() {
+        let big = [i64::MAX, i64::MAX, i64::MIN, 7];
+        let mut tree: SumTree128 = SegmentTree::new(&big.map(|v| Sum(v as i128)));
+        assert_eq!(tree.query(0, 1), Sum(2 * i64::MAX as i128));
+        assert_eq!(tree.all(), Sum(i64::MAX as i128 + 6));
+        tree.set(0, Sum(0));
+        assert_eq!(tree.query(0, 2), Sum(-1));
+
+        let tree: SumTree64 = SegmentTree::new(&[u32::MAX as i64; 3].map(Sum));
+        assert_eq!(tree.all(), Sum(3 * u32::MAX as i64));
+    }
+
+    #[test]
+    #[cfg(any(debug_assertions, feature = "checked-arith"))]
+    #[should_panic(expected = "Sum overflow")]
+    fn test_sum_overflow_panics() {
+        SegmentTree::new(&[Sum(u32::MAX), Sum(1)]);
+    }
+
+    #[test]
+    fn test_top2_and_max_count() {
+        let mut rng = Rng::new(11);
+        let mut arr = random_array(&mut rng, 200, 20);
+        let mut top2 = SegmentTree::new(&arr.iter().map(|&v| Top2::single(v)).collect::<Vec<_>>());
+        let mut count =
+            SegmentTree::new(&arr.iter().map(|&v| MaxCount::single(v)).collect::<Vec<_>>());
+
+        for _ in 0..500 {
+            let i = rng.gen_range(0, 199) as usize;
+            let v = rng.gen_range(0, 19) as u32;
+            arr[i] = v;
+            top2.set(i, Top2::single(v));
+            count.set(i, MaxCount::single(v));
+
+            let a = rng.gen_range(0, 199) as usize;
+            let b = rng.gen_range(0, 199) as usize;
+            let (l, r) = (a.min(b), a.max(b));
+            let mut sorted = arr[l..=r].to_vec();
+            sorted.sort_unstable_by(|x, y| y.cmp(x));
+            let got = top2.query(l, r);
+            assert_eq!(got.first, Some(sorted[0]));
+            assert_eq!(got.second, sorted.get(1).copied());
+
+            let got = count.query(l, r);
+            assert_eq!(got.max, Some(sorted[0]));
+            assert_eq!(
+                got.count,
+                sorted.iter().filter(|&&x| x == sorted[0]).count()
+            );
+        }
+    }
+
+    #[test]
+    fn test_longest_run() {
+        let mut rng = Rng::new(5);
+        let arr = random_array(&mut rng, 300, 3);
+        let tree = SegmentTree::new(
+            &arr.iter()
+                .map(|&v| LongestRun::single(v))
+                .collect::<Vec<_>>(),
+        );
+
+        for l in 0..arr.len() {
+            for r in (l..arr.len()).step_by(7) {
+                let mut best = 1;
+                let mut run = 1;
+                for i in l + 1..=r {
+                    run = if arr[i] == arr[i - 1] { run + 1 } else { 1 };
+                    best = best.max(run);
+                }
+                assert_eq!(tree.query(l, r).best, best, "range [{}, {}]", l, r);
+            }
+        }
+
+        // a run crossing every internal boundary
+        let tree = SegmentTree::new(&[LongestRun::single(4); 13]);
+        assert_eq!(tree.all().best, 13);
+        assert_eq!(tree.query(3, 9).prefix, 7);
+    }
+}
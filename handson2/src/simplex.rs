@@ -0,0 +1,240 @@
+// ------- SIMPLEX -------
+// Dense two-phase simplex over f64 for small linear programs, up to a few
+// hundred variables and constraints: maximize c x subject to A x <= b and
+// x >= 0. Phase one finds a feasible basis through an auxiliary variable
+// when some b is negative. Bland's rule, entering the lowest-numbered
+// improving variable and breaking ratio ties on the lowest-numbered basic
+// one, guarantees termination on degenerate programs.
+
+const EPS: f64 = 1e-9;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LpResult {
+    /// The maximum of the objective and a point reaching it
+    Optimal(f64, Vec<f64>),
+    Infeasible,
+    Unbounded,
+}
+
+// Tableau of the program: rows 0..m are the constraints, row m the objective
+// and row m + 1 the phase one objective; column n is the auxiliary variable
+// and column n + 1 the right-hand sides
+struct Tableau {
+    m: usize,
+    n: usize,
+    basic: Vec<isize>,    // Variable of each row, slacks numbered from n
+    nonbasic: Vec<isize>, // Variable of each column, -1 for the auxiliary one
+    d: Vec<Vec<f64>>,
+}
+
+impl Tableau {
+    fn pivot(&mut self, r: usize, s: usize) {
+        let inv = 1.0 / self.d[r][s];
+        let pivot_row = self.d[r].clone();
+        for i in 0..self.m + 2 {
+            if i != r && self.d[i][s].abs() > EPS {
+                let factor = self.d[i][s] * inv;
+                for (x, &p) in self.d[i].iter_mut().zip(&pivot_row) {
+                    *x -= p * factor;
+                }
+                self.d[i][s] = pivot_row[s] * factor;
+            }
+        }
+        for j in 0..self.n + 2 {
+            if j != s {
+                self.d[r][j] *= inv;
+            }
+        }
+        for i in 0..self.m + 2 {
+            if i != r {
+                self.d[i][s] *= -inv;
+            }
+        }
+        self.d[r][s] = inv;
+        std::mem::swap(&mut self.basic[r], &mut self.nonbasic[s]);
+    }
+
+    // Optimizes row `objective`, keeping `excluded` out of the basis. Returns
+    // false if it is unbounded.
+    fn run(&mut self, objective: usize, excluded: isize) -> bool {
+        loop {
+            let entering = (0..=self.n)
+                .filter(|&j| self.nonbasic[j] != excluded && self.d[objective][j] < -EPS)
+                .min_by_key(|&j| self.nonbasic[j]);
+            let Some(s) = entering else {
+                return true;
+            };
+            let mut leaving: Option<usize> = None;
+            for i in 0..self.m {
+                if self.d[i][s] <= EPS {
+                    continue;
+                }
+                let ratio = self.d[i][self.n + 1] / self.d[i][s];
+                leaving = match leaving {
+                    Some(r) => {
+                        let best = self.d[r][self.n + 1] / self.d[r][s];
+                        let better = ratio < best - EPS
+                            || (ratio <= best + EPS && self.basic[i] < self.basic[r]);
+                        Some(if better { i } else { r })
+                    }
+                    None => Some(i),
+                };
+            }
+            match leaving {
+                Some(r) => self.pivot(r, s),
+                None => return false,
+            }
+        }
+    }
+}
+
+/// Maximizes `c x` subject to `a x <= b` and `x >= 0`, where `a` has one row
+/// per constraint and one column per variable
+pub fn maximize(a: &[Vec<f64>], b: &[f64], c: &[f64]) -> LpResult {
+    let (m, n) = (b.len(), c.len());
+    assert_eq!(a.len(), m, "Dimension mismatch");
+    assert!(a.iter().all(|row| row.len() == n), "Dimension mismatch");
+
+    let mut d = vec![vec![0.0; n + 2]; m + 2];
+    for i in 0..m {
+        d[i][..n].copy_from_slice(&a[i]);
+        d[i][n] = -1.0;
+        d[i][n + 1] = b[i];
+    }
+    for j in 0..n {
+        d[m][j] = -c[j];
+    }
+    d[m + 1][n] = 1.0;
+    let mut tableau = Tableau {
+        m,
+        n,
+        basic: (n..n + m).map(|v| v as isize).collect(),
+        nonbasic: (0..n as isize).chain([-1]).collect(),
+        d,
+    };
+
+    // phase one: enter the auxiliary variable on the most violated constraint,
+    // then drive it back to zero
+    let worst = (0..m).min_by(|&i, &j| tableau.d[i][n + 1].total_cmp(&tableau.d[j][n + 1]));
+    if let Some(r) = worst.filter(|&r| tableau.d[r][n + 1] < -EPS) {
+        tableau.pivot(r, n);
+        if !tableau.run(m + 1, -2) || tableau.d[m + 1][n + 1] < -EPS {
+            return LpResult::Infeasible;
+        }
+        for i in 0..m {
+            if tableau.basic[i] == -1 {
+                let s = (0..=n)
+                    .min_by(|&j, &k| {
+                        let key = |j: usize| (tableau.d[i][j], tableau.nonbasic[j]);
+                        key(j).partial_cmp(&key(k)).unwrap()
+                    })
+                    .unwrap();
+                tableau.pivot(i, s);
+            }
+        }
+    }
+
+    if !tableau.run(m, -1) {
+        return LpResult::Unbounded;
+    }
+    let mut x = vec![0.0; n];
+    for i in 0..m {
+        if let Ok(v) = usize::try_from(tableau.basic[i]) {
+            if v < n {
+                x[v] = tableau.d[i][n + 1];
+            }
+        }
+    }
+    LpResult::Optimal(tableau.d[m][n + 1], x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_small_programs() {
+        // max 3x + 2y with x + y <= 4, x + 3y <= 6, x <= 3
+        let a = [vec![1.0, 1.0], vec![1.0, 3.0], vec![1.0, 0.0]];
+        let LpResult::Optimal(value, x) = maximize(&a, &[4.0, 6.0, 3.0], &[3.0, 2.0]) else {
+            panic!("Expected an optimum");
+        };
+        assert!((value - 11.0).abs() < 1e-6);
+        assert!((x[0] - 3.0).abs() < 1e-6 && (x[1] - 1.0).abs() < 1e-6);
+
+        // x >= 2 written as -x <= -2, with x <= 1
+        let a = [vec![-1.0], vec![1.0]];
+        assert_eq!(maximize(&a, &[-2.0, 1.0], &[1.0]), LpResult::Infeasible);
+        // only x - y <= 1
+        assert_eq!(
+            maximize(&[vec![1.0, -1.0]], &[1.0], &[1.0, 0.0]),
+            LpResult::Unbounded
+        );
+        // a degenerate vertex where many constraints are tight
+        let a = [
+            vec![1.0, 1.0],
+            vec![2.0, 2.0],
+            vec![1.0, -1.0],
+            vec![-1.0, 1.0],
+        ];
+        let result = maximize(&a, &[2.0, 4.0, 0.0, 0.0], &[1.0, 1.0]);
+        assert!(matches!(result, LpResult::Optimal(v, _) if (v - 2.0).abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_against_vertex_enumeration() {
+        // two variables in a box: the optimum, if any, is a vertex where two
+        // of the constraints or axes are tight
+        let mut rng = Rng::new(63);
+        for _ in 0..300 {
+            let mut a = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+            let mut b = vec![10.0, 10.0];
+            for _ in 0..rng.gen_range(0, 5) {
+                a.push(vec![
+                    rng.gen_range_i64(-5, 5) as f64,
+                    rng.gen_range_i64(-5, 5) as f64,
+                ]);
+                b.push(rng.gen_range_i64(-5, 10) as f64);
+            }
+            let c = [
+                rng.gen_range_i64(-5, 5) as f64,
+                rng.gen_range_i64(-5, 5) as f64,
+            ];
+
+            let mut lines: Vec<([f64; 2], f64)> = vec![([1.0, 0.0], 0.0), ([0.0, 1.0], 0.0)];
+            lines.extend(a.iter().zip(&b).map(|(row, &rhs)| ([row[0], row[1]], rhs)));
+            let feasible = |p: [f64; 2]| {
+                p[0] >= -1e-7
+                    && p[1] >= -1e-7
+                    && a.iter()
+                        .zip(&b)
+                        .all(|(row, &rhs)| row[0] * p[0] + row[1] * p[1] <= rhs + 1e-7)
+            };
+            let mut best: Option<f64> = None;
+            for (i, &(p, e)) in lines.iter().enumerate() {
+                for &(q, f) in &lines[i + 1..] {
+                    let det = p[0] * q[1] - p[1] * q[0];
+                    if det.abs() < 1e-9 {
+                        continue;
+                    }
+                    let point = [(e * q[1] - p[1] * f) / det, (p[0] * f - e * q[0]) / det];
+                    if feasible(point) {
+                        let value = c[0] * point[0] + c[1] * point[1];
+                        best = Some(best.map_or(value, |b: f64| b.max(value)));
+                    }
+                }
+            }
+
+            match (maximize(&a, &b, &c), best) {
+                (LpResult::Optimal(value, x), Some(expected)) => {
+                    assert!((value - expected).abs() < 1e-6);
+                    assert!(feasible([x[0], x[1]]));
+                    assert!((c[0] * x[0] + c[1] * x[1] - value).abs() < 1e-6);
+                }
+                (LpResult::Infeasible, None) => {}
+                (result, expected) => panic!("Got {:?}, expected {:?}", result, expected),
+            }
+        }
+    }
+}
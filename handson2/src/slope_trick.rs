@@ -0,0 +1,289 @@
+// ------- SLOPE TRICK -------
+// Multiset with a global offset ("Venice technique"), where adding a delta
+// to every element is O(1), and the slope trick representation of convex
+// piecewise linear functions as two heaps of breakpoints.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
+
+/// Multiset of integers supporting "add delta to all elements" in O(1); the
+/// stored keys are the values minus the global offset
+#[derive(Clone, Debug, Default)]
+pub struct OffsetMultiset {
+    counts: BTreeMap<i64, usize>,
+    offset: i64,
+    len: usize,
+}
+
+impl OffsetMultiset {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of stored elements, counting duplicates
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Adds `delta` to every element currently stored
+    pub fn add_all(&mut self, delta: i64) {
+        self.offset += delta;
+    }
+
+    pub fn insert(&mut self, x: i64) {
+        *self.counts.entry(x - self.offset).or_insert(0) += 1;
+        self.len += 1;
+    }
+
+    /// Removes one occurrence of `x`, returns false if `x` was not present
+    pub fn erase_one(&mut self, x: i64) -> bool {
+        let key = x - self.offset;
+        match self.counts.get_mut(&key) {
+            Some(count) => {
+                *count -= 1;
+                if *count == 0 {
+                    self.counts.remove(&key);
+                }
+                self.len -= 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn count(&self, x: i64) -> usize {
+        self.counts.get(&(x - self.offset)).copied().unwrap_or(0)
+    }
+
+    pub fn min(&self) -> Option<i64> {
+        self.counts.keys().next().map(|&k| k + self.offset)
+    }
+
+    pub fn max(&self) -> Option<i64> {
+        self.counts.keys().next_back().map(|&k| k + self.offset)
+    }
+
+    pub fn pop_min(&mut self) -> Option<i64> {
+        let min = self.min()?;
+        self.erase_one(min);
+        Some(min)
+    }
+
+    pub fn pop_max(&mut self) -> Option<i64> {
+        let max = self.max()?;
+        self.erase_one(max);
+        Some(max)
+    }
+}
+
+/// Convex piecewise linear function f stored as its minimum value plus the
+/// breakpoints left and right of the minimum, each breakpoint changing the
+/// slope by one. Both sides carry a lazy shift.
+#[derive(Clone, Debug, Default)]
+pub struct SlopeTrick {
+    min_value: i64,
+    left: BinaryHeap<i64>,           // Breakpoints where the slope is < 0
+    right: BinaryHeap<Reverse<i64>>, // Breakpoints where the slope is > 0
+    shift_left: i64,
+    shift_right: i64,
+}
+
+impl SlopeTrick {
+    /// The constant function f(x) = 0
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Minimum value of f
+    pub fn min(&self) -> i64 {
+        self.min_value
+    }
+
+    /// Interval of the minimizers of f, None standing for an infinite end
+    pub fn argmin(&self) -> (Option<i64>, Option<i64>) {
+        (self.top_left(), self.top_right())
+    }
+
+    fn top_left(&self) -> Option<i64> {
+        self.left.peek().map(|&l| l + self.shift_left)
+    }
+
+    fn top_right(&self) -> Option<i64> {
+        self.right.peek().map(|&Reverse(r)| r + self.shift_right)
+    }
+
+    fn push_left(&mut self, a: i64) {
+        self.left.push(a - self.shift_left);
+    }
+
+    fn push_right(&mut self, a: i64) {
+        self.right.push(Reverse(a - self.shift_right));
+    }
+
+    fn pop_left(&mut self) -> i64 {
+        self.left.pop().unwrap() + self.shift_left
+    }
+
+    fn pop_right(&mut self) -> i64 {
+        self.right.pop().unwrap().0 + self.shift_right
+    }
+
+    /// f(x) += c
+    pub fn add_const(&mut self, c: i64) {
+        self.min_value += c;
+    }
+
+    /// f(x) += max(0, x - a)
+    pub fn add_x_minus_a(&mut self, a: i64) {
+        if let Some(l) = self.top_left() {
+            self.min_value += (l - a).max(0);
+        }
+        self.push_left(a);
+        let l = self.pop_left();
+        self.push_right(l);
+    }
+
+    /// f(x) += max(0, a - x)
+    pub fn add_a_minus_x(&mut self, a: i64) {
+        if let Some(r) = self.top_right() {
+            self.min_value += (a - r).max(0);
+        }
+        self.push_right(a);
+        let r = self.pop_right();
+        self.push_left(r);
+    }
+
+    /// f(x) += |x - a|
+    pub fn add_abs(&mut self, a: i64) {
+        self.add_x_minus_a(a);
+        self.add_a_minus_x(a);
+    }
+
+    /// f(x) = min over y <= x of f(y), dropping the increasing part
+    pub fn prefix_min(&mut self) {
+        self.right.clear();
+    }
+
+    /// f(x) = min over y >= x of f(y), dropping the decreasing part
+    pub fn suffix_min(&mut self) {
+        self.left.clear();
+    }
+
+    /// f(x) = min over x - b <= y <= x - a of f(y), with a <= b: the
+    /// decreasing part moves right by a and the increasing part by b
+    pub fn sliding_window_min(&mut self, a: i64, b: i64) {
+        assert!(a <= b, "Invalid range");
+        self.shift_left += a;
+        self.shift_right += b;
+    }
+
+    /// f(x) = f(x - a)
+    pub fn translate(&mut self, a: i64) {
+        self.sliding_window_min(a, a);
+    }
+
+    /// Value f(x), O(number of breakpoints)
+    pub fn eval(&self, x: i64) -> i64 {
+        let left: i64 = self
+            .left
+            .iter()
+            .map(|&l| (l + self.shift_left - x).max(0))
+            .sum();
+        let right: i64 = self
+            .right
+            .iter()
+            .map(|&Reverse(r)| (x - r - self.shift_right).max(0))
+            .sum();
+        self.min_value + left + right
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_offset_multiset() {
+        let mut set = OffsetMultiset::new();
+        for x in [5, 1, 5, 3] {
+            set.insert(x);
+        }
+        set.add_all(10);
+        assert_eq!((set.min(), set.max()), (Some(11), Some(15)));
+        assert_eq!(set.count(15), 2);
+        assert!(!set.erase_one(5));
+        assert!(set.erase_one(15));
+        set.insert(12);
+        set.add_all(-2);
+        assert_eq!(set.pop_min(), Some(9));
+        assert_eq!(set.pop_max(), Some(13));
+        assert_eq!((set.len(), set.min(), set.max()), (2, Some(10), Some(11)));
+    }
+
+    // Functions sampled on the grid [-RANGE, RANGE]. The operations keep every
+    // breakpoint within 50 of the origin, so f is linear past the edges.
+    const RANGE: i64 = 60;
+
+    fn at(samples: &[i64], x: i64) -> i64 {
+        let get = |x: i64| samples[(x + RANGE) as usize];
+        if x < -RANGE {
+            get(-RANGE) + (get(-RANGE) - get(-RANGE + 1)) * (-RANGE - x)
+        } else if x > RANGE {
+            get(RANGE) + (get(RANGE) - get(RANGE - 1)) * (x - RANGE)
+        } else {
+            get(x)
+        }
+    }
+
+    #[test]
+    fn test_against_sampled_function() {
+        let mut rng = Rng::new(41);
+        for _ in 0..100 {
+            let mut f = SlopeTrick::new();
+            let mut samples = vec![0i64; (2 * RANGE + 1) as usize];
+            for _ in 0..20 {
+                let a = rng.gen_range_i64(-10, 10);
+                let xs = -RANGE..=RANGE;
+                let next: Vec<i64> = match rng.gen_range(0, 5) {
+                    0 => {
+                        f.add_x_minus_a(a);
+                        xs.map(|x| at(&samples, x) + (x - a).max(0)).collect()
+                    }
+                    1 => {
+                        f.add_a_minus_x(a);
+                        xs.map(|x| at(&samples, x) + (a - x).max(0)).collect()
+                    }
+                    2 => {
+                        f.add_abs(a);
+                        xs.map(|x| at(&samples, x) + (x - a).abs()).collect()
+                    }
+                    3 => {
+                        f.prefix_min();
+                        let mut best = i64::MAX;
+                        xs.map(|x| {
+                            best = best.min(at(&samples, x));
+                            best
+                        })
+                        .collect()
+                    }
+                    _ => {
+                        let (lo, hi) = (rng.gen_range_i64(-1, 1), rng.gen_range_i64(1, 2));
+                        f.sliding_window_min(lo, hi);
+                        xs.map(|x| (x - hi..=x - lo).map(|y| at(&samples, y)).min().unwrap())
+                            .collect()
+                    }
+                };
+                samples = next;
+            }
+            for x in -RANGE..=RANGE {
+                assert_eq!(f.eval(x), at(&samples, x));
+            }
+            assert_eq!(f.min(), *samples.iter().min().unwrap());
+        }
+    }
+}
@@ -0,0 +1,154 @@
+// ------- SMALL TO LARGE -------
+// Bottom-up merging of per-subtree containers where the smaller container is
+// always merged into the larger one. Every element moves O(log n) times, so
+// the whole tree costs O(n log n) insertions instead of O(n^2).
+
+use crate::graph::rooted_order;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::hash::{BuildHasher, Hash};
+
+/// A container whose size decides the merge direction
+pub trait Container {
+    fn size(&self) -> usize;
+}
+
+impl<T> Container for Vec<T> {
+    fn size(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T> Container for BTreeSet<T> {
+    fn size(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<K, V> Container for BTreeMap<K, V> {
+    fn size(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T: Eq + Hash, S: BuildHasher> Container for HashSet<T, S> {
+    fn size(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<K: Eq + Hash, V, S: BuildHasher> Container for HashMap<K, V, S> {
+    fn size(&self) -> usize {
+        self.len()
+    }
+}
+
+/// Runs small-to-large merging on the tree `adj` rooted at `root`.
+/// - `init(v)` builds the container holding `v` alone;
+/// - `merge(big, small)` moves the contents of `small` into `big`;
+/// - `finalize(v, container)` is called once the whole subtree of `v` has
+///   been merged, children before parents.
+///
+/// Returns the container of the root, which covers the whole tree.
+pub fn small_to_large<C, I, M, F>(
+    adj: &[Vec<usize>],
+    root: usize,
+    mut init: I,
+    mut merge: M,
+    mut finalize: F,
+) -> C
+where
+    C: Container,
+    I: FnMut(usize) -> C,
+    M: FnMut(&mut C, C),
+    F: FnMut(usize, &C),
+{
+    let n = adj.len();
+    // preorder with parents, then walk it backwards to see children first
+    let (parent, order) = rooted_order(n, root, |v| adj[v].iter().copied());
+
+    let mut containers: Vec<Option<C>> = (0..n).map(|_| None).collect();
+    for &v in order.iter().rev() {
+        let mut own = init(v);
+        for &u in &adj[v] {
+            if parent[u] != v || u == root {
+                continue;
+            }
+            let mut child = containers[u].take().unwrap();
+            if child.size() > own.size() {
+                std::mem::swap(&mut own, &mut child);
+            }
+            merge(&mut own, child);
+        }
+        finalize(v, &own);
+        containers[v] = Some(own);
+    }
+    containers[root].take().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::FastHashSet;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_distinct_colors_per_subtree() {
+        let mut rng = Rng::new(24);
+        let n = 300;
+        let mut parent = vec![0; n];
+        let mut adj = vec![Vec::new(); n];
+        for v in 1..n {
+            parent[v] = rng.gen_range(0, v as u64 - 1) as usize;
+            adj[v].push(parent[v]);
+            adj[parent[v]].push(v);
+        }
+        let color: Vec<u32> = (0..n).map(|_| rng.gen_range(0, 20) as u32).collect();
+
+        let mut distinct = vec![0; n];
+        let mut moved = 0;
+        let all = small_to_large(
+            &adj,
+            0,
+            |v| FastHashSet::from_iter([color[v]]),
+            |big, small: FastHashSet<u32>| {
+                moved += small.len();
+                big.extend(small);
+            },
+            |v, set| distinct[v] = set.len(),
+        );
+        assert_eq!(all.len(), distinct[0]);
+
+        // parent[v] < v, so walking down the indices completes each subtree
+        // before it is merged into its parent
+        let mut naive: Vec<BTreeSet<u32>> = color.iter().map(|&c| BTreeSet::from([c])).collect();
+        let mut expected = vec![0; n];
+        for v in (0..n).rev() {
+            expected[v] = naive[v].len();
+            if v > 0 {
+                let set = std::mem::take(&mut naive[v]);
+                naive[parent[v]].extend(set);
+            }
+        }
+        assert_eq!(distinct, expected);
+        assert!(moved <= n * (usize::BITS - n.leading_zeros()) as usize);
+    }
+
+    #[test]
+    fn test_finalize_sees_children_first() {
+        // path 0 - 1 - 2 plus leaf 3 under 0
+        let adj = vec![vec![1, 3], vec![0, 2], vec![1], vec![0]];
+        let mut finished = Vec::new();
+        let root = small_to_large(
+            &adj,
+            0,
+            |v| vec![v],
+            |big, small| big.extend(small),
+            |v, values| finished.push((v, values.len())),
+        );
+        let position = |v| finished.iter().position(|&(w, _)| w == v).unwrap();
+        assert!(position(2) < position(1) && position(1) < position(0));
+        assert!(position(3) < position(0));
+        assert!(finished.contains(&(1, 2)) && finished.contains(&(0, 4)));
+        assert_eq!(root.len(), 4);
+    }
+}
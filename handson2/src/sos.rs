@@ -0,0 +1,290 @@
+// ------- SUM OVER SUBSETS -------
+// Zeta and Möbius transforms over the subset lattice in O(n 2^n), plus
+// iterators over submasks, supermasks and masks of a fixed popcount.
+// Bitwise convolutions h[i op j] += a[i] b[j] multiply pointwise in the
+// transformed domain: zeta for OR, superset zeta for AND, and Walsh-Hadamard
+// for XOR, whose inverse divides by 2^n (exact on integers, or a `ModInt`).
+// The subset convolution, over disjoint pairs only, ranks the zeta transforms
+// by popcount in O(n^2 2^n).
+
+use crate::prefix_sum::Summable;
+use std::ops::{Div, Mul};
+
+fn bits_of(len: usize) -> usize {
+    assert!(len.is_power_of_two(), "Length must be a power of two");
+    len.trailing_zeros() as usize
+}
+
+/// `f[mask] = sum of f[sub] over all sub ⊆ mask`
+pub fn subset_zeta<T: Summable>(f: &mut [T]) {
+    for bit in 0..bits_of(f.len()) {
+        for mask in 0..f.len() {
+            if mask >> bit & 1 == 1 {
+                f[mask] = f[mask] + f[mask ^ (1 << bit)];
+            }
+        }
+    }
+}
+
+/// Inverse of `subset_zeta`
+pub fn subset_mobius<T: Summable>(f: &mut [T]) {
+    for bit in 0..bits_of(f.len()) {
+        for mask in 0..f.len() {
+            if mask >> bit & 1 == 1 {
+                f[mask] = f[mask] - f[mask ^ (1 << bit)];
+            }
+        }
+    }
+}
+
+/// `f[mask] = sum of f[sup] over all sup ⊇ mask`
+pub fn superset_zeta<T: Summable>(f: &mut [T]) {
+    for bit in 0..bits_of(f.len()) {
+        for mask in 0..f.len() {
+            if mask >> bit & 1 == 0 {
+                f[mask] = f[mask] + f[mask | (1 << bit)];
+            }
+        }
+    }
+}
+
+/// Inverse of `superset_zeta`
+pub fn superset_mobius<T: Summable>(f: &mut [T]) {
+    for bit in 0..bits_of(f.len()) {
+        for mask in 0..f.len() {
+            if mask >> bit & 1 == 0 {
+                f[mask] = f[mask] - f[mask | (1 << bit)];
+            }
+        }
+    }
+}
+
+/// Walsh-Hadamard transform: `f[mask] = sum of (-1)^popcount(mask & i) f[i]`
+pub fn walsh_hadamard<T: Summable>(f: &mut [T]) {
+    for bit in 0..bits_of(f.len()) {
+        for mask in 0..f.len() {
+            if mask >> bit & 1 == 0 {
+                let (a, b) = (f[mask], f[mask | (1 << bit)]);
+                f[mask] = a + b;
+                f[mask | (1 << bit)] = a - b;
+            }
+        }
+    }
+}
+
+/// Inverse of `walsh_hadamard`
+pub fn walsh_hadamard_inverse<T: Summable + Div<Output = T> + From<u32>>(f: &mut [T]) {
+    walsh_hadamard(f);
+    let len = T::from(f.len() as u32);
+    for x in f.iter_mut() {
+        *x = *x / len;
+    }
+}
+
+// Pointwise product of the transforms of a and b, transformed back
+fn transform_convolution<T: Summable + Mul<Output = T>>(
+    a: &[T],
+    b: &[T],
+    forward: fn(&mut [T]),
+    inverse: fn(&mut [T]),
+) -> Vec<T> {
+    assert_eq!(a.len(), b.len(), "Lengths differ");
+    let (mut fa, mut fb) = (a.to_vec(), b.to_vec());
+    forward(&mut fa);
+    forward(&mut fb);
+    for (x, &y) in fa.iter_mut().zip(&fb) {
+        *x = *x * y;
+    }
+    inverse(&mut fa);
+    fa
+}
+
+/// `h[k] = sum of a[i] b[j] over i | j = k`
+pub fn or_convolution<T: Summable + Mul<Output = T>>(a: &[T], b: &[T]) -> Vec<T> {
+    transform_convolution(a, b, subset_zeta, subset_mobius)
+}
+
+/// `h[k] = sum of a[i] b[j] over i & j = k`
+pub fn and_convolution<T: Summable + Mul<Output = T>>(a: &[T], b: &[T]) -> Vec<T> {
+    transform_convolution(a, b, superset_zeta, superset_mobius)
+}
+
+/// `h[k] = sum of a[i] b[j] over i ^ j = k`
+pub fn xor_convolution<T>(a: &[T], b: &[T]) -> Vec<T>
+where
+    T: Summable + Mul<Output = T> + Div<Output = T> + From<u32>,
+{
+    transform_convolution(a, b, walsh_hadamard, walsh_hadamard_inverse)
+}
+
+/// `h[k] = sum of a[i] b[k ^ i] over the submasks i of k`, in O(n^2 2^n)
+pub fn subset_convolution<T: Summable + Mul<Output = T>>(a: &[T], b: &[T]) -> Vec<T> {
+    assert_eq!(a.len(), b.len(), "Lengths differ");
+    let bits = bits_of(a.len());
+    // zeta transforms of the entries of each popcount
+    let ranked = |f: &[T]| {
+        let mut by_rank = vec![vec![T::default(); f.len()]; bits + 1];
+        for (mask, &x) in f.iter().enumerate() {
+            by_rank[mask.count_ones() as usize][mask] = x;
+        }
+        by_rank.iter_mut().for_each(|g| subset_zeta(g));
+        by_rank
+    };
+    let (ra, rb) = (ranked(a), ranked(b));
+    let mut result = vec![T::default(); a.len()];
+    for rank in 0..=bits {
+        let mut h = vec![T::default(); a.len()];
+        for i in 0..=rank {
+            for (mask, x) in h.iter_mut().enumerate() {
+                *x = *x + ra[i][mask] * rb[rank - i][mask];
+            }
+        }
+        subset_mobius(&mut h);
+        // only masks of this popcount have disjoint parts of these ranks
+        for (mask, &x) in h.iter().enumerate() {
+            if mask.count_ones() as usize == rank {
+                result[mask] = x;
+            }
+        }
+    }
+    result
+}
+
+/// All the submasks of `mask` in decreasing order, `mask` and 0 included
+pub fn submasks(mask: usize) -> impl Iterator<Item = usize> {
+    let mut next = Some(mask);
+    std::iter::from_fn(move || {
+        let current = next?;
+        next = if current == 0 {
+            None
+        } else {
+            Some((current - 1) & mask)
+        };
+        Some(current)
+    })
+}
+
+/// All the supermasks of `mask` with `bits` bits in increasing order, `mask`
+/// included
+pub fn supermasks(mask: usize, bits: usize) -> impl Iterator<Item = usize> {
+    let full = (1 << bits) - 1;
+    assert!(mask & !full == 0, "Mask out of range");
+    let mut next = Some(mask);
+    std::iter::from_fn(move || {
+        let current = next?;
+        next = if current == full {
+            None
+        } else {
+            Some((current + 1) | mask)
+        };
+        Some(current)
+    })
+}
+
+/// All the masks of `bits` bits with exactly `k` ones in increasing order
+/// (Gosper's hack)
+pub fn masks_with_popcount(bits: usize, k: usize) -> impl Iterator<Item = usize> {
+    let mut next = if k > bits {
+        None
+    } else {
+        Some((1usize << k) - 1)
+    };
+    std::iter::from_fn(move || {
+        let current = next?;
+        next = if current == 0 {
+            None
+        } else {
+            let low = current & current.wrapping_neg();
+            let ripple = current + low;
+            let following = (((ripple ^ current) >> 2) / low) | ripple;
+            (following >> bits == 0).then_some(following)
+        };
+        Some(current)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modint::Mod998244353;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_transforms_against_brute_force() {
+        let mut rng = Rng::new(17);
+        let bits = 6;
+        let f: Vec<u64> = (0..1 << bits).map(|_| rng.gen_range(0, 100)).collect();
+
+        let mut sub = f.clone();
+        subset_zeta(&mut sub);
+        let mut sup = f.clone();
+        superset_zeta(&mut sup);
+        for mask in 0..f.len() {
+            let below: u64 = (0..f.len()).filter(|&s| s & mask == s).map(|s| f[s]).sum();
+            let above: u64 = (0..f.len())
+                .filter(|&s| s & mask == mask)
+                .map(|s| f[s])
+                .sum();
+            assert_eq!(sub[mask], below);
+            assert_eq!(sup[mask], above);
+        }
+
+        subset_mobius(&mut sub);
+        superset_mobius(&mut sup);
+        assert_eq!(sub, f);
+        assert_eq!(sup, f);
+    }
+
+    #[test]
+    fn test_convolutions_against_brute_force() {
+        let mut rng = Rng::new(73);
+        let bits = 5;
+        let n = 1 << bits;
+        let a: Vec<i64> = (0..n).map(|_| rng.gen_range_i64(-50, 50)).collect();
+        let b: Vec<i64> = (0..n).map(|_| rng.gen_range_i64(-50, 50)).collect();
+        let brute = |op: fn(usize, usize) -> Option<usize>| {
+            let mut h = vec![0i64; n];
+            for (i, &x) in a.iter().enumerate() {
+                for (j, &y) in b.iter().enumerate() {
+                    if let Some(k) = op(i, j) {
+                        h[k] += x * y;
+                    }
+                }
+            }
+            h
+        };
+        assert_eq!(or_convolution(&a, &b), brute(|i, j| Some(i | j)));
+        assert_eq!(and_convolution(&a, &b), brute(|i, j| Some(i & j)));
+        assert_eq!(xor_convolution(&a, &b), brute(|i, j| Some(i ^ j)));
+        let disjoint = brute(|i, j| (i & j == 0).then_some(i | j));
+        assert_eq!(subset_convolution(&a, &b), disjoint);
+
+        // modulo a prime, with an inverse transform dividing by 2^n
+        let to_mod = |f: &[i64]| f.iter().map(|&x| Mod998244353::from(x)).collect::<Vec<_>>();
+        assert_eq!(
+            xor_convolution(&to_mod(&a), &to_mod(&b)),
+            to_mod(&brute(|i, j| Some(i ^ j)))
+        );
+        assert_eq!(
+            subset_convolution(&to_mod(&a), &to_mod(&b)),
+            to_mod(&disjoint)
+        );
+    }
+
+    #[test]
+    fn test_mask_iterators() {
+        let mut subs: Vec<usize> = submasks(0b1010).collect();
+        assert_eq!(subs, vec![0b1010, 0b1000, 0b0010, 0]);
+        subs = submasks(0).collect();
+        assert_eq!(subs, vec![0]);
+
+        let sups: Vec<usize> = supermasks(0b101, 3).collect();
+        assert_eq!(sups, vec![0b101, 0b111]);
+
+        let masks: Vec<usize> = masks_with_popcount(4, 2).collect();
+        assert_eq!(masks, vec![0b0011, 0b0101, 0b0110, 0b1001, 0b1010, 0b1100]);
+        assert_eq!(masks_with_popcount(5, 0).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(masks_with_popcount(3, 4).count(), 0);
+        assert_eq!(masks_with_popcount(10, 4).count(), 210);
+    }
+}
@@ -0,0 +1,75 @@
+// ------- SPARSE TABLE -------
+// Static range queries in O(1) for idempotent operations (min, max, gcd, ...)
+// after O(n log n) preprocessing. Ranges are 0-based and inclusive.
+
+pub struct SparseTable<T> {
+    levels: Vec<Vec<T>>, // levels[k][i] = op over [i, i + 2^k)
+    op: fn(T, T) -> T,
+}
+
+impl<T: Copy> SparseTable<T> {
+    /// Builds the table for `op`, which must be associative and idempotent
+    pub fn new(values: &[T], op: fn(T, T) -> T) -> Self {
+        let mut levels = vec![values.to_vec()];
+        let mut width = 1;
+        while 2 * width <= values.len() {
+            let prev = levels.last().unwrap();
+            let next = (0..=values.len() - 2 * width)
+                .map(|i| op(prev[i], prev[i + width]))
+                .collect();
+            levels.push(next);
+            width *= 2;
+        }
+        SparseTable { levels, op }
+    }
+
+    pub fn len(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// `op` over the values in `[l, r]`, as two overlapping power-of-two blocks
+    pub fn query(&self, l: usize, r: usize) -> T {
+        assert!(l <= r && r < self.len(), "Invalid range");
+        let k = (r - l + 1).ilog2() as usize;
+        let level = &self.levels[k];
+        (self.op)(level[l], level[r + 1 - (1 << k)])
+    }
+}
+
+impl<T: Copy + Ord> SparseTable<T> {
+    pub fn min(values: &[T]) -> Self {
+        SparseTable::new(values, std::cmp::min)
+    }
+
+    pub fn max(values: &[T]) -> Self {
+        SparseTable::new(values, std::cmp::max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = Rng::new(20);
+        for n in [1, 2, 7, 64, 100] {
+            let values: Vec<u32> = (0..n).map(|_| rng.gen_range(0, 1000) as u32).collect();
+            let min = SparseTable::min(&values);
+            let max = SparseTable::max(&values);
+            for l in 0..n {
+                for r in l..n {
+                    let slice = &values[l..=r];
+                    assert_eq!(min.query(l, r), *slice.iter().min().unwrap());
+                    assert_eq!(max.query(l, r), *slice.iter().max().unwrap());
+                }
+            }
+        }
+        assert!(SparseTable::<u32>::min(&[]).is_empty());
+    }
+}
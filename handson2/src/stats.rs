@@ -0,0 +1,205 @@
+// ------- INSTRUMENTATION -------
+// Operation counters for checking complexity claims empirically: the
+// structures record node visits, lazy pushes and value comparisons into
+// per-thread counters. Without the `stats` feature, recording compiles to
+// nothing and `Stats` always reads as zero.
+
+#[cfg(feature = "stats")]
+use std::cell::Cell;
+
+/// Operations counted on the current thread
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub node_visits: u64,
+    pub lazy_pushes: u64,
+    pub comparisons: u64,
+}
+
+#[cfg(feature = "stats")]
+thread_local! {
+    static COUNTERS: Cell<Stats> = const {
+        Cell::new(Stats {
+            node_visits: 0,
+            lazy_pushes: 0,
+            comparisons: 0,
+        })
+    };
+}
+
+impl Stats {
+    /// Whether the counters are compiled in
+    pub fn enabled() -> bool {
+        cfg!(feature = "stats")
+    }
+
+    /// The counts recorded on the current thread since the last reset
+    pub fn current() -> Stats {
+        #[cfg(feature = "stats")]
+        return COUNTERS.with(Cell::get);
+        #[cfg(not(feature = "stats"))]
+        Stats::default()
+    }
+
+    pub fn reset() {
+        #[cfg(feature = "stats")]
+        COUNTERS.with(|c| c.set(Stats::default()));
+    }
+
+    /// Runs `f` and returns the counts it recorded on the current thread
+    pub fn measure<R, F: FnOnce() -> R>(f: F) -> (R, Stats) {
+        let before = Stats::current();
+        let result = f();
+        let after = Stats::current();
+        let stats = Stats {
+            node_visits: after.node_visits - before.node_visits,
+            lazy_pushes: after.lazy_pushes - before.lazy_pushes,
+            comparisons: after.comparisons - before.comparisons,
+        };
+        (result, stats)
+    }
+}
+
+// Add to the counters of the current thread, a no-op without the feature
+#[inline(always)]
+#[allow(unused_variables)]
+fn record<F: FnOnce(&mut Stats)>(f: F) {
+    #[cfg(feature = "stats")]
+    COUNTERS.with(|c| {
+        let mut stats = c.get();
+        f(&mut stats);
+        c.set(stats);
+    });
+}
+
+#[inline(always)]
+pub(crate) fn visit() {
+    record(|s| s.node_visits += 1);
+}
+
+#[inline(always)]
+pub(crate) fn lazy_push() {
+    record(|s| s.lazy_pushes += 1);
+}
+
+#[inline(always)]
+pub(crate) fn compare() {
+    record(|s| s.comparisons += 1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::historic::HistoricMaxTree;
+    use crate::lazy_segment_tree::{Affine, DualSegmentTree, RangeAffineRangeSum, SumLen};
+    use crate::modint::ModInt;
+    use crate::rng::Rng;
+    use crate::segment_tree::{SegmentTree, Sum};
+    use crate::MaxSegmentTree;
+
+    const N: usize = 1 << 12;
+
+    // Random ranges in [0, N)
+    fn random_range(rng: &mut Rng) -> (usize, usize) {
+        let a = rng.gen_range(0, N as u64 - 1) as usize;
+        let b = rng.gen_range(0, N as u64 - 1) as usize;
+        (a.min(b), a.max(b))
+    }
+
+    // Counter by counter maximum of `runs`
+    fn worst(runs: &[Stats]) -> Stats {
+        runs.iter().fold(Stats::default(), |worst, stats| Stats {
+            node_visits: worst.node_visits.max(stats.node_visits),
+            lazy_pushes: worst.lazy_pushes.max(stats.lazy_pushes),
+            comparisons: worst.comparisons.max(stats.comparisons),
+        })
+    }
+
+    // At most `per_level` visits and pushes on each of the 13 levels of a tree
+    // over N leaves, or nothing recorded without the feature
+    fn assert_logarithmic(worst: Stats, per_level: u64, pushes: bool) {
+        if Stats::enabled() {
+            assert!(worst.node_visits > 0 && worst.node_visits <= per_level * 13);
+            assert_eq!(worst.lazy_pushes > 0, pushes);
+            assert!(worst.lazy_pushes <= per_level * 13);
+        } else {
+            assert_eq!(worst, Stats::default());
+        }
+    }
+
+    #[test]
+    // Run with `--features stats` to check the bounds
+    fn test_segment_tree_operations_are_logarithmic() {
+        let mut rng = Rng::new(52);
+        let arr: Vec<u32> = (0..N).map(|_| rng.gen_range(0, 1000) as u32).collect();
+        let mut tree = MaxSegmentTree::new(&arr);
+        let mut runs = Vec::new();
+        for _ in 0..500 {
+            let (start, end) = random_range(&mut rng);
+            runs.push(Stats::measure(|| tree.update(start, end, 500)).1);
+            runs.push(Stats::measure(|| tree.query(start, end)).1);
+        }
+        let worst = worst(&runs);
+        // at most 4 nodes per level are visited, 12 levels below the root
+        assert_logarithmic(worst, 4, true);
+        assert!(worst.comparisons <= 4 * 13 * 2);
+    }
+
+    #[test]
+    fn test_generic_segment_tree_counts() {
+        let mut rng = Rng::new(100);
+        let mut tree = SegmentTree::new(&vec![Sum(1i64); N]);
+        let mut runs = Vec::new();
+        for _ in 0..500 {
+            let (l, r) = random_range(&mut rng);
+            runs.push(Stats::measure(|| tree.set(l, Sum(2))).1);
+            runs.push(Stats::measure(|| tree.query(l, r)).1);
+        }
+        // two nodes per level at most in a query, one in a point update
+        assert_logarithmic(worst(&runs), 2, false);
+    }
+
+    #[test]
+    fn test_lazy_segment_tree_counts() {
+        type Mint = ModInt<998_244_353>;
+        let mut rng = Rng::new(101);
+        let leaves = vec![SumLen::single(Mint::new(1)); N];
+        let mut tree = RangeAffineRangeSum::new(&leaves);
+        let f = Affine::new(Mint::new(2), Mint::new(3));
+        let mut runs = Vec::new();
+        for _ in 0..500 {
+            let (l, r) = random_range(&mut rng);
+            runs.push(Stats::measure(|| tree.update(l, r, &f)).1);
+            runs.push(Stats::measure(|| tree.query(l, r)).1);
+        }
+        assert_logarithmic(worst(&runs), 4, true);
+    }
+
+    #[test]
+    fn test_dual_segment_tree_counts() {
+        let mut rng = Rng::new(102);
+        let mut tree = DualSegmentTree::new(&vec![Sum(0i64); N]);
+        let mut runs = Vec::new();
+        for _ in 0..500 {
+            let (l, r) = random_range(&mut rng);
+            runs.push(Stats::measure(|| tree.update(l, r, &Sum(1))).1);
+            runs.push(Stats::measure(|| tree.get(l)).1);
+        }
+        // tags are never pushed
+        assert_logarithmic(worst(&runs), 2, false);
+    }
+
+    #[test]
+    fn test_historic_max_tree_counts() {
+        let mut rng = Rng::new(103);
+        let mut tree = HistoricMaxTree::new(&vec![0; N]);
+        let mut runs = Vec::new();
+        for _ in 0..500 {
+            let (l, r) = random_range(&mut rng);
+            let v = rng.gen_range_i64(-100, 100);
+            runs.push(Stats::measure(|| tree.range_add(l, r, v)).1);
+            runs.push(Stats::measure(|| tree.range_assign(l, r, v)).1);
+            runs.push(Stats::measure(|| tree.range_historic_max(l, r)).1);
+        }
+        assert_logarithmic(worst(&runs), 4, true);
+    }
+}
@@ -0,0 +1,137 @@
+// ------- STRESS TESTING -------
+// Differential testing of a solver against a reference one: every case is
+// generated from its own seed, so the first mismatching case can be rebuilt
+// with `replay` and debugged in isolation. Generators usually wrap the
+// `testgen` functions, references the naive oracles.
+
+use crate::rng::Rng;
+use std::fmt::{self, Debug};
+
+/// A case on which the solver disagrees with the reference
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mismatch<I, O> {
+    /// Seed of the `Rng` the case was generated with
+    pub seed: u64,
+    pub input: I,
+    pub expected: O,
+    pub actual: O,
+}
+
+impl<I: Debug, O: Debug> fmt::Display for Mismatch<I, O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "mismatch on seed {}\ninput: {:?}\nexpected: {:?}\nactual: {:?}",
+            self.seed, self.input, self.expected, self.actual
+        )
+    }
+}
+
+/// Runs `iterations` cases with seeds `first_seed`, `first_seed + 1`, ...
+/// and returns the first one where `solver` and `reference` disagree
+pub fn stress<I, O, G, R, S>(
+    first_seed: u64,
+    iterations: usize,
+    mut generate: G,
+    mut reference: R,
+    mut solver: S,
+) -> Result<(), Mismatch<I, O>>
+where
+    O: PartialEq,
+    G: FnMut(&mut Rng) -> I,
+    R: FnMut(&I) -> O,
+    S: FnMut(&I) -> O,
+{
+    for i in 0..iterations {
+        let seed = first_seed.wrapping_add(i as u64);
+        let input = generate(&mut Rng::new(seed));
+        let expected = reference(&input);
+        let actual = solver(&input);
+        if expected != actual {
+            return Err(Mismatch {
+                seed,
+                input,
+                expected,
+                actual,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Like `stress`, but panics with the mismatching case, for use in tests
+pub fn assert_stress<I, O, G, R, S>(
+    first_seed: u64,
+    iterations: usize,
+    generate: G,
+    reference: R,
+    solver: S,
+) where
+    I: Debug,
+    O: PartialEq + Debug,
+    G: FnMut(&mut Rng) -> I,
+    R: FnMut(&I) -> O,
+    S: FnMut(&I) -> O,
+{
+    if let Err(mismatch) = stress(first_seed, iterations, generate, reference, solver) {
+        panic!("{}", mismatch);
+    }
+}
+
+/// The input `generate` produces for `seed`, to rebuild a reported case
+pub fn replay<I, G: FnOnce(&mut Rng) -> I>(seed: u64, generate: G) -> I {
+    generate(&mut Rng::new(seed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testgen::{gen_exercise1, naive_exercise1};
+    use crate::{MaxSegmentTree, TestCase};
+
+    fn generate(rng: &mut Rng) -> TestCase {
+        let n = rng.gen_range(1, 30) as usize;
+        gen_exercise1(rng, n, 20, 50)
+    }
+
+    fn reference(case: &TestCase) -> Vec<u32> {
+        naive_exercise1(case.data(), case.queries())
+    }
+
+    #[test]
+    fn test_segment_tree_against_oracle() {
+        assert_stress(0, 200, generate, reference, |case: &TestCase| {
+            let mut tree = MaxSegmentTree::new(case.data());
+            let mut results = Vec::new();
+            for &(start, end, update) in case.queries() {
+                match update {
+                    Some(value) => tree.range_update(start, end, value),
+                    None => results.push(tree.range_max_query_lazy(start, end)),
+                }
+            }
+            results
+        });
+    }
+
+    #[test]
+    fn test_reports_first_mismatch() {
+        // ignores the updates, so it fails once a case updates before a query
+        let buggy = |case: &TestCase| -> Vec<u32> {
+            let data = case.data();
+            case.queries()
+                .iter()
+                .filter(|q| q.2.is_none())
+                .map(|&(start, end, _)| *data[start - 1..end].iter().max().unwrap())
+                .collect()
+        };
+        let mismatch = stress(100, 200, generate, reference, buggy).unwrap_err();
+        assert!(mismatch.seed >= 100);
+        assert_ne!(mismatch.expected, mismatch.actual);
+        let case = replay(mismatch.seed, generate);
+        assert_eq!(case.data(), mismatch.input.data());
+        assert_eq!(reference(&case), mismatch.expected);
+        // every earlier seed passes
+        let seeds = mismatch.seed - 100;
+        assert!(stress(100, seeds as usize, generate, reference, buggy).is_ok());
+    }
+}
@@ -0,0 +1,164 @@
+// ------- STRING DP -------
+// Longest common subsequence with reconstruction, edit distance in linear
+// memory and Hirschberg's linear-memory LCS reconstruction. The plain LCS
+// length lives in `dp::lcs_length`.
+
+/// One longest common subsequence of `a` and `b`, in O(|a| |b|) time and memory
+pub fn lcs<T: Eq + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    let m = b.len();
+    // table[i][j] = LCS length of a[i..] and b[j..]
+    let mut table = vec![vec![0usize; m + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..m).rev() {
+            table[i][j] = if a[i] == b[j] {
+                table[i + 1][j + 1] + 1
+            } else {
+                table[i + 1][j].max(table[i][j + 1])
+            };
+        }
+    }
+
+    let mut result = Vec::with_capacity(table[0][0]);
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < m {
+        if a[i] == b[j] {
+            result.push(a[i].clone());
+            i += 1;
+            j += 1;
+        } else if table[i + 1][j] >= table[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    result
+}
+
+/// Levenshtein distance between `a` and `b` with unit costs, in O(|a| |b|)
+/// time and O(min(|a|, |b|)) memory
+pub fn edit_distance<T: Eq>(a: &[T], b: &[T]) -> usize {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut row: Vec<usize> = (0..=short.len()).collect();
+    for (i, x) in long.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, y) in short.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if x == y {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[short.len()]
+}
+
+/// One longest common subsequence of `a` and `b` in O(|a| |b|) time and
+/// O(|a| + |b|) memory (Hirschberg's divide and conquer)
+pub fn hirschberg_lcs<T: Eq + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    let mut result = Vec::new();
+    hirschberg(a, b, &mut result);
+    result
+}
+
+fn hirschberg<T: Eq + Clone>(a: &[T], b: &[T], result: &mut Vec<T>) {
+    if a.is_empty() || b.is_empty() {
+        return;
+    }
+    if a.len() == 1 {
+        if b.contains(&a[0]) {
+            result.push(a[0].clone());
+        }
+        return;
+    }
+    // split a in half and find where the optimal path crosses the middle row
+    let mid = a.len() / 2;
+    let forward = lcs_last_row(a[..mid].iter(), b.iter());
+    let backward = lcs_last_row(a[mid..].iter().rev(), b.iter().rev());
+    let split = (0..=b.len())
+        .max_by_key(|&k| (forward[k] + backward[b.len() - k], std::cmp::Reverse(k)))
+        .unwrap();
+    hirschberg(&a[..mid], &b[..split], result);
+    hirschberg(&a[mid..], &b[split..], result);
+}
+
+// LCS lengths of the whole `a` against every prefix of `b`
+fn lcs_last_row<'a, T: Eq + 'a>(
+    a: impl Iterator<Item = &'a T>,
+    b: impl Iterator<Item = &'a T> + Clone,
+) -> Vec<usize> {
+    let mut row = vec![0; b.clone().count() + 1];
+    for x in a {
+        let mut diagonal = 0;
+        for (j, y) in b.clone().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if x == y {
+                diagonal + 1
+            } else {
+                above.max(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dp::lcs_length;
+    use crate::rng::Rng;
+
+    fn is_subsequence(sub: &[u8], s: &[u8]) -> bool {
+        let mut it = s.iter();
+        sub.iter().all(|c| it.any(|x| x == c))
+    }
+
+    // textbook O(nm) table, used as reference
+    fn naive_edit_distance(a: &[u8], b: &[u8]) -> usize {
+        let mut table = vec![vec![0; b.len() + 1]; a.len() + 1];
+        for (i, row) in table.iter_mut().enumerate() {
+            row[0] = i;
+        }
+        for (j, cell) in table[0].iter_mut().enumerate() {
+            *cell = j;
+        }
+        for i in 1..=a.len() {
+            for j in 1..=b.len() {
+                let substitution = table[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+                table[i][j] = substitution
+                    .min(table[i - 1][j] + 1)
+                    .min(table[i][j - 1] + 1);
+            }
+        }
+        table[a.len()][b.len()]
+    }
+
+    #[test]
+    fn test_examples() {
+        assert_eq!(lcs(b"ABCBDAB", b"BDCABA").len(), 4);
+        assert_eq!(edit_distance(b"kitten", b"sitting"), 3);
+        assert_eq!(edit_distance(b"", b"abc"), 3);
+        assert_eq!(edit_distance(b"flaw", b"lawn"), 2);
+        assert_eq!(hirschberg_lcs(b"AGGTAB", b"GXTXAYB"), b"GTAB".to_vec());
+    }
+
+    #[test]
+    fn test_against_reference() {
+        let mut rng = Rng::new(19);
+        for _ in 0..200 {
+            let n = rng.gen_range(0, 30) as usize;
+            let m = rng.gen_range(0, 30) as usize;
+            let a: Vec<u8> = (0..n).map(|_| b'a' + rng.gen_range(0, 3) as u8).collect();
+            let b: Vec<u8> = (0..m).map(|_| b'a' + rng.gen_range(0, 3) as u8).collect();
+            let length = lcs_length(&a, &b);
+            for common in [lcs(&a, &b), hirschberg_lcs(&a, &b)] {
+                assert_eq!(common.len(), length);
+                assert!(is_subsequence(&common, &a) && is_subsequence(&common, &b));
+            }
+            assert_eq!(edit_distance(&a, &b), naive_edit_distance(&a, &b));
+        }
+    }
+}
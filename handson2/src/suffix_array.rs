@@ -0,0 +1,293 @@
+// ------- SUFFIX ARRAY -------
+// Suffix array by prefix doubling in O(n log^2 n), LCP array by Kasai in O(n),
+// and O(1) longest-common-prefix queries between any two suffixes through a
+// sparse table. Deterministic, so no hash collisions to worry about. The
+// generalized variant indexes a set of strings joined by distinct separators.
+
+use std::cmp::Ordering;
+
+use crate::distinct::DistinctCounter;
+use crate::sparse_table::SparseTable;
+
+pub struct SuffixArray {
+    sa: Vec<usize>,   // sa[k] = start of the k-th smallest suffix
+    rank: Vec<usize>, // rank[i] = position of suffix i in sa
+    lcp: Vec<usize>,  // lcp[k] = LCP of the suffixes sa[k] and sa[k + 1]
+}
+
+impl SuffixArray {
+    pub fn new<T: Ord>(s: &[T]) -> Self {
+        let n = s.len();
+        // initial ranks: the compressed characters
+        let mut sa: Vec<usize> = (0..n).collect();
+        sa.sort_by(|&a, &b| s[a].cmp(&s[b]));
+        let mut rank = vec![0; n];
+        for k in 1..n {
+            let step = usize::from(s[sa[k]] != s[sa[k - 1]]);
+            rank[sa[k]] = rank[sa[k - 1]] + step;
+        }
+
+        // sort by (rank[i], rank[i + len]) doubling len until all ranks differ
+        let mut len = 1;
+        let mut next = vec![0; n];
+        while len < n && rank[sa[n - 1]] < n - 1 {
+            let key = |i: usize| (rank[i], rank.get(i + len).map_or(0, |r| r + 1));
+            sa.sort_by_key(|&i| key(i));
+            next[sa[0]] = 0;
+            for k in 1..n {
+                next[sa[k]] = next[sa[k - 1]] + usize::from(key(sa[k]) != key(sa[k - 1]));
+            }
+            std::mem::swap(&mut rank, &mut next);
+            len *= 2;
+        }
+
+        // Kasai: the LCP drops by at most one moving from suffix i to i + 1
+        let mut lcp = vec![0; n.saturating_sub(1)];
+        let mut h: usize = 0;
+        for i in 0..n {
+            if rank[i] + 1 == n {
+                h = 0;
+                continue;
+            }
+            let j = sa[rank[i] + 1];
+            while i + h < n && j + h < n && s[i + h] == s[j + h] {
+                h += 1;
+            }
+            lcp[rank[i]] = h;
+            h = h.saturating_sub(1);
+        }
+        SuffixArray { sa, rank, lcp }
+    }
+
+    pub fn len(&self) -> usize {
+        self.sa.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sa.is_empty()
+    }
+
+    /// Starting positions of the suffixes in lexicographic order
+    pub fn suffixes(&self) -> &[usize] {
+        &self.sa
+    }
+
+    /// Position of the suffix starting at `i` in the sorted order
+    pub fn rank(&self, i: usize) -> usize {
+        self.rank[i]
+    }
+
+    /// `lcp_array()[k]` is the LCP of the k-th and (k + 1)-th smallest suffixes
+    pub fn lcp_array(&self) -> &[usize] {
+        &self.lcp
+    }
+}
+
+/// Longest common prefix of any two suffixes in O(1), and lexicographic
+/// comparison of arbitrary substrings built on top of it
+pub struct LcpQuery {
+    sa: SuffixArray,
+    table: SparseTable<usize>,
+}
+
+impl LcpQuery {
+    pub fn new<T: Ord>(s: &[T]) -> Self {
+        let sa = SuffixArray::new(s);
+        let table = SparseTable::min(sa.lcp_array());
+        LcpQuery { sa, table }
+    }
+
+    pub fn suffix_array(&self) -> &SuffixArray {
+        &self.sa
+    }
+
+    /// Length of the longest common prefix of the suffixes starting at `i`
+    /// and `j`
+    pub fn lcp(&self, i: usize, j: usize) -> usize {
+        let n = self.sa.len();
+        assert!(i < n && j < n, "Index out of range");
+        if i == j {
+            return n - i;
+        }
+        let (a, b) = (self.sa.rank(i), self.sa.rank(j));
+        self.table.query(a.min(b), a.max(b) - 1)
+    }
+
+    /// Compares the substrings `[l1, r1]` and `[l2, r2]` lexicographically
+    pub fn compare(&self, (l1, r1): (usize, usize), (l2, r2): (usize, usize)) -> Ordering {
+        let n = self.sa.len();
+        assert!(l1 <= r1 && r1 < n && l2 <= r2 && r2 < n, "Invalid range");
+        let (len1, len2) = (r1 - l1 + 1, r2 - l2 + 1);
+        let common = self.lcp(l1, l2);
+        if common >= len1.min(len2) {
+            len1.cmp(&len2)
+        } else {
+            // both substrings go on past the common prefix, the suffixes decide
+            self.sa.rank(l1).cmp(&self.sa.rank(l2))
+        }
+    }
+}
+
+// A character of the joined strings: the separators are all distinct and
+// smaller than any character, so no common prefix crosses one
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Symbol<T> {
+    Separator(usize),
+    Char(T),
+}
+
+/// Suffix array of a set of strings, answering how many of them contain a
+/// pattern in O(m log n) after O(n log^2 n) preprocessing
+pub struct GeneralizedSuffixArray<T> {
+    text: Vec<Symbol<T>>, // The strings, each followed by its separator
+    sa: SuffixArray,
+    origin: Vec<(usize, usize)>, // (string, offset) of every position of `text`
+    owners: DistinctCounter,     // String of each suffix, in sorted order
+}
+
+impl<T: Ord + Clone> GeneralizedSuffixArray<T> {
+    pub fn new<S: AsRef<[T]>>(strings: &[S]) -> Self {
+        let mut text = Vec::new();
+        let mut origin = Vec::new();
+        for (i, string) in strings.iter().enumerate() {
+            let string = string.as_ref();
+            text.extend(string.iter().cloned().map(Symbol::Char));
+            text.push(Symbol::Separator(i));
+            origin.extend((0..=string.len()).map(|offset| (i, offset)));
+        }
+        let sa = SuffixArray::new(&text);
+        let by_rank: Vec<usize> = sa.suffixes().iter().map(|&p| origin[p].0).collect();
+        GeneralizedSuffixArray {
+            text,
+            owners: DistinctCounter::new(&by_rank),
+            sa,
+            origin,
+        }
+    }
+
+    /// Suffix array of the joined strings, separators included
+    pub fn suffix_array(&self) -> &SuffixArray {
+        &self.sa
+    }
+
+    /// (string, offset) of position `p` of the joined strings, an offset
+    /// equal to the string length being its separator
+    pub fn origin(&self, p: usize) -> (usize, usize) {
+        self.origin[p]
+    }
+
+    /// Ranks of the suffixes starting with `pattern`, None if there are none
+    pub fn find(&self, pattern: &[T]) -> Option<(usize, usize)> {
+        // the suffix starting at p against the pattern, Equal if it starts
+        // with the pattern
+        let cmp = |p: usize| {
+            let suffix = &self.text[p..];
+            for (k, c) in pattern.iter().enumerate() {
+                match suffix.get(k) {
+                    None | Some(Symbol::Separator(_)) => return Ordering::Less,
+                    Some(Symbol::Char(x)) if x != c => return x.cmp(c),
+                    _ => {}
+                }
+            }
+            Ordering::Equal
+        };
+        let suffixes = self.sa.suffixes();
+        let lo = suffixes.partition_point(|&p| cmp(p) == Ordering::Less);
+        let hi = suffixes.partition_point(|&p| cmp(p) != Ordering::Greater);
+        (lo < hi).then(|| (lo, hi - 1))
+    }
+
+    /// Number of strings containing `pattern`, which must not be empty
+    pub fn count_containing(&self, pattern: &[T]) -> usize {
+        assert!(!pattern.is_empty(), "Empty pattern");
+        self.find(pattern)
+            .map_or(0, |(lo, hi)| self.owners.count(lo, hi))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_banana() {
+        let sa = SuffixArray::new(b"banana");
+        assert_eq!(sa.suffixes(), &[5, 3, 1, 0, 4, 2]);
+        assert_eq!(sa.lcp_array(), &[1, 3, 0, 0, 2]);
+        assert!(SuffixArray::new::<u8>(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = Rng::new(21);
+        for _ in 0..30 {
+            let n = rng.gen_range(1, 60) as usize;
+            let s: Vec<u8> = (0..n).map(|_| b'a' + rng.gen_range(0, 2) as u8).collect();
+            let query = LcpQuery::new(&s);
+
+            let mut expected: Vec<usize> = (0..n).collect();
+            expected.sort_by(|&a, &b| s[a..].cmp(&s[b..]));
+            assert_eq!(query.suffix_array().suffixes(), &expected[..]);
+
+            for i in 0..n {
+                for j in 0..n {
+                    let naive = s[i..]
+                        .iter()
+                        .zip(&s[j..])
+                        .take_while(|(a, b)| a == b)
+                        .count();
+                    assert_eq!(query.lcp(i, j), naive);
+                }
+            }
+            for _ in 0..200 {
+                let (l1, l2) = (
+                    rng.gen_range(0, n as u64 - 1),
+                    rng.gen_range(0, n as u64 - 1),
+                );
+                let r1 = rng.gen_range(l1, n as u64 - 1) as usize;
+                let r2 = rng.gen_range(l2, n as u64 - 1) as usize;
+                let (l1, l2) = (l1 as usize, l2 as usize);
+                assert_eq!(
+                    query.compare((l1, r1), (l2, r2)),
+                    s[l1..=r1].cmp(&s[l2..=r2])
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_generalized_against_naive() {
+        let mut rng = Rng::new(60);
+        for _ in 0..20 {
+            let strings: Vec<Vec<u8>> = (0..rng.gen_range(1, 6))
+                .map(|_| {
+                    let len = rng.gen_range(0, 15) as usize;
+                    (0..len).map(|_| b'a' + rng.gen_range(0, 2) as u8).collect()
+                })
+                .collect();
+            let gsa = GeneralizedSuffixArray::new(&strings);
+            for _ in 0..100 {
+                let len = rng.gen_range(1, 4) as usize;
+                let pattern: Vec<u8> = (0..len).map(|_| b'a' + rng.gen_range(0, 2) as u8).collect();
+                let naive = strings
+                    .iter()
+                    .filter(|s| s.windows(len).any(|w| w == &pattern[..]))
+                    .count();
+                assert_eq!(gsa.count_containing(&pattern), naive);
+                let occurrences = gsa.find(&pattern).map_or(0, |(lo, hi)| hi - lo + 1);
+                let naive: usize = strings
+                    .iter()
+                    .map(|s| s.windows(len).filter(|w| *w == &pattern[..]).count())
+                    .sum();
+                assert_eq!(occurrences, naive);
+            }
+        }
+        let gsa = GeneralizedSuffixArray::new(&["abab", "bab", "aa"]);
+        assert_eq!(gsa.count_containing(b"ab"), 2);
+        assert_eq!(gsa.count_containing(b"a"), 3);
+        assert_eq!(gsa.count_containing(b"abb"), 0);
+        let (lo, _) = gsa.find(b"aa").unwrap();
+        assert_eq!(gsa.origin(gsa.suffix_array().suffixes()[lo]), (2, 0));
+    }
+}
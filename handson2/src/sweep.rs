@@ -0,0 +1,122 @@
+// ------- SWEEP LINE -------
+// Counting overlaps between inclusive intervals, and a small event queue that
+// processes events in key order for custom sweeps.
+
+use crate::prefix_sum::DiffArray;
+
+/// Events sorted by key and processed in order. Events with equal keys are
+/// processed in insertion order, ties between different kinds of events should
+/// be encoded in the key (e.g. `(position, kind)`).
+pub struct EventQueue<K: Ord, E> {
+    events: Vec<(K, E)>,
+}
+
+impl<K: Ord, E> Default for EventQueue<K, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, E> EventQueue<K, E> {
+    pub fn new() -> Self {
+        EventQueue { events: Vec::new() }
+    }
+
+    pub fn push(&mut self, key: K, event: E) {
+        self.events.push((key, event));
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Sorts the events and calls `handler(key, event)` on each of them
+    pub fn run<F: FnMut(&K, E)>(mut self, mut handler: F) {
+        self.events.sort_by(|a, b| a.0.cmp(&b.0));
+        for (key, event) in self.events {
+            handler(&key, event);
+        }
+    }
+}
+
+/// Number of intervals covering each position in `0..len`, computed with a
+/// +1 / -1 sweep over the interval endpoints.
+///
+/// # Panics
+/// Panics if an interval ends at or after `len` or starts after it ends.
+pub fn overlap_counts(intervals: &[(usize, usize)], len: usize) -> Vec<u32> {
+    let mut counts = DiffArray::new(len);
+    for &(start, end) in intervals {
+        assert!(start <= end && end < len, "Interval out of range");
+        counts.add(start, end, 1u32);
+    }
+    counts.build()
+}
+
+/// Maximum number of intervals sharing a common point, together with the
+/// leftmost such point. Intervals are inclusive and may use any coordinates.
+pub fn max_overlap(intervals: &[(i64, i64)]) -> Option<(u32, i64)> {
+    // an interval [l, r] opens at l and closes at r: at equal coordinates the
+    // openings sort first, so intervals touching at a point overlap there and
+    // r + 1, which overflows at i64::MAX, is never needed
+    let mut queue = EventQueue::new();
+    for &(start, end) in intervals {
+        assert!(start <= end, "Invalid interval");
+        queue.push((start, 0), 1i32);
+        queue.push((end, 1), -1i32);
+    }
+
+    let mut current = 0u32;
+    let mut best: Option<(u32, i64)> = None;
+    queue.run(|&(pos, _), delta| {
+        current = (current as i32 + delta) as u32;
+        if delta > 0 && best.is_none_or(|(count, _)| current > count) {
+            best = Some((current, pos));
+        }
+    });
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlap_counts() {
+        let intervals = [(0, 2), (1, 1), (2, 4), (4, 4)];
+        assert_eq!(overlap_counts(&intervals, 6), vec![1, 2, 2, 1, 2, 0]);
+        assert_eq!(overlap_counts(&[], 3), vec![0, 0, 0]);
+    }
+
+    #[test]
+    fn test_max_overlap() {
+        assert_eq!(max_overlap(&[]), None);
+        assert_eq!(max_overlap(&[(-5, 5)]), Some((1, -5)));
+        // touching at 3 counts as overlapping, [6, 7] does not touch [3, 5]
+        assert_eq!(max_overlap(&[(1, 3), (3, 5), (6, 7), (2, 3)]), Some((3, 3)));
+        assert_eq!(max_overlap(&[(1, 2), (3, 4)]), Some((1, 1)));
+        let (min, max) = (i64::MIN, i64::MAX);
+        assert_eq!(max_overlap(&[(min, max), (max, max)]), Some((2, max)));
+        assert_eq!(
+            max_overlap(&[(min, min), (min, 0), (1, max)]),
+            Some((2, min))
+        );
+    }
+
+    #[test]
+    fn test_event_queue_order() {
+        let mut queue = EventQueue::new();
+        queue.push(3, 'c');
+        queue.push(1, 'a');
+        queue.push(3, 'd');
+        queue.push(2, 'b');
+        assert_eq!(queue.len(), 4);
+        let mut seen = String::new();
+        queue.run(|_, e| seen.push(e));
+        assert_eq!(seen, "abcd");
+    }
+}
@@ -0,0 +1,402 @@
+// ------- TEST CASE GENERATOR -------
+// Produces random input/expected-output pairs for both exercises, using naive
+// oracles for the expected results, and writes them in the `data/exerciseN` format.
+// Structured inputs for the stress runner are generated too: uniform random
+// labeled trees from random Prufer sequences, connected graphs as a random tree
+// plus distinct extra edges, bipartite graphs around a planted matching, and
+// uniform (reservoir) or weighted samples without replacement.
+
+use crate::graph::Graph;
+use crate::hash::FastHashSet;
+use crate::prufer::prufer_decode;
+use crate::rng::Rng;
+use crate::{Query, TestCase};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+// ----- exercise 1 -----
+
+/// Naive oracle for exercise 1: applies every update on a plain array and
+/// answers max queries by scanning. Indices are 1-based, as in the input files.
+pub fn naive_exercise1(data: &[u32], queries: &[Query]) -> Vec<u32> {
+    let mut arr = data.to_vec();
+    let mut results = Vec::new();
+    for &(start, end, update_value) in queries {
+        let range = &mut arr[start - 1..end];
+        match update_value {
+            Some(value) => range.iter_mut().for_each(|x| *x = (*x).min(value)),
+            None => results.push(range.iter().copied().max().unwrap()),
+        }
+    }
+    results
+}
+
+/// Generates a random exercise 1 case with `n` elements and `m` queries, values
+/// in `[0, max_value]`. Roughly half of the queries are updates. Panics if
+/// there are queries but no elements.
+pub fn gen_exercise1(rng: &mut Rng, n: usize, m: usize, max_value: u32) -> TestCase {
+    assert!(n >= 1 || m == 0, "Queries need at least one element");
+    let data: Vec<u32> = (0..n)
+        .map(|_| rng.gen_range(0, max_value as u64) as u32)
+        .collect();
+
+    let queries: Vec<Query> = (0..m)
+        .map(|_| {
+            let a = rng.gen_range(1, n as u64) as usize;
+            let b = rng.gen_range(1, n as u64) as usize;
+            let (start, end) = (a.min(b), a.max(b));
+            if rng.next_u64() & 1 == 0 {
+                (start, end, Some(rng.gen_range(0, max_value as u64) as u32))
+            } else {
+                (start, end, None)
+            }
+        })
+        .collect();
+
+    let results = naive_exercise1(&data, &queries);
+    TestCase::new(data, queries, results)
+}
+
+/// Writes an exercise 1 case as `input{index}.txt` / `output{index}.txt` into `dir`
+pub fn write_exercise1(case: &TestCase, dir: &Path, index: usize) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let mut input = BufWriter::new(File::create(dir.join(format!("input{}.txt", index)))?);
+    let mut output = BufWriter::new(File::create(dir.join(format!("output{}.txt", index)))?);
+
+    writeln!(input, "{} {}", case.data().len(), case.queries().len())?;
+    writeln!(input, "{}", join(case.data()))?;
+    for &(start, end, update_value) in case.queries() {
+        match update_value {
+            Some(value) => writeln!(input, "0 {} {} {}", start, end, value)?,
+            None => writeln!(input, "1 {} {}", start, end)?,
+        }
+    }
+    for result in case.results() {
+        writeln!(output, "{}", result)?;
+    }
+    input.flush()?;
+    output.flush()
+}
+
+// ----- exercise 2 -----
+
+/// Naive oracle for exercise 2: `intervals` is the flat list of interval
+/// endpoints (start, end, start, end, ...) over positions `0..n`, each query
+/// asks whether some position in `[start, end]` is covered by exactly `k` intervals.
+pub fn naive_exercise2(intervals: &[u32], queries: &[Query]) -> Vec<u32> {
+    let n = intervals.len() / 2;
+    let mut coverage = vec![0u32; n];
+    for pair in intervals.chunks(2) {
+        for c in &mut coverage[pair[0] as usize..=pair[1] as usize] {
+            *c += 1;
+        }
+    }
+    queries
+        .iter()
+        .map(|&(start, end, k)| coverage[start..=end].contains(&k.unwrap()) as u32)
+        .collect()
+}
+
+/// Generates a random exercise 2 case with `n` intervals over positions `0..n`
+/// and `m` queries. Panics if there are queries but no positions.
+pub fn gen_exercise2(rng: &mut Rng, n: usize, m: usize) -> TestCase {
+    assert!(n >= 1 || m == 0, "Queries need at least one position");
+    let mut intervals = Vec::with_capacity(2 * n);
+    for _ in 0..n {
+        let a = rng.gen_range(0, n as u64 - 1) as u32;
+        let b = rng.gen_range(0, n as u64 - 1) as u32;
+        intervals.push(a.min(b));
+        intervals.push(a.max(b));
+    }
+
+    let queries: Vec<Query> = (0..m)
+        .map(|_| {
+            let a = rng.gen_range(0, n as u64 - 1) as usize;
+            let b = rng.gen_range(0, n as u64 - 1) as usize;
+            // keep k small so that both answers show up often
+            let k = rng.gen_range(0, (n as u64).min(8)) as u32;
+            (a.min(b), a.max(b), Some(k))
+        })
+        .collect();
+
+    let results = naive_exercise2(&intervals, &queries);
+    TestCase::new(intervals, queries, results)
+}
+
+/// Writes an exercise 2 case as `input{index}.txt` / `output{index}.txt` into `dir`
+pub fn write_exercise2(case: &TestCase, dir: &Path, index: usize) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let mut input = BufWriter::new(File::create(dir.join(format!("input{}.txt", index)))?);
+    let mut output = BufWriter::new(File::create(dir.join(format!("output{}.txt", index)))?);
+
+    writeln!(input, "{} {}", case.data().len() / 2, case.queries().len())?;
+    for pair in case.data().chunks(2) {
+        writeln!(input, "{} {}", pair[0], pair[1])?;
+    }
+    for &(start, end, k) in case.queries() {
+        writeln!(input, "{} {} {}", start, end, k.unwrap())?;
+    }
+    for result in case.results() {
+        writeln!(output, "{}", result)?;
+    }
+    input.flush()?;
+    output.flush()
+}
+
+// ----- structured inputs -----
+
+type Pairs = Vec<(usize, usize)>;
+
+/// Uniformly random labeled tree on `n >= 1` vertices, with unit weights
+pub fn random_tree(rng: &mut Rng, n: usize) -> Graph {
+    assert!(n >= 1, "A tree needs a vertex");
+    if n == 1 {
+        return Graph::new(1);
+    }
+    let sequence: Vec<usize> = (0..n - 2)
+        .map(|_| rng.gen_range(0, n as u64 - 1) as usize)
+        .collect();
+    prufer_decode(&sequence)
+}
+
+/// Random connected simple graph with `n >= 1` vertices and `m` edges, unit
+/// weights
+pub fn random_connected_graph(rng: &mut Rng, n: usize, m: usize) -> Graph {
+    assert!(n >= 1, "A graph needs a vertex");
+    assert!(
+        m + 1 >= n && m <= n * (n - 1) / 2,
+        "No connected simple graph has that many edges"
+    );
+    let mut edges: FastHashSet<(usize, usize)> = FastHashSet::default();
+    let tree = random_tree(rng, n);
+    for u in 0..n {
+        for &(v, _) in tree.neighbors(u) {
+            edges.insert((u.min(v), u.max(v)));
+        }
+    }
+    while edges.len() < m {
+        let u = rng.gen_range(0, n as u64 - 1) as usize;
+        let v = rng.gen_range(0, n as u64 - 1) as usize;
+        if u != v {
+            edges.insert((u.min(v), u.max(v)));
+        }
+    }
+    let mut edges: Vec<(usize, usize, i64)> = edges.into_iter().map(|(u, v)| (u, v, 1)).collect();
+    edges.sort_unstable();
+    rng.shuffle(&mut edges);
+    Graph::from_undirected_edges(n, &edges)
+}
+
+/// Random bipartite graph between `left` and `right` vertices containing a
+/// planted matching of `matched` pairs, plus up to `extra` other edges. Returns
+/// the distinct (left, right) edges, shuffled, and the planted pairs.
+pub fn random_bipartite_with_matching(
+    rng: &mut Rng,
+    left: usize,
+    right: usize,
+    matched: usize,
+    extra: usize,
+) -> (Pairs, Pairs) {
+    assert!(matched <= left.min(right), "Matching larger than a side");
+    let mut us: Vec<usize> = (0..left).collect();
+    let mut vs: Vec<usize> = (0..right).collect();
+    rng.shuffle(&mut us);
+    rng.shuffle(&mut vs);
+    let planted: Vec<(usize, usize)> = us.into_iter().zip(vs).take(matched).collect();
+    let mut edges: FastHashSet<(usize, usize)> = planted.iter().copied().collect();
+    if left > 0 && right > 0 {
+        for _ in 0..extra {
+            edges.insert((
+                rng.gen_range(0, left as u64 - 1) as usize,
+                rng.gen_range(0, right as u64 - 1) as usize,
+            ));
+        }
+    }
+    let mut edges: Vec<(usize, usize)> = edges.into_iter().collect();
+    edges.sort_unstable();
+    rng.shuffle(&mut edges);
+    (edges, planted)
+}
+
+/// `k` items of `items` chosen uniformly without replacement in one pass
+/// (reservoir sampling), all of them if there are fewer
+pub fn reservoir_sample<T, I: IntoIterator<Item = T>>(rng: &mut Rng, items: I, k: usize) -> Vec<T> {
+    let mut sample = Vec::with_capacity(k);
+    for (i, item) in items.into_iter().enumerate() {
+        if i < k {
+            sample.push(item);
+        } else {
+            // the i-th item replaces a kept one with probability k / (i + 1)
+            let j = rng.gen_range(0, i as u64) as usize;
+            if j < k {
+                sample[j] = item;
+            }
+        }
+    }
+    sample
+}
+
+/// Indices of `k` distinct items drawn one after the other with probability
+/// proportional to their positive `weights` (Efraimidis-Spirakis keys u^(1/w))
+pub fn weighted_sample(rng: &mut Rng, weights: &[f64], k: usize) -> Vec<usize> {
+    assert!(weights.iter().all(|&w| w > 0.0), "Weights must be positive");
+    let mut keys: Vec<(f64, usize)> = weights
+        .iter()
+        .enumerate()
+        .map(|(i, &w)| (rng.gen_f64().powf(1.0 / w), i))
+        .collect();
+    keys.sort_by(|a, b| b.0.total_cmp(&a.0));
+    keys.into_iter().take(k).map(|(_, i)| i).collect()
+}
+
+// join values with a single space, as in the input files
+fn join(values: &[u32]) -> String {
+    values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MaxSegmentTree;
+
+    #[test]
+    fn test_generator_is_reproducible() {
+        let a = gen_exercise1(&mut Rng::new(7), 50, 50, 100);
+        let b = gen_exercise1(&mut Rng::new(7), 50, 50, 100);
+        assert_eq!(a.data(), b.data());
+        assert_eq!(a.queries(), b.queries());
+        assert_eq!(a.results(), b.results());
+    }
+
+    #[test]
+    fn test_naive_oracles() {
+        let data = vec![5, 1, 4, 3];
+        let queries = vec![(1, 4, None), (1, 3, Some(2)), (1, 4, None), (2, 3, None)];
+        assert_eq!(naive_exercise1(&data, &queries), vec![5, 3, 2]);
+
+        // coverage: [1, 3, 1]
+        let intervals = vec![0, 1, 1, 2, 1, 1];
+        let queries = vec![(0, 2, Some(3)), (0, 0, Some(3)), (2, 2, Some(1))];
+        assert_eq!(naive_exercise2(&intervals, &queries), vec![1, 0, 1]);
+    }
+
+    #[test]
+    fn test_empty_cases() {
+        let case = gen_exercise1(&mut Rng::new(7), 0, 0, 100);
+        assert!(case.data().is_empty() && case.queries().is_empty());
+        let case = gen_exercise2(&mut Rng::new(7), 0, 0);
+        assert!(case.data().is_empty() && case.queries().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "A graph needs a vertex")]
+    fn test_empty_connected_graph_panics() {
+        random_connected_graph(&mut Rng::new(7), 0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Queries need at least one element")]
+    fn test_exercise1_queries_without_elements_panic() {
+        gen_exercise1(&mut Rng::new(7), 0, 1, 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "Queries need at least one position")]
+    fn test_exercise2_queries_without_positions_panic() {
+        gen_exercise2(&mut Rng::new(7), 0, 1);
+    }
+
+    #[test]
+    fn test_large_random_exercise1() {
+        let mut rng = Rng::new(42);
+        let case = gen_exercise1(&mut rng, 20_000, 1_000, 1_000_000);
+        let mut tree = MaxSegmentTree::new(case.data());
+        let mut results = Vec::new();
+        for &(start, end, update_value) in case.queries() {
+            match update_value {
+                Some(value) => tree.range_update(start, end, value),
+                None => results.push(tree.range_max_query_lazy(start, end)),
+            }
+        }
+        assert_eq!(&results, case.results());
+    }
+
+    #[test]
+    fn test_structured_inputs() {
+        use crate::dsu::Dsu;
+        let mut rng = Rng::new(87);
+        let connected = |graph: &Graph| {
+            let mut dsu = Dsu::new(graph.len());
+            for u in 0..graph.len() {
+                for &(v, _) in graph.neighbors(u) {
+                    dsu.union(u, v);
+                }
+            }
+            dsu.components() == 1
+        };
+        for n in 1..30 {
+            let tree = random_tree(&mut rng, n);
+            assert!(connected(&tree) && tree.edge_count() == 2 * (n - 1));
+            let m = rng.gen_range(n as u64 - 1, (n * (n - 1) / 2) as u64) as usize;
+            let graph = random_connected_graph(&mut rng, n, m);
+            assert!(connected(&graph) && graph.edge_count() == 2 * m);
+            let mut edges: Vec<(usize, usize)> = (0..n)
+                .flat_map(|u| graph.neighbors(u).iter().map(move |&(v, _)| (u, v)))
+                .collect();
+            edges.sort();
+            edges.dedup();
+            assert_eq!(edges.len(), 2 * m);
+
+            let (edges, planted) = random_bipartite_with_matching(&mut rng, n, n + 3, n / 2, 2 * n);
+            assert_eq!(planted.len(), n / 2);
+            assert!(planted.iter().all(|p| edges.contains(p)));
+            assert!(edges.iter().all(|&(u, v)| u < n && v < n + 3));
+        }
+
+        // each of the 4^2 labeled trees on 4 vertices about equally often
+        let mut counts = std::collections::HashMap::new();
+        for _ in 0..16_000 {
+            let tree = random_tree(&mut rng, 4);
+            let mut edges: Vec<(usize, usize)> = (0..4)
+                .flat_map(|u| tree.neighbors(u).iter().map(move |&(v, _)| (u, v)))
+                .filter(|&(u, v)| u < v)
+                .collect();
+            edges.sort();
+            *counts.entry(edges).or_insert(0) += 1;
+        }
+        assert_eq!(counts.len(), 16);
+        assert!(counts.values().all(|&c| (800..1200).contains(&c)));
+    }
+
+    #[test]
+    fn test_sampling() {
+        let mut rng = Rng::new(88);
+        let mut hits = [0; 10];
+        for _ in 0..10_000 {
+            let sample = reservoir_sample(&mut rng, 0..10, 3);
+            let mut distinct = sample.clone();
+            distinct.sort();
+            distinct.dedup();
+            assert_eq!(distinct.len(), 3);
+            sample.iter().for_each(|&i| hits[i] += 1);
+        }
+        // each item is kept with probability 3 / 10
+        assert!(hits.iter().all(|&h| (2700..3300).contains(&h)));
+        assert_eq!(reservoir_sample(&mut rng, 0..2, 5), vec![0, 1]);
+
+        let weights = [1.0, 2.0, 7.0];
+        let mut first = [0; 3];
+        for _ in 0..10_000 {
+            let sample = weighted_sample(&mut rng, &weights, 2);
+            assert_eq!(sample.len(), 2);
+            assert_ne!(sample[0], sample[1]);
+            first[sample[0]] += 1;
+        }
+        assert!((900..1100).contains(&first[0]) && (6700..7300).contains(&first[2]));
+    }
+}
@@ -0,0 +1,109 @@
+// ------- QUERIES AT PAST TIMES -------
+// Replays a log of range updates on a MaxSegmentTree to answer "what was the
+// maximum of [start, end] after the first t updates" offline: queries are
+// sorted by time and answered while the log is applied once, in
+// O((n + u + q) log n). Ranges are 0-based and inclusive.
+
+use crate::MaxSegmentTree;
+
+/// A logged range update
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RangeUpdate {
+    /// Every x in [start, end] becomes min(x, value)
+    Chmin(usize, usize, u32),
+    /// Every x in [start, end] becomes max(x, value)
+    Chmax(usize, usize, u32),
+    /// Every x in [start, end] becomes value
+    Assign(usize, usize, u32),
+}
+
+/// Answers every `(time, start, end)` query with the maximum of `[start, end]`
+/// once the first `time` updates of `log` are applied to `arr`
+pub fn max_at_times(
+    arr: &[u32],
+    log: &[RangeUpdate],
+    queries: &[(usize, usize, usize)],
+) -> Vec<u32> {
+    for &(time, start, end) in queries {
+        assert!(time <= log.len(), "Time after the end of the log");
+        assert!(start <= end && end < arr.len(), "Invalid range");
+    }
+    let mut order: Vec<usize> = (0..queries.len()).collect();
+    order.sort_unstable_by_key(|&i| queries[i].0);
+
+    let mut tree = MaxSegmentTree::new(arr);
+    let mut applied = 0;
+    let mut answers = vec![0; queries.len()];
+    for i in order {
+        let (time, start, end) = queries[i];
+        for &update in &log[applied..time] {
+            match update {
+                RangeUpdate::Chmin(l, r, v) => tree.update(l, r, v),
+                RangeUpdate::Chmax(l, r, v) => tree.chmax(l, r, v),
+                RangeUpdate::Assign(l, r, v) => tree.assign(l, r, v),
+            }
+        }
+        applied = applied.max(time);
+        answers[i] = tree.query(start, end);
+    }
+    answers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_random_against_replay() {
+        let mut rng = Rng::new(55);
+        for _ in 0..20 {
+            let n = rng.gen_range(1, 30) as usize;
+            let arr: Vec<u32> = (0..n).map(|_| rng.gen_range(0, 50) as u32).collect();
+            let log: Vec<RangeUpdate> = (0..40)
+                .map(|_| {
+                    let a = rng.gen_range(0, n as u64 - 1) as usize;
+                    let b = rng.gen_range(0, n as u64 - 1) as usize;
+                    let (l, r, v) = (a.min(b), a.max(b), rng.gen_range(0, 50) as u32);
+                    match rng.gen_range(0, 2) {
+                        0 => RangeUpdate::Chmin(l, r, v),
+                        1 => RangeUpdate::Chmax(l, r, v),
+                        _ => RangeUpdate::Assign(l, r, v),
+                    }
+                })
+                .collect();
+
+            // every state of the array, state t after the first t updates
+            let mut states = vec![arr.clone()];
+            for &update in &log {
+                let mut next = states.last().unwrap().clone();
+                match update {
+                    RangeUpdate::Chmin(l, r, v) => {
+                        next[l..=r].iter_mut().for_each(|x| *x = (*x).min(v))
+                    }
+                    RangeUpdate::Chmax(l, r, v) => {
+                        next[l..=r].iter_mut().for_each(|x| *x = (*x).max(v))
+                    }
+                    RangeUpdate::Assign(l, r, v) => next[l..=r].fill(v),
+                }
+                states.push(next);
+            }
+
+            let queries: Vec<(usize, usize, usize)> = (0..60)
+                .map(|_| {
+                    let a = rng.gen_range(0, n as u64 - 1) as usize;
+                    let b = rng.gen_range(0, n as u64 - 1) as usize;
+                    (
+                        rng.gen_range(0, log.len() as u64) as usize,
+                        a.min(b),
+                        a.max(b),
+                    )
+                })
+                .collect();
+            let answers = max_at_times(&arr, &log, &queries);
+            for (&(time, start, end), &answer) in queries.iter().zip(&answers) {
+                assert_eq!(answer, *states[time][start..=end].iter().max().unwrap());
+            }
+        }
+    }
+}
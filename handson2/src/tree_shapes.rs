@@ -0,0 +1,175 @@
+// ------- TREE ISOMORPHISM -------
+// AHU canonical forms: the shape of a rooted subtree is identified by the
+// sorted list of the shapes of its children, interned into a small integer.
+// Ids come from an exact table rather than a hash, so they never collide.
+// Unrooted trees are compared rooted at their centers.
+
+use crate::graph::Graph;
+use crate::hash::FastHashMap;
+
+/// Table of the rooted shapes seen so far. Ids are comparable across all the
+/// trees processed by the same table.
+#[derive(Clone, Debug, Default)]
+pub struct TreeShapes {
+    ids: FastHashMap<Vec<usize>, usize>, // Sorted child shapes -> shape id
+}
+
+impl TreeShapes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of distinct rooted shapes seen so far
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Shape id of the subtree of every vertex of the tree `graph` rooted at
+    /// `root`: two subtrees are isomorphic iff they have the same id
+    pub fn subtree_ids(&mut self, graph: &Graph, root: usize) -> Vec<usize> {
+        let n = graph.len();
+        // children come after their parent
+        let (parent, order) = graph.rooted_order(root);
+
+        let mut children: Vec<Vec<usize>> = vec![Vec::new(); n];
+        let mut ids = vec![0; n];
+        for &v in order.iter().rev() {
+            let mut key = std::mem::take(&mut children[v]);
+            key.sort_unstable();
+            let next = self.ids.len();
+            ids[v] = *self.ids.entry(key).or_insert(next);
+            if v != root {
+                children[parent[v]].push(ids[v]);
+            }
+        }
+        ids
+    }
+
+    /// Canonical id of the unrooted tree `graph`, comparable with the ids of
+    /// the other trees of the same table
+    pub fn unrooted_id(&mut self, graph: &Graph) -> usize {
+        tree_centers(graph)
+            .into_iter()
+            .map(|c| self.subtree_ids(graph, c)[c])
+            .min()
+            .expect("Empty tree")
+    }
+}
+
+/// The one or two vertices minimizing the largest distance to the others,
+/// found by peeling leaves layer by layer
+pub fn tree_centers(graph: &Graph) -> Vec<usize> {
+    let n = graph.len();
+    if n <= 2 {
+        return (0..n).collect();
+    }
+    let mut degree: Vec<usize> = (0..n).map(|v| graph.degree(v)).collect();
+    let mut layer: Vec<usize> = (0..n).filter(|&v| degree[v] == 1).collect();
+    let mut remaining = n;
+    while remaining > 2 {
+        remaining -= layer.len();
+        let mut next = Vec::new();
+        for &v in &layer {
+            for &(u, _) in graph.neighbors(v) {
+                degree[u] -= 1;
+                if degree[u] == 1 {
+                    next.push(u);
+                }
+            }
+        }
+        layer = next;
+    }
+    layer
+}
+
+/// Whether the trees `a` rooted at `root_a` and `b` rooted at `root_b` are
+/// isomorphic as rooted trees
+pub fn are_isomorphic_rooted(a: &Graph, root_a: usize, b: &Graph, root_b: usize) -> bool {
+    let mut shapes = TreeShapes::new();
+    a.len() == b.len()
+        && shapes.subtree_ids(a, root_a)[root_a] == shapes.subtree_ids(b, root_b)[root_b]
+}
+
+/// Whether the unrooted trees `a` and `b` are isomorphic
+pub fn are_isomorphic(a: &Graph, b: &Graph) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    if a.is_empty() {
+        return true;
+    }
+    let mut shapes = TreeShapes::new();
+    shapes.unrooted_id(a) == shapes.unrooted_id(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn random_tree(rng: &mut Rng, n: usize) -> Vec<(usize, usize, i64)> {
+        (1..n)
+            .map(|v| (rng.gen_range(0, v as u64 - 1) as usize, v, 1))
+            .collect()
+    }
+
+    // The same tree with the vertex labels shuffled
+    fn relabel(rng: &mut Rng, n: usize, edges: &[(usize, usize, i64)]) -> (Graph, Vec<usize>) {
+        let mut label: Vec<usize> = (0..n).collect();
+        rng.shuffle(&mut label);
+        let mut edges: Vec<(usize, usize, i64)> = edges
+            .iter()
+            .map(|&(u, v, w)| (label[u], label[v], w))
+            .collect();
+        rng.shuffle(&mut edges);
+        (Graph::from_undirected_edges(n, &edges), label)
+    }
+
+    #[test]
+    fn test_relabelled_trees_are_isomorphic() {
+        let mut rng = Rng::new(49);
+        for _ in 0..50 {
+            let n = rng.gen_range(1, 40) as usize;
+            let edges = random_tree(&mut rng, n);
+            let a = Graph::from_undirected_edges(n, &edges);
+            let (b, label) = relabel(&mut rng, n, &edges);
+            assert!(are_isomorphic(&a, &b));
+            let root = rng.gen_range(0, n as u64 - 1) as usize;
+            assert!(are_isomorphic_rooted(&a, root, &b, label[root]));
+        }
+    }
+
+    #[test]
+    fn test_small_shapes() {
+        // path 0-1-2-3 against the star centred at 0
+        let path = Graph::from_undirected_edges(4, &[(0, 1, 1), (1, 2, 1), (2, 3, 1)]);
+        let star = Graph::from_undirected_edges(4, &[(0, 1, 1), (0, 2, 1), (0, 3, 1)]);
+        assert!(!are_isomorphic(&path, &star));
+        assert!(!are_isomorphic_rooted(&path, 0, &path, 1));
+        assert!(are_isomorphic_rooted(&path, 0, &path, 3));
+        assert_eq!(tree_centers(&path), vec![1, 2]);
+        assert_eq!(tree_centers(&star), vec![0]);
+
+        // all 11 unlabelled trees on 7 vertices appear among random ones
+        let mut rng = Rng::new(50);
+        let mut shapes = TreeShapes::new();
+        let mut seen: Vec<usize> = (0..3000)
+            .map(|_| {
+                let edges = random_tree(&mut rng, 7);
+                let (graph, _) = relabel(&mut rng, 7, &edges);
+                shapes.unrooted_id(&graph)
+            })
+            .collect();
+        seen.sort_unstable();
+        seen.dedup();
+        assert_eq!(seen.len(), 11);
+
+        // subtree ids: the three leaves of the star share a shape
+        let ids = TreeShapes::new().subtree_ids(&star, 0);
+        assert!(ids[1] == ids[2] && ids[2] == ids[3] && ids[0] != ids[1]);
+    }
+}
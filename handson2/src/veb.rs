@@ -0,0 +1,335 @@
+// ------- VAN EMDE BOAS SET -------
+// Set of u32 keys with insert, remove, predecessor and successor in
+// O(log log U) = 5 levels. A node over 2^b keys splits them into clusters of
+// 2^(b/2) by their high bits, keeps a summary set of the non-empty clusters,
+// and stores its minimum outside the clusters, so every operation recurses
+// into one cluster or the summary only. Clusters live in hash maps and are
+// created on demand, so memory is O(n) rather than O(U); sets of at most 64
+// keys are single words.
+
+use crate::hash::FastHashMap;
+
+const LEAF_BITS: u32 = 6;
+
+// Non-empty set of keys below 2^bits
+enum Node {
+    Leaf(u64),
+    Inner(Box<Inner>),
+}
+
+struct Inner {
+    bits: u32,
+    min: u32, // Not stored in a cluster
+    max: u32,
+    summary: Option<Node>, // High halves of the keys in the clusters
+    clusters: FastHashMap<u32, Node>,
+}
+
+impl Inner {
+    fn low_bits(&self) -> u32 {
+        self.bits / 2
+    }
+
+    fn split(&self, x: u32) -> (u32, u32) {
+        (x >> self.low_bits(), x & ((1 << self.low_bits()) - 1))
+    }
+
+    fn join(&self, high: u32, low: u32) -> u32 {
+        high << self.low_bits() | low
+    }
+}
+
+impl Node {
+    fn singleton(bits: u32, x: u32) -> Node {
+        if bits <= LEAF_BITS {
+            Node::Leaf(1 << x)
+        } else {
+            Node::Inner(Box::new(Inner {
+                bits,
+                min: x,
+                max: x,
+                summary: None,
+                clusters: FastHashMap::default(),
+            }))
+        }
+    }
+
+    fn is_singleton(&self) -> bool {
+        match self {
+            Node::Leaf(mask) => mask.is_power_of_two(),
+            Node::Inner(node) => node.summary.is_none(),
+        }
+    }
+
+    fn min(&self) -> u32 {
+        match self {
+            Node::Leaf(mask) => mask.trailing_zeros(),
+            Node::Inner(node) => node.min,
+        }
+    }
+
+    fn max(&self) -> u32 {
+        match self {
+            Node::Leaf(mask) => 63 - mask.leading_zeros(),
+            Node::Inner(node) => node.max,
+        }
+    }
+
+    fn contains(&self, x: u32) -> bool {
+        match self {
+            Node::Leaf(mask) => mask >> x & 1 == 1,
+            Node::Inner(node) => {
+                let (high, low) = node.split(x);
+                x == node.min
+                    || node
+                        .clusters
+                        .get(&high)
+                        .is_some_and(|cluster| cluster.contains(low))
+            }
+        }
+    }
+
+    // Inserts x, returns false if it was already there
+    fn insert(&mut self, x: u32) -> bool {
+        match self {
+            Node::Leaf(mask) => {
+                let new = *mask >> x & 1 == 0;
+                *mask |= 1 << x;
+                new
+            }
+            Node::Inner(node) => {
+                let mut x = x;
+                if x == node.min {
+                    return false;
+                }
+                if x < node.min {
+                    std::mem::swap(&mut x, &mut node.min);
+                }
+                node.max = node.max.max(x);
+                let (high, low) = node.split(x);
+                match node.clusters.get_mut(&high) {
+                    Some(cluster) => cluster.insert(low),
+                    None => {
+                        let low_bits = node.low_bits();
+                        node.clusters.insert(high, Node::singleton(low_bits, low));
+                        match &mut node.summary {
+                            Some(summary) => summary.insert(high),
+                            None => {
+                                let high_bits = node.bits - low_bits;
+                                node.summary = Some(Node::singleton(high_bits, high));
+                                true
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Removes x from a set with at least two keys, returns false if x was not
+    // there
+    fn remove(&mut self, x: u32) -> bool {
+        match self {
+            Node::Leaf(mask) => {
+                let present = *mask >> x & 1 == 1;
+                *mask &= !(1 << x);
+                present
+            }
+            Node::Inner(node) => {
+                let mut x = x;
+                if x == node.min {
+                    // the smallest key of the clusters becomes the minimum
+                    let high = node.summary.as_ref().unwrap().min();
+                    x = node.join(high, node.clusters[&high].min());
+                    node.min = x;
+                }
+                let (high, low) = node.split(x);
+                let Some(cluster) = node.clusters.get_mut(&high) else {
+                    return false;
+                };
+                if cluster.is_singleton() {
+                    if cluster.min() != low {
+                        return false;
+                    }
+                    node.clusters.remove(&high);
+                    let summary = node.summary.as_mut().unwrap();
+                    if summary.is_singleton() {
+                        node.summary = None;
+                    } else {
+                        summary.remove(high);
+                    }
+                } else if !cluster.remove(low) {
+                    return false;
+                }
+                if x == node.max {
+                    node.max = match &node.summary {
+                        None => node.min,
+                        Some(summary) => {
+                            let high = summary.max();
+                            node.join(high, node.clusters[&high].max())
+                        }
+                    };
+                }
+                true
+            }
+        }
+    }
+
+    // Smallest key > x
+    fn successor(&self, x: u32) -> Option<u32> {
+        match self {
+            Node::Leaf(mask) => {
+                let above = if x >= 63 { 0 } else { mask & (!0 << (x + 1)) };
+                (above != 0).then(|| above.trailing_zeros())
+            }
+            Node::Inner(node) => {
+                if x < node.min {
+                    return Some(node.min);
+                }
+                let (high, low) = node.split(x);
+                if let Some(cluster) = node.clusters.get(&high) {
+                    if low < cluster.max() {
+                        return Some(node.join(high, cluster.successor(low).unwrap()));
+                    }
+                }
+                let next = node.summary.as_ref()?.successor(high)?;
+                Some(node.join(next, node.clusters[&next].min()))
+            }
+        }
+    }
+
+    // Largest key < x
+    fn predecessor(&self, x: u32) -> Option<u32> {
+        match self {
+            Node::Leaf(mask) => {
+                let below = mask & ((1 << x) - 1);
+                (below != 0).then(|| 63 - below.leading_zeros())
+            }
+            Node::Inner(node) => {
+                if x > node.max {
+                    return Some(node.max);
+                }
+                let (high, low) = node.split(x);
+                if let Some(cluster) = node.clusters.get(&high) {
+                    if low > cluster.min() {
+                        return Some(node.join(high, cluster.predecessor(low).unwrap()));
+                    }
+                }
+                match node.summary.as_ref().and_then(|s| s.predecessor(high)) {
+                    Some(prev) => Some(node.join(prev, node.clusters[&prev].max())),
+                    None => (x > node.min).then_some(node.min),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct VebSet {
+    root: Option<Node>,
+    len: usize,
+}
+
+impl VebSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains(&self, x: u32) -> bool {
+        self.root.as_ref().is_some_and(|root| root.contains(x))
+    }
+
+    /// Inserts `x`, returns false if it was already in the set
+    pub fn insert(&mut self, x: u32) -> bool {
+        let new = match &mut self.root {
+            Some(root) => root.insert(x),
+            None => {
+                self.root = Some(Node::singleton(32, x));
+                true
+            }
+        };
+        self.len += new as usize;
+        new
+    }
+
+    /// Removes `x`, returns false if it was not in the set
+    pub fn remove(&mut self, x: u32) -> bool {
+        let removed = match &mut self.root {
+            None => false,
+            Some(root) if root.is_singleton() => {
+                let found = root.min() == x;
+                if found {
+                    self.root = None;
+                }
+                found
+            }
+            Some(root) => root.remove(x),
+        };
+        self.len -= removed as usize;
+        removed
+    }
+
+    pub fn min(&self) -> Option<u32> {
+        self.root.as_ref().map(Node::min)
+    }
+
+    pub fn max(&self) -> Option<u32> {
+        self.root.as_ref().map(Node::max)
+    }
+
+    /// Smallest key greater than `x`
+    pub fn successor(&self, x: u32) -> Option<u32> {
+        self.root.as_ref()?.successor(x)
+    }
+
+    /// Largest key smaller than `x`
+    pub fn predecessor(&self, x: u32) -> Option<u32> {
+        self.root.as_ref()?.predecessor(x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use std::collections::BTreeSet;
+    use std::ops::Bound::{Excluded, Unbounded};
+
+    #[test]
+    fn test_against_btreeset() {
+        let mut rng = Rng::new(85);
+        // dense small keys, then keys spread over the whole u32 range
+        for max_key in [100u64, 5000, u32::MAX as u64] {
+            let mut set = VebSet::new();
+            let mut expected = BTreeSet::new();
+            let mut keys: Vec<u32> = (0..300).map(|_| rng.gen_range(0, max_key) as u32).collect();
+            keys.extend([0, u32::MAX, 1 << 16, (1 << 16) - 1]);
+            for _ in 0..20_000 {
+                let x = *rng.choose(&keys).unwrap();
+                match rng.gen_range(0, 4) {
+                    0 | 1 => assert_eq!(set.insert(x), expected.insert(x)),
+                    2 => assert_eq!(set.remove(x), expected.remove(&x)),
+                    _ => {
+                        assert_eq!(set.contains(x), expected.contains(&x));
+                        assert_eq!(
+                            set.successor(x),
+                            expected.range((Excluded(x), Unbounded)).next().copied()
+                        );
+                        assert_eq!(set.predecessor(x), expected.range(..x).next_back().copied());
+                    }
+                }
+                assert_eq!(set.len(), expected.len());
+                assert_eq!(set.min(), expected.first().copied());
+                assert_eq!(set.max(), expected.last().copied());
+            }
+        }
+    }
+}
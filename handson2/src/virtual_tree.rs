@@ -0,0 +1,116 @@
+// ------- VIRTUAL TREE -------
+// The auxiliary tree of a set of k marked vertices: the marked vertices plus
+// the pairwise LCAs, each linked to its closest kept ancestor by an edge
+// weighted with the original path length. Adding the LCAs of the vertices
+// adjacent in DFS order is enough, so the tree has less than 2k vertices and
+// is built in O(k log k), letting a DP run on the marked vertices only.
+
+use crate::graph::Graph;
+use crate::lca::Lca;
+
+pub struct VirtualTree {
+    /// Original label of each vertex of `graph`, in DFS order: vertex 0 is
+    /// the root, the LCA of all the marked vertices
+    pub vertices: Vec<usize>,
+    /// The compressed tree over the indices of `vertices`
+    pub graph: Graph,
+}
+
+/// Builds the virtual tree of `nodes`, duplicates allowed, of the tree
+/// preprocessed by `lca`
+pub fn virtual_tree(nodes: &[usize], lca: &Lca) -> VirtualTree {
+    let mut vertices = nodes.to_vec();
+    vertices.sort_unstable_by_key(|&v| lca.tin(v));
+    vertices.dedup();
+    let pairwise: Vec<usize> = vertices.windows(2).map(|w| lca.lca(w[0], w[1])).collect();
+    vertices.extend(pairwise);
+    vertices.sort_unstable_by_key(|&v| lca.tin(v));
+    vertices.dedup();
+
+    // in DFS order, the parent of each vertex is the LCA with its predecessor
+    let mut graph = Graph::new(vertices.len());
+    let index = |v: usize| vertices.binary_search_by_key(&lca.tin(v), |&u| lca.tin(u));
+    for (i, w) in vertices.windows(2).enumerate() {
+        let parent = lca.lca(w[0], w[1]);
+        graph.add_undirected_edge(index(parent).unwrap(), i + 1, lca.dist(parent, w[1]));
+    }
+    VirtualTree { vertices, graph }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_example() {
+        //        0
+        //      /   \
+        //     1     2
+        //    / \     \
+        //   3   4     5
+        //  /
+        // 6
+        let edges = [
+            (0, 1, 1),
+            (0, 2, 1),
+            (1, 3, 2),
+            (1, 4, 1),
+            (2, 5, 3),
+            (3, 6, 1),
+        ];
+        let lca = Lca::new(&Graph::from_undirected_edges(7, &edges), 0);
+        let tree = virtual_tree(&[6, 4, 6], &lca);
+        // 3 is neither marked nor an LCA, so 6 hangs directly below 1
+        assert_eq!(tree.vertices, vec![1, 6, 4]);
+        assert_eq!(tree.graph.neighbors(0), &[(1, 3), (2, 1)]);
+        assert_eq!(virtual_tree(&[5], &lca).vertices, vec![5]);
+    }
+
+    #[test]
+    fn test_distances_preserved() {
+        let mut rng = Rng::new(48);
+        for _ in 0..30 {
+            let n = rng.gen_range(1, 100) as usize;
+            let edges: Vec<(usize, usize, i64)> = (1..n)
+                .map(|v| {
+                    (
+                        rng.gen_range(0, v as u64 - 1) as usize,
+                        v,
+                        rng.gen_range_i64(1, 9),
+                    )
+                })
+                .collect();
+            let lca = Lca::new(&Graph::from_undirected_edges(n, &edges), 0);
+            let k = rng.gen_range(1, 10) as usize;
+            let marked: Vec<usize> = (0..k)
+                .map(|_| rng.gen_range(0, n as u64 - 1) as usize)
+                .collect();
+            let tree = virtual_tree(&marked, &lca);
+            let m = tree.vertices.len();
+            assert!(m < 2 * k);
+            assert_eq!(tree.graph.edge_count(), 2 * (m - 1));
+
+            // distances from the root of the virtual tree match the original ones
+            let mut dist = vec![None; m];
+            dist[0] = Some(0);
+            let mut stack = vec![0];
+            while let Some(v) = stack.pop() {
+                for &(u, w) in tree.graph.neighbors(v) {
+                    if dist[u].is_none() {
+                        dist[u] = Some(dist[v].unwrap() + w);
+                        stack.push(u);
+                    }
+                }
+            }
+            let root = tree.vertices[0];
+            for (i, &v) in tree.vertices.iter().enumerate() {
+                assert!(lca.is_ancestor(root, v));
+                assert_eq!(dist[i], Some(lca.dist(root, v)));
+            }
+            for &v in &marked {
+                assert!(tree.vertices.contains(&v));
+            }
+        }
+    }
+}
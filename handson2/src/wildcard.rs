@@ -0,0 +1,94 @@
+// ------- WILDCARD MATCHING -------
+// Pattern matching where a wildcard, in the text or in the pattern, matches
+// any character, in O((n + m) log(n + m)) with three convolutions. With
+// wildcards as 0, the pattern matches at i iff
+//   sum over j of p_j t_{i+j} (p_j - t_{i+j})^2 = p^3 t - 2 p^2 t^2 + p t^3
+// is zero, as every term is. Characters are mapped to random values modulo
+// 998244353, so a mismatch sums to zero with probability about 1 / 998244353.
+
+use crate::convolution::convolution;
+use crate::modint::Mod998244353;
+use crate::rng::Rng;
+
+type Mint = Mod998244353;
+
+/// Starting positions in `text` where `pattern` matches, `wildcard` matching
+/// any character on either side
+pub fn wildcard_matches(text: &[u8], pattern: &[u8], wildcard: u8) -> Vec<usize> {
+    let (n, m) = (text.len(), pattern.len());
+    if m == 0 {
+        return (0..=n).collect();
+    }
+    if m > n {
+        return Vec::new();
+    }
+    let mut rng = Rng::from_entropy();
+    let values: Vec<Mint> = (0..256)
+        .map(|c| {
+            if c == wildcard as usize {
+                Mint::new(0)
+            } else {
+                Mint::new(rng.gen_range(1, Mint::modulus() as u64 - 1))
+            }
+        })
+        .collect();
+    // powers 1 to 3 of the values, the pattern reversed
+    let powers = |s: Vec<u8>| {
+        let base: Vec<Mint> = s.iter().map(|&c| values[c as usize]).collect();
+        [1, 2, 3].map(|k| base.iter().map(|&x| x.pow(k)).collect::<Vec<Mint>>())
+    };
+    let [t1, t2, t3] = powers(text.to_vec());
+    let [p1, p2, p3] = powers(pattern.iter().rev().copied().collect());
+    let (a, b, c) = (
+        convolution(&p3, &t1),
+        convolution(&p2, &t2),
+        convolution(&p1, &t3),
+    );
+    (0..=n - m)
+        .filter(|&i| {
+            let k = i + m - 1;
+            a[k] - Mint::new(2) * b[k] + c[k] == Mint::new(0)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_against_naive() {
+        assert_eq!(wildcard_matches(b"abcabd", b"ab?", b'?'), vec![0, 3]);
+        assert_eq!(wildcard_matches(b"a?ca?d", b"abd", b'?'), vec![3]);
+        assert_eq!(wildcard_matches(b"ab", b"abc", b'?'), Vec::<usize>::new());
+
+        let mut rng = Rng::new(72);
+        for _ in 0..50 {
+            let n = rng.gen_range(1, 300) as usize;
+            let m = rng.gen_range(1, 12) as usize;
+            let mut random = |len: usize, wildcards: f64| -> Vec<u8> {
+                (0..len)
+                    .map(|_| {
+                        if rng.gen_bool(wildcards) {
+                            b'?'
+                        } else {
+                            b'a' + rng.gen_range(0, 2) as u8
+                        }
+                    })
+                    .collect()
+            };
+            let text = random(n, 0.1);
+            let pattern = random(m, 0.3);
+            let naive: Vec<usize> = (0..=n.saturating_sub(m))
+                .filter(|&i| {
+                    i + m <= n
+                        && pattern
+                            .iter()
+                            .zip(&text[i..])
+                            .all(|(&p, &t)| p == t || p == b'?' || t == b'?')
+                })
+                .collect();
+            assert_eq!(wildcard_matches(&text, &pattern, b'?'), naive);
+        }
+    }
+}
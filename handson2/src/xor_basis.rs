@@ -0,0 +1,122 @@
+// ------- XOR BASIS -------
+// Linear basis of u64 values over GF(2): the XORs of the subsets of the
+// inserted values are exactly the XORs of the subsets of the basis, which has
+// at most 64 vectors with distinct leading bits. Insertions can be undone in
+// LIFO order, as in RollbackDsu.
+
+#[derive(Clone, Debug)]
+pub struct XorBasis {
+    basis: [u64; 64], // basis[b] has leading bit b, 0 if there is none
+    rank: usize,
+    history: Vec<Option<usize>>, // Leading bit added by each insertion, None if dependent
+}
+
+impl Default for XorBasis {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl XorBasis {
+    pub fn new() -> Self {
+        XorBasis {
+            basis: [0; 64],
+            rank: 0,
+            history: Vec::new(),
+        }
+    }
+
+    /// Number of independent values inserted
+    pub fn rank(&self) -> usize {
+        self.rank
+    }
+
+    // `x` reduced by the basis vectors, from the highest leading bit
+    fn reduce(&self, mut x: u64) -> u64 {
+        for b in (0..64).rev() {
+            if x >> b & 1 == 1 {
+                x ^= self.basis[b];
+            }
+        }
+        x
+    }
+
+    /// Adds `x`, false if it was already the XOR of some inserted values.
+    /// Dependent insertions are recorded too, so every call is undone by one
+    /// step.
+    pub fn insert(&mut self, x: u64) -> bool {
+        let x = self.reduce(x);
+        if x == 0 {
+            self.history.push(None);
+            return false;
+        }
+        let bit = x.ilog2() as usize;
+        self.basis[bit] = x;
+        self.rank += 1;
+        self.history.push(Some(bit));
+        true
+    }
+
+    /// Whether `x` is the XOR of some subset of the inserted values
+    pub fn contains(&self, x: u64) -> bool {
+        self.reduce(x) == 0
+    }
+
+    /// Largest XOR of a subset of the inserted values
+    pub fn max_xor(&self) -> u64 {
+        (0..64)
+            .rev()
+            .fold(0, |best, b| best.max(best ^ self.basis[b]))
+    }
+
+    /// Current version, to be passed to `rollback`
+    pub fn snapshot(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Undoes every insertion made after `snapshot` was taken
+    pub fn rollback(&mut self, snapshot: usize) {
+        assert!(snapshot <= self.history.len(), "Invalid snapshot");
+        while self.history.len() > snapshot {
+            if let Some(bit) = self.history.pop().unwrap() {
+                self.basis[bit] = 0;
+                self.rank -= 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_against_subset_xors() {
+        let mut rng = Rng::new(56);
+        for _ in 0..50 {
+            let mut basis = XorBasis::new();
+            let mut reachable = vec![0u64]; // every subset XOR, without duplicates
+            for _ in 0..rng.gen_range(0, 10) {
+                let x = rng.gen_range(0, 255);
+                let snapshot = basis.snapshot();
+                let independent = basis.insert(x);
+                assert_eq!(independent, !reachable.contains(&x));
+                if independent {
+                    let shifted: Vec<u64> = reachable.iter().map(|&y| y ^ x).collect();
+                    reachable.extend(shifted);
+                }
+                assert_eq!(1 << basis.rank(), reachable.len());
+                assert_eq!(basis.max_xor(), *reachable.iter().max().unwrap());
+                for y in 0..256 {
+                    assert_eq!(basis.contains(y), reachable.contains(&y));
+                }
+                // undoing and redoing the insertion leaves the basis as it was
+                let rank = basis.rank();
+                basis.rollback(snapshot);
+                assert_eq!(basis.rank(), rank - independent as usize);
+                basis.insert(x);
+            }
+        }
+    }
+}